@@ -9,7 +9,7 @@ use move_core_types::{
     identifier::{IdentStr, Identifier},
     language_storage::{ModuleId, CORE_CODE_ADDRESS},
 };
-use move_vm_runtime::move_vm::MoveVM;
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
 use move_vm_test_utils::BlankStorage;
 use move_vm_types::gas_schedule::GasStatus;
 use once_cell::sync::Lazy;
@@ -24,9 +24,10 @@ static MOVE_BENCH_SRC_PATH: Lazy<PathBuf> = Lazy::new(|| {
 /// Entry point for the bench, provide a function name to invoke in Module Bench in bench.move.
 pub fn bench<M: Measurement + 'static>(c: &mut Criterion<M>, fun: &str) {
     let modules = compile_modules();
-    let move_vm = MoveVM::new(move_stdlib::natives::all_natives(
-        AccountAddress::from_hex_literal("0x1").unwrap(),
-    ))
+    let move_vm = MoveVM::new(
+        move_stdlib::natives::all_natives(AccountAddress::from_hex_literal("0x1").unwrap()),
+        VMConfig::default(),
+    )
     .unwrap();
     execute(c, &move_vm, modules, fun);
 }
@@ -1,12 +1,26 @@
-// NOTE: this module is not implemented yet
+/// Futures and continuations, supporting request/response messaging between actors.
+///
+/// A future is a handle to a value which becomes available later, for example the reply to a
+/// request sent to another actor. A continuation is a function of the actor which is called with
+/// the value of a future once it is available. The runtime keeps track of the pending futures of
+/// each actor, and times them out if they are not resolved in time.
 module Async::Future {
     use Async::Unit::{Unit, Self};
 
     /// A type which represents a future.
-    native struct Future<phantom T>;
+    struct Future<phantom T> has drop {
+        id: u64,
+    }
 
-    /// A type which represents a continuation.
-    native struct Cont<phantom T, phantom R>;
+    /// A type which represents a continuation, taking a `T` and producing a `Future<R>`.
+    struct Cont<phantom T, phantom R> has drop {
+        id: u64,
+    }
+
+    /// The pending futures of an actor, stored at its address. Maintained by the runtime.
+    struct PendingFutures has key {
+        data: vector<u8>,
+    }
 
     /// Yield execution to the given continuation.
     public native fun yield<T>(result: T): Future<T>;
@@ -18,4 +32,9 @@ module Async::Future {
     public fun done(): Future<Unit> {
         yield(Unit::unit())
     }
+
+    /// Creates a continuation from the arguments it captures. The captured value is a struct
+    /// generated by the compiler for a continuation function; the runtime determines the
+    /// function from its type.
+    public native fun cont<Captured, T, R>(captured: Captured): Cont<T, R>;
 }
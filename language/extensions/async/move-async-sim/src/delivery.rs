@@ -0,0 +1,54 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// The order in which the simulator delivers pending messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeliveryOrder {
+    /// Messages are delivered in the order they were sent.
+    Fifo,
+    /// Messages are delivered in a random order. The same seed always leads to the same order.
+    Random { seed: u64 },
+    /// The most recently sent message is delivered first, so every message overtakes all
+    /// messages which are still in flight.
+    Adversarial,
+}
+
+impl Default for DeliveryOrder {
+    fn default() -> Self {
+        DeliveryOrder::Fifo
+    }
+}
+
+/// Chooses the next message to deliver, according to a delivery order.
+pub(crate) struct Scheduler {
+    order: DeliveryOrder,
+    /// An rng with a fixed algorithm, unlike `StdRng`, so that a seed replays the same order
+    /// across releases of `rand`.
+    rng: ChaCha8Rng,
+}
+
+impl Scheduler {
+    pub(crate) fn new(order: DeliveryOrder) -> Self {
+        let seed = match order {
+            DeliveryOrder::Random { seed } => seed,
+            _ => 0,
+        };
+        Self {
+            order,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Returns the index of the next message to deliver from `pending` messages, which are
+    /// ordered by the time they were sent. `pending` must not be zero.
+    pub(crate) fn next(&mut self, pending: usize) -> usize {
+        match self.order {
+            DeliveryOrder::Fifo => 0,
+            DeliveryOrder::Random { .. } => self.rng.gen_range(0..pending),
+            DeliveryOrder::Adversarial => pending - 1,
+        }
+    }
+}
@@ -0,0 +1,12 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A deterministic simulator for systems of actors running on the async VM. The simulator owns
+//! the storage of all actors, the queue of messages in flight, and the virtual clock which is
+//! exposed to Move via `Actor::virtual_time`. Messages are delivered one at a time in an order
+//! determined by a [`delivery::DeliveryOrder`], which allows to test actor protocols under
+//! different network behaviors without a real network.
+
+pub mod delivery;
+pub mod simulator;
+pub mod trace;
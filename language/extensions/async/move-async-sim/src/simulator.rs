@@ -0,0 +1,229 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    delivery::{DeliveryOrder, Scheduler},
+    trace::{Outcome, TraceEntry},
+};
+use anyhow::{anyhow, bail, Result};
+use move_async_vm::async_vm::{AsyncVM, Message};
+use move_core_types::{
+    account_address::AccountAddress, language_storage::ModuleId, resolver::ResourceResolver,
+};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas_schedule::GasStatus;
+
+/// Configuration of a simulation.
+#[derive(Clone, Debug)]
+pub struct SimulatorConfig {
+    /// The order in which pending messages are delivered.
+    pub delivery_order: DeliveryOrder,
+    /// The virtual time which passes with each delivered message, in microseconds.
+    pub step_time: u128,
+    /// The maximal number of messages delivered by `Simulator::run` before giving up.
+    pub max_steps: usize,
+}
+
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        Self {
+            delivery_order: DeliveryOrder::Fifo,
+            step_time: 1_000_000,
+            max_steps: 10_000,
+        }
+    }
+}
+
+/// An invariant over the state of a simulation, checked after each step.
+pub type Invariant = Box<dyn Fn(&Simulator) -> Result<()>>;
+
+/// A message in flight.
+struct Envelope {
+    sender: Option<AccountAddress>,
+    message: Message,
+}
+
+/// A simulator for a system of actors. Actors are created with `create_actor`, and messages
+/// injected from outside the system with `send`. Each `step` then delivers one pending message,
+/// commits the effects of its handler, and checks all invariants.
+pub struct Simulator {
+    vm: AsyncVM,
+    storage: InMemoryStorage,
+    config: SimulatorConfig,
+    scheduler: Scheduler,
+    pending: Vec<Envelope>,
+    trace: Vec<TraceEntry>,
+    invariants: Vec<(String, Invariant)>,
+    virtual_time: u128,
+}
+
+impl Simulator {
+    /// Creates a new simulator for the actors known to `vm`.
+    pub fn new(vm: AsyncVM, config: SimulatorConfig) -> Self {
+        Self {
+            vm,
+            storage: InMemoryStorage::new(),
+            scheduler: Scheduler::new(config.delivery_order),
+            config,
+            pending: vec![],
+            trace: vec![],
+            invariants: vec![],
+            virtual_time: 0,
+        }
+    }
+
+    /// Publishes the serialized module with the given id.
+    pub fn publish_module(&mut self, module_id: ModuleId, blob: Vec<u8>) {
+        self.storage.publish_or_overwrite_module(module_id, blob)
+    }
+
+    /// Adds an invariant which must hold after each step of the simulation.
+    pub fn add_invariant(
+        &mut self,
+        name: impl ToString,
+        invariant: impl Fn(&Simulator) -> Result<()> + 'static,
+    ) {
+        self.invariants
+            .push((name.to_string(), Box::new(invariant)))
+    }
+
+    /// Creates the actor `module_id` at `actor_addr`, by calling its initializer. Messages
+    /// sent by the initializer are added to the pending messages.
+    pub fn create_actor(&mut self, module_id: &ModuleId, actor_addr: AccountAddress) -> Result<()> {
+        let mut gas_status = GasStatus::new_unmetered();
+        let success = self
+            .vm
+            .new_session(actor_addr, self.virtual_time, &mut self.storage)
+            .new_actor(module_id, actor_addr, &mut gas_status)?;
+        self.storage.apply(success.change_set)?;
+        self.enqueue(Some(actor_addr), success.messages);
+        Ok(())
+    }
+
+    /// Sends a message from outside the system to `actor`.
+    pub fn send(&mut self, actor: AccountAddress, message_hash: u64, args: Vec<Vec<u8>>) {
+        self.enqueue(
+            None,
+            vec![Message::Send {
+                target: actor,
+                message_hash,
+                args,
+            }],
+        )
+    }
+
+    /// Delivers one pending message and checks the invariants. Returns false if there was no
+    /// message to deliver.
+    pub fn step(&mut self) -> Result<bool> {
+        if self.pending.is_empty() {
+            return Ok(false);
+        }
+        let Envelope { sender, message } =
+            self.pending.remove(self.scheduler.next(self.pending.len()));
+        let receiver = message.target();
+        let handler = match &message {
+            Message::Send { message_hash, .. } | Message::Request { message_hash, .. } => {
+                self.vm.resolve_message_hash(*message_hash).cloned()
+            }
+            Message::Reply { .. } => None,
+        };
+        self.virtual_time += self.config.step_time;
+
+        // Handle the message. A failing handler has no effects besides being recorded in the
+        // trace, and the expiry of futures which timed out.
+        let mut gas_status = GasStatus::new_unmetered();
+        let result = self
+            .vm
+            .new_session(receiver, self.virtual_time, &mut self.storage)
+            .deliver(message.clone(), &mut gas_status);
+        let outcome = match result {
+            Ok(success) => {
+                self.storage.apply(success.change_set)?;
+                let sent = success.messages.len();
+                self.enqueue(Some(receiver), success.messages);
+                Outcome::Handled { sent }
+            }
+            Err(error) => {
+                self.storage.apply(error.change_set.clone())?;
+                Outcome::Failed(error.to_string())
+            }
+        };
+        self.trace.push(TraceEntry {
+            step: self.trace.len(),
+            virtual_time: self.virtual_time,
+            sender,
+            receiver,
+            message,
+            handler,
+            outcome,
+        });
+
+        for (name, invariant) in &self.invariants {
+            if let Err(error) = invariant(self) {
+                bail!(
+                    "invariant `{}` violated after step {}: {}",
+                    name,
+                    self.trace.len() - 1,
+                    error
+                )
+            }
+        }
+        Ok(true)
+    }
+
+    /// Delivers messages until none is pending, and returns the number of steps taken. Fails if
+    /// an invariant is violated or the system does not quiesce within the configured number of
+    /// steps.
+    pub fn run(&mut self) -> Result<usize> {
+        let mut steps = 0;
+        while self.step()? {
+            steps += 1;
+            if steps >= self.config.max_steps && !self.pending.is_empty() {
+                bail!(
+                    "simulation did not quiesce within {} steps ({} messages pending)",
+                    steps,
+                    self.pending.len()
+                )
+            }
+        }
+        Ok(steps)
+    }
+
+    /// Returns the BCS serialized state of the actor `module_id` at `actor_addr`, if it exists.
+    pub fn actor_state(
+        &self,
+        module_id: &ModuleId,
+        actor_addr: AccountAddress,
+    ) -> Result<Option<Vec<u8>>> {
+        let actor = self
+            .vm
+            .actor_metadata(module_id)
+            .ok_or_else(|| anyhow!("actor `{}` unknown", module_id.short_str_lossless()))?;
+        self.storage
+            .get_resource(&actor_addr, &actor.state_tag)
+            .map_err(|_| anyhow!("cannot read actor state"))
+    }
+
+    /// Returns the trace of all messages delivered so far.
+    pub fn trace(&self) -> &[TraceEntry] {
+        &self.trace
+    }
+
+    /// Returns the number of messages in flight.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the current virtual time, in microseconds.
+    pub fn virtual_time(&self) -> u128 {
+        self.virtual_time
+    }
+
+    fn enqueue(&mut self, sender: Option<AccountAddress>, messages: Vec<Message>) {
+        self.pending.extend(
+            messages
+                .into_iter()
+                .map(|message| Envelope { sender, message }),
+        )
+    }
+}
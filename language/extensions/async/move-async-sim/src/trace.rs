@@ -0,0 +1,84 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_async_vm::async_vm::Message;
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+};
+use std::fmt::{Display, Formatter};
+
+/// A record of the delivery of a single message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// The number of the step at which the message was delivered, starting at 0.
+    pub step: usize,
+    /// The virtual time at which the message was delivered, in microseconds.
+    pub virtual_time: u128,
+    /// The actor which sent the message, or none if it was sent from outside the system.
+    pub sender: Option<AccountAddress>,
+    /// The actor which received the message.
+    pub receiver: AccountAddress,
+    /// The message which was delivered.
+    pub message: Message,
+    /// The module and function of the message handler, if the message is not a reply and its
+    /// hash is known.
+    pub handler: Option<(ModuleId, Identifier)>,
+    /// The outcome of handling the message.
+    pub outcome: Outcome,
+}
+
+/// The outcome of handling a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The handler succeeded and sent the given number of messages.
+    Handled { sent: usize },
+    /// The handler failed with the given error; its effects have been discarded.
+    Failed(String),
+}
+
+impl TraceEntry {
+    pub fn is_failure(&self) -> bool {
+        matches!(self.outcome, Outcome::Failed(_))
+    }
+
+    fn fmt_outcome(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.outcome {
+            Outcome::Handled { sent } => write!(f, "handled, sent {}", sent),
+            Outcome::Failed(error) => write!(f, "failed: {}", error),
+        }
+    }
+}
+
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{} t={} ", self.step, self.virtual_time)?;
+        match self.sender {
+            Some(sender) => write!(f, "0x{}", sender.short_str_lossless())?,
+            None => write!(f, "external")?,
+        }
+        write!(f, " -> 0x{} ", self.receiver.short_str_lossless())?;
+        let (message_hash, args) = match &self.message {
+            Message::Send {
+                message_hash, args, ..
+            } => (message_hash, args),
+            Message::Request {
+                message_hash, args, ..
+            } => {
+                write!(f, "request ")?;
+                (message_hash, args)
+            }
+            Message::Reply { future_id, .. } => {
+                write!(f, "reply to future {}: ", future_id)?;
+                return self.fmt_outcome(f);
+            }
+        };
+        match &self.handler {
+            Some((module_id, handler)) => {
+                write!(f, "{}::{}", module_id.short_str_lossless(), handler)?
+            }
+            None => write!(f, "0x{:X}", message_hash)?,
+        }
+        write!(f, " argc={}: ", args.len())?;
+        self.fmt_outcome(f)
+    }
+}
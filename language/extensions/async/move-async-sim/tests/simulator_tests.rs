@@ -0,0 +1,253 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Result};
+use move_async_sim::{
+    delivery::DeliveryOrder,
+    simulator::{Simulator, SimulatorConfig},
+};
+use move_async_vm::{
+    actor_metadata::{message_hash, ActorMetadata},
+    async_vm::{AsyncVM, DEFAULT_FUTURE_TIMEOUT},
+};
+use move_compiler::{
+    compiled_unit::CompiledUnit, diagnostics::report_diagnostics_to_buffer,
+    shared::NumericalAddress, Compiler, Flags,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag},
+};
+use std::collections::BTreeMap;
+
+const TEST_ADDR: &str = "0x3";
+const SOURCES: &[&str] = &[
+    "tests/sources/PingPong.move",
+    "../move-async-lib/sources/Actor.move",
+    "../move-async-lib/sources/Runtime.move",
+    "../move-async-lib/sources/Future.move",
+    "../move-async-lib/sources/Unit.move",
+];
+
+fn test_account() -> AccountAddress {
+    AccountAddress::from_hex_literal(TEST_ADDR).unwrap()
+}
+
+fn ping_pong_id() -> ModuleId {
+    ModuleId::new(test_account(), Identifier::new("PingPong").unwrap())
+}
+
+fn config(delivery_order: DeliveryOrder) -> SimulatorConfig {
+    SimulatorConfig {
+        delivery_order,
+        ..SimulatorConfig::default()
+    }
+}
+
+fn new_simulator(config: SimulatorConfig) -> Result<Simulator> {
+    new_simulator_with_timeout(config, DEFAULT_FUTURE_TIMEOUT)
+}
+
+/// Compiles the test sources and creates a simulator with two ping pong players at 0x4 and 0x5,
+/// whose futures time out after `future_timeout`.
+fn new_simulator_with_timeout(config: SimulatorConfig, future_timeout: u128) -> Result<Simulator> {
+    let test_addr = NumericalAddress::parse_str(TEST_ADDR).unwrap();
+    let address_map: BTreeMap<String, NumericalAddress> = vec![
+        ("Std".to_string(), test_addr),
+        ("Async".to_string(), test_addr),
+        ("Test".to_string(), test_addr),
+    ]
+    .into_iter()
+    .collect();
+    let sources = SOURCES.iter().map(|s| s.to_string()).collect();
+    let (files, units) = Compiler::from_files(sources, vec![], address_map)
+        .set_flags(Flags::empty().set_flavor("async"))
+        .build()?;
+    let units = match units {
+        Ok((units, _)) => units,
+        Err(diags) => bail!(
+            "Compilation failure {{\n{}\n}}",
+            String::from_utf8_lossy(&report_diagnostics_to_buffer(&files, diags))
+        ),
+    };
+
+    let actor = ActorMetadata {
+        module_id: ping_pong_id(),
+        state_tag: StructTag {
+            address: test_account(),
+            module: Identifier::new("PingPong").unwrap(),
+            name: Identifier::new("State").unwrap(),
+            type_params: vec![],
+        },
+        initializer: Identifier::new("init").unwrap(),
+        messages: vec![
+            Identifier::new("start").unwrap(),
+            Identifier::new("ping").unwrap(),
+            Identifier::new("ask").unwrap(),
+            Identifier::new("echo").unwrap(),
+            Identifier::new("answered").unwrap(),
+        ],
+    };
+    let mut vm = AsyncVM::new(
+        test_account(),
+        move_stdlib::natives::all_natives(test_account()),
+        vec![actor],
+    )?;
+    vm.set_future_timeout(future_timeout);
+    let mut sim = Simulator::new(vm, config);
+    for unit in units {
+        let unit = unit.into_compiled_unit();
+        if let CompiledUnit::Module(m) = &unit {
+            sim.publish_module(m.module.self_id(), unit.serialize());
+        }
+    }
+    sim.create_actor(&ping_pong_id(), addr(4))?;
+    sim.create_actor(&ping_pong_id(), addr(5))?;
+    Ok(sim)
+}
+
+fn addr(n: u8) -> AccountAddress {
+    AccountAddress::from_hex_literal(&format!("0x{}", n)).unwrap()
+}
+
+fn start(sim: &mut Simulator, player: AccountAddress, partner: AccountAddress) {
+    send(sim, "start", player, partner)
+}
+
+fn send(sim: &mut Simulator, handler: &str, player: AccountAddress, partner: AccountAddress) {
+    let hash = message_hash(
+        &ping_pong_id(),
+        Identifier::new(handler).unwrap().as_ident_str(),
+    );
+    sim.send(player, hash, vec![bcs::to_bytes(&partner).unwrap()]);
+}
+
+/// Returns the `received` and `last_seen` fields of a player's state.
+fn player_state(sim: &Simulator, player: AccountAddress) -> Result<(u64, u128)> {
+    let blob = sim
+        .actor_state(&ping_pong_id(), player)?
+        .expect("actor exists");
+    Ok(bcs::from_bytes(&blob)?)
+}
+
+fn trace_lines(sim: &Simulator) -> Vec<String> {
+    sim.trace().iter().map(|e| e.to_string()).collect()
+}
+
+#[test]
+fn fifo_runs_to_completion() -> Result<()> {
+    let mut sim = new_simulator(config(DeliveryOrder::Fifo))?;
+    start(&mut sim, addr(4), addr(5));
+    assert_eq!(sim.run()?, 11);
+    assert_eq!(sim.pending(), 0);
+    assert!(sim.trace().iter().all(|e| !e.is_failure()));
+
+    // 0x5 receives the odd rounds, 0x4 the even ones.
+    let (received_5, last_seen_5) = player_state(&sim, addr(5))?;
+    let (received_4, last_seen_4) = player_state(&sim, addr(4))?;
+    assert_eq!((received_4, received_5), (5, 5));
+    assert_eq!(last_seen_4, sim.virtual_time());
+    assert_eq!(last_seen_5, sim.virtual_time() - 1_000_000);
+
+    let trace = trace_lines(&sim);
+    assert_eq!(
+        trace[0],
+        "#0 t=1000000 external -> 0x4 0x3::PingPong::start argc=1: handled, sent 1"
+    );
+    assert_eq!(
+        trace[1],
+        "#1 t=2000000 0x4 -> 0x5 0x3::PingPong::ping argc=2: handled, sent 1"
+    );
+    Ok(())
+}
+
+#[test]
+fn random_order_is_deterministic() -> Result<()> {
+    let run = |seed| -> Result<Vec<String>> {
+        let mut sim = new_simulator(config(DeliveryOrder::Random { seed }))?;
+        start(&mut sim, addr(4), addr(5));
+        start(&mut sim, addr(5), addr(4));
+        sim.run()?;
+        assert_eq!(player_state(&sim, addr(4))?.0, 10);
+        assert_eq!(player_state(&sim, addr(5))?.0, 10);
+        Ok(trace_lines(&sim))
+    };
+    assert_eq!(run(1)?, run(1)?);
+    Ok(())
+}
+
+#[test]
+fn adversarial_order_delivers_latest_first() -> Result<()> {
+    let mut sim = new_simulator(config(DeliveryOrder::Adversarial))?;
+    start(&mut sim, addr(4), addr(5));
+    start(&mut sim, addr(5), addr(4));
+    sim.step()?;
+    // The start message to 0x5 was sent last, and overtakes the one to 0x4.
+    assert_eq!(sim.trace()[0].receiver, addr(5));
+    sim.run()?;
+    assert_eq!(sim.trace().len(), 22);
+    Ok(())
+}
+
+#[test]
+fn invariant_violation_stops_simulation() -> Result<()> {
+    let mut sim = new_simulator(config(DeliveryOrder::Fifo))?;
+    sim.add_invariant("at most three pings", |sim| {
+        let (received, _) = player_state(sim, addr(5))?;
+        if received > 3 {
+            bail!("0x5 received {} pings", received)
+        }
+        Ok(())
+    });
+    start(&mut sim, addr(4), addr(5));
+    let err = sim.run().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invariant `at most three pings` violated after step 7: 0x5 received 4 pings"
+    );
+    Ok(())
+}
+
+#[test]
+fn max_steps_bounds_simulation() -> Result<()> {
+    let mut sim = new_simulator(SimulatorConfig {
+        max_steps: 5,
+        ..SimulatorConfig::default()
+    })?;
+    start(&mut sim, addr(4), addr(5));
+    let err = sim.run().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "simulation did not quiesce within 5 steps (1 messages pending)"
+    );
+    Ok(())
+}
+
+#[test]
+fn requests_time_out_in_virtual_time() -> Result<()> {
+    // The reply to the request of 0x4 arrives two steps after the request was sent.
+    let mut sim = new_simulator_with_timeout(config(DeliveryOrder::Fifo), 2_000_000)?;
+    send(&mut sim, "ask", addr(4), addr(5));
+    assert_eq!(sim.run()?, 3);
+    assert_eq!(
+        trace_lines(&sim),
+        vec![
+            "#0 t=1000000 external -> 0x4 0x3::PingPong::ask argc=1: handled, sent 1",
+            "#1 t=2000000 0x4 -> 0x5 request 0x3::PingPong::echo argc=1: handled, sent 1",
+            "#2 t=3000000 0x5 -> 0x4 reply to future 0: handled, sent 0",
+        ]
+    );
+    assert_eq!(player_state(&sim, addr(4))?.0, 7);
+
+    // A reply which arrives after its future timed out is dropped.
+    let mut sim = new_simulator_with_timeout(config(DeliveryOrder::Fifo), 1_000_000)?;
+    send(&mut sim, "ask", addr(4), addr(5));
+    sim.run()?;
+    assert_eq!(
+        sim.trace()[2].to_string(),
+        "#2 t=3000000 0x5 -> 0x4 reply to future 0: handled, sent 0"
+    );
+    assert_eq!(player_state(&sim, addr(4))?.0, 0);
+    Ok(())
+}
@@ -0,0 +1,50 @@
+/// Two actors which play a bounded game of ping pong, and can also ask each other via a request.
+#[actor]
+module Test::PingPong {
+    use Async::Actor;
+    use Async::Future::{Future, done, followed_by, yield};
+    use Async::Unit::Unit;
+
+    const MAX_ROUNDS: u64 = 10;
+
+    #[state]
+    struct State {
+        received: u64,
+        last_seen: u128,
+    }
+
+    #[init]
+    fun init(): State {
+        State{received: 0, last_seen: 0}
+    }
+
+    #[message]
+    fun start(_s: &State, partner: address) {
+        send_ping(partner, Actor::self(), 1);
+    }
+
+    #[message]
+    fun ping(s: &mut State, from: address, round: u64) {
+        s.received = s.received + 1;
+        s.last_seen = Actor::virtual_time();
+        if (round < MAX_ROUNDS) {
+            send_ping(from, Actor::self(), round + 1);
+        }
+    }
+
+    #[message]
+    fun ask(_s: &State, partner: address) {
+        let _ = followed_by(rpc_echo(partner, 7), cont_answered());
+    }
+
+    #[rpc]
+    fun echo(_s: &State, v: u64): Future<u64> {
+        yield(v)
+    }
+
+    #[cont]
+    fun answered(s: &mut State, v: u64): Future<Unit> {
+        s.received = s.received + v;
+        done()
+    }
+}
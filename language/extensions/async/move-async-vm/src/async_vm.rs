@@ -10,38 +10,52 @@ use std::{
 use move_binary_format::errors::{Location, PartialVMError, PartialVMResult, VMError, VMResult};
 use move_core_types::{
     account_address::AccountAddress,
-    effects::{ChangeSet, Event},
+    effects::{ChangeSet, ContractEvent},
     gas_schedule::GasAlgebra,
-    identifier::Identifier,
+    ident_str,
+    identifier::{IdentStr, Identifier},
     language_storage::{ModuleId, StructTag, TypeTag},
     resolver::MoveResolver,
     vm_status::StatusCode,
 };
 use move_vm_runtime::{
+    config::VMConfig,
     move_vm::MoveVM,
     native_extensions::NativeContextExtensions,
-    native_functions::NativeFunction,
+    native_functions::NativeFunctionEntry,
     session::{SerializedReturnValues, Session},
 };
 use move_vm_types::{
     gas_schedule::GasStatus,
+    loaded_data::runtime_types::Type,
     values::{Reference, Value},
 };
 
-use crate::{actor_metadata, actor_metadata::ActorMetadata, natives, natives::AsyncExtension};
+use crate::{
+    actor_metadata,
+    actor_metadata::ActorMetadata,
+    futures::{PendingFuture, PendingFutures, Then},
+    natives,
+    natives::AsyncExtension,
+};
+
+/// The default time after which a future times out, in microseconds.
+pub const DEFAULT_FUTURE_TIMEOUT: u128 = 60_000_000;
 
 /// Represents an instance of an async VM.
 pub struct AsyncVM {
     move_vm: MoveVM,
     actor_metadata: HashMap<ModuleId, ActorMetadata>,
     message_table: HashMap<u64, (ModuleId, Identifier)>,
+    async_lib_addr: AccountAddress,
+    future_timeout: u128,
 }
 
 impl AsyncVM {
     /// Creates a new VM, registering the given natives and actors.
     pub fn new<I, A>(async_lib_addr: AccountAddress, natives: I, actors: A) -> VMResult<Self>
     where
-        I: IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
+        I: IntoIterator<Item = NativeFunctionEntry>,
         A: IntoIterator<Item = ActorMetadata>,
     {
         let actor_metadata: HashMap<ModuleId, ActorMetadata> = actors
@@ -64,25 +78,49 @@ impl AsyncVM {
                 natives
                     .into_iter()
                     .chain(natives::actor_natives(async_lib_addr).into_iter()),
+                VMConfig::default(),
             )?,
             actor_metadata,
             message_table,
+            async_lib_addr,
+            future_timeout: DEFAULT_FUTURE_TIMEOUT,
         })
     }
 
-    /// Creates a new session.
+    /// Creates a new session. The pending futures of the actor which have timed out at
+    /// `virtual_time` are dropped.
     pub fn new_session<'r, 'l, S: MoveResolver>(
         &'l self,
         for_actor: AccountAddress,
         virtual_time: u128,
         move_resolver: &'r mut S,
     ) -> AsyncSession<'r, 'l, S> {
-        let extensions = make_extensions(for_actor, virtual_time, true);
+        let (loaded_futures, futures_error) =
+            match self.load_pending_futures(for_actor, move_resolver) {
+                Ok(futures) => (futures, None),
+                Err(error) => (PendingFutures::default(), Some(error)),
+            };
+        let mut futures = loaded_futures.clone();
+        let expired_futures = futures.expire(virtual_time);
+        let mut extensions = NativeContextExtensions::default();
+        extensions.add(AsyncExtension {
+            current_actor: for_actor,
+            sent: vec![],
+            in_initializer: true,
+            virtual_time,
+            futures,
+            future_deadline: virtual_time + self.future_timeout,
+            ready: Default::default(),
+            conts: vec![],
+        });
         AsyncSession {
             vm: self,
             vm_session: self
                 .move_vm
                 .new_session_with_extensions(move_resolver, extensions),
+            loaded_futures,
+            expired_futures,
+            futures_error,
         }
     }
 
@@ -105,23 +143,108 @@ impl AsyncVM {
     pub fn actors(&self) -> Vec<ModuleId> {
         self.actor_metadata.keys().cloned().collect()
     }
+
+    /// Get the time after which a future times out, in microseconds.
+    pub fn future_timeout(&self) -> u128 {
+        self.future_timeout
+    }
+
+    /// Set the time after which a future times out, in microseconds.
+    pub fn set_future_timeout(&mut self, timeout: u128) {
+        self.future_timeout = timeout
+    }
+
+    /// Get the tag of the resource in which the pending futures of an actor are stored.
+    pub fn pending_futures_tag(&self) -> StructTag {
+        StructTag {
+            address: self.async_lib_addr,
+            module: ident_str!("Future").to_owned(),
+            name: ident_str!("PendingFutures").to_owned(),
+            type_params: vec![],
+        }
+    }
+
+    fn load_pending_futures<S: MoveResolver>(
+        &self,
+        actor_addr: AccountAddress,
+        move_resolver: &S,
+    ) -> Result<PendingFutures, String> {
+        match move_resolver.get_resource(&actor_addr, &self.pending_futures_tag()) {
+            Ok(Some(blob)) => bcs::from_bytes::<Vec<u8>>(&blob)
+                .and_then(|data| bcs::from_bytes(&data))
+                .map_err(|err| format!("cannot decode pending futures: {}", err)),
+            Ok(None) => Ok(PendingFutures::default()),
+            Err(err) => Err(format!("cannot load pending futures: {:?}", err)),
+        }
+    }
+
+    fn is_future_type(&self, tag: &TypeTag) -> bool {
+        matches!(tag, TypeTag::Struct(StructTag { address, module, name, .. })
+            if *address == self.async_lib_addr
+                && module.as_str() == "Future"
+                && name.as_str() == "Future")
+    }
 }
 
 /// Represents an Async Move execution session.
 pub struct AsyncSession<'r, 'l, S> {
     vm: &'l AsyncVM,
     vm_session: Session<'r, 'l, S>,
+    /// The pending futures of the actor as loaded from storage.
+    loaded_futures: PendingFutures,
+    /// The ids of the futures which timed out at the start of this session.
+    expired_futures: Vec<u64>,
+    /// An error which occurred while loading the pending futures.
+    futures_error: Option<String>,
 }
 
-/// Represents a message being sent, consisting of target address, message hash, and arguments.
-pub type Message = (AccountAddress, u64, Vec<Vec<u8>>);
+/// Represents a message being sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// A message for the handler identified by `message_hash`.
+    Send {
+        target: AccountAddress,
+        message_hash: u64,
+        args: Vec<Vec<u8>>,
+    },
+    /// A request for the handler identified by `message_hash`, which returns a future. The
+    /// value of that future is replied to the future `future_id` of `reply_to`.
+    Request {
+        target: AccountAddress,
+        message_hash: u64,
+        args: Vec<Vec<u8>>,
+        reply_to: AccountAddress,
+        future_id: u64,
+    },
+    /// A reply which resolves the future `future_id` of the target with the BCS serialized
+    /// `value`.
+    Reply {
+        target: AccountAddress,
+        future_id: u64,
+        value: Vec<u8>,
+    },
+}
+
+impl Message {
+    /// Returns the address of the actor the message is sent to.
+    pub fn target(&self) -> AccountAddress {
+        match self {
+            Message::Send { target, .. }
+            | Message::Request { target, .. }
+            | Message::Reply { target, .. } => *target,
+        }
+    }
+}
 
 /// A structure to represent success for the execution of an async session operation.
 #[derive(Debug, Clone)]
 pub struct AsyncSuccess {
     pub change_set: ChangeSet,
-    pub events: Vec<Event>,
+    pub events: Vec<ContractEvent>,
     pub messages: Vec<Message>,
+    pub expired_futures: Vec<u64>,
+    /// The ids of futures whose reply was dropped because they had timed out already.
+    pub dropped_replies: Vec<u64>,
     pub gas_used: u64,
 }
 
@@ -129,12 +252,32 @@ pub struct AsyncSuccess {
 #[derive(Debug, Clone)]
 pub struct AsyncError {
     pub error: VMError,
+    /// Changes which need to be committed even though the operation failed. These remove
+    /// the futures which timed out at the start of the session.
+    pub change_set: ChangeSet,
+    pub expired_futures: Vec<u64>,
     pub gas_used: u64,
 }
 
 /// Result type for operations of an AsyncSession.
 pub type AsyncResult = Result<AsyncSuccess, AsyncError>;
 
+/// A function to execute in a session, and how to process its results.
+enum Invocation<'l> {
+    /// Call the initializer of the actor, which returns the initial actor state.
+    Initializer(&'l ActorMetadata),
+    /// Call a handler with the actor state and the given arguments. If `then` is given, the
+    /// handler returns a future whose value is passed on as described by it.
+    Handler {
+        actor: &'l ActorMetadata,
+        handler_id: &'l IdentStr,
+        args: Vec<Vec<u8>>,
+        then: Option<Then>,
+    },
+    /// Do not call any function, only process the pending futures.
+    Nothing,
+}
+
 impl<'r, 'l, S: MoveResolver> AsyncSession<'r, 'l, S> {
     /// Get the underlying Move VM session.
     pub fn get_move_session(&mut self) -> &mut Session<'r, 'l, S> {
@@ -145,107 +288,398 @@ impl<'r, 'l, S: MoveResolver> AsyncSession<'r, 'l, S> {
     /// This calls the initializer function of the actor, and returns on success
     /// a changeset which needs to be committed to persist the new actors state.
     pub fn new_actor(
-        mut self,
+        self,
         module_id: &ModuleId,
         actor_addr: AccountAddress,
         gas_status: &mut GasStatus,
     ) -> AsyncResult {
-        let actor = self
-            .vm
-            .actor_metadata
-            .get(module_id)
-            .ok_or_else(|| async_extension_error(format!("actor `{}` unknown", module_id)))?;
-        let state_type_tag = TypeTag::Struct(actor.state_tag.clone());
-        let state_type = self
-            .vm_session
-            .load_type(&state_type_tag)
-            .map_err(vm_error_to_async)?;
+        self.with_expiry(|mut session| {
+            session.check_futures()?;
+            let vm = session.vm;
+            let actor = vm
+                .actor_metadata
+                .get(module_id)
+                .ok_or_else(|| async_extension_error(format!("actor `{}` unknown", module_id)))?;
+            let state_type_tag = TypeTag::Struct(actor.state_tag.clone());
+            let state_type = session
+                .vm_session
+                .load_type(&state_type_tag)
+                .map_err(vm_error_to_async)?;
+
+            // Check whether the actor state already exists.
+            let state = session
+                .vm_session
+                .get_data_store()
+                .load_resource(actor_addr, &state_type)
+                .map_err(partial_vm_error_to_async)?;
+            if state.exists().map_err(partial_vm_error_to_async)? {
+                return Err(async_extension_error(format!(
+                    "actor `{}` already exists at `{}`",
+                    module_id.short_str_lossless(),
+                    actor_addr.short_str_lossless()
+                )));
+            }
 
-        // Check whether the actor state already exists.
-        let state = self
-            .vm_session
-            .get_data_store()
-            .load_resource(actor_addr, &state_type)
-            .map_err(partial_vm_error_to_async)?;
-        if state.exists().map_err(partial_vm_error_to_async)? {
-            return Err(async_extension_error(format!(
-                "actor `{}` already exists at `{}`",
-                module_id.short_str_lossless(),
-                actor_addr.short_str_lossless()
-            )));
-        }
+            session.execute(actor_addr, Invocation::Initializer(actor), gas_status)
+        })
+    }
 
-        // Execute the initializer.
-        let gas_before = gas_status.remaining_gas().get();
-        let result = self
-            .vm_session
-            .execute_function_bypass_visibility(
-                &actor.module_id,
-                &actor.initializer,
-                vec![],
-                Vec::<Vec<u8>>::new(),
+    /// Handles a message at `actor` with the given `message_hash`. This will call the
+    /// according function as determined by the AsyncResolver, passing a reference to
+    /// the actors state.
+    pub fn handle_message(
+        self,
+        actor_addr: AccountAddress,
+        message_hash: u64,
+        args: Vec<Vec<u8>>,
+        gas_status: &mut GasStatus,
+    ) -> AsyncResult {
+        self.with_expiry(|mut session| {
+            session.check_futures()?;
+            let (actor, handler_id) = session.resolve_handler(message_hash)?;
+            let (args, _) = session.handler_args(actor_addr, actor, handler_id, args)?;
+            session.execute(
+                actor_addr,
+                Invocation::Handler {
+                    actor,
+                    handler_id,
+                    args,
+                    then: None,
+                },
                 gas_status,
             )
-            .and_then(|ret| Ok((ret, self.vm_session.finish_with_extensions()?)));
-        let gas_used = gas_status.remaining_gas().get() - gas_before;
+        })
+    }
 
-        // Process the result, moving the return value of the initializer function into the
-        // changeset.
-        match result {
-            Ok((
-                SerializedReturnValues {
-                    mutable_reference_outputs: _,
-                    mut return_values,
+    /// Handles a request at `actor` with the given `message_hash`. This is like
+    /// `handle_message`, but the handler must return a future. Once that future is resolved,
+    /// its value is replied to the future `future_id` of the actor `reply_to`.
+    pub fn handle_request(
+        self,
+        actor_addr: AccountAddress,
+        message_hash: u64,
+        args: Vec<Vec<u8>>,
+        reply_to: AccountAddress,
+        future_id: u64,
+        gas_status: &mut GasStatus,
+    ) -> AsyncResult {
+        self.with_expiry(|mut session| {
+            session.check_futures()?;
+            let (actor, handler_id) = session.resolve_handler(message_hash)?;
+            let (args, returns) = session.handler_args(actor_addr, actor, handler_id, args)?;
+            session.check_returns_future(actor, handler_id, &returns)?;
+            session.execute(
+                actor_addr,
+                Invocation::Handler {
+                    actor,
+                    handler_id,
+                    args,
+                    then: Some(Then::Reply {
+                        actor: reply_to,
+                        future_id,
+                    }),
                 },
-                (mut change_set, events, mut native_extensions),
-            )) => {
+                gas_status,
+            )
+        })
+    }
+
+    /// Handles a reply at `actor`, resolving its future `future_id` with the BCS serialized
+    /// `value`. If a continuation is registered for the future, it is called with the value.
+    pub fn handle_reply(
+        self,
+        actor_addr: AccountAddress,
+        future_id: u64,
+        value: Vec<u8>,
+        gas_status: &mut GasStatus,
+    ) -> AsyncResult {
+        self.with_expiry(|mut session| {
+            session.check_futures()?;
+            let ext = session
+                .vm_session
+                .get_native_extensions()
+                .get_mut::<AsyncExtension>();
+            let future = match ext.futures.futures.remove(&future_id) {
+                Some(future) => future,
+                // The future is no longer pending because it timed out before the reply
+                // arrived. This is expected after a timeout, so the reply is dropped.
+                None if future_id < ext.futures.next_id => {
+                    return session
+                        .execute(actor_addr, Invocation::Nothing, gas_status)
+                        .map(|mut success| {
+                            success.dropped_replies.push(future_id);
+                            success
+                        })
+                }
+                None => {
+                    return Err(async_extension_error(format!(
+                        "future `{}` of actor `{}` is unknown",
+                        future_id,
+                        actor_addr.short_str_lossless()
+                    )))
+                }
+            };
+            match &future.then {
+                None => session.execute(actor_addr, Invocation::Nothing, gas_status),
+                Some(Then::Cont {
+                    message_hash,
+                    args,
+                    result,
+                }) => {
+                    let (message_hash, result) = (*message_hash, *result);
+                    let mut args = args.clone();
+                    args.insert(0, value);
+                    let (actor, handler_id) = session.resolve_handler(message_hash)?;
+                    let (args, returns) =
+                        session.handler_args(actor_addr, actor, handler_id, args)?;
+                    session.check_returns_future(actor, handler_id, &returns)?;
+                    session.execute(
+                        actor_addr,
+                        Invocation::Handler {
+                            actor,
+                            handler_id,
+                            args,
+                            then: Some(Then::Resolve { future_id: result }),
+                        },
+                        gas_status,
+                    )
+                }
+                Some(_) => {
+                    pass_on(ext, future_id, future, value);
+                    session.execute(actor_addr, Invocation::Nothing, gas_status)
+                }
+            }
+        })
+    }
+
+    /// Delivers a message to its target, dispatching to `handle_message`, `handle_request`,
+    /// or `handle_reply`.
+    pub fn deliver(self, message: Message, gas_status: &mut GasStatus) -> AsyncResult {
+        match message {
+            Message::Send {
+                target,
+                message_hash,
+                args,
+            } => self.handle_message(target, message_hash, args, gas_status),
+            Message::Request {
+                target,
+                message_hash,
+                args,
+                reply_to,
+                future_id,
+            } => self.handle_request(target, message_hash, args, reply_to, future_id, gas_status),
+            Message::Reply {
+                target,
+                future_id,
+                value,
+            } => self.handle_reply(target, future_id, value, gas_status),
+        }
+    }
+
+    /// Executes the invocation and processes its results: publishes the actor state, links
+    /// the future returned by a handler, passes on the values of futures resolved in this
+    /// session, and persists the pending futures if they changed.
+    fn execute(
+        self,
+        actor_addr: AccountAddress,
+        invocation: Invocation<'l>,
+        gas_status: &mut GasStatus,
+    ) -> AsyncResult {
+        let AsyncSession {
+            vm,
+            mut vm_session,
+            loaded_futures,
+            expired_futures,
+            ..
+        } = self;
+
+        let gas_before = gas_status.remaining_gas().get();
+        let result = match &invocation {
+            Invocation::Initializer(actor) => vm_session
+                .execute_function_bypass_visibility(
+                    &actor.module_id,
+                    &actor.initializer,
+                    vec![],
+                    Vec::<Vec<u8>>::new(),
+                    gas_status,
+                )
+                .map(Some),
+            Invocation::Handler {
+                actor,
+                handler_id,
+                args,
+                ..
+            } => vm_session
+                .execute_function_bypass_visibility(
+                    &actor.module_id,
+                    handler_id,
+                    vec![],
+                    args.clone(),
+                    gas_status,
+                )
+                .map(Some),
+            Invocation::Nothing => Ok(None),
+        }
+        .and_then(|ret| Ok((ret, vm_session.finish_with_extensions()?)));
+        let gas_used = gas_status.remaining_gas().get() - gas_before;
+        let (ret, (mut change_set, events, _, mut native_extensions)) =
+            result.map_err(|error| AsyncError {
+                gas_used,
+                ..vm_error_to_async(error)
+            })?;
+        let mut async_ext = native_extensions.remove::<AsyncExtension>();
+
+        // Process the result, moving the return value of the initializer or the mutated value
+        // of the handlers first parameter into the changeset.
+        match (invocation, ret) {
+            (
+                Invocation::Initializer(actor),
+                Some(SerializedReturnValues {
+                    mut return_values, ..
+                }),
+            ) => {
                 if return_values.len() != 1 {
-                    Err(async_extension_error(format!(
+                    return Err(async_extension_error(format!(
                         "inconsistent initializer `{}`",
                         actor.initializer
-                    )))
-                } else {
+                    )));
+                }
+                publish_actor_state(
+                    &mut change_set,
+                    actor_addr,
+                    actor.state_tag.clone(),
+                    return_values.remove(0).0,
+                )
+                .map_err(partial_vm_error_to_async)?
+            }
+            (
+                Invocation::Handler {
+                    actor,
+                    handler_id,
+                    then,
+                    ..
+                },
+                Some(SerializedReturnValues {
+                    mut mutable_reference_outputs,
+                    return_values,
+                }),
+            ) => {
+                if mutable_reference_outputs.len() > 1 {
+                    return Err(async_extension_error(format!(
+                        "inconsistent handler `{}`",
+                        handler_id
+                    )));
+                }
+                if !mutable_reference_outputs.is_empty() {
                     publish_actor_state(
                         &mut change_set,
                         actor_addr,
                         actor.state_tag.clone(),
-                        return_values.remove(0).0,
+                        mutable_reference_outputs.remove(0).1,
                     )
                     .map_err(partial_vm_error_to_async)?;
-                    let async_ext = native_extensions.remove::<AsyncExtension>();
-                    Ok(AsyncSuccess {
-                        change_set,
-                        events,
-                        messages: async_ext.sent,
-                        gas_used,
-                    })
+                }
+                if let Some(then) = then {
+                    let future_id = return_values
+                        .first()
+                        .and_then(|(blob, _)| bcs::from_bytes::<u64>(blob).ok())
+                        .ok_or_else(|| {
+                            async_extension_error(format!("inconsistent handler `{}`", handler_id))
+                        })?;
+                    link_future(&mut async_ext, future_id, then)
+                        .map_err(partial_vm_error_to_async)?
                 }
             }
-            Err(error) => Err(AsyncError { error, gas_used }),
+            _ => {}
+        }
+
+        // Pass on the values of futures resolved in this session, and persist the pending
+        // futures.
+        resolve_ready_futures(&mut async_ext);
+        if async_ext.futures != loaded_futures {
+            publish_pending_futures(
+                vm,
+                &mut change_set,
+                async_ext.current_actor,
+                &async_ext.futures,
+            )?
         }
+        Ok(AsyncSuccess {
+            change_set,
+            events,
+            messages: async_ext.sent,
+            expired_futures,
+            dropped_replies: vec![],
+            gas_used,
+        })
     }
 
-    /// Handles a message at `actor` with the given `message_hash`. This will call the
-    /// according function as determined by the AsyncResolver, passing a reference to
-    /// the actors state.
-    pub fn handle_message(
-        mut self,
-        actor_addr: AccountAddress,
+    /// Runs an operation on this session. If the operation fails, the error carries the removal
+    /// of the futures which timed out at the start of the session, as those are expired
+    /// independently of the outcome of the operation.
+    fn with_expiry(mut self, operation: impl FnOnce(Self) -> AsyncResult) -> AsyncResult {
+        let actor_addr = self
+            .vm_session
+            .get_native_extensions()
+            .get::<AsyncExtension>()
+            .current_actor;
+        let expired_futures = self.expired_futures.clone();
+        let mut futures = self.loaded_futures.clone();
+        for future_id in &expired_futures {
+            futures.futures.remove(future_id);
+        }
+        let vm = self.vm;
+        operation(self).map_err(|mut error| {
+            if !expired_futures.is_empty() {
+                let mut change_set = ChangeSet::new();
+                match publish_pending_futures(vm, &mut change_set, actor_addr, &futures) {
+                    Ok(()) => {
+                        error.change_set = change_set;
+                        error.expired_futures = expired_futures;
+                    }
+                    Err(serialize_error) => return serialize_error,
+                }
+            }
+            error
+        })
+    }
+
+    fn check_futures(&self) -> Result<(), AsyncError> {
+        match &self.futures_error {
+            Some(error) => Err(async_extension_error(error)),
+            None => Ok(()),
+        }
+    }
+
+    /// Resolves a message hash into the actor and the handler function.
+    fn resolve_handler(
+        &self,
         message_hash: u64,
-        mut args: Vec<Vec<u8>>,
-        gas_status: &mut GasStatus,
-    ) -> AsyncResult {
-        // Resolve actor and function which handles the message.
-        let (module_id, handler_id) =
-            self.vm.message_table.get(&message_hash).ok_or_else(|| {
-                async_extension_error(format!("unknown message hash `{}`", message_hash))
-            })?;
-        let actor = self.vm.actor_metadata.get(module_id).ok_or_else(|| {
+    ) -> Result<(&'l ActorMetadata, &'l IdentStr), AsyncError> {
+        let vm = self.vm;
+        let (module_id, handler_id) = vm.message_table.get(&message_hash).ok_or_else(|| {
+            async_extension_error(format!("unknown message hash `{}`", message_hash))
+        })?;
+        let actor = vm.actor_metadata.get(module_id).ok_or_else(|| {
             async_extension_error(format!(
                 "actor `{}` unknown",
                 module_id.short_str_lossless()
             ))
         })?;
+        Ok((actor, handler_id.as_ident_str()))
+    }
+
+    /// Checks the arguments of a message against the parameters of its handler, and adds the
+    /// actor state as first argument. Returns the arguments and the return types of the handler.
+    fn handler_args(
+        &mut self,
+        actor_addr: AccountAddress,
+        actor: &ActorMetadata,
+        handler_id: &IdentStr,
+        mut args: Vec<Vec<u8>>,
+    ) -> Result<(Vec<Vec<u8>>, Vec<Type>), AsyncError> {
+        // Check the arguments against the parameters of the handler.
+        let returns = self.check_message_args(&actor.module_id, handler_id, &args)?;
 
         // Load the resource representing the actor state and add to arguments.
         let state_type_tag = TypeTag::Struct(actor.state_tag.clone());
@@ -269,51 +703,77 @@ impl<'r, 'l, S: MoveResolver> AsyncSession<'r, 'l, S> {
             self.to_bcs(actor_state, &state_type_tag)
                 .map_err(partial_vm_error_to_async)?,
         );
+        Ok((args, returns))
+    }
 
-        // Execute the handler.
-        let gas_before = gas_status.remaining_gas().get();
-        let result = self
+    /// Checks whether the arguments of a message deserialize to the parameter types of its
+    /// handler, excluding the first parameter which is the actor state. Returns the return
+    /// types of the handler.
+    fn check_message_args(
+        &self,
+        module_id: &ModuleId,
+        handler_id: &IdentStr,
+        args: &[Vec<u8>],
+    ) -> Result<Vec<Type>, AsyncError> {
+        let handler = self
             .vm_session
-            .execute_function_bypass_visibility(module_id, handler_id, vec![], args, gas_status)
-            .and_then(|ret| Ok((ret, self.vm_session.finish_with_extensions()?)));
-
-        let gas_used = gas_status.remaining_gas().get() - gas_before;
-
-        // Process the result, moving the mutated value of the handlers first parameter
-        // into the changeset.
-        match result {
-            Ok((
-                SerializedReturnValues {
-                    mut mutable_reference_outputs,
-                    return_values: _,
-                },
-                (mut change_set, events, mut native_extensions),
-            )) => {
-                if mutable_reference_outputs.len() > 1 {
-                    Err(async_extension_error(format!(
-                        "inconsistent handler `{}`",
-                        handler_id
-                    )))
-                } else {
-                    if !mutable_reference_outputs.is_empty() {
-                        publish_actor_state(
-                            &mut change_set,
-                            actor_addr,
-                            actor.state_tag.clone(),
-                            mutable_reference_outputs.remove(0).1,
-                        )
-                        .map_err(partial_vm_error_to_async)?;
-                    }
-                    let async_ext = native_extensions.remove::<AsyncExtension>();
-                    Ok(AsyncSuccess {
-                        change_set,
-                        events,
-                        messages: async_ext.sent,
-                        gas_used,
-                    })
-                }
+            .load_function(module_id, handler_id, &[])
+            .map_err(vm_error_to_async)?;
+        let handler_name = format!("{}::{}", module_id.short_str_lossless(), handler_id);
+        let params = handler.parameters.get(1..).unwrap_or_default();
+        if params.len() != args.len() {
+            return Err(async_extension_error(format!(
+                "message handler `{}` expects {} argument(s) but {} were given",
+                handler_name,
+                params.len(),
+                args.len()
+            )));
+        }
+        for (i, (param, arg)) in params.iter().zip(args.iter()).enumerate() {
+            let tag = self
+                .vm_session
+                .get_type_tag(param)
+                .map_err(vm_error_to_async)?;
+            let layout = self
+                .vm_session
+                .get_type_layout(&tag)
+                .map_err(vm_error_to_async)?;
+            if Value::simple_deserialize(arg, &layout).is_none() {
+                return Err(async_extension_error(format!(
+                    "argument {} of message handler `{}` cannot be deserialized as `{}`",
+                    i + 1,
+                    handler_name,
+                    tag
+                )));
             }
-            Err(error) => Err(AsyncError { error, gas_used }),
+        }
+        Ok(handler.return_)
+    }
+
+    /// Checks whether a handler returns a future, as required for requests and
+    /// continuations.
+    fn check_returns_future(
+        &self,
+        actor: &ActorMetadata,
+        handler_id: &IdentStr,
+        returns: &[Type],
+    ) -> Result<(), AsyncError> {
+        let returns_future = match returns {
+            [ty] => self
+                .vm_session
+                .get_type_tag(ty)
+                .map(|tag| self.vm.is_future_type(&tag))
+                .map_err(vm_error_to_async)?,
+            _ => false,
+        };
+        if returns_future {
+            Ok(())
+        } else {
+            Err(async_extension_error(format!(
+                "handler `{}::{}` must return a future",
+                actor.module_id.short_str_lossless(),
+                handler_id
+            )))
         }
     }
 
@@ -328,19 +788,72 @@ impl<'r, 'l, S: MoveResolver> AsyncSession<'r, 'l, S> {
     }
 }
 
-fn make_extensions<'a>(
+/// Links the future `future_id`, returned by a handler, to `then`.
+fn link_future(ext: &mut AsyncExtension, future_id: u64, then: Then) -> PartialVMResult<()> {
+    match ext.futures.futures.get_mut(&future_id) {
+        Some(future) if future.then.is_none() => {
+            future.then = Some(then);
+            Ok(())
+        }
+        _ => Err(partial_extension_error(format!(
+            "future `{}` is not pending",
+            future_id
+        ))),
+    }
+}
+
+/// Passes on the values of the futures which have been resolved in this session by
+/// `Future::yield`.
+fn resolve_ready_futures(ext: &mut AsyncExtension) {
+    for (future_id, value) in std::mem::take(&mut ext.ready) {
+        if let Some(future) = ext.futures.futures.remove(&future_id) {
+            pass_on(ext, future_id, future, value)
+        }
+    }
+}
+
+/// Passes the value of the resolved future `future_id`, which has been removed from the pending
+/// futures, on as described by its `then`. A future followed by a continuation stays pending,
+/// and a reply to the actor itself is sent for it, so that the continuation is called in a
+/// session of its own. The value of a future without `then` is dropped.
+fn pass_on(ext: &mut AsyncExtension, future_id: u64, future: PendingFuture, value: Vec<u8>) {
+    match &future.then {
+        Some(Then::Cont { .. }) => {
+            ext.futures.futures.insert(future_id, future);
+            ext.sent.push(Message::Reply {
+                target: ext.current_actor,
+                future_id,
+                value,
+            })
+        }
+        Some(Then::Reply { actor, future_id }) => ext.sent.push(Message::Reply {
+            target: *actor,
+            future_id: *future_id,
+            value,
+        }),
+        Some(Then::Resolve { future_id }) => {
+            // If the future has timed out, the value is dropped.
+            if let Some(next) = ext.futures.futures.remove(future_id) {
+                pass_on(ext, *future_id, next, value)
+            }
+        }
+        None => {}
+    }
+}
+
+fn publish_pending_futures(
+    vm: &AsyncVM,
+    change_set: &mut ChangeSet,
     actor_addr: AccountAddress,
-    virtual_time: u128,
-    in_initializer: bool,
-) -> NativeContextExtensions<'a> {
-    let mut exts = NativeContextExtensions::default();
-    exts.add(AsyncExtension {
-        current_actor: actor_addr,
-        sent: vec![],
-        in_initializer,
-        virtual_time,
-    });
-    exts
+    futures: &PendingFutures,
+) -> Result<(), AsyncError> {
+    let blob = bcs::to_bytes(futures)
+        .and_then(|data| bcs::to_bytes(&data))
+        .map_err(|err| {
+            async_extension_error(format!("cannot serialize pending futures: {}", err))
+        })?;
+    change_set.publish_or_overwrite_resource(actor_addr, vm.pending_futures_tag(), blob);
+    Ok(())
 }
 
 fn publish_actor_state(
@@ -363,14 +876,16 @@ pub(crate) fn extension_error(msg: impl ToString) -> VMError {
 }
 
 fn async_extension_error(msg: impl ToString) -> AsyncError {
-    AsyncError {
-        error: extension_error(msg),
-        gas_used: 0,
-    }
+    vm_error_to_async(extension_error(msg))
 }
 
 fn vm_error_to_async(error: VMError) -> AsyncError {
-    AsyncError { error, gas_used: 0 }
+    AsyncError {
+        error,
+        change_set: ChangeSet::new(),
+        expired_futures: vec![],
+        gas_used: 0,
+    }
 }
 
 fn partial_vm_error_to_async(error: PartialVMError) -> AsyncError {
@@ -394,11 +909,15 @@ impl Display for AsyncSuccess {
             change_set,
             events,
             messages,
+            expired_futures,
+            dropped_replies,
             gas_used,
         } = self;
         write!(f, "change_set: {:?}", change_set)?;
         write!(f, ", events: {:?}", events)?;
         write!(f, ", messages: {:?}", messages)?;
+        write!(f, ", expired_futures: {:?}", expired_futures)?;
+        write!(f, ", dropped_replies: {:?}", dropped_replies)?;
         write!(f, ", gas: {}", gas_used)
     }
 }
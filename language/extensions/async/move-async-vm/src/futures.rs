@@ -0,0 +1,78 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pending futures of an actor. A future is created when an actor sends a request or yields a
+//! value, and is resolved by a reply message addressed to it. The futures of an actor are persisted
+//! in its `Future::PendingFutures` resource between messages.
+
+use move_core_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The pending futures of an actor.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingFutures {
+    /// The id of the next future to create. Ids are never reused.
+    pub next_id: u64,
+    /// The futures which have not been resolved yet, by id.
+    pub futures: BTreeMap<u64, PendingFuture>,
+}
+
+/// A future which has not been resolved yet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingFuture {
+    /// The virtual time after which the future times out.
+    pub deadline: u128,
+    /// What to do with the value of the future once it is resolved. If this is none, the value
+    /// is dropped.
+    pub then: Option<Then>,
+}
+
+/// An action to take with the value of a resolved future.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Then {
+    /// Call the continuation handler with the value and the captured arguments, then resolve
+    /// the future `result` with the future the continuation returns.
+    Cont {
+        message_hash: u64,
+        args: Vec<Vec<u8>>,
+        result: u64,
+    },
+    /// Reply the value to the future `future_id` of `actor`.
+    Reply {
+        actor: AccountAddress,
+        future_id: u64,
+    },
+    /// Resolve the future `future_id` of the same actor with the value.
+    Resolve { future_id: u64 },
+}
+
+impl PendingFutures {
+    /// Creates a new future which times out after `deadline`, and returns its id.
+    pub fn create(&mut self, deadline: u128) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.futures.insert(
+            id,
+            PendingFuture {
+                deadline,
+                then: None,
+            },
+        );
+        id
+    }
+
+    /// Removes all futures which have timed out at `virtual_time`, and returns their ids.
+    pub fn expire(&mut self, virtual_time: u128) -> Vec<u64> {
+        let expired = self
+            .futures
+            .iter()
+            .filter(|(_, f)| f.deadline < virtual_time)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in &expired {
+            self.futures.remove(id);
+        }
+        expired
+    }
+}
@@ -2,4 +2,5 @@
 // SPDX-License-Identifier: Apache-2.0
 pub mod actor_metadata;
 pub mod async_vm;
+pub mod futures;
 pub mod natives;
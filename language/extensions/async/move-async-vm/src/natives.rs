@@ -1,29 +1,61 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::async_vm::Message;
+use crate::{
+    actor_metadata::message_hash,
+    async_vm::{partial_extension_error, Message},
+    futures::{PendingFuture, PendingFutures, Then},
+};
 use better_any::{Tid, TidAble};
-use move_binary_format::errors::PartialVMResult;
-use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+use move_binary_format::errors::{PartialVMError, PartialVMResult};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+    value::MoveTypeLayout,
+};
 use move_vm_runtime::{
     native_functions,
-    native_functions::{NativeContext, NativeFunction},
+    native_functions::{NativeContext, NativeFunctionTable},
 };
 use move_vm_types::{
     gas_schedule::NativeCostIndex,
     loaded_data::runtime_types::Type,
     natives::function::{native_gas, NativeResult},
     pop_arg,
-    values::{Value, Vector},
+    values::{Struct, Value},
 };
 use smallvec::smallvec;
-use std::collections::VecDeque;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    convert::TryFrom,
+};
 
 // TODO: make cost tables extensible; right now we forward to one of the predefined cost indices
 // as an approximation.
 const SELF_COST_INDEX: NativeCostIndex = NativeCostIndex::LENGTH;
 const SEND_COST_INDEX: NativeCostIndex = NativeCostIndex::EMIT_EVENT;
 const EPOCH_TIME_INDEX: NativeCostIndex = NativeCostIndex::LENGTH;
+const FUTURE_COST_INDEX: NativeCostIndex = NativeCostIndex::LENGTH;
+
+/// The prefix of the name of the struct which the compiler generates for a message handler to
+/// carry its arguments. The remainder of the name is the name of the handler.
+pub const MESSAGE_STRUCT_PREFIX: &str = "Message__";
+
+/// The prefix of the name of the struct which the compiler generates for a continuation to
+/// carry its captured arguments. The remainder of the name is the name of the continuation.
+pub const CONT_STRUCT_PREFIX: &str = "Cont__";
+
+/// The number of leading parameters of a message handler which are not carried by its message
+/// struct: the actor state.
+const MESSAGE_IMPLICIT_PARAMS: usize = 1;
+
+/// The number of leading parameters of a continuation which are not carried by its struct: the
+/// actor state and the result of the future it continues.
+const CONT_IMPLICIT_PARAMS: usize = 2;
+
+/// The name of the field the compiler adds to structs which are declared without fields.
+const DUMMY_FIELD_NAME: &str = "dummy_field";
 
 /// Environment extension for the Move VM which we pass down to native functions,
 /// to implement message sending and retrieval of actor address.
@@ -33,25 +65,28 @@ pub struct AsyncExtension {
     pub sent: Vec<Message>,
     pub virtual_time: u128,
     pub in_initializer: bool,
+    /// The pending futures of the current actor, including those created in this session.
+    pub futures: PendingFutures,
+    /// The time after which futures created in this session time out.
+    pub future_deadline: u128,
+    /// The BCS serialized values of futures created by `Future::yield` in this session.
+    pub ready: BTreeMap<u64, Vec<u8>>,
+    /// The continuations created in this session, as message hash and captured arguments,
+    /// indexed by their id.
+    pub conts: Vec<(u64, Vec<Vec<u8>>)>,
 }
 
-pub fn actor_natives(
-    async_addr: AccountAddress,
-) -> Vec<(AccountAddress, Identifier, Identifier, NativeFunction)> {
+pub fn actor_natives(async_addr: AccountAddress) -> NativeFunctionTable {
     native_functions::make_table(
         async_addr,
         &[
             ("Actor", "self", native_self),
             ("Actor", "virtual_time", native_virtual_time),
-            ("Runtime", "send__0", native_send),
-            ("Runtime", "send__1", native_send),
-            ("Runtime", "send__2", native_send),
-            ("Runtime", "send__3", native_send),
-            ("Runtime", "send__4", native_send),
-            ("Runtime", "send__5", native_send),
-            ("Runtime", "send__6", native_send),
-            ("Runtime", "send__7", native_send),
-            ("Runtime", "send__8", native_send),
+            ("Runtime", "send", native_send),
+            ("Runtime", "request", native_request),
+            ("Future", "yield", native_yield),
+            ("Future", "followed_by", native_followed_by),
+            ("Future", "cont", native_cont),
         ],
     )
 }
@@ -71,22 +106,227 @@ fn native_self(
 
 fn native_send(
     context: &mut NativeContext,
-    mut _ty_args: Vec<Type>,
+    ty_args: Vec<Type>,
     mut args: VecDeque<Value>,
 ) -> PartialVMResult<NativeResult> {
-    let ext = context.extensions_mut().get_mut::<AsyncExtension>();
-    let mut bcs_args = vec![];
-    while args.len() > 2 {
-        bcs_args.push(pop_arg!(args, Vector).to_vec_u8()?);
-    }
-    bcs_args.reverse();
-    let message_hash = pop_arg!(args, u64);
+    debug_assert!(ty_args.len() == 1);
+    debug_assert!(args.len() == 2);
+
+    let (message_hash, bcs_args) = message_parts(
+        context,
+        &ty_args[0],
+        pop_arg!(args, Struct),
+        MESSAGE_STRUCT_PREFIX,
+        MESSAGE_IMPLICIT_PARAMS,
+    )?;
     let target = pop_arg!(args, AccountAddress);
-    ext.sent.push((target, message_hash, bcs_args));
-    let cost = native_gas(context.cost_table(), SEND_COST_INDEX, args.len());
+
+    let cost = native_gas(context.cost_table(), SEND_COST_INDEX, bcs_args.len());
+    let ext = context.extensions_mut().get_mut::<AsyncExtension>();
+    ext.sent.push(Message::Send {
+        target,
+        message_hash,
+        args: bcs_args,
+    });
     Ok(NativeResult::ok(cost, smallvec![]))
 }
 
+fn native_request(
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    debug_assert!(ty_args.len() == 2);
+    debug_assert!(args.len() == 2);
+
+    let (message_hash, bcs_args) = message_parts(
+        context,
+        &ty_args[0],
+        pop_arg!(args, Struct),
+        MESSAGE_STRUCT_PREFIX,
+        MESSAGE_IMPLICIT_PARAMS,
+    )?;
+    let target = pop_arg!(args, AccountAddress);
+
+    let cost = native_gas(context.cost_table(), SEND_COST_INDEX, bcs_args.len());
+    let ext = context.extensions_mut().get_mut::<AsyncExtension>();
+    let future_id = ext.futures.create(ext.future_deadline);
+    ext.sent.push(Message::Request {
+        target,
+        message_hash,
+        args: bcs_args,
+        reply_to: ext.current_actor,
+        future_id,
+    });
+    Ok(NativeResult::ok(cost, smallvec![handle_value(future_id)]))
+}
+
+fn native_yield(
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    debug_assert!(ty_args.len() == 1);
+    debug_assert!(args.len() == 1);
+
+    let layout = context
+        .type_to_type_layout(&ty_args[0])?
+        .ok_or_else(|| partial_extension_error("cannot determine layout of yielded value"))?;
+    let value = args
+        .pop_back()
+        .unwrap()
+        .simple_serialize(&layout)
+        .ok_or_else(|| partial_extension_error("cannot serialize yielded value"))?;
+
+    let cost = native_gas(context.cost_table(), FUTURE_COST_INDEX, 1);
+    let ext = context.extensions_mut().get_mut::<AsyncExtension>();
+    let future_id = ext.futures.create(ext.future_deadline);
+    ext.ready.insert(future_id, value);
+    Ok(NativeResult::ok(cost, smallvec![handle_value(future_id)]))
+}
+
+fn native_followed_by(
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    debug_assert!(ty_args.len() == 2);
+    debug_assert!(args.len() == 2);
+
+    let cont_id = handle_id(pop_arg!(args, Struct))?;
+    let future_id = handle_id(pop_arg!(args, Struct))?;
+
+    let cost = native_gas(context.cost_table(), FUTURE_COST_INDEX, 1);
+    let ext = context.extensions_mut().get_mut::<AsyncExtension>();
+    let (message_hash, cont_args) = usize::try_from(cont_id)
+        .ok()
+        .and_then(|i| ext.conts.get(i))
+        .cloned()
+        .ok_or_else(|| partial_extension_error(format!("unknown continuation `{}`", cont_id)))?;
+    let result = ext.futures.create(ext.future_deadline);
+    match ext.futures.futures.get_mut(&future_id) {
+        Some(PendingFuture {
+            then: then @ None, ..
+        }) => {
+            *then = Some(Then::Cont {
+                message_hash,
+                args: cont_args,
+                result,
+            })
+        }
+        _ => {
+            return Err(partial_extension_error(format!(
+                "future `{}` is not pending",
+                future_id
+            )))
+        }
+    }
+    Ok(NativeResult::ok(cost, smallvec![handle_value(result)]))
+}
+
+fn native_cont(
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    debug_assert!(ty_args.len() == 3);
+    debug_assert!(args.len() == 1);
+
+    let cont = message_parts(
+        context,
+        &ty_args[0],
+        pop_arg!(args, Struct),
+        CONT_STRUCT_PREFIX,
+        CONT_IMPLICIT_PARAMS,
+    )?;
+
+    let cost = native_gas(context.cost_table(), FUTURE_COST_INDEX, 1);
+    let ext = context.extensions_mut().get_mut::<AsyncExtension>();
+    let cont_id = ext.conts.len() as u64;
+    ext.conts.push(cont);
+    Ok(NativeResult::ok(cost, smallvec![handle_value(cont_id)]))
+}
+
+/// Determines the handler and the BCS serialized arguments from a value of the struct type `ty`,
+/// which must be a struct generated by the compiler whose name starts with `prefix`, followed by
+/// the name of the handler. The struct carries all but the first `implicit_params` parameters of
+/// the handler.
+fn message_parts(
+    context: &NativeContext,
+    ty: &Type,
+    value: Struct,
+    prefix: &str,
+    implicit_params: usize,
+) -> PartialVMResult<(u64, Vec<Vec<u8>>)> {
+    let (module_id, handler) = match context.type_to_type_tag(ty)? {
+        TypeTag::Struct(tag) => match tag.name.as_str().strip_prefix(prefix) {
+            Some(handler) => match Identifier::new(handler) {
+                Ok(handler) => (tag.module_id(), handler),
+                Err(_) => return Err(not_a_message_type(&tag, prefix)),
+            },
+            None => return Err(not_a_message_type(&tag, prefix)),
+        },
+        tag => {
+            return Err(partial_extension_error(format!(
+                "type `{}` is not a message type",
+                tag
+            )))
+        }
+    };
+    let message_hash = message_hash(&module_id, &handler);
+
+    let layout = match context.type_to_type_layout(ty)? {
+        Some(MoveTypeLayout::Struct(layout)) => layout,
+        _ => return Err(partial_extension_error("cannot determine message layout")),
+    };
+
+    // A handler without arguments has an empty message struct, which the compiler represents
+    // with a single boolean dummy field. That field is not an argument of the handler. The
+    // handler is checked as well, as a handler may name its only argument like the dummy field.
+    let field_names = context.struct_field_names(ty)?;
+    if matches!(
+        (field_names.as_slice(), layout.fields()),
+        ([name], [MoveTypeLayout::Bool]) if name.as_str() == DUMMY_FIELD_NAME
+    ) {
+        let param_count = context.function_parameter_count(&module_id, &handler);
+        if matches!(param_count, Some(count) if count <= implicit_params) {
+            return Ok((message_hash, vec![]));
+        }
+    }
+
+    // Serialize each field of the struct as an argument for the handler.
+    let bcs_args = value
+        .unpack()?
+        .zip(layout.fields())
+        .map(|(value, layout)| {
+            value
+                .simple_serialize(layout)
+                .ok_or_else(|| partial_extension_error("cannot serialize message argument"))
+        })
+        .collect::<PartialVMResult<Vec<_>>>()?;
+    Ok((message_hash, bcs_args))
+}
+
+fn not_a_message_type(tag: &StructTag, prefix: &str) -> PartialVMError {
+    partial_extension_error(format!(
+        "type `{}` is not a message type (expected a struct named `{}<handler>`)",
+        tag, prefix
+    ))
+}
+
+/// Creates a value of type `Future::Future` or `Future::Cont` with the given id.
+fn handle_value(id: u64) -> Value {
+    Value::struct_(Struct::pack(vec![Value::u64(id)]))
+}
+
+/// Extracts the id from a value of type `Future::Future` or `Future::Cont`.
+fn handle_id(handle: Struct) -> PartialVMResult<u64> {
+    match handle.unpack()?.next() {
+        Some(id) => id.value_as::<u64>(),
+        None => Err(partial_extension_error("malformed handle")),
+    }
+}
+
 fn native_virtual_time(
     context: &mut NativeContext,
     mut _ty_args: Vec<Type>,
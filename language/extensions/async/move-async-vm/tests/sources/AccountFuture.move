@@ -0,0 +1,80 @@
+#[actor]
+/// This is an instance of the Account example using futures and continuations.
+///
+/// In contrast to `AccountStateMachine`, the state of an outstanding transfer is not kept in the
+/// actor state. Instead, the account sends a request and registers continuations which are called
+/// once the reply arrives. Continuations are chained: the continuation which credits the
+/// transferred amount itself sends a request, whose reply is passed on to the next continuation.
+
+// dep: BCS
+// dep: Runtime
+// dep: Future
+// dep: Unit
+// dep: Actor
+// future_timeout: 1000000000
+// actor: 0x3 AccountFuture Account init start withdraw balance credit verify
+// instance: 0x3 AccountFuture 0x4
+// instance: 0x3 AccountFuture 0x5
+module Test::AccountFuture {
+    use Async::Actor::self;
+    use Async::Future::{Future, done, followed_by, yield};
+    use Async::Unit::Unit;
+
+    #[state]
+    struct Account {
+        value: u64,
+    }
+
+    #[init]
+    fun init(): Account {
+        Account{value: 100}
+    }
+
+    #[message]
+    /// Entry point for the test run
+    fun start(_this: &Account) {
+        if (self() == @5) {
+            // Start is called for each actor, we only want one of them doing this.
+            return
+        };
+        // Transfer 20 from @5 to @4, then check the balance of @5. By the time the balance is
+        // requested, the last transfer below has been withdrawn as well.
+        let _ = followed_by(
+            followed_by(rpc_withdraw(@5, 20), cont_credit(20)),
+            cont_verify(70)
+        );
+        // A transfer which fails. Its continuation is never called.
+        let _ = followed_by(rpc_withdraw(@5, 1000), cont_credit(1000));
+        // A transfer whose verification fails.
+        let _ = followed_by(
+            followed_by(rpc_withdraw(@5, 10), cont_credit(10)),
+            cont_verify(100)
+        );
+    }
+
+    #[rpc]
+    /// Withdraws from the account, returning the remaining balance.
+    fun withdraw(this: &mut Account, v: u64): Future<u64> {
+        assert!(this.value >= v, 1);
+        this.value = this.value - v;
+        yield(this.value)
+    }
+
+    #[rpc]
+    fun balance(this: &Account): Future<u64> {
+        yield(this.value)
+    }
+
+    #[cont]
+    /// Credits the amount withdrawn from @5, then requests the new balance of @5.
+    fun credit(this: &mut Account, _remaining: u64, amount: u64): Future<u64> {
+        this.value = this.value + amount;
+        rpc_balance(@5)
+    }
+
+    #[cont]
+    fun verify(_this: &Account, balance: u64, expected: u64): Future<Unit> {
+        assert!(balance == expected, 2);
+        done()
+    }
+}
@@ -9,6 +9,8 @@
 // dep: BCS
 // dep: Vector
 // dep: Runtime
+// dep: Future
+// dep: Unit
 // dep: Actor
 // actor: 0x3 AccountStateMachine Account init start end verify deposit withdraw xfer xfer_deposit xfer_finish cleanup
 // instance: 0x3 AccountStateMachine 0x4
@@ -1,5 +1,7 @@
 // dep: BCS
 // dep: Runtime
+// dep: Future
+// dep: Unit
 // actor: 0x3 Basic State init start count_down
 // instance: 0x3 Basic 0x4
 #[actor]
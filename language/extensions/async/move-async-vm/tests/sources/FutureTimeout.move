@@ -0,0 +1,65 @@
+// dep: Runtime
+// dep: Future
+// dep: Unit
+// dep: Actor
+// future_timeout: 35000000
+// actor: 0x3 FutureTimeout State init start idle fail echo received
+// instance: 0x3 FutureTimeout 0x4
+// instance: 0x3 FutureTimeout 0x5
+#[actor]
+/// Checks that futures time out deterministically in virtual time. Each message in the test
+/// harness advances virtual time by about 10 seconds, and futures time out after 35 seconds.
+module Test::FutureTimeout {
+    use Async::Actor::self;
+    use Async::Future::{Future, done, followed_by, yield};
+    use Async::Unit::Unit;
+
+    #[state]
+    struct State {
+        received: u64,
+    }
+
+    #[init]
+    fun init(): State {
+        State{received: 0}
+    }
+
+    #[message]
+    fun start(_s: &State) {
+        if (self() == @5) {
+            return
+        };
+        // This request is answered in time.
+        let _ = followed_by(rpc_echo(@5, 1), cont_received(1));
+    }
+
+    #[message]
+    fun idle(_s: &State) {}
+
+    #[message]
+    fun fail(_s: &State) {
+        abort 2
+    }
+
+    #[rpc]
+    fun echo(_s: &State, v: u64): Future<u64> {
+        yield(v)
+    }
+
+    #[cont]
+    fun received(s: &mut State, v: u64, expected: u64): Future<Unit> {
+        assert!(v == expected, 1);
+        s.received = s.received + 1;
+        if (s.received == 1) {
+            // This request is answered after the idle messages, when its future has timed out.
+            send_idle(@5);
+            send_idle(@5);
+            send_idle(@5);
+            let _ = followed_by(rpc_echo(@5, 2), cont_received(2));
+            // The futures time out when this message is handled, even though it fails. The
+            // reply to the request arrives after that, and is dropped.
+            send_fail(self());
+        };
+        done()
+    }
+}
@@ -0,0 +1,57 @@
+// dep: Runtime
+// dep: Future
+// dep: Unit
+// actor: 0x3 MessageErrors State init start f g h k d
+// instance: 0x3 MessageErrors 0x4
+/// Constructs messages by hand instead of using the code derived for actors, to check how the
+/// runtime validates them.
+module Test::MessageErrors {
+    use Async::Runtime;
+
+    struct State {
+        value: u64,
+    }
+
+    struct Message__f { x: bool }
+    struct Message__g { x: u64, y: u64 }
+    struct Message__h {}
+    struct Message__k { x: bool }
+    struct Message__d { dummy_field: bool }
+    struct NotAMessage { x: u64 }
+
+    fun init(): State {
+        State{value: 0}
+    }
+
+    fun start(_s: &State) {
+        // Argument type does not match the handler.
+        Runtime::send(@4, Message__f{x: true});
+        // Argument count does not match the handler.
+        Runtime::send(@4, Message__g{x: 1, y: 2});
+        Runtime::send(@4, Message__h{});
+        // A handler without arguments does not accept a `false` argument.
+        Runtime::send(@4, Message__k{x: false});
+        // A handler argument named like the dummy field of empty structs is still passed.
+        Runtime::send(@4, Message__d{dummy_field: true});
+    }
+
+    fun f(s: &mut State, x: u64) {
+        s.value = x
+    }
+
+    fun g(s: &mut State, x: u64) {
+        s.value = x
+    }
+
+    fun h(_s: &State) {
+        // Type is not a message.
+        Runtime::send(@4, NotAMessage{x: 1});
+    }
+
+    fun k(_s: &State) {
+    }
+
+    fun d(s: &mut State, dummy_field: bool) {
+        if (dummy_field) s.value = 1
+    }
+}
@@ -59,12 +59,17 @@ fn test_runner(path: &Path) -> datatest_stable::Result<()> {
     let deps = extract_test_directives(path, "// dep:")?;
     let actors = extract_test_directives(path, "// actor: ")?;
     let instances = extract_test_directives(path, "// instance: ")?;
+    let future_timeout = extract_test_directives(path, "// future_timeout: ")?
+        .first()
+        .map(|t| t.parse::<u128>())
+        .transpose()?;
     let harness = Harness::new(
         std::iter::once(target_module.clone())
             .chain(deps.into_iter())
             .collect(),
         actors,
         instances,
+        future_timeout,
     )?;
     harness.run(&target_module)?;
     let baseline_path = path.with_extension(EXP_EXT);
@@ -106,32 +111,55 @@ impl Harness {
             // Put a start message for this actor into the mailbox.
             let entry_point_id = Identifier::from_str("start")?;
             let hash = actor_metadata::message_hash(&actor, &entry_point_id);
-            mailbox.push_back((addr, hash, vec![]));
+            mailbox.push_back(Message::Send {
+                target: addr,
+                message_hash: hash,
+                args: vec![],
+            });
         }
 
         // Handle messages until the mailbox is empty.
-        while let Some((actor, message_hash, args)) = mailbox.pop_front() {
+        while let Some(message) = mailbox.pop_front() {
             // Baseline logging
-            if let Some((module_id, fun_id)) = self.vm.resolve_message_hash(message_hash).cloned() {
-                self.log(format!(
-                    "actor 0x{} handling {}::{} (hash=0x{:X})",
-                    actor.short_str_lossless(),
-                    module_id.short_str_lossless(),
-                    fun_id,
-                    message_hash
-                ));
-            } else {
-                self.log(format!(
-                    "actor 0x{} handling ???? (hash={})",
+            let actor = message.target();
+            match &message {
+                Message::Send { message_hash, .. } | Message::Request { message_hash, .. } => {
+                    let kind = if matches!(message, Message::Send { .. }) {
+                        ""
+                    } else {
+                        "request "
+                    };
+                    if let Some((module_id, fun_id)) =
+                        self.vm.resolve_message_hash(*message_hash).cloned()
+                    {
+                        self.log(format!(
+                            "actor 0x{} handling {}{}::{} (hash=0x{:X})",
+                            actor.short_str_lossless(),
+                            kind,
+                            module_id.short_str_lossless(),
+                            fun_id,
+                            message_hash
+                        ));
+                    } else {
+                        self.log(format!(
+                            "actor 0x{} handling {}???? (hash={})",
+                            actor.short_str_lossless(),
+                            kind,
+                            message_hash
+                        ))
+                    }
+                }
+                Message::Reply { future_id, .. } => self.log(format!(
+                    "actor 0x{} handling reply to future {}",
                     actor.short_str_lossless(),
-                    message_hash
-                ))
+                    future_id
+                )),
             }
             // Handling
             let mut proxy = HarnessProxy { harness: self };
             let session = self.vm.new_session(actor, tick, &mut proxy);
             tick += 1000_1000; // micros
-            let result = session.handle_message(actor, message_hash, args, &mut gas);
+            let result = session.deliver(message, &mut gas);
             self.handle_result(&mut mailbox, result);
         }
         Ok(())
@@ -164,18 +192,60 @@ impl Harness {
         match result {
             Ok(success) => {
                 self.log("  SUCCESS");
+                if !success.expired_futures.is_empty() {
+                    self.log(format!("  expired futures {:?}", success.expired_futures))
+                }
+                if !success.dropped_replies.is_empty() {
+                    self.log(format!("  dropped replies {:?}", success.dropped_replies))
+                }
                 for m in &success.messages {
-                    self.log(format!(
-                        "  sent 0x{} <- 0x{:X} argc={}",
-                        m.0.short_str_lossless(),
-                        m.1,
-                        m.2.len()
-                    ))
+                    self.log(match m {
+                        Message::Send {
+                            target,
+                            message_hash,
+                            args,
+                        } => format!(
+                            "  sent 0x{} <- 0x{:X} argc={}",
+                            target.short_str_lossless(),
+                            message_hash,
+                            args.len()
+                        ),
+                        Message::Request {
+                            target,
+                            message_hash,
+                            args,
+                            reply_to,
+                            future_id,
+                        } => format!(
+                            "  sent request 0x{} <- 0x{:X} argc={} reply_to=0x{} future={}",
+                            target.short_str_lossless(),
+                            message_hash,
+                            args.len(),
+                            reply_to.short_str_lossless(),
+                            future_id
+                        ),
+                        Message::Reply {
+                            target,
+                            future_id,
+                            value,
+                        } => format!(
+                            "  sent reply 0x{} <- future={} value={:02X?}",
+                            target.short_str_lossless(),
+                            future_id,
+                            value
+                        ),
+                    })
                 }
                 mailbox.extend(success.messages);
                 self.commit_changeset(success.change_set)
             }
-            Err(error) => self.log(format!("  FAIL  {:}", error)),
+            Err(error) => {
+                self.log(format!("  FAIL  {:}", error));
+                if !error.expired_futures.is_empty() {
+                    self.log(format!("  expired futures {:?}", error.expired_futures))
+                }
+                self.commit_changeset(error.change_set)
+            }
         }
     }
 
@@ -215,6 +285,7 @@ impl Harness {
         modules: Vec<String>,
         actors: Vec<String>,
         instances: Vec<String>,
+        future_timeout: Option<u128>,
     ) -> anyhow::Result<Self> {
         // Create address map. We are mapping all aliases to TEST_ADDR for simplicity.
         let test_addr = NumericalAddress::parse_str(TEST_ADDR).unwrap();
@@ -230,15 +301,19 @@ impl Harness {
         let actor_instances = Self::collect_instances(instances)?;
         let module_files = Self::collect_modules(modules)?;
         let module_cache = Self::compile(&address_map, &module_files)?;
+        let mut vm = AsyncVM::new(
+            test_account(),
+            move_stdlib::natives::all_natives(test_account()),
+            actor_metadata,
+        )?;
+        if let Some(timeout) = future_timeout {
+            vm.set_future_timeout(timeout)
+        }
         let harness = Harness {
             baseline: Default::default(),
             module_cache,
             resource_store: Default::default(),
-            vm: AsyncVM::new(
-                test_account(),
-                move_stdlib::natives::all_natives(test_account()),
-                actor_metadata,
-            )?,
+            vm,
             actor_instances,
         };
         Ok(harness)
@@ -0,0 +1,176 @@
+/// Type of large-scale storage tables which can be iterated in Move.
+///
+/// An `IterableTable` wraps a `Table` and links its entries into a doubly linked list over
+/// their keys, in insertion order. Starting from `head_key` or `tail_key`, the `*_iter`
+/// functions give access to an entry together with the keys of its neighbours.
+module Extensions::IterableTable {
+    use Std::Errors;
+    use Std::Option::{Self, Option};
+    use Extensions::Table::{Self, Table};
+
+    const ENOT_EMPTY: u64 = 102;
+
+    /// The wrapper around a value, which links to the keys of the previous and next entry.
+    struct IterableValue<K: copy + store + drop, V: store> has store {
+        val: V,
+        prev: Option<K>,
+        next: Option<K>,
+    }
+
+    /// Type of iterable tables
+    struct IterableTable<K: copy + store + drop, V: store> has store {
+        inner: Table<K, IterableValue<K, V>>,
+        head: Option<K>,
+        tail: Option<K>,
+    }
+
+    /// Create a new IterableTable.
+    public fun new<K: copy + store + drop, V: store>(): IterableTable<K, V> {
+        IterableTable {
+            inner: Table::new(),
+            head: Option::none(),
+            tail: Option::none(),
+        }
+    }
+
+    /// Destroy a table. The table must be empty to succeed.
+    public fun destroy_empty<K: copy + store + drop, V: store>(table: IterableTable<K, V>) {
+        assert!(empty(&table), Errors::invalid_state(ENOT_EMPTY));
+        let IterableTable { inner, head: _, tail: _ } = table;
+        Table::destroy_empty(inner);
+    }
+
+    /// Add a new entry to the end of the table. Aborts if an entry for this key already exists.
+    public fun add<K: copy + store + drop, V: store>(table: &mut IterableTable<K, V>, key: &K, val: V) {
+        let wrapped_value = IterableValue {
+            val,
+            prev: table.tail,
+            next: Option::none(),
+        };
+        Table::add(&mut table.inner, key, wrapped_value);
+        if (Option::is_some(&table.tail)) {
+            let k = Option::borrow(&table.tail);
+            Table::borrow_mut(&mut table.inner, k).next = Option::some(*key);
+        } else {
+            table.head = Option::some(*key);
+        };
+        table.tail = Option::some(*key);
+    }
+
+    /// Remove from `table` and return the value which `key` maps to.
+    /// Aborts if there is no entry for `key`.
+    public fun remove<K: copy + store + drop, V: store>(table: &mut IterableTable<K, V>, key: &K): V {
+        let (val, _, _) = remove_iter(table, key);
+        val
+    }
+
+    /// Acquire an immutable reference to the value which `key` maps to.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow<K: copy + store + drop, V: store>(table: &IterableTable<K, V>, key: &K): &V {
+        &Table::borrow(&table.inner, key).val
+    }
+
+    /// Acquire a mutable reference to the value which `key` maps to.
+    /// Aborts if there is no entry for `key`.
+    public fun borrow_mut<K: copy + store + drop, V: store>(table: &mut IterableTable<K, V>, key: &K): &mut V {
+        &mut Table::borrow_mut(&mut table.inner, key).val
+    }
+
+    /// Acquire a mutable reference to the value which `key` maps to.
+    /// Insert the pair (`key`, `default`) first if there is no entry for `key`.
+    public fun borrow_mut_with_default<K: copy + store + drop, V: store + drop>(
+        table: &mut IterableTable<K, V>,
+        key: &K,
+        default: V
+    ): &mut V {
+        if (!contains(table, key)) {
+            add(table, key, default)
+        };
+        borrow_mut(table, key)
+    }
+
+    /// Returns the length of the table, i.e. the number of entries.
+    public fun length<K: copy + store + drop, V: store>(table: &IterableTable<K, V>): u64 {
+        Table::length(&table.inner)
+    }
+
+    /// Returns true if this table is empty.
+    public fun empty<K: copy + store + drop, V: store>(table: &IterableTable<K, V>): bool {
+        Table::empty(&table.inner)
+    }
+
+    /// Returns true iff `table` contains an entry for `key`.
+    public fun contains<K: copy + store + drop, V: store>(table: &IterableTable<K, V>, key: &K): bool {
+        Table::contains(&table.inner, key)
+    }
+
+    // ======================================================================================================
+    // Iteration
+
+    /// Returns the key of the first entry of the table, or none if the table is empty.
+    public fun head_key<K: copy + store + drop, V: store>(table: &IterableTable<K, V>): Option<K> {
+        table.head
+    }
+
+    /// Returns the key of the last entry of the table, or none if the table is empty.
+    public fun tail_key<K: copy + store + drop, V: store>(table: &IterableTable<K, V>): Option<K> {
+        table.tail
+    }
+
+    /// Acquire an immutable reference to the value which `key` maps to, together with the
+    /// keys of the previous and next entries. Aborts if there is no entry for `key`.
+    public fun borrow_iter<K: copy + store + drop, V: store>(
+        table: &IterableTable<K, V>,
+        key: &K
+    ): (&V, Option<K>, Option<K>) {
+        let v = Table::borrow(&table.inner, key);
+        (&v.val, v.prev, v.next)
+    }
+
+    /// Acquire a mutable reference to the value which `key` maps to, together with the
+    /// keys of the previous and next entries. Aborts if there is no entry for `key`.
+    public fun borrow_iter_mut<K: copy + store + drop, V: store>(
+        table: &mut IterableTable<K, V>,
+        key: &K
+    ): (&mut V, Option<K>, Option<K>) {
+        let v = Table::borrow_mut(&mut table.inner, key);
+        (&mut v.val, v.prev, v.next)
+    }
+
+    /// Remove from `table` and return the value which `key` maps to, together with the keys
+    /// of the previous and next entries. Aborts if there is no entry for `key`.
+    public fun remove_iter<K: copy + store + drop, V: store>(
+        table: &mut IterableTable<K, V>,
+        key: &K
+    ): (V, Option<K>, Option<K>) {
+        let IterableValue { val, prev, next } = Table::remove(&mut table.inner, key);
+        if (Option::is_some(&prev)) {
+            Table::borrow_mut(&mut table.inner, Option::borrow(&prev)).next = next;
+        } else {
+            table.head = next;
+        };
+        if (Option::is_some(&next)) {
+            Table::borrow_mut(&mut table.inner, Option::borrow(&next)).prev = prev;
+        } else {
+            table.tail = prev;
+        };
+        (val, prev, next)
+    }
+
+    /// Remove all entries from `v2` and append them to `v1`, preserving their order.
+    public fun append<K: copy + store + drop, V: store>(v1: &mut IterableTable<K, V>, v2: &mut IterableTable<K, V>) {
+        let key = v2.head;
+        while (Option::is_some(&key)) {
+            let (val, _, next) = remove_iter(v2, Option::borrow(&key));
+            add(v1, Option::borrow(&key), val);
+            key = next;
+        };
+    }
+
+    #[test_only]
+    /// Testing only: allows to drop a table even if it is not empty.
+    public fun drop_unchecked<K: copy + store + drop, V: store>(table: IterableTable<K, V>) {
+        let IterableTable { inner, head: _, tail: _ } = table;
+        Table::drop_unchecked(inner);
+    }
+}
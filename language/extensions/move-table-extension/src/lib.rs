@@ -3,7 +3,8 @@
 
 //! A crate which extends Move by tables.
 //!
-//! See [`Table.move`](../sources/Table.move) for language use.
+//! See [`Table.move`](../sources/Table.move) and [`IterableTable.move`](../sources/IterableTable.move)
+//! for language use.
 //! See [`README.md`](../README.md) for integration into an adapter.
 
 use better_any::{Tid, TidAble};
@@ -11,10 +12,12 @@ use move_binary_format::errors::{PartialVMError, PartialVMResult};
 use move_core_types::{
     account_address::AccountAddress,
     gas_schedule::{GasAlgebra, GasCarrier, InternalGasUnits},
+    language_storage::TypeTag,
     value::MoveTypeLayout,
     vm_status::StatusCode,
 };
 use move_vm_runtime::{
+    native_extensions::SessionExtension,
     native_functions,
     native_functions::{NativeContext, NativeFunctionTable},
 };
@@ -22,7 +25,7 @@ use move_vm_types::{
     loaded_data::runtime_types::Type,
     natives::function::NativeResult,
     pop_arg,
-    values::{GlobalValue, GlobalValueEffect, Reference, StructRef, Value},
+    values::{GlobalValue, GlobalValueEffect, Reference, Struct, StructRef, Value},
 };
 use sha3::{Digest, Sha3_256};
 use smallvec::smallvec;
@@ -44,21 +47,32 @@ pub struct TableHandle(pub u128);
 
 impl Display for TableHandle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "T-{:X}", self.0)
+        write!(f, "T-{:X}", self.0)
     }
 }
 
 /// A table change set.
-#[derive(Default)]
+#[derive(Default, Debug)]
 pub struct TableChangeSet {
     pub new_tables: BTreeSet<TableHandle>,
     pub removed_tables: BTreeSet<TableHandle>,
+    /// Tables which were dropped via `drop_unchecked` instead of being destroyed. Their entries
+    /// are no longer reachable from Move, and should be garbage collected by the environment,
+    /// for example by enumerating them with `TableResolver::iter_table_entries`. If the values
+    /// of a dropped table are tables themselves, those nested tables are included as well,
+    /// unless they are only in remote storage and the resolver cannot iterate the dropped table.
+    pub dropped_tables: BTreeSet<TableHandle>,
     pub changes: BTreeMap<TableHandle, TableChange>,
 }
 
 /// A change of a single table.
+#[derive(Debug)]
 pub struct TableChange {
     pub entries: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+    /// The net change of the number of bytes stored for the table, counting both keys and
+    /// values. This is negative if the change frees storage, and allows the environment to
+    /// charge deposits for new entries and refund them on deletion.
+    pub bytes_delta: i64,
 }
 
 /// A table resolver which needs to be provided by the environment. This allows to lookup
@@ -70,6 +84,21 @@ pub trait TableResolver {
         key: &[u8],
     ) -> Result<Option<Vec<u8>>, anyhow::Error>;
 
+    /// Returns up to `limit` entries of the table, ordered by their serialized keys, starting
+    /// with the first key which is greater or equal to `start_key`. Move code cannot enumerate
+    /// a table; this is meant for off-chain reads such as indexers and tooling. Resolvers which
+    /// cannot enumerate their tables keep the default, which reports iteration as unsupported.
+    fn iter_table_entries(
+        &self,
+        _handle: &TableHandle,
+        _start_key: Option<&[u8]>,
+        _limit: usize,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, anyhow::Error> {
+        Err(anyhow::anyhow!(
+            "iterating over table entries is not supported by this resolver"
+        ))
+    }
+
     fn operation_cost(
         &self,
         op: TableOperation,
@@ -97,6 +126,7 @@ pub struct NativeTableContext<'a> {
     resolver: &'a dyn TableResolver,
     txn_hash: u128,
     table_data: RefCell<TableData>,
+    savepoints: Vec<TableData>,
 }
 
 // See stdlib/Error.move
@@ -117,6 +147,7 @@ const _NOT_EMPTY: u64 = (102 << 8) + _ECATEGORY_INVALID_STATE as u64;
 struct TableData {
     new_tables: BTreeSet<TableHandle>,
     removed_tables: BTreeSet<TableHandle>,
+    dropped_tables: BTreeSet<TableHandle>,
     tables: BTreeMap<TableHandle, Table>,
 }
 
@@ -126,11 +157,24 @@ struct Table {
     key_layout: MoveTypeLayout,
     value_layout: MoveTypeLayout,
     content: BTreeMap<Vec<u8>, GlobalValue>,
+    /// For each entry in `content`, the size of its value in remote storage at the time it
+    /// was loaded, or `None` if there was no such entry. Used to compute storage deltas.
+    remote_sizes: BTreeMap<Vec<u8>, Option<usize>>,
 }
 
 /// The field index of the `handle` field in the `Table` Move struct.
 const HANDLE_FIELD_INDEX: usize = 0;
 
+/// The field index of the `val` field in the `Box` Move struct.
+const VAL_FIELD_INDEX: usize = 0;
+
+/// The name of the `Table` Move struct, used to recognize tables nested in table values.
+const TABLE_STRUCT_NAME: &str = "Table";
+
+/// The number of remote entries read at a time when looking for the nested tables of a dropped
+/// table.
+const DROP_PAGE_SIZE: usize = 64;
+
 // =========================================================================================
 // Implementation of Native Table Context
 
@@ -142,6 +186,7 @@ impl<'a> NativeTableContext<'a> {
             resolver,
             txn_hash,
             table_data: Default::default(),
+            savepoints: vec![],
         }
     }
 
@@ -151,6 +196,7 @@ impl<'a> NativeTableContext<'a> {
         let TableData {
             new_tables,
             removed_tables,
+            dropped_tables,
             tables,
         } = table_data.into_inner();
         let mut changes = BTreeMap::new();
@@ -158,34 +204,100 @@ impl<'a> NativeTableContext<'a> {
             let Table {
                 value_layout,
                 content,
+                remote_sizes,
                 ..
             } = table;
             let mut entries = BTreeMap::new();
+            let mut bytes_delta = 0;
             for (key, gv) in content {
+                let old_size = match remote_sizes.get(&key) {
+                    Some(Some(val_size)) => (key.len() + val_size) as i64,
+                    _ => 0,
+                };
                 match gv.into_effect()? {
                     GlobalValueEffect::Deleted => {
+                        bytes_delta -= old_size;
                         entries.insert(key, None);
                     }
                     GlobalValueEffect::Changed(new_val) => {
                         let new_bytes = serialize(&value_layout, &new_val)?;
+                        bytes_delta += (key.len() + new_bytes.len()) as i64 - old_size;
                         entries.insert(key, Some(new_bytes));
                     }
                     _ => {}
                 }
             }
             if !entries.is_empty() {
-                changes.insert(handle, TableChange { entries });
+                changes.insert(
+                    handle,
+                    TableChange {
+                        entries,
+                        bytes_delta,
+                    },
+                );
             }
         }
         Ok(TableChangeSet {
             new_tables,
             removed_tables,
+            dropped_tables,
             changes,
         })
     }
 }
 
+impl<'a> SessionExtension for NativeTableContext<'a> {
+    fn savepoint(&mut self) -> PartialVMResult<()> {
+        let copy = self.table_data.get_mut().copy_value()?;
+        self.savepoints.push(copy);
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> PartialVMResult<()> {
+        let savepoint = self
+            .savepoints
+            .last()
+            .ok_or_else(|| partial_extension_error("no savepoint to roll back to"))?;
+        *self.table_data.get_mut() = savepoint.copy_value()?;
+        Ok(())
+    }
+
+    fn release(&mut self) -> PartialVMResult<()> {
+        self.savepoints
+            .pop()
+            .map(|_| ())
+            .ok_or_else(|| partial_extension_error("no savepoint to release"))
+    }
+}
+
 impl TableData {
+    /// Makes a deep copy of the table data, used to implement savepoints.
+    fn copy_value(&self) -> PartialVMResult<Self> {
+        let mut tables = BTreeMap::new();
+        for (handle, table) in &self.tables {
+            let mut content = BTreeMap::new();
+            for (key, gv) in &table.content {
+                content.insert(key.clone(), gv.copy_value()?);
+            }
+            tables.insert(
+                *handle,
+                Table {
+                    handle: table.handle,
+                    key_layout: table.key_layout.clone(),
+                    value_layout: table.value_layout.clone(),
+                    content,
+                    remote_sizes: table.remote_sizes.clone(),
+                },
+            );
+        }
+        Ok(Self {
+            new_tables: self.new_tables.clone(),
+            removed_tables: self.removed_tables.clone(),
+            dropped_tables: self.dropped_tables.clone(),
+            tables,
+        })
+    }
+
     /// Gets or creates a new table in the TableData. This initializes information about
     /// the table, like the type layout for keys and values.
     fn get_or_create_table(
@@ -203,11 +315,98 @@ impl TableData {
                 key_layout,
                 value_layout,
                 content: Default::default(),
+                remote_sizes: Default::default(),
             };
             e.insert(table);
         }
         Ok(self.tables.get_mut(&handle).unwrap())
     }
+
+    /// Marks a table as dropped. If the values of the table are tables themselves, as described
+    /// by `nesting_depth`, the tables stored in it are unreachable after the drop, and are marked
+    /// as dropped as well. Each dropped table is charged like a destroyed one, and each remote
+    /// entry which needs to be read to find nested tables like a borrow.
+    fn drop_table(
+        &mut self,
+        context: &NativeTableContext,
+        handle: TableHandle,
+        nesting_depth: usize,
+    ) -> PartialVMResult<InternalGasUnits<GasCarrier>> {
+        let mut cost = InternalGasUnits::new(0_u64);
+        let mut pending = vec![(handle, nesting_depth)];
+        while let Some((handle, nesting_depth)) = pending.pop() {
+            // `destroy_empty` also drops the table after destroying it, which is not a leak.
+            if self.removed_tables.contains(&handle) || !self.dropped_tables.insert(handle) {
+                continue;
+            }
+            cost = cost.add(
+                context
+                    .resolver
+                    .operation_cost(TableOperation::Destroy, 0, 0),
+            );
+            if nesting_depth == 0 {
+                continue;
+            }
+            let content = self.tables.get(&handle).map(|table| &table.content);
+            if let Some(content) = content {
+                for gv in content.values() {
+                    if gv.exists()? {
+                        let val = gv.borrow_global()?.value_as::<StructRef>()?;
+                        let table = val.borrow_field(VAL_FIELD_INDEX)?.value_as::<StructRef>()?;
+                        pending.push((get_table_handle(&table)?, nesting_depth - 1));
+                    }
+                }
+            }
+            // A table created by this transaction has no entries in remote storage.
+            if self.new_tables.contains(&handle) {
+                continue;
+            }
+            // Remote entries are read page by page, and each page is charged before the next one
+            // is read. If the resolver cannot iterate the table, its nested tables in remote
+            // storage are not found, and only the table itself is reported as dropped.
+            let mut start_key = None;
+            loop {
+                let page = match context.resolver.iter_table_entries(
+                    &handle,
+                    start_key.as_deref(),
+                    DROP_PAGE_SIZE,
+                ) {
+                    Ok(page) => page,
+                    Err(_) if start_key.is_none() => break,
+                    Err(err) => {
+                        return Err(partial_extension_error(format!(
+                            "remote table resolver failure: {}",
+                            err
+                        )))
+                    }
+                };
+                let full_page = page.len() == DROP_PAGE_SIZE;
+                for (key, val_bytes) in &page {
+                    cost = cost.add(context.resolver.operation_cost(
+                        TableOperation::Borrow,
+                        key.len(),
+                        val_bytes.len(),
+                    ));
+                    if matches!(content, Some(content) if content.contains_key(key)) {
+                        continue;
+                    }
+                    // A nested table is serialized as its handle followed by its length.
+                    let (nested_handle, _length) = bcs::from_bytes::<(u128, u64)>(val_bytes)
+                        .map_err(|_| partial_extension_error("cannot deserialize nested table"))?;
+                    pending.push((TableHandle(nested_handle), nesting_depth - 1));
+                }
+                match page.into_iter().last() {
+                    // The next page starts at the smallest key greater than the last one read.
+                    Some((mut last_key, _)) if full_page => {
+                        last_key.push(0);
+                        start_key = Some(last_key);
+                    }
+                    _ => break,
+                }
+            }
+        }
+        Ok(cost)
+    }
 }
 
 impl Table {
@@ -288,7 +487,7 @@ impl Table {
         let mut val_size = 0;
         if !self.content.contains_key(&key_bytes) {
             // Try to retrieve a value from the remote resolver.
-            let gv = match context
+            let (gv, remote_size) = match context
                 .resolver
                 .resolve_table_entry(&self.handle, &key_bytes)
                 .map_err(|err| {
@@ -297,11 +496,12 @@ impl Table {
                 Some(val_bytes) => {
                     val_size = val_bytes.len();
                     let val = deserialize(&self.value_layout, &val_bytes)?;
-                    GlobalValue::cached(val)?
+                    (GlobalValue::cached(val)?, Some(val_size))
                 }
-                None => GlobalValue::none(),
+                None => (GlobalValue::none(), None),
             };
             self.content.insert(key_bytes.clone(), gv);
+            self.remote_sizes.insert(key_bytes.clone(), remote_size);
         }
 
         let gv = self.content.get_mut(&key_bytes).unwrap();
@@ -504,14 +704,21 @@ fn native_destroy_empty_box(
 }
 
 fn native_drop_unchecked_box(
-    _context: &mut NativeContext,
+    context: &mut NativeContext,
     ty_args: Vec<Type>,
-    args: VecDeque<Value>,
+    mut args: VecDeque<Value>,
 ) -> PartialVMResult<NativeResult> {
     assert!(ty_args.len() == 3);
     assert!(args.len() == 1);
 
-    Ok(NativeResult::ok(InternalGasUnits::new(0_u64), smallvec![]))
+    let table_context = context.extensions().get::<NativeTableContext>();
+    let mut table_data = table_context.table_data.borrow_mut();
+
+    let handle = get_table_handle_of_value(pop_arg!(args, Struct))?;
+    let nesting_depth = get_nesting_depth(context, &ty_args[2])?;
+    let cost = table_data.drop_table(table_context, handle, nesting_depth)?;
+
+    Ok(NativeResult::ok(cost, smallvec![]))
 }
 
 // =========================================================================================
@@ -524,6 +731,37 @@ fn get_table_handle(table: &StructRef) -> PartialVMResult<TableHandle> {
     field_ref.read_ref()?.value_as::<u128>().map(TableHandle)
 }
 
+fn get_table_handle_of_value(table: Struct) -> PartialVMResult<TableHandle> {
+    table
+        .unpack()?
+        .nth(HANDLE_FIELD_INDEX)
+        .ok_or_else(|| partial_extension_error("table has no handle field"))?
+        .value_as::<u128>()
+        .map(TableHandle)
+}
+
+/// Returns how many levels of tables are nested in the values of a table with the given box
+/// type, e.g. 0 for `Table<u64, u64>` and 1 for `Table<u64, Table<u64, u64>>`.
+fn get_nesting_depth(context: &NativeContext, box_ty: &Type) -> PartialVMResult<usize> {
+    let box_tag = match context.type_to_type_tag(box_ty)? {
+        TypeTag::Struct(tag) => tag,
+        _ => return Err(partial_extension_error("table box is not a struct")),
+    };
+    let mut depth = 0;
+    let mut val_tag = &box_tag.type_params[0];
+    while let TypeTag::Struct(tag) = val_tag {
+        if tag.address != box_tag.address
+            || tag.module != box_tag.module
+            || tag.name.as_str() != TABLE_STRUCT_NAME
+        {
+            break;
+        }
+        depth += 1;
+        val_tag = &tag.type_params[1];
+    }
+    Ok(depth)
+}
+
 fn serialize(layout: &MoveTypeLayout, val: &Value) -> PartialVMResult<Vec<u8>> {
     val.simple_serialize(layout)
         .ok_or_else(|| partial_extension_error("cannot serialize table key or value"))
@@ -0,0 +1,147 @@
+#[test_only]
+module Extensions::IterableTableTests {
+    use Std::Option;
+    use Extensions::IterableTable as T;
+
+    struct S<K: copy + store + drop, V: store> has key {
+        t: T::IterableTable<K, V>
+    }
+
+    #[test]
+    fun simple_read_write() {
+        let t = T::new<u64, u64>();
+        T::add(&mut t, &1, 2);
+        T::add(&mut t, &10, 33);
+        assert!(*T::borrow(&t, &1) == 2, 1);
+        assert!(*T::borrow(&t, &10) == 33, 2);
+        *T::borrow_mut(&mut t, &1) = 3;
+        assert!(*T::borrow(&t, &1) == 3, 3);
+        assert!(T::length(&t) == 2, 4);
+        T::drop_unchecked(t)
+    }
+
+    #[test]
+    fun iterate_forward_and_backward() {
+        let t = T::new<u64, u64>();
+        let i = 0;
+        while (i < 10) {
+            T::add(&mut t, &i, i * 10);
+            i = i + 1;
+        };
+        assert!(T::head_key(&t) == Option::some(0), 1);
+        assert!(T::tail_key(&t) == Option::some(9), 2);
+
+        let key = T::head_key(&t);
+        let count = 0;
+        while (Option::is_some(&key)) {
+            let k = *Option::borrow(&key);
+            let (val, _, next) = T::borrow_iter(&t, &k);
+            assert!(k == count, 3);
+            assert!(*val == k * 10, 4);
+            key = next;
+            count = count + 1;
+        };
+        assert!(count == 10, 5);
+
+        let key = T::tail_key(&t);
+        while (Option::is_some(&key)) {
+            let k = *Option::borrow(&key);
+            let (val, prev, _) = T::borrow_iter_mut(&mut t, &k);
+            *val = *val + 1;
+            key = prev;
+            count = count - 1;
+        };
+        assert!(count == 0, 6);
+        assert!(*T::borrow(&t, &5) == 51, 7);
+        T::drop_unchecked(t)
+    }
+
+    #[test]
+    fun remove_relinks_neighbours() {
+        let t = T::new<u64, u64>();
+        T::add(&mut t, &1, 1);
+        T::add(&mut t, &2, 2);
+        T::add(&mut t, &3, 3);
+
+        let (val, prev, next) = T::remove_iter(&mut t, &2);
+        assert!(val == 2, 1);
+        assert!(prev == Option::some(1), 2);
+        assert!(next == Option::some(3), 3);
+        let (_, prev, next) = T::borrow_iter(&t, &1);
+        assert!(Option::is_none(&prev) && next == Option::some(3), 4);
+        let (_, prev, next) = T::borrow_iter(&t, &3);
+        assert!(prev == Option::some(1) && Option::is_none(&next), 5);
+
+        assert!(T::remove(&mut t, &1) == 1, 6);
+        assert!(T::head_key(&t) == Option::some(3), 7);
+        assert!(T::remove(&mut t, &3) == 3, 8);
+        assert!(Option::is_none(&T::head_key(&t)), 9);
+        assert!(Option::is_none(&T::tail_key(&t)), 10);
+
+        // Re-adding after the table became empty starts a new list.
+        T::add(&mut t, &4, 4);
+        assert!(T::head_key(&t) == Option::some(4), 11);
+        assert!(T::tail_key(&t) == Option::some(4), 12);
+        T::remove(&mut t, &4);
+        T::destroy_empty(t)
+    }
+
+    #[test]
+    fun borrow_mut_with_default() {
+        let t = T::new<u64, u64>();
+        *T::borrow_mut_with_default(&mut t, &7, 0) = 1;
+        *T::borrow_mut_with_default(&mut t, &7, 0) = 2;
+        assert!(T::length(&t) == 1, 1);
+        assert!(*T::borrow(&t, &7) == 2, 2);
+        T::drop_unchecked(t)
+    }
+
+    #[test]
+    fun append() {
+        let t1 = T::new<u64, u64>();
+        let t2 = T::new<u64, u64>();
+        T::add(&mut t1, &1, 1);
+        T::add(&mut t2, &3, 3);
+        T::add(&mut t2, &2, 2);
+        T::append(&mut t1, &mut t2);
+        assert!(T::empty(&t2), 1);
+        assert!(T::length(&t1) == 3, 2);
+        let (_, _, next) = T::borrow_iter(&t1, &1);
+        assert!(next == Option::some(3), 3);
+        let (_, _, next) = T::borrow_iter(&t1, &3);
+        assert!(next == Option::some(2), 4);
+        assert!(T::tail_key(&t1) == Option::some(2), 5);
+        T::destroy_empty(t2);
+        T::drop_unchecked(t1)
+    }
+
+    #[test(s = @0x42)]
+    fun stored_in_global(s: signer) acquires S {
+        let t = T::new<address, u128>();
+        T::add(&mut t, &@0xAB, 11);
+        move_to(&s, S { t });
+
+        let global_t = &mut borrow_global_mut<S<address, u128>>(@0x42).t;
+        T::add(global_t, &@0xCD, 45);
+        assert!(T::head_key(global_t) == Option::some(@0xAB), 1);
+        let (_, _, next) = T::borrow_iter(global_t, &@0xAB);
+        assert!(next == Option::some(@0xCD), 2);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 26113)]
+    fun destroy_non_empty_fails() {
+        let t = T::new<u64, u64>();
+        T::add(&mut t, &1, 2);
+        T::destroy_empty(t)
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 25607)]
+    fun add_existing_fails() {
+        let t = T::new<u64, u64>();
+        T::add(&mut t, &1, 2);
+        T::add(&mut t, &1, 3);
+        T::drop_unchecked(t)
+    }
+}
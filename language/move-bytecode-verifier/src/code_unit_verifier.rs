@@ -6,18 +6,20 @@
 //! abstract_interpreter.rs. CodeUnitVerifier simply orchestrates calls into these two files.
 use crate::{
     acquires_list_verifier::AcquiresVerifier, control_flow, locals_safety, reference_safety,
-    stack_usage_verifier::StackUsageVerifier, type_safety,
+    stack_usage_verifier::StackUsageVerifier, type_safety, verifier::VerifierConfig,
 };
 use move_binary_format::{
     access::ModuleAccess,
     binary_views::{BinaryIndexedView, FunctionView},
-    errors::{Location, PartialVMResult, VMResult},
+    control_flow_graph::ControlFlowGraph,
+    errors::{Location, PartialVMError, PartialVMResult, VMResult},
     file_format::{
         CompiledModule, CompiledScript, FunctionDefinition, FunctionDefinitionIndex,
         IdentifierIndex, TableIndex,
     },
     IndexKind,
 };
+use move_core_types::vm_status::StatusCode;
 use std::collections::HashMap;
 
 pub struct CodeUnitVerifier<'a> {
@@ -28,26 +30,48 @@ pub struct CodeUnitVerifier<'a> {
 
 impl<'a> CodeUnitVerifier<'a> {
     pub fn verify_module(module: &'a CompiledModule) -> VMResult<()> {
-        Self::verify_module_impl(module).map_err(|e| e.finish(Location::Module(module.self_id())))
+        Self::verify_module_with_config(&VerifierConfig::default(), module)
     }
 
-    fn verify_module_impl(module: &'a CompiledModule) -> PartialVMResult<()> {
+    pub fn verify_module_with_config(
+        config: &VerifierConfig,
+        module: &'a CompiledModule,
+    ) -> VMResult<()> {
+        Self::verify_module_impl(config, module)
+            .map_err(|e| e.finish(Location::Module(module.self_id())))
+    }
+
+    fn verify_module_impl(
+        config: &VerifierConfig,
+        module: &'a CompiledModule,
+    ) -> PartialVMResult<()> {
         for (idx, function_definition) in module.function_defs().iter().enumerate() {
             let index = FunctionDefinitionIndex(idx as TableIndex);
-            Self::verify_function(index, function_definition, module)
+            Self::verify_function(config, index, function_definition, module)
                 .map_err(|err| err.at_index(IndexKind::FunctionDefinition, index.0))?
         }
         Ok(())
     }
 
     pub fn verify_script(module: &'a CompiledScript) -> VMResult<()> {
-        Self::verify_script_impl(module).map_err(|e| e.finish(Location::Script))
+        Self::verify_script_with_config(&VerifierConfig::default(), module)
+    }
+
+    pub fn verify_script_with_config(
+        config: &VerifierConfig,
+        module: &'a CompiledScript,
+    ) -> VMResult<()> {
+        Self::verify_script_impl(config, module).map_err(|e| e.finish(Location::Script))
     }
 
-    fn verify_script_impl(script: &'a CompiledScript) -> PartialVMResult<()> {
+    fn verify_script_impl(
+        config: &VerifierConfig,
+        script: &'a CompiledScript,
+    ) -> PartialVMResult<()> {
         // create `FunctionView` and `BinaryIndexedView`
-        control_flow::verify(None, &script.code)?;
+        control_flow::verify_with_config(config, None, &script.code)?;
         let function_view = FunctionView::script(script);
+        verify_basic_blocks(config, &function_view)?;
         let resolver = BinaryIndexedView::Script(script);
         //verify
         let code_unit_verifier = CodeUnitVerifier {
@@ -59,6 +83,7 @@ impl<'a> CodeUnitVerifier<'a> {
     }
 
     fn verify_function(
+        config: &VerifierConfig,
         index: FunctionDefinitionIndex,
         function_definition: &'a FunctionDefinition,
         module: &'a CompiledModule,
@@ -70,8 +95,9 @@ impl<'a> CodeUnitVerifier<'a> {
         };
         // create `FunctionView` and `BinaryIndexedView`
         let function_handle = module.function_handle_at(function_definition.function);
-        control_flow::verify(Some(index), code)?;
+        control_flow::verify_with_config(config, Some(index), code)?;
         let function_view = FunctionView::function(module, index, code, function_handle);
+        verify_basic_blocks(config, &function_view)?;
         let resolver = BinaryIndexedView::Module(module);
         let mut name_def_map = HashMap::new();
         for (idx, func_def) in module.function_defs().iter().enumerate() {
@@ -95,3 +121,15 @@ impl<'a> CodeUnitVerifier<'a> {
         reference_safety::verify(&self.resolver, &self.function_view, &self.name_def_map)
     }
 }
+
+fn verify_basic_blocks(
+    config: &VerifierConfig,
+    function_view: &FunctionView,
+) -> PartialVMResult<()> {
+    match config.max_basic_blocks {
+        Some(max) if function_view.cfg().num_blocks() as usize > max => {
+            Err(PartialVMError::new(StatusCode::TOO_MANY_BASIC_BLOCKS))
+        }
+        _ => Ok(()),
+    }
+}
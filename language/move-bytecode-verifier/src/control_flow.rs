@@ -6,6 +6,7 @@
 //! - All forward jumps do not enter into the middle of a loop
 //! - All "breaks" (forward, loop-exiting jumps) go to the "end" of the loop
 //! - All "continues" (back jumps in a loop) are only to the current loop
+use crate::verifier::VerifierConfig;
 use move_binary_format::{
     errors::{PartialVMError, PartialVMResult},
     file_format::{Bytecode, CodeOffset, CodeUnit, FunctionDefinitionIndex},
@@ -16,6 +17,14 @@ use std::{collections::HashSet, convert::TryInto};
 pub fn verify(
     current_function_opt: Option<FunctionDefinitionIndex>,
     code: &CodeUnit,
+) -> PartialVMResult<()> {
+    verify_with_config(&VerifierConfig::default(), current_function_opt, code)
+}
+
+pub fn verify_with_config(
+    config: &VerifierConfig,
+    current_function_opt: Option<FunctionDefinitionIndex>,
+    code: &CodeUnit,
 ) -> PartialVMResult<()> {
     let current_function = current_function_opt.unwrap_or(FunctionDefinitionIndex(0));
     // check fall through
@@ -35,7 +44,8 @@ pub fn verify(
         code: &code.code,
     };
     let labels = instruction_labels(context);
-    check_jumps(context, labels)
+    check_jumps(context, &labels)?;
+    check_loop_depth(context, &labels, config.max_loop_depth)
 }
 
 #[derive(Clone, Copy)]
@@ -94,13 +104,34 @@ fn instruction_labels(context: &ControlFlowVerifier) -> Vec<Label> {
 //   - All forward jumps do not enter into the middle of a loop
 //   - All "breaks" go to the "end" of the loop
 //   - All back jumps are only to the current loop
-fn check_jumps(context: &ControlFlowVerifier, labels: Vec<Label>) -> PartialVMResult<()> {
+fn check_jumps(context: &ControlFlowVerifier, labels: &[Label]) -> PartialVMResult<()> {
     // All back jumps are only to the current loop
-    check_continues(context, &labels)?;
+    check_continues(context, labels)?;
     // All "breaks" go to the "end" of the loop
-    check_breaks(context, &labels)?;
+    check_breaks(context, labels)?;
     // All forward jumps do not enter into the middle of a loop
-    check_no_loop_splits(context, &labels)
+    check_no_loop_splits(context, labels)
+}
+
+// Loops are not nested deeper than `max_loop_depth`
+fn check_loop_depth(
+    context: &ControlFlowVerifier,
+    labels: &[Label],
+    max_loop_depth: Option<usize>,
+) -> PartialVMResult<()> {
+    let max_loop_depth = match max_loop_depth {
+        Some(max) => max,
+        None => return Ok(()),
+    };
+    match count_loop_depth(labels)
+        .into_iter()
+        .position(|depth| depth > max_loop_depth)
+    {
+        Some(offset) => {
+            Err(context.error(StatusCode::LOOP_MAX_DEPTH_REACHED, offset as CodeOffset))
+        }
+        None => Ok(()),
+    }
 }
 
 fn check_code<
@@ -0,0 +1,106 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a checker for the limits of a `VerifierConfig` which apply to
+//! declarations rather than code: the number of parameters and type parameters of functions,
+//! the number of type parameters of structs, and the length of type instantiations.
+use crate::verifier::VerifierConfig;
+use move_binary_format::{
+    binary_views::BinaryIndexedView,
+    errors::{Location, PartialVMError, PartialVMResult, VMResult},
+    file_format::{CompiledModule, CompiledScript, SignatureToken, TableIndex},
+    IndexKind,
+};
+use move_core_types::vm_status::StatusCode;
+
+pub struct LimitsVerifier<'a> {
+    config: &'a VerifierConfig,
+    resolver: BinaryIndexedView<'a>,
+}
+
+impl<'a> LimitsVerifier<'a> {
+    pub fn verify_module(config: &'a VerifierConfig, module: &'a CompiledModule) -> VMResult<()> {
+        Self::verify_module_impl(config, module)
+            .map_err(|e| e.finish(Location::Module(module.self_id())))
+    }
+
+    fn verify_module_impl(
+        config: &'a VerifierConfig,
+        module: &'a CompiledModule,
+    ) -> PartialVMResult<()> {
+        let limits_check = Self {
+            config,
+            resolver: BinaryIndexedView::Module(module),
+        };
+        limits_check.verify_struct_handles()?;
+        limits_check.verify_function_handles()?;
+        limits_check.verify_signatures()
+    }
+
+    pub fn verify_script(config: &'a VerifierConfig, script: &'a CompiledScript) -> VMResult<()> {
+        Self::verify_script_impl(config, script).map_err(|e| e.finish(Location::Script))
+    }
+
+    fn verify_script_impl(
+        config: &'a VerifierConfig,
+        script: &'a CompiledScript,
+    ) -> PartialVMResult<()> {
+        let limits_check = Self {
+            config,
+            resolver: BinaryIndexedView::Script(script),
+        };
+        limits_check.verify_function_handles()?;
+        limits_check.verify_signatures()?;
+        limits_check.check_type_parameters(script.type_parameters.len())?;
+        limits_check.check_parameters(limits_check.resolver.signature_at(script.parameters).len())
+    }
+
+    fn verify_struct_handles(&self) -> PartialVMResult<()> {
+        for (idx, struct_handle) in self.resolver.struct_handles().iter().enumerate() {
+            self.check_type_parameters(struct_handle.type_parameters.len())
+                .map_err(|err| err.at_index(IndexKind::StructHandle, idx as TableIndex))?;
+        }
+        Ok(())
+    }
+
+    fn verify_function_handles(&self) -> PartialVMResult<()> {
+        for (idx, function_handle) in self.resolver.function_handles().iter().enumerate() {
+            self.check_type_parameters(function_handle.type_parameters.len())
+                .and_then(|()| {
+                    self.check_parameters(
+                        self.resolver.signature_at(function_handle.parameters).len(),
+                    )
+                })
+                .map_err(|err| err.at_index(IndexKind::FunctionHandle, idx as TableIndex))?;
+        }
+        Ok(())
+    }
+
+    fn verify_signatures(&self) -> PartialVMResult<()> {
+        for (idx, signature) in self.resolver.signatures().iter().enumerate() {
+            for ty in signature.0.iter().flat_map(|ty| ty.preorder_traversal()) {
+                if let SignatureToken::StructInstantiation(_, type_args) = ty {
+                    self.check_type_parameters(type_args.len())
+                        .map_err(|err| err.at_index(IndexKind::Signature, idx as TableIndex))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_type_parameters(&self, count: usize) -> PartialVMResult<()> {
+        match self.config.max_generic_instantiation_length {
+            Some(max) if count > max => {
+                Err(PartialVMError::new(StatusCode::TOO_MANY_TYPE_PARAMETERS))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_parameters(&self, count: usize) -> PartialVMResult<()> {
+        match self.config.max_function_parameters {
+            Some(max) if count > max => Err(PartialVMError::new(StatusCode::TOO_MANY_PARAMETERS)),
+            _ => Ok(()),
+        }
+    }
+}
@@ -6,8 +6,9 @@ use crate::{
     ability_field_requirements, check_duplication::DuplicationChecker,
     code_unit_verifier::CodeUnitVerifier, constants, friends,
     instantiation_loops::InstantiationLoopChecker, instruction_consistency::InstructionConsistency,
-    script_signature, script_signature::no_additional_script_signature_checks,
-    signature::SignatureChecker, struct_defs::RecursiveStructDefChecker,
+    limits::LimitsVerifier, script_signature,
+    script_signature::no_additional_script_signature_checks, signature::SignatureChecker,
+    struct_defs::RecursiveStructDefChecker,
 };
 use move_binary_format::{
     check_bounds::BoundsChecker,
@@ -15,6 +16,21 @@ use move_binary_format::{
     file_format::{CompiledModule, CompiledScript},
 };
 
+/// Limits enforced by the bytecode verifier on top of the checks which are always performed.
+/// No limits are enforced by default.
+#[derive(Clone, Debug, Default)]
+pub struct VerifierConfig {
+    /// Maximum nesting depth of loops in a function, `LOOP_MAX_DEPTH_REACHED` otherwise.
+    pub max_loop_depth: Option<usize>,
+    /// Maximum number of parameters of a function, `TOO_MANY_PARAMETERS` otherwise.
+    pub max_function_parameters: Option<usize>,
+    /// Maximum number of type parameters of a function or struct, and of type arguments in an
+    /// instantiation, `TOO_MANY_TYPE_PARAMETERS` otherwise.
+    pub max_generic_instantiation_length: Option<usize>,
+    /// Maximum number of basic blocks in a function, `TOO_MANY_BASIC_BLOCKS` otherwise.
+    pub max_basic_blocks: Option<usize>,
+}
+
 /// Helper for a "canonical" verification of a module.
 ///
 /// Clients that rely on verification should call the proper passes
@@ -26,11 +42,17 @@ use move_binary_format::{
 /// minimize the code locations that need to be updated should a new checker
 /// is introduced.
 pub fn verify_module(module: &CompiledModule) -> VMResult<()> {
+    verify_module_with_config(&VerifierConfig::default(), module)
+}
+
+/// Same as `verify_module`, but additionally enforces the limits of `config`.
+pub fn verify_module_with_config(config: &VerifierConfig, module: &CompiledModule) -> VMResult<()> {
     BoundsChecker::verify_module(module).map_err(|e| {
         // We can't point the error at the module, because if bounds-checking
         // failed, we cannot safely index into module's handle to itself.
         e.finish(Location::Undefined)
     })?;
+    LimitsVerifier::verify_module(config, module)?;
     DuplicationChecker::verify_module(module)?;
     SignatureChecker::verify_module(module)?;
     InstructionConsistency::verify_module(module)?;
@@ -39,7 +61,7 @@ pub fn verify_module(module: &CompiledModule) -> VMResult<()> {
     ability_field_requirements::verify_module(module)?;
     RecursiveStructDefChecker::verify_module(module)?;
     InstantiationLoopChecker::verify_module(module)?;
-    CodeUnitVerifier::verify_module(module)?;
+    CodeUnitVerifier::verify_module_with_config(config, module)?;
     script_signature::verify_module(module, no_additional_script_signature_checks)
 }
 
@@ -54,11 +76,17 @@ pub fn verify_module(module: &CompiledModule) -> VMResult<()> {
 /// minimize the code locations that need to be updated should a new checker
 /// is introduced.
 pub fn verify_script(script: &CompiledScript) -> VMResult<()> {
+    verify_script_with_config(&VerifierConfig::default(), script)
+}
+
+/// Same as `verify_script`, but additionally enforces the limits of `config`.
+pub fn verify_script_with_config(config: &VerifierConfig, script: &CompiledScript) -> VMResult<()> {
     BoundsChecker::verify_script(script).map_err(|e| e.finish(Location::Script))?;
+    LimitsVerifier::verify_script(config, script)?;
     DuplicationChecker::verify_script(script)?;
     SignatureChecker::verify_script(script)?;
     InstructionConsistency::verify_script(script)?;
     constants::verify_script(script)?;
-    CodeUnitVerifier::verify_script(script)?;
+    CodeUnitVerifier::verify_script_with_config(config, script)?;
     script_signature::verify_script(script, no_additional_script_signature_checks)
 }
@@ -3,43 +3,63 @@
 
 use crate::{
     attr_derivation::{
-        find_attr_slice, new_borrow_exp, new_call_exp, new_full_name, new_fun, new_simple_name_exp,
-        new_simple_type, new_u64, new_var,
+        find_attr_slice, new_call_exp, new_full_name, new_full_type, new_fun, new_pack_exp,
+        new_simple_name, new_simple_name_exp, new_simple_type, new_struct, new_var,
     },
     diag,
     parser::ast::{
-        Definition, Exp, Exp_, Function, FunctionName, LeadingNameAccess_, ModuleDefinition,
-        ModuleMember, NameAccessChain_, StructName, Type, Type_, Visibility,
+        Definition, Field, Function, FunctionName, ModuleDefinition, ModuleMember,
+        NameAccessChain_, StructName, Type, Type_, Visibility,
     },
-    shared::{CompilationEnv, NamedAddressMap},
+    shared::CompilationEnv,
 };
-use move_core_types::account_address::AccountAddress;
 use move_ir_types::location::{sp, Loc};
 use move_symbol_pool::Symbol;
-use sha3::{Digest, Sha3_256};
-use std::convert::TryInto;
 
 const ACTOR_ATTR: &str = "actor";
 const STATE_ATTR: &str = "state";
 const INIT_ATTR: &str = "init";
 const MESSAGE_ATTR: &str = "message";
-const MAX_SEND_PARAM_COUNT: usize = 8;
+const RPC_ATTR: &str = "rpc";
+const CONT_ATTR: &str = "cont";
+const MESSAGE_STRUCT_PREFIX: &str = "Message__";
+const CONT_STRUCT_PREFIX: &str = "Cont__";
 
-pub(crate) fn derive_for_async(
-    env: &mut CompilationEnv,
-    address_map: &NamedAddressMap,
-    def: &mut Definition,
-) {
+/// The kinds of handler functions of an actor.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HandlerKind {
+    /// A `#[message]` handler, for which a `send_<name>` function is generated.
+    Message,
+    /// A `#[rpc]` handler returning a future, for which a `rpc_<name>` function is generated.
+    Rpc,
+    /// A `#[cont]` continuation, for which a `cont_<name>` function is generated.
+    Cont,
+}
+
+impl HandlerKind {
+    fn attr(self) -> &'static str {
+        match self {
+            HandlerKind::Message => MESSAGE_ATTR,
+            HandlerKind::Rpc => RPC_ATTR,
+            HandlerKind::Cont => CONT_ATTR,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            HandlerKind::Message | HandlerKind::Rpc => "message handler",
+            HandlerKind::Cont => "continuation",
+        }
+    }
+}
+
+pub(crate) fn derive_for_async(env: &mut CompilationEnv, def: &mut Definition) {
     if let Definition::Module(mod_def) = def {
-        derive_module_for_async(env, address_map, mod_def)
+        derive_module_for_async(env, mod_def)
     }
 }
 
-fn derive_module_for_async(
-    env: &mut CompilationEnv,
-    address_map: &NamedAddressMap,
-    mod_def: &mut ModuleDefinition,
-) {
+fn derive_module_for_async(env: &mut CompilationEnv, mod_def: &mut ModuleDefinition) {
     if find_attr_slice(&mod_def.attributes, ACTOR_ATTR).is_none() {
         // Not an actor module
         return;
@@ -47,79 +67,187 @@ fn derive_module_for_async(
 
     let state_name = check_state(env, mod_def);
 
-    // Go over the functions marked as #[message]
-    let mut new_funs = vec![];
+    // Go over the functions marked as #[message], #[rpc], or #[cont]
+    let mut new_members = vec![];
     for mem in &mod_def.members {
         if let ModuleMember::Function(fun_def) = mem {
-            if let Some(attr) = find_attr_slice(&fun_def.attributes, MESSAGE_ATTR) {
-                // Generate `send_<name>(actor: address, message_hash: vector<u8>, <args>)`.
-                let loc = attr.loc;
-                let call_name =
-                    FunctionName(sp(loc, Symbol::from(format!("send_{}", fun_def.name))));
-
-                // Replace the first parameter which is a reference to the actor state with an
-                // actor address.
-                let mut sign = fun_def.signature.clone();
-                if sign.parameters.is_empty() {
-                    env.add_diag(diag!(
-                        Derivation::DeriveFailed,
-                        (
-                            loc,
-                            "expected at least one parameter for message handler".to_string()
-                        )
-                    ));
-                    continue;
-                }
-                check_state_ref_param(env, &sign.parameters[0].1, &state_name);
-                sign.parameters[0] = (
-                    new_var(loc, "actor"),
-                    new_simple_type(loc, "address", vec![]),
-                );
-
-                // Create a call `Actor::send__<N>(actor, message_hash, BCS::to_bytes(&arg1), ..)`.
-                let param_count = sign.parameters.len() - 1;
-                if param_count > MAX_SEND_PARAM_COUNT {
-                    env.add_diag(diag!(
-                        Derivation::DeriveFailed,
-                        (
-                            loc,
-                            format!(
-                                "too many arguments for message handler (current max is {})",
-                                MAX_SEND_PARAM_COUNT
-                            )
-                        )
-                    ))
-                }
-                let mut args = vec![new_simple_name_exp(loc, sp(loc, Symbol::from("actor")))];
-                args.push(message_hash(env, address_map, loc, mod_def, fun_def));
-                for i in 0..param_count {
-                    let name_exp = new_simple_name_exp(loc, sign.parameters[i + 1].0 .0);
-                    args.push(new_call_exp(
-                        loc,
-                        new_full_name(loc, "Std", "BCS", "to_bytes"),
-                        vec![new_borrow_exp(loc, name_exp)],
-                    ));
+            for kind in [HandlerKind::Message, HandlerKind::Rpc, HandlerKind::Cont] {
+                if let Some(attr) = find_attr_slice(&fun_def.attributes, kind.attr()) {
+                    derive_handler(env, fun_def, attr.loc, kind, &state_name, &mut new_members)
                 }
-                let internal_send_name = format!("send__{}", param_count);
-                let send_call = new_call_exp(
-                    loc,
-                    new_full_name(loc, "Async", "Runtime", &internal_send_name),
-                    args,
-                );
+            }
+        }
+    }
+    mod_def.members.extend(new_members)
+}
 
-                new_funs.push(new_fun(
-                    loc,
-                    call_name,
-                    sp(loc, vec![]),
-                    Visibility::Public(loc),
-                    sign,
-                    send_call,
+fn derive_handler(
+    env: &mut CompilationEnv,
+    fun_def: &Function,
+    loc: Loc,
+    kind: HandlerKind,
+    state_name: &Option<StructName>,
+    new_members: &mut Vec<ModuleMember>,
+) {
+    let mut sign = fun_def.signature.clone();
+    // A continuation takes the value of the future it follows after the actor state.
+    let (leading, expected) = match kind {
+        HandlerKind::Cont => (2, "expected at least two parameters for continuation"),
+        _ => (1, "expected at least one parameter for message handler"),
+    };
+    if sign.parameters.len() < leading {
+        env.add_diag(diag!(Derivation::DeriveFailed, (loc, expected.to_string())));
+        return;
+    }
+    check_state_ref_param(env, &sign.parameters[0].1, state_name);
+    if !sign.type_parameters.is_empty() {
+        env.add_diag(diag!(
+            Derivation::DeriveFailed,
+            (loc, format!("{} cannot be generic", kind.describe()))
+        ));
+        return;
+    }
+    if let Some((_, ty)) = sign.parameters[1..]
+        .iter()
+        .find(|(_, ty)| matches!(ty.value, Type_::Ref(..)))
+    {
+        env.add_diag(diag!(
+            Derivation::DeriveFailed,
+            (
+                ty.loc,
+                format!("{} argument cannot be a reference", kind.describe())
+            )
+        ));
+        return;
+    }
+    // Handlers which are called for a request or as a continuation return a future, whose
+    // value is passed on by the runtime.
+    let result_ty = match kind {
+        HandlerKind::Message => None,
+        _ => match future_result_type(&sign.return_type) {
+            Some(ty) => Some(ty.clone()),
+            None => {
+                env.add_diag(diag!(
+                    Derivation::DeriveFailed,
+                    (
+                        sign.return_type.loc,
+                        format!("{} must return a `Future`", kind.describe())
+                    )
                 ));
+                return;
             }
+        },
+    };
+
+    // Generate `struct Message__<name> { <args> }`, carrying the arguments of the
+    // handler, or `struct Cont__<name> { <args> }`, carrying the arguments a continuation
+    // captures. The runtime identifies the handler from the type of this struct.
+    let struct_name = StructName(sp(
+        loc,
+        Symbol::from(handler_struct_name(kind, &fun_def.name)),
+    ));
+    let fields = sign.parameters[leading..]
+        .iter()
+        .map(|(var, ty)| (Field(var.0), ty.clone()))
+        .collect::<Vec<_>>();
+    new_members.push(ModuleMember::Struct(new_struct(
+        loc,
+        struct_name,
+        fields.clone(),
+    )));
+    let pack = new_pack_exp(
+        loc,
+        new_simple_name(loc, struct_name.0.value.as_str()),
+        fields
+            .into_iter()
+            .map(|(f, _)| (f, new_simple_name_exp(loc, f.0)))
+            .collect(),
+    );
+
+    let (name, body) = match kind {
+        HandlerKind::Message | HandlerKind::Rpc => {
+            // Generate `send_<name>(actor: address, <args>)` or `rpc_<name>(actor: address,
+            // <args>): Future<R>`, replacing the first parameter which is a reference to the
+            // actor state with an actor address. The body `Async::Runtime::send(actor,
+            // Message__<name> { <args> })` resp. `Async::Runtime::request(..)` is type
+            // checked against the message struct, and by that against the handler.
+            sign.parameters[0] = (
+                new_var(loc, "actor"),
+                new_simple_type(loc, "address", vec![]),
+            );
+            let (prefix, runtime_fun) = if kind == HandlerKind::Message {
+                ("send", "send")
+            } else {
+                ("rpc", "request")
+            };
+            let call = new_call_exp(
+                loc,
+                new_full_name(loc, "Async", "Runtime", runtime_fun),
+                vec![
+                    new_simple_name_exp(loc, sp(loc, Symbol::from("actor"))),
+                    pack,
+                ],
+            );
+            (format!("{}_{}", prefix, fun_def.name), call)
         }
-    }
-    for fun_def in new_funs {
-        mod_def.members.push(ModuleMember::Function(fun_def))
+        HandlerKind::Cont => {
+            // Generate `cont_<name>(<args>): Async::Future::Cont<T, R>`, where `T` is the type
+            // of the value the continuation takes and `Future<R>` its return type. The body
+            // is `Async::Future::cont(Cont__<name> { <args> })`.
+            let value_ty = sign.parameters[1].1.clone();
+            sign.parameters.drain(..leading);
+            sign.return_type = new_full_type(
+                loc,
+                "Async",
+                "Future",
+                "Cont",
+                vec![value_ty, result_ty.unwrap()],
+            );
+            let call = new_call_exp(
+                loc,
+                new_full_name(loc, "Async", "Future", "cont"),
+                vec![pack],
+            );
+            (format!("cont_{}", fun_def.name), call)
+        }
+    };
+    new_members.push(ModuleMember::Function(new_fun(
+        loc,
+        FunctionName(sp(loc, Symbol::from(name))),
+        sp(loc, vec![]),
+        Visibility::Public(loc),
+        sign,
+        body,
+    )));
+}
+
+/// The name of the struct generated to carry the arguments of a message handler or the captured
+/// arguments of a continuation. This naming convention is shared with the async runtime, which
+/// resolves the handler from the struct type.
+fn handler_struct_name(kind: HandlerKind, handler: &FunctionName) -> String {
+    let prefix = match kind {
+        HandlerKind::Message | HandlerKind::Rpc => MESSAGE_STRUCT_PREFIX,
+        HandlerKind::Cont => CONT_STRUCT_PREFIX,
+    };
+    format!("{}{}", prefix, handler)
+}
+
+/// If the type is a future `Future<R>`, returns `R`.
+fn future_result_type(ty: &Type) -> Option<&Type> {
+    match &ty.value {
+        Type_::Apply(access, ty_args) if ty_args.len() == 1 => {
+            let name = match &access.value {
+                NameAccessChain_::One(n)
+                | NameAccessChain_::Two(_, n)
+                | NameAccessChain_::Three(_, n) => n,
+            };
+            if name.value.as_str() == "Future" {
+                Some(&ty_args[0])
+            } else {
+                None
+            }
+        }
+        _ => None,
     }
 }
 
@@ -247,46 +375,3 @@ fn check_state_param(env: &mut CompilationEnv, state_ty: &Type, struct_name: &Op
         )),
     }
 }
-
-/// Computes a constant expression for the message hash of the given function.
-fn message_hash(
-    env: &mut CompilationEnv,
-    address_map: &NamedAddressMap,
-    loc: Loc,
-    mod_def: &ModuleDefinition,
-    fun_def: &Function,
-) -> Exp {
-    let addr = match mod_def.address {
-        Some(x) => x.value,
-        None => {
-            env.add_diag(diag!(
-                Derivation::DeriveFailed,
-                (
-                    mod_def.loc,
-                    "require explicit module address for async attribute derivation"
-                )
-            ));
-            return sp(loc, Exp_::UnresolvedError);
-        }
-    };
-    let account_addr = match addr {
-        LeadingNameAccess_::AnonymousAddress(num) => num.into_inner(),
-        LeadingNameAccess_::Name(name) => {
-            if let Some(n) = address_map.get(&name.value) {
-                n.into_inner()
-            } else {
-                env.add_diag(diag!(
-                    Derivation::DeriveFailed,
-                    (loc, format!("cannot resolve address alias `{}`", name))
-                ));
-                AccountAddress::from_hex_literal("0x0").unwrap()
-            }
-        }
-    };
-    let addr_str = format!("0x{:X}", account_addr);
-    let hash_str = format!("{}::{}::{}", addr_str, mod_def.name, fun_def.name);
-    let hash_bytes: [u8; 8] = Sha3_256::digest(hash_str.as_bytes())[0..8]
-        .try_into()
-        .expect("valid u64");
-    new_u64(loc, u64::from_be_bytes(hash_bytes))
-}
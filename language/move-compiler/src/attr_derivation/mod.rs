@@ -7,9 +7,10 @@ use move_symbol_pool::Symbol;
 use crate::{
     attr_derivation::{async_deriver::derive_for_async, evm_deriver::derive_for_evm},
     parser::ast::{
-        Attribute, AttributeValue, Attribute_, Attributes, Definition, Exp, Exp_, Function,
+        Attribute, AttributeValue, Attribute_, Attributes, Definition, Exp, Exp_, Field, Function,
         FunctionBody_, FunctionName, FunctionSignature, LeadingNameAccess_, NameAccessChain,
-        NameAccessChain_, Type, Type_, Value_, Var, Visibility,
+        NameAccessChain_, StructDefinition, StructFields, StructName, Type, Type_, Value_, Var,
+        Visibility,
     },
     shared::{CompilationEnv, Name, NamedAddressMap},
 };
@@ -31,7 +32,7 @@ pub fn derive_from_attributes(
         derive_for_evm(env, address_map, def)
     }
     if env.flags().has_flavor(ASYNC_FLAVOR) {
-        derive_for_async(env, address_map, def)
+        derive_for_async(env, def)
     }
 }
 
@@ -128,6 +129,18 @@ pub fn new_fun(
     }
 }
 
+/// Helper to create a new struct declaration without abilities.
+pub fn new_struct(loc: Loc, name: StructName, fields: Vec<(Field, Type)>) -> StructDefinition {
+    StructDefinition {
+        attributes: vec![],
+        loc,
+        abilities: vec![],
+        name,
+        type_parameters: vec![],
+        fields: StructFields::Defined(fields),
+    }
+}
+
 /// Helper to create a new named variable.
 pub fn new_var(loc: Loc, name: &str) -> Var {
     Var(sp(loc, Symbol::from(name)))
@@ -141,6 +154,23 @@ pub fn new_simple_type(loc: Loc, ty_str: &str, ty_args: Vec<Type>) -> Type {
     )
 }
 
+/// Helper to create a new type, based on its full name.
+pub fn new_full_type(
+    loc: Loc,
+    addr_alias: &str,
+    module: &str,
+    name: &str,
+    ty_args: Vec<Type>,
+) -> Type {
+    sp(
+        loc,
+        Type_::Apply(
+            Box::new(new_full_name(loc, addr_alias, module, name)),
+            ty_args,
+        ),
+    )
+}
+
 /// Helper to create a simple name.
 pub fn new_simple_name(loc: Loc, name: &str) -> NameAccessChain {
     sp(loc, NameAccessChain_::One(sp(loc, Symbol::from(name))))
@@ -166,6 +196,12 @@ pub fn new_call_exp(loc: Loc, fun: NameAccessChain, args: Vec<Exp>) -> Exp {
     sp(loc, Exp_::Call(fun, false, None, sp(loc, args)))
 }
 
+/// Helper to create a pack exp.
+pub fn new_pack_exp(loc: Loc, name: NameAccessChain, fields: Vec<(Field, Exp)>) -> Exp {
+    sp(loc, Exp_::Pack(name, None, fields))
+}
+
+#[allow(unused)]
 pub fn new_borrow_exp(loc: Loc, arg: Exp) -> Exp {
     sp(loc, Exp_::Borrow(false, Box::new(arg)))
 }
@@ -198,6 +234,7 @@ pub fn new_vec_u8(loc: Loc, vec: &[u8]) -> Exp {
 }
 
 /// Helper to create new u64.
+#[allow(unused)]
 pub fn new_u64(loc: Loc, val: u64) -> Exp {
     sp(
         loc,
@@ -9,9 +9,13 @@ module 0x3::M {
     fun expect_send_f_resolves() {
         send_f(@10, 22)
     }
+
+    fun expect_message_f_resolves(): Message__f {
+        Message__f { _y: 22 }
+    }
 }
 
 // Simulate Async runtime module
 module Async::Runtime {
-    public native fun send__1(a: address, message_hash: u64, arg: vector<u8>);
+    public native fun send<Message>(actor: address, message: Message);
 }
@@ -19,27 +19,32 @@ module 0x3::WrongHandlerParam {
     #[message] fun f4(_x: &u64) {}
     #[message] fun f5(_x: &A) {}
     #[message] fun f6(_x: &mut A) {}
-    #[message] fun f7(_x: &A, _a1: u64, _a2: u64, _a3: u64, _a4: u64, _a5: u64, _a6: u64,
-                              _a7: u64, _a8: u64, _a9: u64) {}
+    #[message] fun f7(_x: &A, _a1: u64, _a2: &u64) {}
     #[message] fun f8(_x: &mut Self::A) {}
+    #[message] fun f9<T>(_x: &A, _a1: T) {}
 }
 
-// Simulate Async runtime module
+#[actor]
+module 0x3::WrongFutureHandler {
+    use Async::Future::Future;
+
+    #[state] struct A {}
+
+    #[init] fun init(): A { A{} }
+
+    #[rpc] fun r1(_x: &A): u64 { 1 }
+    #[rpc] fun r2(_x: &A) {}
+    #[cont] fun c1(_x: &A): Future<u64> { abort 1 }
+    #[cont] fun c2(_x: &A, _v: u64) {}
+    #[cont] fun c3(_x: &A, _v: &u64): Future<u64> { abort 1 }
+    #[cont] fun c4<T>(_x: &A, _v: T): Future<u64> { abort 1 }
+}
+
+// Simulate Async runtime modules
 module Async::Runtime {
-    public native fun send__0(actor: address, message_hash: u64);
-    public native fun send__1(actor: address, message_hash: u64, arg1: vector<u8>);
-    public native fun send__2(actor: address, message_hash: u64, arg1: vector<u8>, arg2: vector<u8>);
-    public native fun send__3(actor: address, message_hash: u64, arg1: vector<u8>, arg2: vector<u8>, arg3: vector<u8>);
-    public native fun send__4(actor: address, message_hash: u64,
-        arg1: vector<u8>, arg2: vector<u8>, arg3: vector<u8>, arg4: vector<u8>);
-    public native fun send__5(actor: address, message_hash: u64,
-        arg1: vector<u8>, arg2: vector<u8>, arg3: vector<u8>, arg4: vector<u8>, arg5: vector<u8>);
-    public native fun send__6(actor: address, message_hash: u64,
-        arg1: vector<u8>, arg2: vector<u8>, arg3: vector<u8>, arg4: vector<u8>, arg5: vector<u8>, arg6: vector<u8>);
-    public native fun send__7(actor: address, message_hash: u64,
-        arg1: vector<u8>, arg2: vector<u8>, arg3: vector<u8>, arg4: vector<u8>, arg5: vector<u8>, arg6: vector<u8>,
-        arg7: vector<u8>);
-    public native fun send__8(actor: address, message_hash: u64,
-        arg1: vector<u8>, arg2: vector<u8>, arg3: vector<u8>, arg4: vector<u8>, arg5: vector<u8>, arg6: vector<u8>,
-        arg7: vector<u8>, arg8: vector<u8>);
+    public native fun send<Message>(actor: address, message: Message);
+}
+
+module Async::Future {
+    struct Future<phantom T> has drop { id: u64 }
 }
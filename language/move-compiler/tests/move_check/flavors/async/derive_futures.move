@@ -0,0 +1,43 @@
+#[actor]
+module 0x3::M {
+    use Async::Future::{Cont, Future};
+
+    #[state] struct A {}
+
+    #[init] fun init(): A { A{} }
+
+    #[rpc] fun get(_x: &A, _y: u64): Future<u64> { Async::Future::yield(1) }
+
+    #[cont] fun got(_x: &mut A, _v: u64, _c: bool): Future<u64> { Async::Future::yield(2) }
+
+    fun expect_rpc_get_resolves(): Future<u64> {
+        rpc_get(@10, 22)
+    }
+
+    fun expect_cont_got_resolves(): Cont<u64, u64> {
+        cont_got(true)
+    }
+
+    fun expect_cont_struct_resolves(): Cont__got {
+        Cont__got { _c: true }
+    }
+
+    fun expect_chain_resolves(): Future<u64> {
+        Async::Future::followed_by(rpc_get(@10, 22), cont_got(false))
+    }
+}
+
+// Simulate Async runtime modules
+module Async::Runtime {
+    use Async::Future::Future;
+    public native fun send<Message>(actor: address, message: Message);
+    public native fun request<Message, T>(actor: address, message: Message): Future<T>;
+}
+
+module Async::Future {
+    struct Future<phantom T> has drop { id: u64 }
+    struct Cont<phantom T, phantom R> has drop { id: u64 }
+    public native fun yield<T>(result: T): Future<T>;
+    public native fun followed_by<T, R>(f: Future<T>, c: Cont<T, R>): Future<R>;
+    public native fun cont<Captured, T, R>(captured: Captured): Cont<T, R>;
+}
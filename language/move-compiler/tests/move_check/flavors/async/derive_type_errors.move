@@ -0,0 +1,21 @@
+#[actor]
+module 0x3::M {
+    #[state] struct A {}
+
+    #[init] fun init(): A { A{} }
+
+    #[message] fun f(_x: &A, _y: u64) {}
+
+    fun expect_send_f_arity_error() {
+        send_f(@10, 22, 23)
+    }
+
+    fun expect_send_f_type_error() {
+        send_f(@10, true)
+    }
+}
+
+// Simulate Async runtime module
+module Async::Runtime {
+    public native fun send<Message>(actor: address, message: Message);
+}
@@ -7,6 +7,7 @@ use crate::{
     language_storage::{ModuleId, StructTag, TypeTag},
 };
 use anyhow::{format_err, Error, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::btree_map::{self, BTreeMap};
 
 /// A collection of changes to modules and resources under a Move account.
@@ -277,4 +278,33 @@ impl ChangeSet {
     }
 }
 
-pub type Event = (Vec<u8>, u64, TypeTag, Vec<u8>);
+/// An event emitted during execution, carrying the type of the message and its BCS payload.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ContractEvent {
+    /// An event emitted to the event stream identified by `guid`, as the `seq_num`th event
+    /// of that stream.
+    Handle {
+        guid: Vec<u8>,
+        seq_num: u64,
+        type_tag: TypeTag,
+        data: Vec<u8>,
+    },
+    /// An event emitted without a handle, identified by its type only.
+    Module { type_tag: TypeTag, data: Vec<u8> },
+}
+
+impl ContractEvent {
+    /// The type of the event message.
+    pub fn type_tag(&self) -> &TypeTag {
+        match self {
+            Self::Handle { type_tag, .. } | Self::Module { type_tag, .. } => type_tag,
+        }
+    }
+
+    /// The BCS-serialized event message.
+    pub fn data(&self) -> &[u8] {
+        match self {
+            Self::Handle { data, .. } | Self::Module { data, .. } => data,
+        }
+    }
+}
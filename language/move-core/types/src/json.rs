@@ -0,0 +1,332 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The canonical JSON representation of Move values, available with the `json` feature.
+//!
+//! Since JSON does not carry enough information to recover a Move value, the representation is
+//! always interpreted with respect to a `MoveTypeLayout`:
+//! - `bool` and `u8` are JSON booleans and numbers,
+//! - `u64` and `u128` are decimal strings, as they do not fit into the numbers of many JSON
+//!   implementations,
+//! - `address` and `signer` are `0x`-prefixed hex strings,
+//! - `vector<u8>` is a `0x`-prefixed hex string and other vectors are JSON arrays,
+//! - structs are JSON objects keyed by field name, which requires a layout with field names.
+//!
+//! When reading JSON, integers may be given both as JSON numbers and decimal strings, `vector<u8>`
+//! may also be given as an array of numbers, and addresses may be given in their short form
+//! (e.g., `0x1`).
+
+use crate::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    value::{
+        MoveFieldLayout, MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue, VALUE_DEPTH_MAX,
+    },
+};
+use anyhow::{bail, Result as AResult};
+use serde::{
+    de::{DeserializeSeed, Error as DeError, MapAccess, SeqAccess, Visitor},
+    ser::{Error as SerError, SerializeMap, SerializeSeq},
+    Deserialize, Serialize, Serializer,
+};
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// A `MoveValue` paired with its layout, which serializes into the canonical JSON representation.
+pub struct JsonMoveValue<'a> {
+    value: &'a MoveValue,
+    layout: &'a MoveTypeLayout,
+}
+
+impl<'a> JsonMoveValue<'a> {
+    pub fn new(value: &'a MoveValue, layout: &'a MoveTypeLayout) -> Self {
+        Self { value, layout }
+    }
+}
+
+/// A `DeserializeSeed` which reads a `MoveValue` of the given layout from its canonical JSON
+/// representation. Structs are returned in the decorated representation of the layout.
+pub struct JsonMoveTypeLayout<'a>(pub &'a MoveTypeLayout);
+
+impl MoveValue {
+    /// Convert `self` into its canonical JSON representation according to `layout`.
+    pub fn to_json(&self, layout: &MoveTypeLayout) -> AResult<serde_json::Value> {
+        if !layout.is_within_depth(VALUE_DEPTH_MAX) {
+            bail!(
+                "Layout exceeds the maximum value depth of {}",
+                VALUE_DEPTH_MAX
+            )
+        }
+        Ok(serde_json::to_value(JsonMoveValue::new(self, layout))?)
+    }
+
+    /// Read a value of `layout` from its canonical JSON representation.
+    pub fn from_json(json: &serde_json::Value, layout: &MoveTypeLayout) -> AResult<Self> {
+        if !layout.is_within_depth(VALUE_DEPTH_MAX) {
+            bail!(
+                "Layout exceeds the maximum value depth of {}",
+                VALUE_DEPTH_MAX
+            )
+        }
+        Ok(JsonMoveTypeLayout(layout).deserialize(json)?)
+    }
+}
+
+fn field_layouts(layout: &MoveStructLayout) -> Option<&[MoveFieldLayout]> {
+    match layout {
+        MoveStructLayout::Runtime(_) => None,
+        MoveStructLayout::WithFields(fields) | MoveStructLayout::WithTypes { fields, .. } => {
+            Some(fields)
+        }
+    }
+}
+
+fn field_values(s: &MoveStruct) -> Vec<&MoveValue> {
+    match s {
+        MoveStruct::Runtime(vals) => vals.iter().collect(),
+        MoveStruct::WithFields(fields) | MoveStruct::WithTypes { fields, .. } => {
+            fields.iter().map(|(_, val)| val).collect()
+        }
+    }
+}
+
+fn hex_literal(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+impl<'a> Serialize for JsonMoveValue<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self.layout, self.value) {
+            (MoveTypeLayout::Bool, MoveValue::Bool(b)) => serializer.serialize_bool(*b),
+            (MoveTypeLayout::U8, MoveValue::U8(i)) => serializer.serialize_u8(*i),
+            (MoveTypeLayout::U64, MoveValue::U64(i)) => serializer.collect_str(i),
+            (MoveTypeLayout::U128, MoveValue::U128(i)) => serializer.collect_str(i),
+            (MoveTypeLayout::Address, MoveValue::Address(a))
+            | (MoveTypeLayout::Signer, MoveValue::Signer(a)) => {
+                serializer.serialize_str(&hex_literal(a.as_ref()))
+            }
+            (MoveTypeLayout::Vector(layout), MoveValue::Vector(vals)) => match &**layout {
+                MoveTypeLayout::U8 => {
+                    let bytes = vals
+                        .iter()
+                        .map(|val| match val {
+                            MoveValue::U8(b) => Ok(*b),
+                            _ => Err(S::Error::custom(format!(
+                                "Value {:?} does not match layout u8",
+                                val
+                            ))),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    serializer.serialize_str(&hex_literal(&bytes))
+                }
+                layout => {
+                    let mut t = serializer.serialize_seq(Some(vals.len()))?;
+                    for val in vals {
+                        t.serialize_element(&JsonMoveValue::new(val, layout))?;
+                    }
+                    t.end()
+                }
+            },
+            (MoveTypeLayout::Struct(layout), MoveValue::Struct(s)) => {
+                let fields = field_layouts(layout).ok_or_else(|| {
+                    S::Error::custom("Serializing a struct to JSON requires field names")
+                })?;
+                let vals = field_values(s);
+                if fields.len() != vals.len() {
+                    return Err(S::Error::custom(format!(
+                        "Struct has {} fields but its layout has {}",
+                        vals.len(),
+                        fields.len()
+                    )));
+                }
+                let mut t = serializer.serialize_map(Some(fields.len()))?;
+                for (field, val) in fields.iter().zip(vals) {
+                    t.serialize_entry(
+                        field.name.as_str(),
+                        &JsonMoveValue::new(val, &field.layout),
+                    )?;
+                }
+                t.end()
+            }
+            (layout, val) => Err(S::Error::custom(format!(
+                "Value {:?} does not match layout {}",
+                val, layout
+            ))),
+        }
+    }
+}
+
+impl<'d, 'a> DeserializeSeed<'d> for JsonMoveTypeLayout<'a> {
+    type Value = MoveValue;
+
+    fn deserialize<D: serde::de::Deserializer<'d>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        match self.0 {
+            MoveTypeLayout::Bool => bool::deserialize(deserializer).map(MoveValue::Bool),
+            MoveTypeLayout::U8 => deserializer
+                .deserialize_any(IntegerVisitor::<u8>::new("u8"))
+                .map(MoveValue::U8),
+            MoveTypeLayout::U64 => deserializer
+                .deserialize_any(IntegerVisitor::<u64>::new("u64"))
+                .map(MoveValue::U64),
+            MoveTypeLayout::U128 => deserializer
+                .deserialize_any(IntegerVisitor::<u128>::new("u128"))
+                .map(MoveValue::U128),
+            MoveTypeLayout::Address => deserializer
+                .deserialize_str(AddressVisitor)
+                .map(MoveValue::Address),
+            MoveTypeLayout::Signer => deserializer
+                .deserialize_str(AddressVisitor)
+                .map(MoveValue::Signer),
+            MoveTypeLayout::Vector(layout) => match &**layout {
+                MoveTypeLayout::U8 => deserializer
+                    .deserialize_any(BytesVisitor)
+                    .map(MoveValue::vector_u8),
+                layout => deserializer
+                    .deserialize_seq(VectorVisitor(layout))
+                    .map(MoveValue::Vector),
+            },
+            MoveTypeLayout::Struct(layout) => {
+                let fields = field_layouts(layout).ok_or_else(|| {
+                    D::Error::custom("Deserializing a struct from JSON requires field names")
+                })?;
+                let vals = deserializer.deserialize_map(StructVisitor(fields))?;
+                Ok(MoveValue::Struct(match layout {
+                    MoveStructLayout::WithTypes { type_, .. } => {
+                        MoveStruct::with_types(type_.clone(), vals)
+                    }
+                    _ => MoveStruct::with_fields(vals),
+                }))
+            }
+        }
+    }
+}
+
+struct IntegerVisitor<T> {
+    name: &'static str,
+    phantom: std::marker::PhantomData<T>,
+}
+
+impl<T> IntegerVisitor<T> {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'d, T: FromStr + TryFrom<u64>> Visitor<'d> for IntegerVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a {} as a decimal string", self.name)
+    }
+
+    fn visit_str<E: DeError>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse()
+            .map_err(|_| E::custom(format!("Invalid {} {:?}", self.name, v)))
+    }
+
+    fn visit_u64<E: DeError>(self, v: u64) -> Result<Self::Value, E> {
+        T::try_from(v).map_err(|_| E::custom(format!("Invalid {} {}", self.name, v)))
+    }
+
+    fn visit_i64<E: DeError>(self, v: i64) -> Result<Self::Value, E> {
+        match u64::try_from(v) {
+            Ok(v) => self.visit_u64(v),
+            Err(_) => Err(E::custom(format!("Invalid {} {}", self.name, v))),
+        }
+    }
+}
+
+struct AddressVisitor;
+
+impl<'d> Visitor<'d> for AddressVisitor {
+    type Value = AccountAddress;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an address as a 0x-prefixed hex string")
+    }
+
+    fn visit_str<E: DeError>(self, v: &str) -> Result<Self::Value, E> {
+        AccountAddress::from_hex_literal(v)
+            .map_err(|_| E::custom(format!("Invalid address {:?}", v)))
+    }
+}
+
+struct BytesVisitor;
+
+impl<'d> Visitor<'d> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a vector<u8> as a 0x-prefixed hex string")
+    }
+
+    fn visit_str<E: DeError>(self, v: &str) -> Result<Self::Value, E> {
+        v.strip_prefix("0x")
+            .and_then(|hex| hex::decode(hex).ok())
+            .ok_or_else(|| E::custom(format!("Invalid hex string {:?}", v)))
+    }
+
+    fn visit_seq<A: SeqAccess<'d>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = vec![];
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        Ok(bytes)
+    }
+}
+
+struct VectorVisitor<'a>(&'a MoveTypeLayout);
+
+impl<'d, 'a> Visitor<'d> for VectorVisitor<'a> {
+    type Value = Vec<MoveValue>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array")
+    }
+
+    fn visit_seq<A: SeqAccess<'d>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut vals = vec![];
+        while let Some(elem) = seq.next_element_seed(JsonMoveTypeLayout(self.0))? {
+            vals.push(elem)
+        }
+        Ok(vals)
+    }
+}
+
+struct StructVisitor<'a>(&'a [MoveFieldLayout]);
+
+impl<'d, 'a> Visitor<'d> for StructVisitor<'a> {
+    type Value = Vec<(Identifier, MoveValue)>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an object")
+    }
+
+    fn visit_map<A: MapAccess<'d>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut vals: Vec<Option<MoveValue>> = vec![None; self.0.len()];
+        while let Some(name) = map.next_key::<String>()? {
+            let idx = self
+                .0
+                .iter()
+                .position(|field| field.name.as_str() == name)
+                .ok_or_else(|| A::Error::custom(format!("Unknown field {:?}", name)))?;
+            if vals[idx].is_some() {
+                return Err(A::Error::custom(format!("Duplicate field {:?}", name)));
+            }
+            vals[idx] = Some(map.next_value_seed(JsonMoveTypeLayout(&self.0[idx].layout))?);
+        }
+        self.0
+            .iter()
+            .zip(vals)
+            .map(|(field, val)| match val {
+                Some(val) => Ok((field.name.clone(), val)),
+                None => Err(A::Error::custom(format!("Missing field {:?}", field.name))),
+            })
+            .collect()
+    }
+}
@@ -4,11 +4,15 @@
 use crate::{
     account_address::AccountAddress,
     identifier::{IdentStr, Identifier},
+    parser::{parse_module_id, parse_struct_tag, parse_type_tag},
 };
 #[cfg(any(test, feature = "fuzzing"))]
 use proptest_derive::Arbitrary;
 use serde::{Deserialize, Serialize};
-use std::fmt::{Display, Formatter};
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
 pub const CODE_TAG: u8 = 0;
 pub const RESOURCE_TAG: u8 = 1;
@@ -16,6 +20,30 @@ pub const RESOURCE_TAG: u8 = 1;
 /// Hex address: 0x1
 pub const CORE_CODE_ADDRESS: AccountAddress = AccountAddress::ONE;
 
+/// How addresses are written in the canonical textual format of type tags, struct tags, module
+/// IDs and Move values.
+///
+/// The canonical format is the one produced by `to_canonical_string` and accepted by the parsers
+/// in `crate::parser` (and the `FromStr` impls). Addresses are written in lowercase hex with a
+/// `0x` prefix and type arguments are separated by `, `. Parsing accepts both address forms,
+/// uppercase hex digits and arbitrary whitespace between tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressFormat {
+    /// All `2 * AccountAddress::LENGTH` hex digits, e.g. `0x00000000000000000000000000000001`.
+    Long,
+    /// Hex digits without leading zeros, e.g. `0x1`.
+    Short,
+}
+
+impl AddressFormat {
+    pub fn format(self, address: &AccountAddress) -> String {
+        match self {
+            AddressFormat::Long => format!("0x{}", address.to_hex()),
+            AddressFormat::Short => address.to_hex_literal(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Hash, Eq, Clone, PartialOrd, Ord)]
 pub enum TypeTag {
     // alias for compatibility with old json serialized data.
@@ -58,6 +86,43 @@ impl StructTag {
     pub fn module_id(&self) -> ModuleId {
         ModuleId::new(self.address, self.module.to_owned())
     }
+
+    /// Returns the canonical textual format of this struct tag, e.g. `0x1::M::S<u8, 0x1::M::T>`.
+    pub fn to_canonical_string(&self, format: AddressFormat) -> String {
+        let mut s = format!(
+            "{}::{}::{}",
+            format.format(&self.address),
+            self.module,
+            self.name
+        );
+        if !self.type_params.is_empty() {
+            let ty_args = self
+                .type_params
+                .iter()
+                .map(|ty| ty.to_canonical_string(format))
+                .collect::<Vec<_>>();
+            s.push('<');
+            s.push_str(&ty_args.join(", "));
+            s.push('>');
+        }
+        s
+    }
+}
+
+impl TypeTag {
+    /// Returns the canonical textual format of this type tag, e.g. `vector<0x1::M::S<u8>>`.
+    pub fn to_canonical_string(&self, format: AddressFormat) -> String {
+        match self {
+            TypeTag::Struct(s) => s.to_canonical_string(format),
+            TypeTag::Vector(ty) => format!("vector<{}>", ty.to_canonical_string(format)),
+            TypeTag::U8 => "u8".to_string(),
+            TypeTag::U64 => "u64".to_string(),
+            TypeTag::U128 => "u128".to_string(),
+            TypeTag::Address => "address".to_string(),
+            TypeTag::Signer => "signer".to_string(),
+            TypeTag::Bool => "bool".to_string(),
+        }
+    }
 }
 
 /// Represents the intitial key into global storage where we first index by the address, and then
@@ -120,51 +185,59 @@ impl ModuleId {
     }
 }
 
+/// Module IDs are displayed in their canonical format with long addresses.
 impl Display for ModuleId {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(f, "{}::{}", self.address, self.name)
+        write!(f, "{}", self.to_canonical_string(AddressFormat::Long))
     }
 }
 
 impl ModuleId {
     pub fn short_str_lossless(&self) -> String {
-        format!("0x{}::{}", self.address.short_str_lossless(), self.name)
+        self.to_canonical_string(AddressFormat::Short)
+    }
+
+    /// Returns the canonical textual format of this module ID, e.g. `0x1::M`.
+    pub fn to_canonical_string(&self, format: AddressFormat) -> String {
+        format!("{}::{}", format.format(&self.address), self.name)
+    }
+}
+
+impl FromStr for ModuleId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_module_id(s)
     }
 }
 
+/// Struct tags are displayed in their canonical format with short addresses.
 impl Display for StructTag {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(
-            f,
-            "0x{}::{}::{}",
-            self.address.short_str_lossless(),
-            self.module,
-            self.name
-        )?;
-        if let Some(first_ty) = self.type_params.first() {
-            write!(f, "<")?;
-            write!(f, "{}", first_ty)?;
-            for ty in self.type_params.iter().skip(1) {
-                write!(f, ", {}", ty)?;
-            }
-            write!(f, ">")?;
-        }
-        Ok(())
+        write!(f, "{}", self.to_canonical_string(AddressFormat::Short))
+    }
+}
+
+impl FromStr for StructTag {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_struct_tag(s)
     }
 }
 
+/// Type tags are displayed in their canonical format with short addresses.
 impl Display for TypeTag {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        match self {
-            TypeTag::Struct(s) => write!(f, "{}", s),
-            TypeTag::Vector(ty) => write!(f, "vector<{}>", ty),
-            TypeTag::U8 => write!(f, "u8"),
-            TypeTag::U64 => write!(f, "u64"),
-            TypeTag::U128 => write!(f, "u128"),
-            TypeTag::Address => write!(f, "address"),
-            TypeTag::Signer => write!(f, "signer"),
-            TypeTag::Bool => write!(f, "bool"),
-        }
+        write!(f, "{}", self.to_canonical_string(AddressFormat::Short))
+    }
+}
+
+impl FromStr for TypeTag {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_type_tag(s)
     }
 }
 
@@ -9,6 +9,8 @@ pub mod effects;
 pub mod errmap;
 pub mod gas_schedule;
 pub mod identifier;
+#[cfg(any(test, feature = "json"))]
+pub mod json;
 pub mod language_storage;
 pub mod move_resource;
 pub mod parser;
@@ -4,8 +4,9 @@
 use crate::{
     account_address::AccountAddress,
     identifier::{self, Identifier},
-    language_storage::{StructTag, TypeTag},
-    transaction_argument::TransactionArgument,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    transaction_argument::{is_ascii_string, is_option, TransactionArgument},
+    value::{MoveStruct, MoveValue, VALUE_DEPTH_MAX},
 };
 use anyhow::{bail, format_err, Result};
 use std::iter::Peekable;
@@ -22,16 +23,26 @@ enum Token {
     Whitespace(String),
     Name(String),
     Address(String),
+    Number(String),
     U8(String),
     U64(String),
     U128(String),
     Bytes(String),
+    String(String),
     True,
     False,
     ColonColon,
+    Colon,
+    AtSign,
     Lt,
     Gt,
     Comma,
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
     EOF,
 }
 
@@ -39,6 +50,24 @@ impl Token {
     fn is_whitespace(&self) -> bool {
         matches!(self, Self::Whitespace(_))
     }
+
+    /// Returns the identifier spelled by this token, including keywords such as `u8` or `vector`,
+    /// which are valid module, struct and field names.
+    fn into_identifier(self) -> Option<String> {
+        Some(match self {
+            Self::Name(s) => s,
+            Self::U8Type => "u8".to_string(),
+            Self::U64Type => "u64".to_string(),
+            Self::U128Type => "u128".to_string(),
+            Self::BoolType => "bool".to_string(),
+            Self::AddressType => "address".to_string(),
+            Self::VectorType => "vector".to_string(),
+            Self::SignerType => "signer".to_string(),
+            Self::True => "true".to_string(),
+            Self::False => "false".to_string(),
+            _ => return None,
+        })
+    }
 }
 
 fn name_token(s: String) -> Token {
@@ -83,7 +112,7 @@ fn next_number(initial: char, mut it: impl Iterator<Item = char>) -> Result<(Tok
             }
             _ => {
                 let len = num.len();
-                return Ok((Token::U64(num), len));
+                return Ok((Token::Number(num), len));
             }
         }
     }
@@ -98,10 +127,17 @@ fn next_token(s: &str) -> Result<Option<(Token, usize)>> {
             '<' => (Token::Lt, 1),
             '>' => (Token::Gt, 1),
             ',' => (Token::Comma, 1),
+            '[' => (Token::LBracket, 1),
+            ']' => (Token::RBracket, 1),
+            '(' => (Token::LParen, 1),
+            ')' => (Token::RParen, 1),
             ':' => match it.next() {
                 Some(':') => (Token::ColonColon, 2),
-                _ => bail!("unrecognized token"),
+                _ => (Token::Colon, 1),
             },
+            '@' => (Token::AtSign, 1),
+            '{' => (Token::LBrace, 1),
+            '}' => (Token::RBrace, 1),
             '0' if it.peek() == Some(&'x') || it.peek() == Some(&'X') => {
                 it.next().unwrap();
                 match it.next() {
@@ -137,6 +173,18 @@ fn next_token(s: &str) -> Result<Option<(Token, usize)>> {
                 let len = r.len() + 3;
                 (Token::Bytes(hex::encode(r)), len)
             }
+            '"' => {
+                let mut r = String::new();
+                loop {
+                    match it.next() {
+                        Some('"') => break,
+                        Some(c) if c.is_ascii() => r.push(c),
+                        _ => bail!("unrecognized token"),
+                    }
+                }
+                let len = r.len() + 2;
+                (Token::String(r), len)
+            }
             'x' if it.peek() == Some(&'"') => {
                 it.next().unwrap();
                 let mut r = String::new();
@@ -163,7 +211,7 @@ fn next_token(s: &str) -> Result<Option<(Token, usize)>> {
                 let len = r.len();
                 (Token::Whitespace(r), len)
             }
-            c if c.is_ascii_alphabetic() => {
+            c if c.is_ascii_alphabetic() || c == '_' => {
                 let mut r = String::new();
                 r.push(c);
                 for c in it {
@@ -267,46 +315,130 @@ impl<I: Iterator<Item = Token>> Parser<I> {
                 self.consume(Token::Gt)?;
                 TypeTag::Vector(Box::new(ty))
             }
+            Token::Address(addr) => TypeTag::Struct(self.parse_struct_tag(addr)?),
+            tok => bail!("unexpected token {:?}, expected type tag", tok),
+        })
+    }
+
+    fn parse_identifier(&mut self) -> Result<Identifier> {
+        let tok = self.next()?;
+        match tok.into_identifier() {
+            Some(s) => Identifier::new(s),
+            None => bail!("expected identifier"),
+        }
+    }
+
+    /// Parses `::module` after the address `addr` of a module ID.
+    fn parse_module_id(&mut self, addr: String) -> Result<ModuleId> {
+        self.consume(Token::ColonColon)?;
+        let name = self.parse_identifier()?;
+        Ok(ModuleId::new(
+            AccountAddress::from_hex_literal(&addr)?,
+            name,
+        ))
+    }
+
+    /// Parses `::module::name<type_args>` after the address `addr` of a struct tag.
+    fn parse_struct_tag(&mut self, addr: String) -> Result<StructTag> {
+        let module_id = self.parse_module_id(addr)?;
+        self.consume(Token::ColonColon)?;
+        let name = self.parse_identifier()?;
+        let type_params = if self.peek() == Some(&Token::Lt) {
+            self.next()?;
+            let ty_args =
+                self.parse_comma_list(|parser| parser.parse_type_tag(), Token::Gt, true)?;
+            self.consume(Token::Gt)?;
+            ty_args
+        } else {
+            vec![]
+        };
+        let (address, module) = module_id.into();
+        Ok(StructTag {
+            address,
+            module,
+            name,
+            type_params,
+        })
+    }
+
+    /// Parses a Move value in its canonical format, see `MoveValue::to_canonical_string`.
+    fn parse_move_value(&mut self, depth: usize) -> Result<MoveValue> {
+        if depth > VALUE_DEPTH_MAX {
+            bail!("value exceeds the maximum depth of {}", VALUE_DEPTH_MAX)
+        }
+        Ok(match self.next()? {
+            Token::True => MoveValue::Bool(true),
+            Token::False => MoveValue::Bool(false),
+            Token::U8(s) => MoveValue::U8(s.parse()?),
+            Token::U64(s) => MoveValue::U64(s.parse()?),
+            Token::U128(s) => MoveValue::U128(s.parse()?),
+            Token::AtSign => MoveValue::Address(self.parse_address_value()?),
+            Token::SignerType => {
+                self.consume(Token::LParen)?;
+                self.consume(Token::AtSign)?;
+                let addr = self.parse_address_value()?;
+                self.consume(Token::RParen)?;
+                MoveValue::Signer(addr)
+            }
+            Token::VectorType => {
+                self.consume(Token::LBracket)?;
+                let vals = self.parse_comma_list(
+                    |parser| parser.parse_move_value(depth + 1),
+                    Token::RBracket,
+                    true,
+                )?;
+                self.consume(Token::RBracket)?;
+                MoveValue::Vector(vals)
+            }
+            Token::LParen => {
+                let vals = self.parse_comma_list(
+                    |parser| parser.parse_move_value(depth + 1),
+                    Token::RParen,
+                    true,
+                )?;
+                self.consume(Token::RParen)?;
+                MoveValue::Struct(MoveStruct::Runtime(vals))
+            }
+            Token::LBrace => MoveValue::Struct(MoveStruct::WithFields(self.parse_fields(depth)?)),
             Token::Address(addr) => {
-                self.consume(Token::ColonColon)?;
-                match self.next()? {
-                    Token::Name(module) => {
-                        self.consume(Token::ColonColon)?;
-                        match self.next()? {
-                            Token::Name(name) => {
-                                let ty_args = if self.peek() == Some(&Token::Lt) {
-                                    self.next()?;
-                                    let ty_args = self.parse_comma_list(
-                                        |parser| parser.parse_type_tag(),
-                                        Token::Gt,
-                                        true,
-                                    )?;
-                                    self.consume(Token::Gt)?;
-                                    ty_args
-                                } else {
-                                    vec![]
-                                };
-                                TypeTag::Struct(StructTag {
-                                    address: AccountAddress::from_hex_literal(&addr)?,
-                                    module: Identifier::new(module)?,
-                                    name: Identifier::new(name)?,
-                                    type_params: ty_args,
-                                })
-                            }
-                            t => bail!("expected name, got {:?}", t),
-                        }
-                    }
-                    t => bail!("expected name, got {:?}", t),
-                }
+                let type_ = self.parse_struct_tag(addr)?;
+                self.consume(Token::LBrace)?;
+                let fields = self.parse_fields(depth)?;
+                MoveValue::Struct(MoveStruct::with_types(type_, fields))
             }
-            tok => bail!("unexpected token {:?}, expected type tag", tok),
+            tok => bail!("unexpected token {:?}, expected value", tok),
         })
     }
 
+    fn parse_address_value(&mut self) -> Result<AccountAddress> {
+        match self.next()? {
+            Token::Address(addr) => Ok(AccountAddress::from_hex_literal(&addr)?),
+            tok => bail!("unexpected token {:?}, expected address", tok),
+        }
+    }
+
+    /// Parses the named fields of a struct value after the opening brace.
+    fn parse_fields(&mut self, depth: usize) -> Result<Vec<(Identifier, MoveValue)>> {
+        let fields = self.parse_comma_list(
+            |parser| {
+                let field = match parser.next()?.into_identifier() {
+                    Some(s) => Identifier::new(s)?,
+                    None => bail!("expected field name"),
+                };
+                parser.consume(Token::Colon)?;
+                Ok((field, parser.parse_move_value(depth + 1)?))
+            },
+            Token::RBrace,
+            true,
+        )?;
+        self.consume(Token::RBrace)?;
+        Ok(fields)
+    }
+
     fn parse_transaction_argument(&mut self) -> Result<TransactionArgument> {
         Ok(match self.next()? {
             Token::U8(s) => TransactionArgument::U8(s.parse()?),
-            Token::U64(s) => TransactionArgument::U64(s.parse()?),
+            Token::Number(s) | Token::U64(s) => TransactionArgument::U64(s.parse()?),
             Token::U128(s) => TransactionArgument::U128(s.parse()?),
             Token::True => TransactionArgument::Bool(true),
             Token::False => TransactionArgument::Bool(false),
@@ -314,9 +446,125 @@ impl<I: Iterator<Item = Token>> Parser<I> {
                 TransactionArgument::Address(AccountAddress::from_hex_literal(&addr)?)
             }
             Token::Bytes(s) => TransactionArgument::U8Vector(hex::decode(s)?),
+            Token::String(s) => TransactionArgument::AsciiString(s),
+            Token::VectorType => {
+                self.consume(Token::Lt)?;
+                let ty = self.parse_type_tag()?;
+                self.consume(Token::Gt)?;
+                self.parse_vector_argument(&ty)?
+            }
+            Token::Name(s) if s == "some" => {
+                self.consume(Token::LParen)?;
+                let arg = self.parse_transaction_argument()?;
+                self.consume(Token::RParen)?;
+                TransactionArgument::Option(Some(Box::new(arg)))
+            }
+            Token::Name(s) if s == "none" => TransactionArgument::Option(None),
             tok => bail!("unexpected token {:?}, expected transaction argument", tok),
         })
     }
+
+    /// Parses the value of a transaction argument of type `ty`. Integer literals may omit their
+    /// suffix, as the type is known from the context.
+    fn parse_typed_transaction_argument(&mut self, ty: &TypeTag) -> Result<TransactionArgument> {
+        Ok(match ty {
+            TypeTag::U8 => match self.next()? {
+                Token::Number(s) | Token::U8(s) => TransactionArgument::U8(s.parse()?),
+                tok => bail!("unexpected token {:?}, expected u8", tok),
+            },
+            TypeTag::U64 => match self.next()? {
+                Token::Number(s) | Token::U64(s) => TransactionArgument::U64(s.parse()?),
+                tok => bail!("unexpected token {:?}, expected u64", tok),
+            },
+            TypeTag::U128 => match self.next()? {
+                Token::Number(s) | Token::U128(s) => TransactionArgument::U128(s.parse()?),
+                tok => bail!("unexpected token {:?}, expected u128", tok),
+            },
+            TypeTag::Bool => match self.next()? {
+                Token::True => TransactionArgument::Bool(true),
+                Token::False => TransactionArgument::Bool(false),
+                tok => bail!("unexpected token {:?}, expected bool", tok),
+            },
+            TypeTag::Address => match self.next()? {
+                Token::Address(addr) => {
+                    TransactionArgument::Address(AccountAddress::from_hex_literal(&addr)?)
+                }
+                tok => bail!("unexpected token {:?}, expected address", tok),
+            },
+            TypeTag::Vector(ty) => self.parse_vector_argument(ty)?,
+            TypeTag::Struct(tag)
+                if is_ascii_string(&tag.address, &tag.module, &tag.name)
+                    && tag.type_params.is_empty() =>
+            {
+                match self.next()? {
+                    Token::String(s) => TransactionArgument::AsciiString(s),
+                    tok => bail!("unexpected token {:?}, expected string", tok),
+                }
+            }
+            TypeTag::Struct(tag)
+                if is_option(&tag.address, &tag.module, &tag.name)
+                    && tag.type_params.len() == 1 =>
+            {
+                match self.next()? {
+                    Token::Name(s) if s == "some" => {
+                        self.consume(Token::LParen)?;
+                        let arg = self.parse_typed_transaction_argument(&tag.type_params[0])?;
+                        self.consume(Token::RParen)?;
+                        TransactionArgument::Option(Some(Box::new(arg)))
+                    }
+                    Token::Name(s) if s == "none" => TransactionArgument::Option(None),
+                    tok => bail!("unexpected token {:?}, expected option", tok),
+                }
+            }
+            TypeTag::Signer | TypeTag::Struct(_) => {
+                bail!("unsupported transaction argument type {}", ty)
+            }
+        })
+    }
+
+    /// Parses a `vector<ty>` argument, either as a bracketed list of elements, or for
+    /// `vector<u8>`, also as a byte string.
+    fn parse_vector_argument(&mut self, ty: &TypeTag) -> Result<TransactionArgument> {
+        if !is_valid_argument_type(ty) {
+            bail!("unsupported transaction argument type vector<{}>", ty)
+        }
+        if let (TypeTag::U8, Some(Token::Bytes(_))) = (ty, self.peek()) {
+            return self.parse_transaction_argument();
+        }
+        self.consume(Token::LBracket)?;
+        let args = self.parse_comma_list(
+            |parser| parser.parse_typed_transaction_argument(ty),
+            Token::RBracket,
+            true,
+        )?;
+        self.consume(Token::RBracket)?;
+        Ok(match ty {
+            TypeTag::U8 => TransactionArgument::U8Vector(
+                args.into_iter()
+                    .map(|arg| match arg {
+                        TransactionArgument::U8(byte) => byte,
+                        _ => unreachable!(),
+                    })
+                    .collect(),
+            ),
+            _ => TransactionArgument::Vector(args),
+        })
+    }
+}
+
+/// Returns true if values of type `ty` can be written as transaction arguments.
+fn is_valid_argument_type(ty: &TypeTag) -> bool {
+    match ty {
+        TypeTag::Bool | TypeTag::U8 | TypeTag::U64 | TypeTag::U128 | TypeTag::Address => true,
+        TypeTag::Vector(ty) => is_valid_argument_type(ty),
+        TypeTag::Struct(tag) => {
+            (is_ascii_string(&tag.address, &tag.module, &tag.name) && tag.type_params.is_empty())
+                || (is_option(&tag.address, &tag.module, &tag.name)
+                    && tag.type_params.len() == 1
+                    && is_valid_argument_type(&tag.type_params[0]))
+        }
+        TypeTag::Signer => false,
+    }
 }
 
 fn parse<F, T>(s: &str, f: F) -> Result<T>
@@ -364,6 +612,19 @@ pub fn parse_transaction_argument(s: &str) -> Result<TransactionArgument> {
     parse(s, |parser| parser.parse_transaction_argument())
 }
 
+pub fn parse_module_id(s: &str) -> Result<ModuleId> {
+    parse(s, |parser| match parser.next()? {
+        Token::Address(addr) => parser.parse_module_id(addr),
+        tok => bail!("unexpected token {:?}, expected module id", tok),
+    })
+    .map_err(|e| format_err!("invalid module id: {}, {}", s, e))
+}
+
+/// Parses a Move value in its canonical format, see `MoveValue::to_canonical_string`.
+pub fn parse_move_value(s: &str) -> Result<MoveValue> {
+    parse(s, |parser| parser.parse_move_value(1))
+}
+
 pub fn parse_struct_tag(s: &str) -> Result<StructTag> {
     let type_tag = parse(s, |parser| parser.parse_type_tag())
         .map_err(|e| format_err!("invalid struct tag: {}, {}", s, e))?;
@@ -419,6 +680,54 @@ mod tests {
             ("x\"\"", T::U8Vector(vec![])),
             ("x\"00\"", T::U8Vector(vec![0x00])),
             ("x\"deadbeef\"", T::U8Vector(vec![0xde, 0xad, 0xbe, 0xef])),
+            ("vector<u8>[]", T::U8Vector(vec![])),
+            ("vector<u8>[1, 2u8]", T::U8Vector(vec![1, 2])),
+            ("vector<u8>x\"0102\"", T::U8Vector(vec![1, 2])),
+            ("vector<u64>[]", T::Vector(vec![])),
+            ("vector<u64>[1,2,]", T::Vector(vec![T::U64(1), T::U64(2)])),
+            ("vector<u128>[1u128]", T::Vector(vec![T::U128(1)])),
+            (
+                "vector<bool>[true, false]",
+                T::Vector(vec![T::Bool(true), T::Bool(false)]),
+            ),
+            (
+                "vector<address>[0x1, 0x2]",
+                T::Vector(vec![
+                    T::Address(AccountAddress::from_hex_literal("0x1").unwrap()),
+                    T::Address(AccountAddress::from_hex_literal("0x2").unwrap()),
+                ]),
+            ),
+            (
+                "vector<vector<u8>>[x\"01\", b\"a\", []]",
+                T::Vector(vec![
+                    T::U8Vector(vec![1]),
+                    T::U8Vector(vec![b'a']),
+                    T::U8Vector(vec![]),
+                ]),
+            ),
+            (
+                "vector<vector<u64>>[[1], []]",
+                T::Vector(vec![T::Vector(vec![T::U64(1)]), T::Vector(vec![])]),
+            ),
+            ("\"\"", T::AsciiString("".to_string())),
+            ("\"hello world\"", T::AsciiString("hello world".to_string())),
+            (
+                "vector<0x1::ASCII::String>[\"a\", \"b\"]",
+                T::Vector(vec![
+                    T::AsciiString("a".to_string()),
+                    T::AsciiString("b".to_string()),
+                ]),
+            ),
+            ("none", T::Option(None)),
+            ("some(1u8)", T::Option(Some(Box::new(T::U8(1))))),
+            (
+                "some(vector<u64>[1])",
+                T::Option(Some(Box::new(T::Vector(vec![T::U64(1)])))),
+            ),
+            (
+                "vector<0x1::Option::Option<u8>>[some(1), none]",
+                T::Vector(vec![T::Option(Some(Box::new(T::U8(1)))), T::Option(None)]),
+            ),
         ] {
             assert_eq!(&parse_transaction_argument(s).unwrap(), expected)
         }
@@ -452,8 +761,26 @@ mod tests {
             "3false",
             "3 false",
             "",
+            "vector<u8>[256]",
+            "vector<u8>[1u64]",
+            "vector<u64>[1u8]",
+            "vector<u64>[true]",
+            "vector<u64>[1, 2",
+            "vector<u64>",
+            "vector<u64>x\"01\"",
+            "vector<signer>[]",
+            "vector<0x1::M::S>[]",
+            "vector<0x1::ASCII::String>[b\"a\"]",
+            "vector<0x1::Option::Option<u8>>[some(256)]",
+            "vector<0x1::Option::Option<u8>>[1]",
+            "\"\u{e9}\"",
+            "\"abc",
+            "some",
+            "some()",
+            "some(1, 2)",
+            "none(1)",
         ] {
-            assert!(parse_transaction_argument(s).is_err())
+            assert!(parse_transaction_argument(s).is_err(), "{}", s)
         }
     }
 
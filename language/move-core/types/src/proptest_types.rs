@@ -6,6 +6,7 @@ use crate::{
     identifier::Identifier,
     language_storage::{StructTag, TypeTag},
     transaction_argument::TransactionArgument,
+    value::{MoveStruct, MoveValue},
 };
 use proptest::{collection::vec, prelude::*};
 impl Arbitrary for TypeTag {
@@ -47,6 +48,66 @@ impl Arbitrary for TypeTag {
     }
 }
 
+impl Arbitrary for StructTag {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        (
+            any::<AccountAddress>(),
+            any::<Identifier>(),
+            any::<Identifier>(),
+            vec(any::<TypeTag>(), 0..4),
+        )
+            .prop_map(|(address, module, name, type_params)| StructTag {
+                address,
+                module,
+                name,
+                type_params,
+            })
+            .boxed()
+    }
+}
+
+/// Generates values of all shapes, which are not necessarily well-typed: vector elements may have
+/// different types, and struct fields do not need to match their declared types.
+impl Arbitrary for MoveValue {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        let leaf = prop_oneof![
+            any::<bool>().prop_map(MoveValue::Bool),
+            any::<u8>().prop_map(MoveValue::U8),
+            any::<u64>().prop_map(MoveValue::U64),
+            any::<u128>().prop_map(MoveValue::U128),
+            any::<AccountAddress>().prop_map(MoveValue::Address),
+            any::<AccountAddress>().prop_map(MoveValue::Signer),
+        ];
+        leaf.prop_recursive(
+            8,  // levels deep
+            32, // max size
+            4,  // max number of items per collection
+            |inner| {
+                prop_oneof![
+                    vec(inner.clone(), 0..4).prop_map(MoveValue::Vector),
+                    vec(inner.clone(), 0..4)
+                        .prop_map(|vals| MoveValue::Struct(MoveStruct::Runtime(vals))),
+                    vec((any::<Identifier>(), inner.clone()), 0..4)
+                        .prop_map(|fields| MoveValue::Struct(MoveStruct::WithFields(fields))),
+                    (any::<StructTag>(), vec((any::<Identifier>(), inner), 0..4)).prop_map(
+                        |(type_, fields)| MoveValue::Struct(MoveStruct::WithTypes {
+                            type_,
+                            fields
+                        })
+                    ),
+                ]
+            },
+        )
+        .boxed()
+    }
+}
+
 impl Arbitrary for TransactionArgument {
     type Parameters = ();
     fn arbitrary_with(_args: ()) -> Self::Strategy {
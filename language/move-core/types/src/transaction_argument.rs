@@ -1,8 +1,14 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{account_address::AccountAddress, value::MoveValue};
-use anyhow::{anyhow, Error, Result};
+use crate::{
+    account_address::AccountAddress,
+    ident_str,
+    identifier::IdentStr,
+    language_storage::CORE_CODE_ADDRESS,
+    value::{MoveStruct, MoveValue},
+};
+use anyhow::{anyhow, bail, Error, Result};
 use serde::{Deserialize, Serialize};
 use std::{convert::TryFrom, fmt};
 
@@ -14,6 +20,32 @@ pub enum TransactionArgument {
     Address(AccountAddress),
     U8Vector(#[serde(with = "serde_bytes")] Vec<u8>),
     Bool(bool),
+    /// A vector of arguments of the same type. Vectors of `u8` are represented as `U8Vector`.
+    Vector(Vec<TransactionArgument>),
+    /// A `0x1::ASCII::String`.
+    AsciiString(String),
+    /// A `0x1::Option::Option<T>`.
+    Option(Option<Box<TransactionArgument>>),
+}
+
+pub const ASCII_MODULE_NAME: &IdentStr = ident_str!("ASCII");
+pub const ASCII_STRING_STRUCT_NAME: &IdentStr = ident_str!("String");
+pub const OPTION_MODULE_NAME: &IdentStr = ident_str!("Option");
+pub const OPTION_STRUCT_NAME: &IdentStr = ident_str!("Option");
+
+/// Returns true if `address::module::name` is the `0x1::ASCII::String` struct.
+pub fn is_ascii_string(address: &AccountAddress, module: &IdentStr, name: &IdentStr) -> bool {
+    address == &CORE_CODE_ADDRESS && module == ASCII_MODULE_NAME && name == ASCII_STRING_STRUCT_NAME
+}
+
+/// Returns true if `address::module::name` is the `0x1::Option::Option` struct.
+pub fn is_option(address: &AccountAddress, module: &IdentStr, name: &IdentStr) -> bool {
+    address == &CORE_CODE_ADDRESS && module == OPTION_MODULE_NAME && name == OPTION_STRUCT_NAME
+}
+
+/// Returns true if every byte of `bytes` is a valid character of an `0x1::ASCII::String`.
+pub fn is_valid_ascii(bytes: &[u8]) -> bool {
+    bytes.iter().all(|b| b.is_ascii())
 }
 
 impl fmt::Debug for TransactionArgument {
@@ -27,6 +59,9 @@ impl fmt::Debug for TransactionArgument {
             TransactionArgument::U8Vector(vector) => {
                 write!(f, "{{U8Vector: 0x{}}}", hex::encode(vector))
             }
+            TransactionArgument::Vector(vector) => write!(f, "{{VECTOR: {:?}}}", vector),
+            TransactionArgument::AsciiString(string) => write!(f, "{{STRING: {:?}}}", string),
+            TransactionArgument::Option(option) => write!(f, "{{OPTION: {:?}}}", option),
         }
     }
 }
@@ -40,6 +75,17 @@ impl From<TransactionArgument> for MoveValue {
             TransactionArgument::Address(a) => MoveValue::Address(a),
             TransactionArgument::Bool(b) => MoveValue::Bool(b),
             TransactionArgument::U8Vector(v) => MoveValue::vector_u8(v),
+            TransactionArgument::Vector(v) => {
+                MoveValue::Vector(v.into_iter().map(MoveValue::from).collect())
+            }
+            TransactionArgument::AsciiString(s) => {
+                MoveValue::Struct(MoveStruct::new(vec![MoveValue::vector_u8(s.into_bytes())]))
+            }
+            TransactionArgument::Option(o) => {
+                MoveValue::Struct(MoveStruct::new(vec![MoveValue::Vector(
+                    o.into_iter().map(|v| MoveValue::from(*v)).collect(),
+                )]))
+            }
         }
     }
 }
@@ -53,17 +99,34 @@ impl TryFrom<MoveValue> for TransactionArgument {
             MoveValue::U128(i) => TransactionArgument::U128(i),
             MoveValue::Address(a) => TransactionArgument::Address(a),
             MoveValue::Bool(b) => TransactionArgument::Bool(b),
-            MoveValue::Vector(v) => TransactionArgument::U8Vector(
+            MoveValue::Vector(v) if v.iter().all(|mv| matches!(mv, MoveValue::U8(_))) => {
+                TransactionArgument::U8Vector(
+                    v.into_iter()
+                        .map(|mv| match mv {
+                            MoveValue::U8(byte) => byte,
+                            _ => unreachable!(),
+                        })
+                        .collect(),
+                )
+            }
+            MoveValue::Vector(v) => TransactionArgument::Vector(
                 v.into_iter()
-                    .map(|mv| {
-                        if let MoveValue::U8(byte) = mv {
-                            Ok(byte)
-                        } else {
-                            Err(anyhow!("unexpected value in bytes: {:?}", mv))
-                        }
-                    })
-                    .collect::<Result<Vec<u8>>>()?,
+                    .map(TransactionArgument::try_from)
+                    .collect::<Result<_>>()?,
             ),
+            MoveValue::Struct(MoveStruct::WithTypes { type_, mut fields }) => {
+                let field = match fields.pop() {
+                    Some((_, field)) if fields.is_empty() => field,
+                    _ => bail!("invalid transaction argument of type {}", type_),
+                };
+                if is_ascii_string(&type_.address, &type_.module, &type_.name) {
+                    struct_argument_as_ascii_string(field)?
+                } else if is_option(&type_.address, &type_.module, &type_.name) {
+                    struct_argument_as_option(field)?
+                } else {
+                    bail!("invalid transaction argument of type {}", type_)
+                }
+            }
             MoveValue::Signer(_) | MoveValue::Struct(_) => {
                 return Err(anyhow!("invalid transaction argument: {:?}", val))
             }
@@ -71,6 +134,27 @@ impl TryFrom<MoveValue> for TransactionArgument {
     }
 }
 
+fn struct_argument_as_ascii_string(bytes: MoveValue) -> Result<TransactionArgument> {
+    match TransactionArgument::try_from(bytes)? {
+        TransactionArgument::U8Vector(bytes) if is_valid_ascii(&bytes) => {
+            Ok(TransactionArgument::AsciiString(String::from_utf8(bytes)?))
+        }
+        arg => bail!("invalid ASCII string: {:?}", arg),
+    }
+}
+
+fn struct_argument_as_option(vec: MoveValue) -> Result<TransactionArgument> {
+    match vec {
+        MoveValue::Vector(mut v) if v.len() <= 1 => Ok(TransactionArgument::Option(
+            v.pop()
+                .map(TransactionArgument::try_from)
+                .transpose()?
+                .map(Box::new),
+        )),
+        _ => bail!("invalid option: {:?}", vec),
+    }
+}
+
 /// Convert the transaction arguments into Move values.
 pub fn convert_txn_args(args: &[TransactionArgument]) -> Vec<Vec<u8>> {
     args.iter()
@@ -106,11 +190,14 @@ impl VecBytes {
 
 #[cfg(test)]
 mod tests {
-    use std::convert::{From, TryInto};
+    use std::convert::{From, TryFrom, TryInto};
 
     use crate::{
-        account_address::AccountAddress, transaction_argument::TransactionArgument,
-        value::MoveValue,
+        account_address::AccountAddress,
+        identifier::Identifier,
+        language_storage::{StructTag, TypeTag, CORE_CODE_ADDRESS},
+        transaction_argument::{convert_txn_args, TransactionArgument},
+        value::{MoveStruct, MoveValue},
     };
 
     #[test]
@@ -122,10 +209,85 @@ mod tests {
             TransactionArgument::Bool(true),
             TransactionArgument::Address(AccountAddress::from_hex_literal("0x1").unwrap()),
             TransactionArgument::U8Vector(vec![1, 2, 3, 4]),
+            TransactionArgument::Vector(vec![
+                TransactionArgument::U64(1),
+                TransactionArgument::U64(2),
+            ]),
+            TransactionArgument::Vector(vec![
+                TransactionArgument::U8Vector(vec![1, 2]),
+                TransactionArgument::U8Vector(vec![]),
+            ]),
         ];
         for val in vals {
             let ret: TransactionArgument = MoveValue::from(val.clone()).try_into().unwrap();
             assert_eq!(ret, val);
         }
     }
+
+    fn struct_value(
+        module: &str,
+        name: &str,
+        ty_args: Vec<TypeTag>,
+        field: MoveValue,
+    ) -> MoveValue {
+        MoveValue::Struct(MoveStruct::WithTypes {
+            type_: StructTag {
+                address: CORE_CODE_ADDRESS,
+                module: Identifier::new(module).unwrap(),
+                name: Identifier::new(name).unwrap(),
+                type_params: ty_args,
+            },
+            fields: vec![(Identifier::new("f").unwrap(), field)],
+        })
+    }
+
+    #[test]
+    fn test_struct_arguments() {
+        let string = struct_value(
+            "ASCII",
+            "String",
+            vec![],
+            MoveValue::vector_u8(b"hi".to_vec()),
+        );
+        let some = struct_value(
+            "Option",
+            "Option",
+            vec![TypeTag::U64],
+            MoveValue::Vector(vec![MoveValue::U64(5)]),
+        );
+        let none = struct_value(
+            "Option",
+            "Option",
+            vec![TypeTag::U64],
+            MoveValue::Vector(vec![]),
+        );
+        for (value, arg) in vec![
+            (string, TransactionArgument::AsciiString("hi".to_string())),
+            (
+                some,
+                TransactionArgument::Option(Some(Box::new(TransactionArgument::U64(5)))),
+            ),
+            (none, TransactionArgument::Option(None)),
+        ] {
+            assert_eq!(
+                convert_txn_args(&[arg.clone()]),
+                vec![value.clone().undecorate().simple_serialize().unwrap()]
+            );
+            let ret: TransactionArgument = value.try_into().unwrap();
+            assert_eq!(ret, arg);
+        }
+
+        for value in vec![
+            struct_value("ASCII", "String", vec![], MoveValue::vector_u8(vec![0x80])),
+            struct_value(
+                "Option",
+                "Option",
+                vec![TypeTag::U64],
+                MoveValue::Vector(vec![MoveValue::U64(1), MoveValue::U64(2)]),
+            ),
+            struct_value("M", "S", vec![], MoveValue::U64(1)),
+        ] {
+            assert!(TransactionArgument::try_from(value).is_err());
+        }
+    }
 }
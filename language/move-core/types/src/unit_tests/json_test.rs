@@ -0,0 +1,153 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account_address::AccountAddress,
+    ident_str,
+    language_storage::StructTag,
+    value::{MoveFieldLayout, MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue},
+};
+use serde_json::json;
+
+fn struct_layout() -> MoveTypeLayout {
+    MoveTypeLayout::Struct(MoveStructLayout::with_fields(vec![
+        MoveFieldLayout::new(ident_str!("flag").to_owned(), MoveTypeLayout::Bool),
+        MoveFieldLayout::new(ident_str!("small").to_owned(), MoveTypeLayout::U8),
+        MoveFieldLayout::new(ident_str!("big").to_owned(), MoveTypeLayout::U128),
+        MoveFieldLayout::new(ident_str!("owner").to_owned(), MoveTypeLayout::Address),
+        MoveFieldLayout::new(
+            ident_str!("bytes").to_owned(),
+            MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+        ),
+        MoveFieldLayout::new(
+            ident_str!("nums").to_owned(),
+            MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U64)),
+        ),
+    ]))
+}
+
+fn struct_value() -> MoveValue {
+    MoveValue::Struct(MoveStruct::with_fields(vec![
+        (ident_str!("flag").to_owned(), MoveValue::Bool(true)),
+        (ident_str!("small").to_owned(), MoveValue::U8(7)),
+        (ident_str!("big").to_owned(), MoveValue::U128(u128::MAX)),
+        (
+            ident_str!("owner").to_owned(),
+            MoveValue::Address(AccountAddress::from_hex_literal("0x2a").unwrap()),
+        ),
+        (
+            ident_str!("bytes").to_owned(),
+            MoveValue::vector_u8(vec![0, 0x12, 0xff]),
+        ),
+        (
+            ident_str!("nums").to_owned(),
+            MoveValue::Vector(vec![MoveValue::U64(1), MoveValue::U64(u64::MAX)]),
+        ),
+    ]))
+}
+
+fn struct_json() -> serde_json::Value {
+    json!({
+        "flag": true,
+        "small": 7,
+        "big": u128::MAX.to_string(),
+        "owner": format!("0x{:x}", AccountAddress::from_hex_literal("0x2a").unwrap()),
+        "bytes": "0x0012ff",
+        "nums": ["1", u64::MAX.to_string()],
+    })
+}
+
+#[test]
+fn json_round_trip() {
+    let layout = struct_layout();
+    let value = struct_value();
+    let json = value.to_json(&layout).unwrap();
+    assert_eq!(json, struct_json());
+    assert_eq!(MoveValue::from_json(&json, &layout).unwrap(), value);
+
+    // runtime values are named after the layout
+    assert_eq!(value.clone().undecorate().to_json(&layout).unwrap(), json);
+}
+
+#[test]
+fn json_with_types() {
+    let type_ = StructTag {
+        address: AccountAddress::ONE,
+        module: ident_str!("M").to_owned(),
+        name: ident_str!("S").to_owned(),
+        type_params: vec![],
+    };
+    let layout = MoveTypeLayout::Struct(MoveStructLayout::with_types(
+        type_.clone(),
+        vec![MoveFieldLayout::new(
+            ident_str!("f").to_owned(),
+            MoveTypeLayout::U64,
+        )],
+    ));
+    let value = MoveValue::Struct(MoveStruct::with_types(
+        type_,
+        vec![(ident_str!("f").to_owned(), MoveValue::U64(3))],
+    ));
+    let json = value.to_json(&layout).unwrap();
+    assert_eq!(json, json!({ "f": "3" }));
+    assert_eq!(MoveValue::from_json(&json, &layout).unwrap(), value);
+}
+
+#[test]
+fn json_lenient_input() {
+    let layout = struct_layout();
+    let json = json!({
+        "nums": [1, "2"],
+        "bytes": [1, 2],
+        "owner": "0x2a",
+        "big": 5,
+        "small": 0,
+        "flag": false,
+    });
+    let value = MoveValue::from_json(&json, &layout).unwrap();
+    assert_eq!(
+        value.to_json(&layout).unwrap(),
+        json!({
+            "flag": false,
+            "small": 0,
+            "big": "5",
+            "owner": format!("0x{:x}", AccountAddress::from_hex_literal("0x2a").unwrap()),
+            "bytes": "0x0102",
+            "nums": ["1", "2"],
+        })
+    );
+}
+
+#[test]
+fn json_invalid_input() {
+    let layout = struct_layout();
+    let with_field = |name: &str, val: serde_json::Value| {
+        let mut json = struct_json();
+        json[name] = val;
+        json
+    };
+    for json in [
+        with_field("small", json!(256)),
+        with_field("big", json!("-1")),
+        with_field("nums", json!(["1", "18446744073709551616"])),
+        with_field("owner", json!("2a")),
+        with_field("bytes", json!("0x123")),
+        with_field("flag", json!("true")),
+        with_field("unknown", json!(1)),
+    ] {
+        assert!(MoveValue::from_json(&json, &layout).is_err(), "{}", json);
+    }
+
+    let mut json = struct_json();
+    json.as_object_mut().unwrap().remove("flag");
+    assert!(MoveValue::from_json(&json, &layout).is_err());
+
+    // structs require field names
+    let runtime_layout = MoveTypeLayout::Struct(MoveStructLayout::new(vec![MoveTypeLayout::U64]));
+    let runtime_value = MoveValue::Struct(MoveStruct::new(vec![MoveValue::U64(1)]));
+    assert!(runtime_value.to_json(&runtime_layout).is_err());
+    assert!(MoveValue::from_json(&json!({ "f": "1" }), &runtime_layout).is_err());
+
+    // values must match the layout
+    assert!(MoveValue::U64(1).to_json(&MoveTypeLayout::U8).is_err());
+}
@@ -4,16 +4,91 @@
 use crate::{
     account_address::AccountAddress,
     identifier::{IdentStr, Identifier},
-    language_storage::{ModuleId, StructTag, TypeTag},
+    language_storage::{AddressFormat, ModuleId, StructTag, TypeTag},
 };
 use bcs::test_helpers::assert_canonical_encode_decode;
 use proptest::prelude::*;
+use std::str::FromStr;
 
 proptest! {
     #[test]
     fn test_module_id_canonical_roundtrip(module_id in any::<ModuleId>()) {
         assert_canonical_encode_decode(module_id);
     }
+
+    #[test]
+    fn test_module_id_string_roundtrip(module_id in any::<ModuleId>()) {
+        for format in [AddressFormat::Long, AddressFormat::Short] {
+            let s = module_id.to_canonical_string(format);
+            prop_assert_eq!(ModuleId::from_str(&s).unwrap(), module_id.clone());
+        }
+        prop_assert_eq!(module_id.short_str_lossless(), module_id.to_canonical_string(AddressFormat::Short));
+        prop_assert_eq!(ModuleId::from_str(&module_id.to_string()).unwrap(), module_id.clone());
+        prop_assert_eq!(module_id.to_string(), module_id.to_canonical_string(AddressFormat::Long));
+    }
+
+    #[test]
+    fn test_struct_tag_string_roundtrip(struct_tag in any::<StructTag>()) {
+        for format in [AddressFormat::Long, AddressFormat::Short] {
+            let s = struct_tag.to_canonical_string(format);
+            prop_assert_eq!(StructTag::from_str(&s).unwrap(), struct_tag.clone());
+        }
+        prop_assert_eq!(struct_tag.to_string(), struct_tag.to_canonical_string(AddressFormat::Short));
+    }
+
+    #[test]
+    fn test_type_tag_string_roundtrip(type_tag in any::<TypeTag>()) {
+        for format in [AddressFormat::Long, AddressFormat::Short] {
+            let s = type_tag.to_canonical_string(format);
+            prop_assert_eq!(TypeTag::from_str(&s).unwrap(), type_tag.clone());
+            // whitespace between tokens is not significant
+            let spaced = s.replace("<", " < ").replace(">", " > ").replace("::", " :: ");
+            prop_assert_eq!(TypeTag::from_str(&spaced).unwrap(), type_tag.clone());
+        }
+        prop_assert_eq!(type_tag.to_string(), type_tag.to_canonical_string(AddressFormat::Short));
+    }
+}
+
+#[test]
+fn test_canonical_strings() {
+    let struct_tag = StructTag {
+        address: AccountAddress::ONE,
+        module: Identifier::new("vector").unwrap(),
+        name: Identifier::new("_S").unwrap(),
+        type_params: vec![
+            TypeTag::Vector(Box::new(TypeTag::U8)),
+            TypeTag::Struct(StructTag {
+                address: AccountAddress::from_hex_literal("0xa1").unwrap(),
+                module: Identifier::new("M").unwrap(),
+                name: Identifier::new("true").unwrap(),
+                type_params: vec![TypeTag::Address],
+            }),
+        ],
+    };
+    assert_eq!(
+        struct_tag.to_canonical_string(AddressFormat::Short),
+        "0x1::vector::_S<vector<u8>, 0xa1::M::true<address>>"
+    );
+    assert_eq!(
+        struct_tag.to_canonical_string(AddressFormat::Long),
+        "0x00000000000000000000000000000001::vector::_S<vector<u8>, \
+         0x000000000000000000000000000000a1::M::true<address>>"
+    );
+    for s in [
+        "0x1::vector::_S<vector<u8>,0xa1::M::true<address>>",
+        "0x01 :: vector :: _S < vector<u8> , 0xA1::M::true<address> , >",
+    ] {
+        assert_eq!(StructTag::from_str(s).unwrap(), struct_tag);
+    }
+
+    let module_id = struct_tag.module_id();
+    assert_eq!(
+        module_id.to_canonical_string(AddressFormat::Long),
+        "0x00000000000000000000000000000001::vector"
+    );
+    for s in ["0x1", "0x1::", "0x1::M::S", "M", "0x1::M<u8>"] {
+        assert!(ModuleId::from_str(s).is_err(), "{}", s);
+    }
 }
 
 #[test]
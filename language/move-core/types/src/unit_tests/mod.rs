@@ -2,5 +2,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod identifier_test;
+mod json_test;
 mod language_storage_test;
 mod value_test;
@@ -5,10 +5,106 @@ use crate::{
     account_address::AccountAddress,
     ident_str,
     identifier::Identifier,
-    language_storage::{StructTag, TypeTag},
-    value::{MoveStruct, MoveValue},
+    language_storage::{AddressFormat, StructTag, TypeTag},
+    value::{MoveStruct, MoveValue, VALUE_DEPTH_MAX},
 };
+use proptest::prelude::*;
 use serde_json::json;
+use std::str::FromStr;
+
+proptest! {
+    #[test]
+    fn value_string_roundtrip(value in any::<MoveValue>()) {
+        for format in [AddressFormat::Long, AddressFormat::Short] {
+            let s = value.to_canonical_string(format);
+            prop_assert_eq!(MoveValue::from_str(&s).unwrap(), value.clone());
+        }
+        prop_assert_eq!(value.to_string(), value.to_canonical_string(AddressFormat::Short));
+    }
+}
+
+#[test]
+fn value_canonical_strings() {
+    let type_ = StructTag {
+        address: AccountAddress::from_hex_literal("0x2").unwrap(),
+        module: ident_str!("M").to_owned(),
+        name: ident_str!("S").to_owned(),
+        type_params: vec![TypeTag::U64],
+    };
+    let values = vec![
+        MoveValue::U8(1),
+        MoveValue::U64(2),
+        MoveValue::U128(3),
+        MoveValue::Bool(true),
+        MoveValue::Address(AccountAddress::ONE),
+        MoveValue::Signer(AccountAddress::ONE),
+        MoveValue::vector_u8(vec![]),
+        MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::U8(1)])),
+        MoveValue::Struct(MoveStruct::WithFields(vec![(
+            ident_str!("vector").to_owned(),
+            MoveValue::vector_u8(vec![1, 2]),
+        )])),
+        MoveValue::Struct(MoveStruct::WithTypes {
+            type_: type_.clone(),
+            fields: vec![(ident_str!("f").to_owned(), MoveValue::U64(4))],
+        }),
+    ];
+    let value = MoveValue::Struct(MoveStruct::Runtime(values));
+    let s = "(1u8, 2u64, 3u128, true, @0x1, signer(@0x1), vector[], (1u8), \
+             {vector: vector[1u8, 2u8]}, 0x2::M::S<u64> {f: 4u64})";
+    assert_eq!(value.to_canonical_string(AddressFormat::Short), s);
+    assert_eq!(MoveValue::from_str(s).unwrap(), value);
+    assert_eq!(
+        MoveValue::Address(AccountAddress::ONE).to_canonical_string(AddressFormat::Long),
+        "@0x00000000000000000000000000000001"
+    );
+    assert_eq!(
+        MoveValue::from_str(" vector [ 1u8 , {a : true ,} , ] ").unwrap(),
+        MoveValue::Vector(vec![
+            MoveValue::U8(1),
+            MoveValue::Struct(MoveStruct::WithFields(vec![(
+                ident_str!("a").to_owned(),
+                MoveValue::Bool(true)
+            )])),
+        ])
+    );
+
+    // structs without fields keep their kind
+    for value in [
+        MoveStruct::Runtime(vec![]),
+        MoveStruct::WithFields(vec![]),
+        MoveStruct::with_types(type_.clone(), vec![]),
+    ] {
+        let s = value.to_canonical_string(AddressFormat::Short);
+        assert_eq!(MoveValue::from_str(&s).unwrap(), MoveValue::Struct(value));
+    }
+
+    for s in [
+        "1",
+        "0x1",
+        "@1",
+        "256u8",
+        "signer(0x1)",
+        "[1u8]",
+        "vector[1u8",
+        "{a: 1u8, 2u8}",
+        "{1u8, a: 2u8}",
+        "{1u8}",
+        "(a: 1u8)",
+        "0x2::M::S {1u8}",
+        "0x2::M::S (1u8)",
+        "0x2::M::S",
+    ] {
+        assert!(MoveValue::from_str(s).is_err(), "{}", s);
+    }
+
+    let deep = format!(
+        "{}{}",
+        "vector[".repeat(VALUE_DEPTH_MAX + 1),
+        "]".repeat(VALUE_DEPTH_MAX + 1)
+    );
+    assert!(MoveValue::from_str(&deep).is_err());
+}
 
 #[test]
 fn struct_deserialization() {
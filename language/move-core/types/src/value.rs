@@ -4,7 +4,8 @@
 use crate::{
     account_address::AccountAddress,
     identifier::Identifier,
-    language_storage::{StructTag, TypeTag},
+    language_storage::{AddressFormat, StructTag, TypeTag},
+    parser::parse_move_value,
 };
 use anyhow::{bail, Result as AResult};
 use serde::{
@@ -15,8 +16,14 @@ use serde::{
 use std::{
     convert::TryInto,
     fmt::{self, Debug},
+    str::FromStr,
 };
 
+/// The maximum nesting depth of values, counting the outermost value as depth 1. Values and
+/// layouts nested deeper than this are rejected by (de)serialization instead of exhausting the
+/// stack.
+pub const VALUE_DEPTH_MAX: usize = 128;
+
 /// In the `WithTypes` configuration, a Move struct gets serialized into a Serde struct with this name
 pub const MOVE_STRUCT_NAME: &str = "struct";
 
@@ -99,13 +106,65 @@ pub enum MoveTypeLayout {
 
 impl MoveValue {
     pub fn simple_deserialize(blob: &[u8], ty: &MoveTypeLayout) -> AResult<Self> {
+        Self::simple_deserialize_with_max_depth(blob, ty, VALUE_DEPTH_MAX)
+    }
+
+    /// Like `simple_deserialize`, but rejects layouts nested deeper than `max_depth`. As the
+    /// deserializer follows the layout, this bounds the recursion for any input.
+    pub fn simple_deserialize_with_max_depth(
+        blob: &[u8],
+        ty: &MoveTypeLayout,
+        max_depth: usize,
+    ) -> AResult<Self> {
+        if !ty.is_within_depth(max_depth) {
+            bail!("layout exceeds the maximum value depth of {}", max_depth)
+        }
         Ok(bcs::from_bytes_seed(ty, blob)?)
     }
 
     pub fn simple_serialize(&self) -> Option<Vec<u8>> {
+        self.simple_serialize_with_max_depth(VALUE_DEPTH_MAX)
+    }
+
+    /// Like `simple_serialize`, but fails for values nested deeper than `max_depth`.
+    pub fn simple_serialize_with_max_depth(&self, max_depth: usize) -> Option<Vec<u8>> {
+        if !self.is_within_depth(max_depth) {
+            return None;
+        }
         bcs::to_bytes(self).ok()
     }
 
+    /// Returns whether this value is nested at most `max_depth` levels deep. The check stops
+    /// descending once the limit is exceeded, so it is safe on arbitrarily deep values.
+    pub fn is_within_depth(&self, max_depth: usize) -> bool {
+        self.is_within_depth_impl(1, max_depth)
+    }
+
+    fn is_within_depth_impl(&self, depth: usize, max_depth: usize) -> bool {
+        if depth > max_depth {
+            return false;
+        }
+        match self {
+            MoveValue::U8(_)
+            | MoveValue::U64(_)
+            | MoveValue::U128(_)
+            | MoveValue::Bool(_)
+            | MoveValue::Address(_)
+            | MoveValue::Signer(_) => true,
+            MoveValue::Vector(vals) => vals
+                .iter()
+                .all(|v| v.is_within_depth_impl(depth + 1, max_depth)),
+            MoveValue::Struct(s) => match s {
+                MoveStruct::Runtime(vals) => vals
+                    .iter()
+                    .all(|v| v.is_within_depth_impl(depth + 1, max_depth)),
+                MoveStruct::WithFields(fields) | MoveStruct::WithTypes { fields, .. } => fields
+                    .iter()
+                    .all(|(_, v)| v.is_within_depth_impl(depth + 1, max_depth)),
+            },
+        }
+    }
+
     pub fn vector_u8(v: Vec<u8>) -> Self {
         MoveValue::Vector(v.into_iter().map(MoveValue::U8).collect())
     }
@@ -133,6 +192,45 @@ impl MoveValue {
             v => v,
         }
     }
+
+    /// Returns the canonical textual format of this value. Integers always carry their type
+    /// suffix and addresses are prefixed with `@`, as in Move source:
+    /// - `true`, `1u8`, `2u64`, `3u128`, `@0x1`, `signer(@0x1)`, `vector[1u8, 2u8]`
+    /// - `(1u64, true)` for `Runtime` structs, `{a: 1u64, b: true}` for `WithFields` structs and
+    ///   `0x1::M::S {a: 1u64, b: true}` for `WithTypes` structs.
+    pub fn to_canonical_string(&self, format: AddressFormat) -> String {
+        match self {
+            MoveValue::Bool(b) => b.to_string(),
+            MoveValue::U8(i) => format!("{}u8", i),
+            MoveValue::U64(i) => format!("{}u64", i),
+            MoveValue::U128(i) => format!("{}u128", i),
+            MoveValue::Address(a) => format!("@{}", format.format(a)),
+            MoveValue::Signer(a) => format!("signer(@{})", format.format(a)),
+            MoveValue::Vector(vals) => format!(
+                "vector[{}]",
+                vals.iter()
+                    .map(|v| v.to_canonical_string(format))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            MoveValue::Struct(s) => s.to_canonical_string(format),
+        }
+    }
+}
+
+/// Move values are displayed in their canonical format with short addresses.
+impl fmt::Display for MoveValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_canonical_string(AddressFormat::Short))
+    }
+}
+
+impl FromStr for MoveValue {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_move_value(s)
+    }
 }
 
 pub fn serialize_values<'a, I>(vals: I) -> Vec<Vec<u8>>
@@ -161,6 +259,12 @@ impl MoveStruct {
     }
 
     pub fn simple_deserialize(blob: &[u8], ty: &MoveStructLayout) -> AResult<Self> {
+        if !ty.is_within_depth(VALUE_DEPTH_MAX) {
+            bail!(
+                "layout exceeds the maximum value depth of {}",
+                VALUE_DEPTH_MAX
+            )
+        }
         Ok(bcs::from_bytes_seed(ty, blob)?)
     }
 
@@ -218,6 +322,31 @@ impl MoveStruct {
         }
     }
 
+    /// Returns the canonical textual format of this struct, see `MoveValue::to_canonical_string`.
+    pub fn to_canonical_string(&self, format: AddressFormat) -> String {
+        let fields = |fields: &[(Identifier, MoveValue)]| {
+            fields
+                .iter()
+                .map(|(name, v)| format!("{}: {}", name, v.to_canonical_string(format)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Self::Runtime(vals) => format!(
+                "({})",
+                vals.iter()
+                    .map(|v| v.to_canonical_string(format))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::WithFields(vals) => format!("{{{}}}", fields(vals)),
+            Self::WithTypes {
+                type_,
+                fields: vals,
+            } => format!("{} {{{}}}", type_.to_canonical_string(format), fields(vals)),
+        }
+    }
+
     pub fn undecorate(self) -> Self {
         Self::Runtime(
             self.into_fields()
@@ -261,6 +390,46 @@ impl MoveStructLayout {
             }
         }
     }
+
+    /// Returns whether values of this layout are nested at most `max_depth` levels deep, the
+    /// struct itself being at depth 1.
+    pub fn is_within_depth(&self, max_depth: usize) -> bool {
+        self.is_within_depth_impl(1, max_depth)
+    }
+
+    fn is_within_depth_impl(&self, depth: usize, max_depth: usize) -> bool {
+        if depth > max_depth {
+            return false;
+        }
+        match self {
+            Self::Runtime(layouts) => layouts
+                .iter()
+                .all(|l| l.is_within_depth_impl(depth + 1, max_depth)),
+            Self::WithFields(fields) | Self::WithTypes { fields, .. } => fields
+                .iter()
+                .all(|f| f.layout.is_within_depth_impl(depth + 1, max_depth)),
+        }
+    }
+}
+
+impl MoveTypeLayout {
+    /// Returns whether values of this layout are nested at most `max_depth` levels deep. The
+    /// check stops descending once the limit is exceeded, so it is safe on arbitrarily deep
+    /// layouts.
+    pub fn is_within_depth(&self, max_depth: usize) -> bool {
+        self.is_within_depth_impl(1, max_depth)
+    }
+
+    fn is_within_depth_impl(&self, depth: usize, max_depth: usize) -> bool {
+        if depth > max_depth {
+            return false;
+        }
+        match self {
+            Self::Bool | Self::U8 | Self::U64 | Self::U128 | Self::Address | Self::Signer => true,
+            Self::Vector(layout) => layout.is_within_depth_impl(depth + 1, max_depth),
+            Self::Struct(layout) => layout.is_within_depth_impl(depth, max_depth),
+        }
+    }
 }
 
 impl<'d> serde::de::DeserializeSeed<'d> for &MoveTypeLayout {
@@ -596,6 +596,19 @@ pub enum StatusCode {
     INVALID_PHANTOM_TYPE_PARAM_POSITION = 1108,
     VEC_UPDATE_EXISTS_MUTABLE_BORROW_ERROR = 1109,
     VEC_BORROW_ELEMENT_EXISTS_MUTABLE_BORROW_ERROR = 1110,
+    // The declaration of a native function does not match the signature of its implementation
+    NATIVE_FUNCTION_SIGNATURE_MISMATCH = 1111,
+    // A native function is declared but no implementation is registered for it
+    MISSING_NATIVE_FUNCTION = 1112,
+    // Loops are nested deeper than the verifier configuration allows
+    LOOP_MAX_DEPTH_REACHED = 1113,
+    // A function has more parameters than the verifier configuration allows
+    TOO_MANY_PARAMETERS = 1114,
+    // A function, struct or instantiation has more type parameters than the verifier
+    // configuration allows
+    TOO_MANY_TYPE_PARAMETERS = 1115,
+    // A function has more basic blocks than the verifier configuration allows
+    TOO_MANY_BASIC_BLOCKS = 1116,
 
     // These are errors that the VM might raise if a violation of internal
     // invariants takes place.
@@ -661,6 +674,14 @@ pub enum StatusCode {
     VM_MAX_TYPE_DEPTH_REACHED = 4024,
     VM_MAX_VALUE_DEPTH_REACHED = 4025,
     VM_EXTENSION_ERROR = 4026,
+    // The effects of a transaction exceed the configured write set size.
+    VM_MAX_WRITE_SET_SIZE_REACHED = 4027,
+    // A transaction creates, modifies or deletes more resources than allowed.
+    VM_MAX_WRITTEN_RESOURCES_REACHED = 4028,
+    // A vector created with `VecPack` exceeds the configured length.
+    VM_MAX_VECTOR_LENGTH_REACHED = 4029,
+    // An instantiated type exceeds the configured number of type nodes.
+    VM_MAX_TYPE_NODES_REACHED = 4030,
 
 
     // A reserved status to represent an unknown vm status.
@@ -679,10 +679,31 @@ fn call_native_function(
                 state.assign_local(rets[0], args[0], func_env)
             }
         }
+        ("String", "internal_check_utf8")
+        | ("String", "internal_is_char_boundary")
+        | ("String", "internal_sub_string") => {
+            if state.locals.local_exists(args[0], func_env) {
+                state.record_access(args[0], Access::Read, func_env); // reads the bytes
+            }
+        }
+        ("String", "internal_index_of") => {
+            for arg in &args[..2] {
+                if state.locals.local_exists(*arg, func_env) {
+                    state.record_access(*arg, Access::Read, func_env); // reads the bytes
+                }
+            }
+        }
         ("Vector", "empty") | ("Vector", "destroy_empty") | ("Vector", "reverse") => (),
-        ("Event", "write_to_event_store") => (),
-        ("Hash", "sha3_256") | ("Hash", "sha2_256") => (),
-        ("Signature", "ed25519_validate_pubkey") | ("Signature", "ed25519_verify") => (),
+        ("Event", "write_to_event_store") | ("Event", "emit") => (),
+        ("Hash", "sha3_256")
+        | ("Hash", "sha2_256")
+        | ("Hash", "keccak256")
+        | ("Hash", "blake2b_256")
+        | ("Hash", "ripemd160") => (),
+        ("Signature", "ed25519_validate_pubkey")
+        | ("Signature", "ed25519_verify")
+        | ("Signature", "secp256k1_verify")
+        | ("Signature", "secp256k1_recover_internal") => (),
         (m, f) => {
             panic!("Unsupported native function {:?}::{:?}", m, f)
         }
@@ -10,14 +10,26 @@
 //! native functions will likely be implemented in a Diem crate (most likely
 //! diem-framework) and be passed into the VM for execution. In this way we no
 //! longer need to worry about depending on diem-crypto.
+//!
+//! The validation of keys and signatures is also used by the signature natives of the Move
+//! standard library, so that the natives and the interpreter accept the same inputs.
 
 use anyhow::{bail, Result};
+use blake2::{
+    digest::{Update, VariableOutput},
+    VarBlake2b,
+};
 use ed25519_dalek::{
     ed25519::signature::Signature, PublicKey as Ed25519PublicKey, Signature as Ed25519Signature,
     PUBLIC_KEY_LENGTH as ED25519_PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH as ED25519_SIGNATURE_LENGTH,
 };
+use libsecp256k1::{
+    Message as Secp256k1Message, PublicKey as Secp256k1PublicKey, PublicKeyFormat, RecoveryId,
+    Signature as Secp256k1Signature,
+};
+use ripemd160::Ripemd160;
 use sha2::{Digest, Sha256};
-use sha3::Sha3_256;
+use sha3::{Keccak256, Sha3_256};
 use std::cmp::Ordering;
 
 /// The order of ed25519 as defined in [RFC8032](https://tools.ietf.org/html/rfc8032).
@@ -35,6 +47,20 @@ pub fn sha3_256_of(bytes: &[u8]) -> Vec<u8> {
     Sha3_256::digest(bytes).to_vec()
 }
 
+pub fn keccak256_of(bytes: &[u8]) -> Vec<u8> {
+    Keccak256::digest(bytes).to_vec()
+}
+
+pub fn blake2b_256_of(bytes: &[u8]) -> Vec<u8> {
+    let mut hasher = VarBlake2b::new(32).expect("32 is a valid BLAKE2b output size");
+    hasher.update(bytes);
+    hasher.finalize_boxed().into_vec()
+}
+
+pub fn ripemd160_of(bytes: &[u8]) -> Vec<u8> {
+    Ripemd160::digest(bytes).to_vec()
+}
+
 // Ed25519
 fn validate_public_key(bytes: &[u8]) -> bool {
     // We need to access the Edwards point which is not directly accessible from
@@ -99,3 +125,34 @@ pub fn ed25519_verify_signature(
     }
     Ok(key.verify_strict(msg, sig)?)
 }
+
+// Secp256k1
+fn secp256k1_deserialize_signature(bytes: &[u8]) -> Result<Secp256k1Signature> {
+    let sig = Secp256k1Signature::parse_standard_slice(bytes)?;
+    if sig.s.is_high() {
+        bail!("Malleable signature");
+    }
+    Ok(sig)
+}
+
+/// Verify a secp256k1 ECDSA signature in compact form against a 32 byte message hash and a
+/// public key given as the 64 byte uncompressed point without the tag byte.
+pub fn secp256k1_verify_signature(sig: &[u8], key: &[u8], hash: &[u8]) -> Result<()> {
+    let sig = secp256k1_deserialize_signature(sig)?;
+    let key = Secp256k1PublicKey::parse_slice(key, Some(PublicKeyFormat::Raw))?;
+    let msg = Secp256k1Message::parse_slice(hash)?;
+    if !libsecp256k1::verify(&msg, &sig, &key) {
+        bail!("Invalid signature");
+    }
+    Ok(())
+}
+
+/// Recover the public key of a secp256k1 ECDSA signature, in the format accepted by
+/// `secp256k1_verify_signature`.
+pub fn secp256k1_recover_public_key(hash: &[u8], recovery_id: u8, sig: &[u8]) -> Result<Vec<u8>> {
+    let msg = Secp256k1Message::parse_slice(hash)?;
+    let recovery_id = RecoveryId::parse(recovery_id)?;
+    let sig = secp256k1_deserialize_signature(sig)?;
+    let key = libsecp256k1::recover(&msg, &sig, &recovery_id)?;
+    Ok(key.serialize()[1..].to_vec())
+}
@@ -7,8 +7,9 @@ use num::{BigInt, ToPrimitive, Zero};
 use std::{collections::BTreeMap, rc::Rc};
 
 use bytecode_interpreter_crypto::{
-    ed25519_deserialize_public_key, ed25519_deserialize_signature, ed25519_verify_signature,
-    sha2_256_of, sha3_256_of,
+    blake2b_256_of, ed25519_deserialize_public_key, ed25519_deserialize_signature,
+    ed25519_verify_signature, keccak256_of, ripemd160_of, secp256k1_recover_public_key,
+    secp256k1_verify_signature, sha2_256_of, sha3_256_of,
 };
 use move_binary_format::errors::Location;
 use move_core_types::{
@@ -266,6 +267,65 @@ impl<'env> FunctionContext<'env> {
                 let res = self.native_hash_sha3_256(dummy_state.del_value(0));
                 Ok(vec![res])
             }
+            (DIEM_CORE_ADDR, "Hash", "keccak256") => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(srcs.len(), 1);
+                }
+                let res = self.native_hash(dummy_state.del_value(0), keccak256_of);
+                Ok(vec![res])
+            }
+            (DIEM_CORE_ADDR, "Hash", "blake2b_256") => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(srcs.len(), 1);
+                }
+                let res = self.native_hash(dummy_state.del_value(0), blake2b_256_of);
+                Ok(vec![res])
+            }
+            (DIEM_CORE_ADDR, "Hash", "ripemd160") => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(srcs.len(), 1);
+                }
+                let res = self.native_hash(dummy_state.del_value(0), ripemd160_of);
+                Ok(vec![res])
+            }
+            (DIEM_CORE_ADDR, "String", "internal_check_utf8") => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(srcs.len(), 1);
+                }
+                let res = self.native_string_internal_check_utf8(dummy_state.del_value(0));
+                Ok(vec![res])
+            }
+            (DIEM_CORE_ADDR, "String", "internal_is_char_boundary") => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(srcs.len(), 2);
+                }
+                let res = self.native_string_internal_is_char_boundary(
+                    dummy_state.del_value(0),
+                    dummy_state.del_value(1),
+                );
+                Ok(vec![res])
+            }
+            (DIEM_CORE_ADDR, "String", "internal_sub_string") => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(srcs.len(), 3);
+                }
+                let res = self.native_string_internal_sub_string(
+                    dummy_state.del_value(0),
+                    dummy_state.del_value(1),
+                    dummy_state.del_value(2),
+                );
+                Ok(vec![res])
+            }
+            (DIEM_CORE_ADDR, "String", "internal_index_of") => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(srcs.len(), 2);
+                }
+                let res = self.native_string_internal_index_of(
+                    dummy_state.del_value(0),
+                    dummy_state.del_value(1),
+                );
+                Ok(vec![res])
+            }
             (DIEM_CORE_ADDR, "BCS", "to_bytes") => {
                 if cfg!(debug_assertions) {
                     assert_eq!(srcs.len(), 1);
@@ -285,6 +345,13 @@ impl<'env> FunctionContext<'env> {
                 );
                 Ok(vec![])
             }
+            (DIEM_CORE_ADDR, "Event", "emit") => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(srcs.len(), 1);
+                }
+                self.native_event_emit(dummy_state.del_value(0), global_state);
+                Ok(vec![])
+            }
             (DIEM_CORE_ADDR, "Signature", "ed25519_validate_pubkey") => {
                 if cfg!(debug_assertions) {
                     assert_eq!(srcs.len(), 1);
@@ -303,6 +370,28 @@ impl<'env> FunctionContext<'env> {
                 );
                 Ok(vec![res])
             }
+            (DIEM_CORE_ADDR, "Signature", "secp256k1_verify") => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(srcs.len(), 3);
+                }
+                let res = self.native_signature_secp256k1_verify(
+                    dummy_state.del_value(0),
+                    dummy_state.del_value(1),
+                    dummy_state.del_value(2),
+                );
+                Ok(vec![res])
+            }
+            (DIEM_CORE_ADDR, "Signature", "secp256k1_recover_internal") => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(srcs.len(), 3);
+                }
+                let res = self.native_signature_secp256k1_recover_internal(
+                    dummy_state.del_value(0),
+                    dummy_state.del_value(1),
+                    dummy_state.del_value(2),
+                );
+                Ok(vec![res])
+            }
             (DIEM_CORE_ADDR, "DiemAccount", "create_signer") => {
                 if cfg!(debug_assertions) {
                     assert_eq!(srcs.len(), 1);
@@ -2030,6 +2119,101 @@ impl<'env> FunctionContext<'env> {
         TypedValue::mk_vector(elem_ty, hashed)
     }
 
+    fn native_hash(&self, bytes_val: TypedValue, hash_of: fn(&[u8]) -> Vec<u8>) -> TypedValue {
+        let elem_ty = BaseType::mk_u8();
+        if cfg!(debug_assertions) {
+            assert_eq!(self.ty_args.len(), 0);
+            assert!(bytes_val.get_ty().is_vector_of(&elem_ty));
+        }
+        let bytes: Vec<_> = bytes_val
+            .into_vector()
+            .into_iter()
+            .map(|e| e.into_u8())
+            .collect();
+        let digest = hash_of(&bytes);
+        let hashed = digest.into_iter().map(TypedValue::mk_u8).collect();
+        TypedValue::mk_vector(elem_ty, hashed)
+    }
+
+    fn native_string_internal_check_utf8(&self, bytes_val: TypedValue) -> TypedValue {
+        if cfg!(debug_assertions) {
+            assert_eq!(self.ty_args.len(), 0);
+        }
+        // NOTE: the string natives accept values instead of references!
+        // This is different from the Move native implementation.
+        let bytes = Self::string_bytes_of(bytes_val);
+        TypedValue::mk_bool(std::str::from_utf8(&bytes).is_ok())
+    }
+
+    fn native_string_internal_is_char_boundary(
+        &self,
+        bytes_val: TypedValue,
+        index_val: TypedValue,
+    ) -> TypedValue {
+        if cfg!(debug_assertions) {
+            assert_eq!(self.ty_args.len(), 0);
+        }
+        let bytes = Self::string_bytes_of(bytes_val);
+        let index = index_val.into_u64() as usize;
+        let is_boundary = std::str::from_utf8(&bytes)
+            .map(|s| s.is_char_boundary(index))
+            .unwrap_or(false);
+        TypedValue::mk_bool(is_boundary)
+    }
+
+    fn native_string_internal_sub_string(
+        &self,
+        bytes_val: TypedValue,
+        i_val: TypedValue,
+        j_val: TypedValue,
+    ) -> TypedValue {
+        if cfg!(debug_assertions) {
+            assert_eq!(self.ty_args.len(), 0);
+        }
+        let bytes = Self::string_bytes_of(bytes_val);
+        let i = i_val.into_u64() as usize;
+        let j = j_val.into_u64() as usize;
+        let sub = bytes
+            .get(i..j)
+            .unwrap_or_default()
+            .iter()
+            .map(|b| TypedValue::mk_u8(*b))
+            .collect();
+        TypedValue::mk_vector(BaseType::mk_u8(), sub)
+    }
+
+    fn native_string_internal_index_of(
+        &self,
+        bytes_val: TypedValue,
+        sub_val: TypedValue,
+    ) -> TypedValue {
+        if cfg!(debug_assertions) {
+            assert_eq!(self.ty_args.len(), 0);
+        }
+        let bytes = Self::string_bytes_of(bytes_val);
+        let sub = Self::string_bytes_of(sub_val);
+        let pos = if sub.is_empty() {
+            0
+        } else {
+            bytes
+                .windows(sub.len())
+                .position(|window| window == sub.as_slice())
+                .unwrap_or(bytes.len())
+        };
+        TypedValue::mk_u64(pos as u64)
+    }
+
+    fn string_bytes_of(bytes_val: TypedValue) -> Vec<u8> {
+        if cfg!(debug_assertions) {
+            assert!(bytes_val.get_ty().is_vector_of(&BaseType::mk_u8()));
+        }
+        bytes_val
+            .into_vector()
+            .into_iter()
+            .map(|e| e.into_u8())
+            .collect()
+    }
+
     fn native_bcs_to_bytes(&self, object: TypedValue) -> Result<TypedValue, AbortInfo> {
         if cfg!(debug_assertions) {
             assert_eq!(self.ty_args.len(), 1);
@@ -2069,6 +2253,17 @@ impl<'env> FunctionContext<'env> {
         global_state.emit_event(guid, seq, msg_val);
     }
 
+    fn native_event_emit(&self, msg_val: TypedValue, global_state: &mut GlobalState) {
+        if cfg!(debug_assertions) {
+            assert_eq!(self.ty_args.len(), 1);
+            assert_eq!(
+                msg_val.get_ty().get_base_type(),
+                self.ty_args.get(0).unwrap()
+            );
+        }
+        global_state.emit_module_event(msg_val);
+    }
+
     fn native_signature_ed25519_validate_pubkey(&self, key: TypedValue) -> TypedValue {
         if cfg!(debug_assertions) {
             assert_eq!(self.ty_args.len(), 0);
@@ -2121,6 +2316,60 @@ impl<'env> FunctionContext<'env> {
         TypedValue::mk_bool(verified)
     }
 
+    fn native_signature_secp256k1_verify(
+        &self,
+        sig_val: TypedValue,
+        key_val: TypedValue,
+        hash_val: TypedValue,
+    ) -> TypedValue {
+        if cfg!(debug_assertions) {
+            assert_eq!(self.ty_args.len(), 0);
+        }
+        let sig_bytes: Vec<_> = sig_val
+            .into_vector()
+            .into_iter()
+            .map(|e| e.into_u8())
+            .collect();
+        let key_bytes: Vec<_> = key_val
+            .into_vector()
+            .into_iter()
+            .map(|e| e.into_u8())
+            .collect();
+        let hash_bytes: Vec<_> = hash_val
+            .into_vector()
+            .into_iter()
+            .map(|e| e.into_u8())
+            .collect();
+        let verified = secp256k1_verify_signature(&sig_bytes, &key_bytes, &hash_bytes).is_ok();
+        TypedValue::mk_bool(verified)
+    }
+
+    fn native_signature_secp256k1_recover_internal(
+        &self,
+        hash_val: TypedValue,
+        recovery_id_val: TypedValue,
+        sig_val: TypedValue,
+    ) -> TypedValue {
+        if cfg!(debug_assertions) {
+            assert_eq!(self.ty_args.len(), 0);
+        }
+        let hash_bytes: Vec<_> = hash_val
+            .into_vector()
+            .into_iter()
+            .map(|e| e.into_u8())
+            .collect();
+        let sig_bytes: Vec<_> = sig_val
+            .into_vector()
+            .into_iter()
+            .map(|e| e.into_u8())
+            .collect();
+        // An empty vector signals that no public key could be recovered.
+        let key = secp256k1_recover_public_key(&hash_bytes, recovery_id_val.into_u8(), &sig_bytes)
+            .unwrap_or_default();
+        let elems = key.into_iter().map(TypedValue::mk_u8).collect();
+        TypedValue::mk_vector(BaseType::mk_u8(), elems)
+    }
+
     fn native_diem_account_create_signer(&self, addr: TypedValue) -> TypedValue {
         if cfg!(debug_assertions) {
             assert_eq!(self.ty_args.len(), 0);
@@ -2260,6 +2509,14 @@ pub fn entrypoint(
 ) -> ExecResult<Vec<TypedValue>> {
     let mut eval_state = EvalState::default();
     let ctxt = FunctionContext::new(holder, target, ty_args.to_vec(), skip_specs, level);
+
+    // natives have no bytecode to interpret, e.g., when they are called from a spec expression
+    if ctxt.target.func_env.is_native() {
+        let srcs: Vec<_> = (0..typed_args.len()).collect();
+        let mut local_state = ctxt.prepare_local_state(typed_args.clone());
+        return ctxt.exec_native_function(&srcs, typed_args, &mut local_state, global_state);
+    }
+
     let local_state = ctxt.exec_user_function(typed_args, global_state, &mut eval_state)?;
     let termination = local_state.into_termination_status();
     match termination {
@@ -1007,6 +1007,7 @@ impl AccountState {
 pub struct GlobalState {
     accounts: BTreeMap<AccountAddress, AccountState>,
     events: BTreeMap<Vec<u8>, BTreeMap<u64, TypedValue>>,
+    module_events: Vec<TypedValue>,
 }
 
 impl GlobalState {
@@ -1087,6 +1088,11 @@ impl GlobalState {
         }
     }
 
+    /// Emit an event which is not associated with an event handle to the event store
+    pub fn emit_module_event(&mut self, msg: TypedValue) {
+        self.module_events.push(msg);
+    }
+
     /// Calculate the delta (i.e., a ChangeSet) against the old state
     pub fn delta(&self, old_state: &GlobalState) -> ChangeSet {
         fn bcs_serialize_resource(key: &StructInstantiation, val: &BaseValue) -> Vec<u8> {
@@ -114,16 +114,7 @@ pub fn interpret_with_options(
         .signers
         .into_iter()
         .map(MoveValue::Signer)
-        .chain(options.args.into_iter().map(|arg| match arg {
-            TransactionArgument::Bool(v) => MoveValue::Bool(v),
-            TransactionArgument::U8(v) => MoveValue::U8(v),
-            TransactionArgument::U64(v) => MoveValue::U64(v),
-            TransactionArgument::U128(v) => MoveValue::U128(v),
-            TransactionArgument::Address(v) => MoveValue::Address(v),
-            TransactionArgument::U8Vector(v) => {
-                MoveValue::Vector(v.into_iter().map(MoveValue::U8).collect())
-            }
-        }))
+        .chain(options.args.into_iter().map(MoveValue::from))
         .collect();
 
     // collect settings
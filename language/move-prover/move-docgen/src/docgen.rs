@@ -1609,13 +1609,19 @@ impl<'env> Docgen<'env> {
             (Some(module), 1) => try_func_struct_or_const(&module, parts_sym[0], true),
             (None, 0) => None,
             (None, 1) => {
-                // A simple name. Resolve either to module or to item in current module.
-                if let Some(module) = self.env.find_module_by_name(parts_sym[0]) {
-                    Some(self.ref_for_module(&module))
-                } else if let Some(module) = &self.current_module {
-                    try_func_struct_or_const(module, parts_sym[0], false)
-                } else {
-                    None
+                // A simple name. Resolve either to module or to item in current module. An item
+                // in the current module shadows another module of the same name.
+                match (
+                    self.env.find_module_by_name(parts_sym[0]),
+                    &self.current_module,
+                ) {
+                    (Some(module), Some(current)) if module.get_id() != current.get_id() => {
+                        try_func_struct_or_const(current, parts_sym[0], false)
+                            .or_else(|| Some(self.ref_for_module(&module)))
+                    }
+                    (Some(module), _) => Some(self.ref_for_module(&module)),
+                    (None, Some(current)) => try_func_struct_or_const(current, parts_sym[0], false),
+                    (None, None) => None,
                 }
             }
             (None, 2) => {
@@ -0,0 +1,294 @@
+/// An `OrderedMap` is a map backed by a vector of key-value pairs which is kept sorted by key.
+/// Keys are ordered by their BCS representation as defined by `Compare::cmp_bcs_bytes`, which
+/// coincides with the numeric order for the integer types. Lookups use binary search, while
+/// insertions and removals take linear time. Entries can be iterated by index in key order.
+module Std::OrderedMap {
+    use Std::BCS;
+    use Std::Compare;
+    use Std::Errors;
+    use Std::Vector;
+
+    /// The map already contains the key
+    const EKEY_ALREADY_EXISTS: u64 = 0;
+    /// The map does not contain the key
+    const EKEY_NOT_FOUND: u64 = 1;
+    /// The index is out of bounds
+    const EINDEX_OUT_OF_BOUNDS: u64 = 2;
+
+    // Results of `Compare::cmp_bcs_bytes`.
+    const EQUAL: u8 = 0;
+    const LESS_THAN: u8 = 1;
+    const GREATER_THAN: u8 = 2;
+
+    struct OrderedMap<Key, Value> has copy, drop, store {
+        data: vector<Element<Key, Value>>,
+    }
+    spec OrderedMap {
+        /// Keys are unique.
+        invariant forall i in 0..len(data), j in 0..len(data):
+            data[i].key == data[j].key ==> i == j;
+        /// Keys are sorted.
+        invariant forall i in 0..len(data), j in 0..len(data):
+            i < j ==> spec_key_lt(data[i].key, data[j].key);
+    }
+
+    struct Element<Key, Value> has copy, drop, store {
+        key: Key,
+        value: Value,
+    }
+
+    /// Create an empty map.
+    public fun empty<Key, Value>(): OrderedMap<Key, Value> {
+        OrderedMap { data: Vector::empty() }
+    }
+    spec empty {
+        aborts_if false;
+        ensures len(result.data) == 0;
+    }
+
+    /// Return the number of entries in `map`.
+    public fun length<Key, Value>(map: &OrderedMap<Key, Value>): u64 {
+        Vector::length(&map.data)
+    }
+    spec length {
+        aborts_if false;
+        ensures result == len(map.data);
+    }
+
+    /// Return true if `map` has no entries.
+    public fun is_empty<Key, Value>(map: &OrderedMap<Key, Value>): bool {
+        Vector::is_empty(&map.data)
+    }
+
+    /// Return true if `map` contains an entry for `key`.
+    public fun contains_key<Key, Value>(map: &OrderedMap<Key, Value>, key: &Key): bool {
+        let (found, _) = find(map, key);
+        found
+    }
+    spec contains_key {
+        pragma opaque;
+        aborts_if false;
+        ensures result == spec_contains_key(map, key);
+    }
+
+    /// Return an immutable reference to the value stored for `key`.
+    /// Aborts if there is no such entry.
+    public fun borrow<Key, Value>(map: &OrderedMap<Key, Value>, key: &Key): &Value {
+        let (found, i) = find(map, key);
+        assert!(found, Errors::invalid_argument(EKEY_NOT_FOUND));
+        &Vector::borrow(&map.data, i).value
+    }
+    spec borrow {
+        aborts_if !spec_contains_key(map, key) with Errors::INVALID_ARGUMENT;
+    }
+
+    /// Return a mutable reference to the value stored for `key`.
+    /// Aborts if there is no such entry.
+    public fun borrow_mut<Key, Value>(map: &mut OrderedMap<Key, Value>, key: &Key): &mut Value {
+        let (found, i) = find(map, key);
+        assert!(found, Errors::invalid_argument(EKEY_NOT_FOUND));
+        &mut Vector::borrow_mut(&mut map.data, i).value
+    }
+    spec borrow_mut {
+        aborts_if !spec_contains_key(map, key) with Errors::INVALID_ARGUMENT;
+    }
+
+    /// Add an entry for `key` to `map`, keeping the entries sorted. Aborts if there is
+    /// already an entry for `key`.
+    public fun add<Key, Value>(map: &mut OrderedMap<Key, Value>, key: Key, value: Value) {
+        let (found, i) = find(map, &key);
+        assert!(!found, Errors::invalid_argument(EKEY_ALREADY_EXISTS));
+        // Append the new entry and move it to position `i`.
+        Vector::push_back(&mut map.data, Element { key, value });
+        let j = Vector::length(&map.data) - 1;
+        while ({
+            spec {
+                invariant i <= j && j < len(map.data);
+                invariant len(map.data) == len(old(map).data) + 1;
+                invariant map.data[j] == Element { key, value };
+                invariant forall k in 0..j: map.data[k] == old(map).data[k];
+                invariant forall k in j + 1..len(map.data): map.data[k] == old(map).data[k - 1];
+            };
+            j > i
+        }) {
+            Vector::swap(&mut map.data, j - 1, j);
+            j = j - 1;
+        };
+    }
+    spec add {
+        aborts_if spec_contains_key(map, key) with Errors::INVALID_ARGUMENT;
+        ensures len(map.data) == len(old(map.data)) + 1;
+        /// The new entry is inserted at its position in key order, after the smaller keys.
+        ensures exists i in 0..len(map.data):
+            map.data[i] == Element { key, value }
+            && (forall k in 0..i: map.data[k] == old(map.data)[k])
+            && (forall k in i + 1..len(map.data): map.data[k] == old(map.data)[k - 1]);
+    }
+
+    /// Remove the entry for `key` from `map` and return it. Aborts if there is no such entry.
+    public fun remove<Key, Value>(map: &mut OrderedMap<Key, Value>, key: &Key): (Key, Value) {
+        let (found, i) = find(map, key);
+        assert!(found, Errors::invalid_argument(EKEY_NOT_FOUND));
+        let Element { key, value } = Vector::remove(&mut map.data, i);
+        (key, value)
+    }
+    spec remove {
+        aborts_if !spec_contains_key(map, key) with Errors::INVALID_ARGUMENT;
+        ensures len(map.data) == len(old(map.data)) - 1;
+        ensures !spec_contains_key(map, key);
+        ensures result_1 == key;
+    }
+
+    /// Destroy an empty `map`. Aborts if `map` is not empty.
+    public fun destroy_empty<Key, Value>(map: OrderedMap<Key, Value>) {
+        let OrderedMap { data } = map;
+        Vector::destroy_empty(data);
+    }
+
+    /// Return the entry at position `i`, which must be smaller than the length of `map`.
+    /// This allows iterating over all entries in key order.
+    public fun borrow_at<Key, Value>(map: &OrderedMap<Key, Value>, i: u64): (&Key, &Value) {
+        assert!(i < Vector::length(&map.data), Errors::invalid_argument(EINDEX_OUT_OF_BOUNDS));
+        let element = Vector::borrow(&map.data, i);
+        (&element.key, &element.value)
+    }
+    spec borrow_at {
+        aborts_if i >= len(map.data) with Errors::INVALID_ARGUMENT;
+        ensures result_1 == map.data[i].key;
+        ensures result_2 == map.data[i].value;
+    }
+
+    /// Return the entry at position `i` with a mutable reference to its value.
+    public fun borrow_at_mut<Key, Value>(map: &mut OrderedMap<Key, Value>, i: u64): (&Key, &mut Value) {
+        assert!(i < Vector::length(&map.data), Errors::invalid_argument(EINDEX_OUT_OF_BOUNDS));
+        let element = Vector::borrow_mut(&mut map.data, i);
+        (&element.key, &mut element.value)
+    }
+    spec borrow_at_mut {
+        aborts_if i >= len(map.data) with Errors::INVALID_ARGUMENT;
+    }
+
+    /// Return all keys of `map` in ascending order.
+    public fun keys<Key: copy, Value>(map: &OrderedMap<Key, Value>): vector<Key> {
+        let keys = Vector::empty();
+        let i = 0;
+        let n = Vector::length(&map.data);
+        while ({
+            spec {
+                invariant i <= n;
+                invariant len(keys) == i;
+                invariant forall j in 0..i: keys[j] == map.data[j].key;
+            };
+            i < n
+        }) {
+            Vector::push_back(&mut keys, Vector::borrow(&map.data, i).key);
+            i = i + 1;
+        };
+        keys
+    }
+    spec keys {
+        aborts_if false;
+        ensures len(result) == len(map.data);
+        ensures forall i in 0..len(result): result[i] == map.data[i].key;
+    }
+
+    /// Return all values of `map`, ordered by their keys.
+    public fun values<Key, Value: copy>(map: &OrderedMap<Key, Value>): vector<Value> {
+        let values = Vector::empty();
+        let i = 0;
+        let n = Vector::length(&map.data);
+        while ({
+            spec {
+                invariant i <= n;
+                invariant len(values) == i;
+                invariant forall j in 0..i: values[j] == map.data[j].value;
+            };
+            i < n
+        }) {
+            Vector::push_back(&mut values, Vector::borrow(&map.data, i).value);
+            i = i + 1;
+        };
+        values
+    }
+    spec values {
+        aborts_if false;
+        ensures len(result) == len(map.data);
+        ensures forall i in 0..len(result): result[i] == map.data[i].value;
+    }
+
+    /// Binary search for `key`. Returns `(true, i)` if the entry for `key` is at position `i`,
+    /// and `(false, i)` if there is no such entry and `i` is the position where it belongs.
+    fun find<Key, Value>(map: &OrderedMap<Key, Value>, key: &Key): (bool, u64) {
+        let key_bytes = BCS::to_bytes(key);
+        let lo = 0;
+        let hi = Vector::length(&map.data);
+        while ({
+            spec {
+                invariant lo <= hi && hi <= len(map.data);
+                invariant forall i in 0..lo: spec_key_lt(map.data[i].key, key);
+                invariant forall i in hi..len(map.data): spec_key_lt(key, map.data[i].key);
+            };
+            lo < hi
+        }) {
+            let mid = lo + (hi - lo) / 2;
+            let mid_bytes = BCS::to_bytes(&Vector::borrow(&map.data, mid).key);
+            let cmp = cmp_bytes(&mid_bytes, &key_bytes);
+            if (cmp == EQUAL) {
+                return (true, mid)
+            } else if (cmp == LESS_THAN) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        };
+        (false, lo)
+    }
+    spec find {
+        pragma opaque;
+        aborts_if false;
+        ensures result_1 == spec_contains_key(map, key);
+        ensures result_1 ==> result_2 < len(map.data) && map.data[result_2].key == key;
+        ensures !result_1 ==> result_2 <= len(map.data)
+            && (forall i in 0..result_2: spec_key_lt(map.data[i].key, key))
+            && (forall i in result_2..len(map.data): spec_key_lt(key, map.data[i].key));
+    }
+
+    /// Compare BCS bytes with `Compare::cmp_bcs_bytes`.
+    fun cmp_bytes(v1: &vector<u8>, v2: &vector<u8>): u8 {
+        Compare::cmp_bcs_bytes(v1, v2)
+    }
+    spec cmp_bytes {
+        // `Compare::cmp_bcs_bytes` has no specification, so its result is described by the
+        // abstract order `spec_bytes_lt`.
+        pragma opaque;
+        pragma verify = false;
+        aborts_if false;
+        ensures result == EQUAL <==> v1 == v2;
+        ensures result == LESS_THAN <==> spec_bytes_lt(v1, v2);
+        ensures result == GREATER_THAN <==> spec_bytes_lt(v2, v1);
+    }
+
+    // **************** SPECIFICATIONS ****************
+
+    spec module {} // switch documentation context to module level
+
+    spec module {
+        /// Whether `map` contains an entry for `key`.
+        fun spec_contains_key<Key, Value>(map: OrderedMap<Key, Value>, key: Key): bool {
+            exists i in 0..len(map.data): map.data[i].key == key
+        }
+
+        /// Whether `k1` comes before `k2` in the order of their BCS bytes.
+        fun spec_key_lt<Key>(k1: Key, k2: Key): bool {
+            spec_bytes_lt(BCS::serialize(k1), BCS::serialize(k2))
+        }
+
+        /// The order of `Compare::cmp_bcs_bytes`, which is a strict total order.
+        fun spec_bytes_lt(v1: vector<u8>, v2: vector<u8>): bool;
+        axiom forall v: vector<u8>: !spec_bytes_lt(v, v);
+        axiom forall v1: vector<u8>, v2: vector<u8>, v3: vector<u8>:
+            spec_bytes_lt(v1, v2) && spec_bytes_lt(v2, v3) ==> spec_bytes_lt(v1, v3);
+        axiom forall v1: vector<u8>, v2: vector<u8>:
+            v1 == v2 || spec_bytes_lt(v1, v2) || spec_bytes_lt(v2, v1);
+    }
+}
@@ -0,0 +1,80 @@
+#[test_only]
+module Std::OrderedMapTests {
+    use Std::OrderedMap::{Self, OrderedMap};
+    use Std::Vector;
+
+    fun is_sorted(map: &OrderedMap<u64, u64>): bool {
+        let keys = OrderedMap::keys(map);
+        let i = 1;
+        while (i < Vector::length(&keys)) {
+            if (*Vector::borrow(&keys, i - 1) >= *Vector::borrow(&keys, i)) return false;
+            i = i + 1;
+        };
+        true
+    }
+
+    #[test]
+    fun add_remove_many() {
+        let map = OrderedMap::empty<u64, u64>();
+
+        OrderedMap::add(&mut map, 300, 3);
+        OrderedMap::add(&mut map, 1, 1);
+        OrderedMap::add(&mut map, 20, 2);
+        OrderedMap::add(&mut map, 4000, 4);
+        assert!(OrderedMap::length(&map) == 4, 0);
+        // Entries are kept sorted by key.
+        assert!(is_sorted(&map), 1);
+        let (k, v) = OrderedMap::borrow_at(&map, 2);
+        assert!(*k == 300 && *v == 3, 2);
+
+        assert!(OrderedMap::contains_key(&map, &20), 3);
+        assert!(!OrderedMap::contains_key(&map, &21), 4);
+        assert!(OrderedMap::borrow(&map, &300) == &3, 5);
+        *OrderedMap::borrow_mut(&mut map, &300) = 30;
+        assert!(OrderedMap::borrow(&map, &300) == &30, 6);
+
+        let (k, v) = OrderedMap::remove(&mut map, &20);
+        assert!(k == 20 && v == 2, 7);
+        assert!(is_sorted(&map), 8);
+        let (k, _) = OrderedMap::borrow_at(&map, 1);
+        assert!(*k == 300, 8);
+        OrderedMap::remove(&mut map, &1);
+        OrderedMap::remove(&mut map, &4000);
+        OrderedMap::remove(&mut map, &300);
+        assert!(OrderedMap::is_empty(&map), 9);
+        OrderedMap::destroy_empty(map);
+    }
+
+    #[test]
+    fun iterate_in_order() {
+        let map = OrderedMap::empty<u64, u64>();
+        let i = 10;
+        while (i > 0) {
+            OrderedMap::add(&mut map, i, i * i);
+            i = i - 1;
+        };
+        let i = 0;
+        while (i < OrderedMap::length(&map)) {
+            let (k, v) = OrderedMap::borrow_at(&map, i);
+            assert!(*k == i + 1, i);
+            assert!(*v == *k * *k, i);
+            i = i + 1;
+        };
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 7)]
+    fun add_twice() {
+        let map = OrderedMap::empty<u64, u64>();
+        OrderedMap::add(&mut map, 3, 1);
+        OrderedMap::add(&mut map, 3, 1);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 263)]
+    fun borrow_missing() {
+        let map = OrderedMap::empty<u64, u64>();
+        OrderedMap::add(&mut map, 3, 1);
+        OrderedMap::borrow(&map, &2);
+    }
+}
@@ -57,6 +57,10 @@ module Std::Event {
     /// Log `msg` as the `count`th event associated with the event stream identified by `guid`
     native fun write_to_event_store<T: drop + store>(guid: vector<u8>, count: u64, msg: T);
 
+    /// Emit an event with payload `msg` which is not associated with an event handle. Such events
+    /// are identified by the type `T` of their payload only.
+    public native fun emit<T: drop + store>(msg: T);
+
     /// Destroy a unique handle.
     public fun destroy_handle<T: drop + store>(handle: EventHandle<T>) {
         EventHandle<T> { counter: _, guid: _ } = handle;
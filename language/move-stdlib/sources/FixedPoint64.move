@@ -0,0 +1,136 @@
+/// Defines a fixed-point numeric type with a 64-bit integer part and
+/// a 64-bit fractional part.
+
+module Std::FixedPoint64 {
+
+    use Std::Errors;
+
+    /// Define a fixed-point numeric type with 64 fractional bits.
+    /// This is just a u128 integer but it is wrapped in a struct to
+    /// make a unique type. This is a binary representation, so decimal
+    /// values may not be exactly representable, but it provides more
+    /// than 19 decimal digits of precision both before and after the
+    /// decimal point (38 digits total).
+    struct FixedPoint64 has copy, drop, store { value: u128 }
+
+    const MAX_U64: u128 = 18446744073709551615;
+
+    /// The denominator provided was zero
+    const EDENOMINATOR: u64 = 0;
+    /// The quotient value would be too large to be held in a `u64`
+    const EDIVISION: u64 = 1;
+    /// The multiplied value would be too large to be held in a `u64`
+    const EMULTIPLICATION: u64 = 2;
+    /// A division by zero was encountered
+    const EDIVISION_BY_ZERO: u64 = 3;
+
+    /// Multiply a u64 integer by a fixed-point number, truncating any
+    /// fractional part of the product. This will abort if the product
+    /// overflows.
+    public fun multiply_u64(val: u64, multiplier: FixedPoint64): u64 {
+        // The full product has up to 192 bits, so multiply the integer and
+        // the fractional part of the multiplier separately. Both partial
+        // products fit into 128 bits, and only the fractional one has to
+        // be rescaled by shifting away the low bits. Their sum cannot
+        // overflow 128 bits either.
+        let integer_part = multiplier.value >> 64;
+        let fractional_part = multiplier.value - (integer_part << 64);
+        let product = (val as u128) * integer_part + (((val as u128) * fractional_part) >> 64);
+        // Check whether the value is too large.
+        assert!(product <= MAX_U64, Errors::limit_exceeded(EMULTIPLICATION));
+        (product as u64)
+    }
+    spec multiply_u64 {
+        pragma opaque;
+        include MultiplyAbortsIf;
+        ensures result == spec_multiply_u64(val, multiplier);
+    }
+    spec schema MultiplyAbortsIf {
+        val: num;
+        multiplier: FixedPoint64;
+        aborts_if spec_multiply_u64(val, multiplier) > MAX_U64 with Errors::LIMIT_EXCEEDED;
+    }
+    spec fun spec_multiply_u64(val: num, multiplier: FixedPoint64): num {
+        (val * multiplier.value) >> 64
+    }
+
+    /// Divide a u64 integer by a fixed-point number, truncating any
+    /// fractional part of the quotient. This will abort if the divisor
+    /// is zero or if the quotient overflows.
+    public fun divide_u64(val: u64, divisor: FixedPoint64): u64 {
+        // Check for division by zero.
+        assert!(divisor.value != 0, Errors::invalid_argument(EDIVISION_BY_ZERO));
+        // First convert to 128 bits and then shift left to
+        // add 64 fractional zero bits to the dividend.
+        let scaled_value = (val as u128) << 64;
+        let quotient = scaled_value / divisor.value;
+        // Check whether the value is too large.
+        assert!(quotient <= MAX_U64, Errors::limit_exceeded(EDIVISION));
+        (quotient as u64)
+    }
+    spec divide_u64 {
+        pragma opaque;
+        include DivideAbortsIf;
+        ensures result == spec_divide_u64(val, divisor);
+    }
+    spec schema DivideAbortsIf {
+        val: num;
+        divisor: FixedPoint64;
+        aborts_if divisor.value == 0 with Errors::INVALID_ARGUMENT;
+        aborts_if spec_divide_u64(val, divisor) > MAX_U64 with Errors::LIMIT_EXCEEDED;
+    }
+    spec fun spec_divide_u64(val: num, divisor: FixedPoint64): num {
+        (val << 64) / divisor.value
+    }
+
+    /// Create a fixed-point value from a rational number specified by its
+    /// numerator and denominator. Calling this function should be preferred
+    /// for using `Self::create_from_raw_value` which is also available.
+    /// This will abort if the denominator is zero. As both numbers are
+    /// `u64`s, every ratio is in the representable range 2^-64 .. 2^64-1.
+    public fun create_from_rational(numerator: u64, denominator: u64): FixedPoint64 {
+        // Scale the numerator to have 64 fractional bits, so that the
+        // quotient will have 64 fractional bits.
+        let scaled_numerator = (numerator as u128) << 64;
+        assert!(denominator != 0, Errors::invalid_argument(EDENOMINATOR));
+        FixedPoint64 { value: scaled_numerator / (denominator as u128) }
+    }
+    spec create_from_rational {
+        pragma opaque;
+        aborts_if denominator == 0 with Errors::INVALID_ARGUMENT;
+        ensures result == spec_create_from_rational(numerator, denominator);
+    }
+    spec fun spec_create_from_rational(numerator: num, denominator: num): FixedPoint64 {
+        FixedPoint64{value: (numerator << 64) / denominator}
+    }
+
+    /// Create a fixedpoint value from a raw value.
+    public fun create_from_raw_value(value: u128): FixedPoint64 {
+        FixedPoint64 { value }
+    }
+    spec create_from_raw_value {
+        pragma opaque;
+        aborts_if false;
+        ensures result.value == value;
+    }
+
+    /// Accessor for the raw u128 value. Other less common operations, such as
+    /// adding or subtracting FixedPoint64 values, can be done using the raw
+    /// values directly.
+    public fun get_raw_value(num: FixedPoint64): u128 {
+        num.value
+    }
+
+    /// Returns true if the ratio is zero.
+    public fun is_zero(num: FixedPoint64): bool {
+        num.value == 0
+    }
+
+    // **************** SPECIFICATIONS ****************
+
+    spec module {} // switch documentation context to module level
+
+    spec module {
+        pragma aborts_if_is_strict;
+    }
+}
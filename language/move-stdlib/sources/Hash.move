@@ -1,8 +1,15 @@
-/// Module which defines SHA hashes for byte vectors.
+/// Module which defines SHA, Keccak, BLAKE2b and RIPEMD hashes for byte vectors.
 ///
 /// The functions in this module are natively declared both in the Move runtime
 /// as in the Move prover's prelude.
 module Std::Hash {
     native public fun sha2_256(data: vector<u8>): vector<u8>;
     native public fun sha3_256(data: vector<u8>): vector<u8>;
+
+    /// The Keccak-256 hash as used by Ethereum, which differs from `sha3_256` in its padding.
+    native public fun keccak256(data: vector<u8>): vector<u8>;
+    /// The BLAKE2b hash with a 32 byte output.
+    native public fun blake2b_256(data: vector<u8>): vector<u8>;
+    /// The RIPEMD-160 hash, which has a 20 byte output.
+    native public fun ripemd160(data: vector<u8>): vector<u8>;
 }
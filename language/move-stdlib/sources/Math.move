@@ -0,0 +1,249 @@
+/// Standard math utilities for `u64` integers, including checked and saturating variants
+/// of the arithmetic operators.
+module Std::Math {
+    use Std::Errors;
+    use Std::Option::{Self, Option};
+
+    const MAX_U64: u128 = 18446744073709551615;
+
+    /// A division by zero was encountered
+    const EDIVISION_BY_ZERO: u64 = 0;
+    /// The result would be too large to be held in a `u64`
+    const EOVERFLOW: u64 = 1;
+    /// The logarithm of zero is undefined
+    const ELOG2_OF_ZERO: u64 = 2;
+
+    /// Return the largest of two numbers.
+    public fun max(a: u64, b: u64): u64 {
+        if (a >= b) a else b
+    }
+    spec max {
+        aborts_if false;
+        ensures a >= b ==> result == a;
+        ensures a < b ==> result == b;
+    }
+
+    /// Return the smallest of two numbers.
+    public fun min(a: u64, b: u64): u64 {
+        if (a < b) a else b
+    }
+    spec min {
+        aborts_if false;
+        ensures a < b ==> result == a;
+        ensures a >= b ==> result == b;
+    }
+
+    /// Return the average of two numbers, rounded down. Unlike `(a + b) / 2`, this
+    /// cannot overflow.
+    public fun average(a: u64, b: u64): u64 {
+        if (a < b) {
+            a + (b - a) / 2
+        } else {
+            b + (a - b) / 2
+        }
+    }
+    spec average {
+        aborts_if false;
+        ensures result == (a + b) / 2;
+    }
+
+    /// Return `(a * b) / c`, rounded down. The product is computed with a `u128`
+    /// intermediate, so this only aborts if `c` is zero or the quotient does not fit
+    /// into a `u64`.
+    public fun mul_div(a: u64, b: u64, c: u64): u64 {
+        assert!(c != 0, Errors::invalid_argument(EDIVISION_BY_ZERO));
+        let result = (a as u128) * (b as u128) / (c as u128);
+        assert!(result <= MAX_U64, Errors::limit_exceeded(EOVERFLOW));
+        (result as u64)
+    }
+    spec mul_div {
+        aborts_if c == 0 with Errors::INVALID_ARGUMENT;
+        aborts_if a * b / c > MAX_U64 with Errors::LIMIT_EXCEEDED;
+        ensures result == a * b / c;
+    }
+
+    /// Return the value of `n` raised to the power `e`. This aborts with an arithmetic
+    /// error if the result does not fit into a `u64`.
+    public fun pow(n: u64, e: u64): u64 {
+        if (e == 0) {
+            1
+        } else {
+            let p = 1;
+            // Exponentiation by squaring. Every square of `n` contributes to the result,
+            // so squaring only overflows if the result overflows as well.
+            while (e > 1) {
+                if (e % 2 == 1) {
+                    p = p * n;
+                };
+                e = e / 2;
+                n = n * n;
+            };
+            p * n
+        }
+    }
+    spec pow {
+        pragma opaque;
+        aborts_if [abstract] spec_pow(n, e) > MAX_U64 with EXECUTION_FAILURE;
+        ensures [abstract] result == spec_pow(n, e);
+    }
+
+    /// Return the square root of `x`, rounded down.
+    public fun sqrt(x: u64): u64 {
+        // Compute the result bit by bit, starting at the highest power of four which
+        // is not larger than `x`.
+        let bit = 1u64 << 62;
+        while (bit > x) {
+            bit = bit >> 2;
+        };
+        let res = 0;
+        while (bit != 0) {
+            if (x >= res + bit) {
+                x = x - (res + bit);
+                res = (res >> 1) + bit;
+            } else {
+                res = res >> 1;
+            };
+            bit = bit >> 2;
+        };
+        res
+    }
+    spec sqrt {
+        pragma opaque;
+        aborts_if [abstract] false;
+        ensures [abstract] result * result <= x;
+        ensures [abstract] (result + 1) * (result + 1) > x;
+    }
+
+    /// Return the base-2 logarithm of `x`, rounded down. Aborts if `x` is zero.
+    public fun log2(x: u64): u8 {
+        assert!(x != 0, Errors::invalid_argument(ELOG2_OF_ZERO));
+        let res = 0;
+        let n = 32;
+        while (n > 0) {
+            if (x >= (1 << n)) {
+                x = x >> n;
+                res = res + n;
+            };
+            n = n >> 1;
+        };
+        res
+    }
+    spec log2 {
+        pragma opaque;
+        aborts_if [abstract] x == 0 with Errors::INVALID_ARGUMENT;
+        ensures [abstract] spec_pow(2, result) <= x;
+        ensures [abstract] spec_pow(2, result + 1) > x;
+    }
+
+    /// Return `a + b`, or `None` if the sum overflows.
+    public fun checked_add(a: u64, b: u64): Option<u64> {
+        if ((a as u128) + (b as u128) > MAX_U64) {
+            Option::none()
+        } else {
+            Option::some(a + b)
+        }
+    }
+    spec checked_add {
+        aborts_if false;
+        ensures a + b > MAX_U64 ==> result == Option::spec_none();
+        ensures a + b <= MAX_U64 ==> result == Option::spec_some(a + b);
+    }
+
+    /// Return `a - b`, or `None` if the difference underflows.
+    public fun checked_sub(a: u64, b: u64): Option<u64> {
+        if (a < b) {
+            Option::none()
+        } else {
+            Option::some(a - b)
+        }
+    }
+    spec checked_sub {
+        aborts_if false;
+        ensures a < b ==> result == Option::spec_none();
+        ensures a >= b ==> result == Option::spec_some(a - b);
+    }
+
+    /// Return `a * b`, or `None` if the product overflows.
+    public fun checked_mul(a: u64, b: u64): Option<u64> {
+        let product = (a as u128) * (b as u128);
+        if (product > MAX_U64) {
+            Option::none()
+        } else {
+            Option::some((product as u64))
+        }
+    }
+    spec checked_mul {
+        aborts_if false;
+        ensures a * b > MAX_U64 ==> result == Option::spec_none();
+        ensures a * b <= MAX_U64 ==> result == Option::spec_some(a * b);
+    }
+
+    /// Return `a / b`, or `None` if `b` is zero.
+    public fun checked_div(a: u64, b: u64): Option<u64> {
+        if (b == 0) {
+            Option::none()
+        } else {
+            Option::some(a / b)
+        }
+    }
+    spec checked_div {
+        aborts_if false;
+        ensures b == 0 ==> result == Option::spec_none();
+        ensures b != 0 ==> result == Option::spec_some(a / b);
+    }
+
+    /// Return `a + b`, or the largest `u64` if the sum overflows.
+    public fun saturating_add(a: u64, b: u64): u64 {
+        let sum = (a as u128) + (b as u128);
+        if (sum > MAX_U64) {
+            (MAX_U64 as u64)
+        } else {
+            (sum as u64)
+        }
+    }
+    spec saturating_add {
+        aborts_if false;
+        ensures a + b > MAX_U64 ==> result == MAX_U64;
+        ensures a + b <= MAX_U64 ==> result == a + b;
+    }
+
+    /// Return `a - b`, or zero if the difference underflows.
+    public fun saturating_sub(a: u64, b: u64): u64 {
+        if (a < b) 0 else a - b
+    }
+    spec saturating_sub {
+        aborts_if false;
+        ensures a < b ==> result == 0;
+        ensures a >= b ==> result == a - b;
+    }
+
+    /// Return `a * b`, or the largest `u64` if the product overflows.
+    public fun saturating_mul(a: u64, b: u64): u64 {
+        let product = (a as u128) * (b as u128);
+        if (product > MAX_U64) {
+            (MAX_U64 as u64)
+        } else {
+            (product as u64)
+        }
+    }
+    spec saturating_mul {
+        aborts_if false;
+        ensures a * b > MAX_U64 ==> result == MAX_U64;
+        ensures a * b <= MAX_U64 ==> result == a * b;
+    }
+
+    // **************** SPECIFICATIONS ****************
+
+    spec module {} // switch documentation context to module level
+
+    spec module {
+        /// `n` raised to the power `e`.
+        fun spec_pow(n: num, e: num): num {
+            if (e == 0) {
+                1
+            } else {
+                n * spec_pow(n, e - 1)
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+/// Module which defines signature verification for Ed25519 and secp256k1 ECDSA signatures,
+/// and public key recovery for the latter.
+///
+/// The natives in this module are declared both in the Move runtime as in the Move
+/// prover's prelude, where they are modeled as uninterpreted functions.
+module Std::Signature {
+    use Std::Option::{Self, Option};
+    use Std::Vector;
+
+    /// Return true if `public_key` is a valid Ed25519 public key: a 32 byte encoding of a
+    /// point on the curve which is not of small order.
+    native public fun ed25519_validate_pubkey(public_key: vector<u8>): bool;
+
+    /// Return true if `signature` is a valid Ed25519 signature of `message` under `public_key`.
+    /// Non-canonical signatures and invalid public keys are rejected.
+    native public fun ed25519_verify(
+        signature: vector<u8>,
+        public_key: vector<u8>,
+        message: vector<u8>,
+    ): bool;
+
+    /// Return true if `signature` is a valid secp256k1 ECDSA signature of the 32 byte
+    /// `message_hash` under `public_key`. The signature is expected in its 64 byte compact
+    /// form `r || s` with a low `s`, and the public key as the 64 byte uncompressed point
+    /// `x || y` without a tag byte.
+    native public fun secp256k1_verify(
+        signature: vector<u8>,
+        public_key: vector<u8>,
+        message_hash: vector<u8>,
+    ): bool;
+
+    /// Recover the public key which signed the 32 byte `message_hash` from a secp256k1 ECDSA
+    /// `signature` and its `recovery_id`, which must be between 0 and 3. Returns the key in the
+    /// 64 byte form accepted by `secp256k1_verify`, or `None` if no key can be recovered.
+    public fun secp256k1_recover(
+        message_hash: vector<u8>,
+        recovery_id: u8,
+        signature: vector<u8>,
+    ): Option<vector<u8>> {
+        let public_key = secp256k1_recover_internal(message_hash, recovery_id, signature);
+        if (Vector::is_empty(&public_key)) {
+            Option::none()
+        } else {
+            Option::some(public_key)
+        }
+    }
+    spec secp256k1_recover {
+        pragma opaque;
+        aborts_if false;
+        let public_key = secp256k1_recover_internal(message_hash, recovery_id, signature);
+        ensures len(public_key) == 0 ==> result == Option::spec_none();
+        ensures len(public_key) > 0 ==> result == Option::spec_some(public_key);
+    }
+
+    /// Returns the recovered public key, or an empty vector if recovery fails.
+    native fun secp256k1_recover_internal(
+        message_hash: vector<u8>,
+        recovery_id: u8,
+        signature: vector<u8>,
+    ): vector<u8>;
+}
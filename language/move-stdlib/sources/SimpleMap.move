@@ -0,0 +1,229 @@
+/// A `SimpleMap` is a map backed by an unsorted vector of key-value pairs. It is cheap to
+/// construct and store for small maps, but lookups and removals take linear time. Entries can
+/// be iterated by index, in which case their order is unspecified.
+module Std::SimpleMap {
+    use Std::Errors;
+    use Std::Option::{Self, Option};
+    use Std::Vector;
+
+    /// The map already contains the key
+    const EKEY_ALREADY_EXISTS: u64 = 0;
+    /// The map does not contain the key
+    const EKEY_NOT_FOUND: u64 = 1;
+    /// The index is out of bounds
+    const EINDEX_OUT_OF_BOUNDS: u64 = 2;
+
+    struct SimpleMap<Key, Value> has copy, drop, store {
+        data: vector<Element<Key, Value>>,
+    }
+    spec SimpleMap {
+        /// Keys are unique.
+        invariant forall i in 0..len(data), j in 0..len(data):
+            data[i].key == data[j].key ==> i == j;
+    }
+
+    struct Element<Key, Value> has copy, drop, store {
+        key: Key,
+        value: Value,
+    }
+
+    /// Create an empty map.
+    public fun empty<Key, Value>(): SimpleMap<Key, Value> {
+        SimpleMap { data: Vector::empty() }
+    }
+    spec empty {
+        aborts_if false;
+        ensures len(result.data) == 0;
+    }
+
+    /// Return the number of entries in `map`.
+    public fun length<Key, Value>(map: &SimpleMap<Key, Value>): u64 {
+        Vector::length(&map.data)
+    }
+    spec length {
+        aborts_if false;
+        ensures result == len(map.data);
+    }
+
+    /// Return true if `map` has no entries.
+    public fun is_empty<Key, Value>(map: &SimpleMap<Key, Value>): bool {
+        Vector::is_empty(&map.data)
+    }
+
+    /// Return true if `map` contains an entry for `key`.
+    public fun contains_key<Key, Value>(map: &SimpleMap<Key, Value>, key: &Key): bool {
+        Option::is_some(&find(map, key))
+    }
+    spec contains_key {
+        aborts_if false;
+        ensures result == spec_contains_key(map, key);
+    }
+
+    /// Return an immutable reference to the value stored for `key`.
+    /// Aborts if there is no such entry.
+    public fun borrow<Key, Value>(map: &SimpleMap<Key, Value>, key: &Key): &Value {
+        let idx = find(map, key);
+        assert!(Option::is_some(&idx), Errors::invalid_argument(EKEY_NOT_FOUND));
+        &Vector::borrow(&map.data, Option::extract(&mut idx)).value
+    }
+    spec borrow {
+        aborts_if !spec_contains_key(map, key) with Errors::INVALID_ARGUMENT;
+        ensures result == spec_get(map, key);
+    }
+
+    /// Return a mutable reference to the value stored for `key`.
+    /// Aborts if there is no such entry.
+    public fun borrow_mut<Key, Value>(map: &mut SimpleMap<Key, Value>, key: &Key): &mut Value {
+        let idx = find(map, key);
+        assert!(Option::is_some(&idx), Errors::invalid_argument(EKEY_NOT_FOUND));
+        &mut Vector::borrow_mut(&mut map.data, Option::extract(&mut idx)).value
+    }
+    spec borrow_mut {
+        aborts_if !spec_contains_key(map, key) with Errors::INVALID_ARGUMENT;
+    }
+
+    /// Add an entry for `key` to `map`. Aborts if there is already an entry for `key`.
+    public fun add<Key, Value>(map: &mut SimpleMap<Key, Value>, key: Key, value: Value) {
+        assert!(Option::is_none(&find(map, &key)), Errors::invalid_argument(EKEY_ALREADY_EXISTS));
+        Vector::push_back(&mut map.data, Element { key, value });
+    }
+    spec add {
+        aborts_if spec_contains_key(map, key) with Errors::INVALID_ARGUMENT;
+        ensures len(map.data) == len(old(map.data)) + 1;
+        ensures spec_contains_key(map, key);
+        ensures spec_get(map, key) == value;
+    }
+
+    /// Remove the entry for `key` from `map` and return it. Aborts if there is no such entry.
+    /// This changes the position of the last entry of `map`.
+    public fun remove<Key, Value>(map: &mut SimpleMap<Key, Value>, key: &Key): (Key, Value) {
+        let idx = find(map, key);
+        assert!(Option::is_some(&idx), Errors::invalid_argument(EKEY_NOT_FOUND));
+        let Element { key, value } = Vector::swap_remove(&mut map.data, Option::extract(&mut idx));
+        (key, value)
+    }
+    spec remove {
+        aborts_if !spec_contains_key(map, key) with Errors::INVALID_ARGUMENT;
+        ensures len(map.data) == len(old(map.data)) - 1;
+        ensures !spec_contains_key(map, key);
+        ensures result_1 == key;
+        ensures result_2 == spec_get(old(map), key);
+    }
+
+    /// Destroy an empty `map`. Aborts if `map` is not empty.
+    public fun destroy_empty<Key, Value>(map: SimpleMap<Key, Value>) {
+        let SimpleMap { data } = map;
+        Vector::destroy_empty(data);
+    }
+
+    /// Return the entry at position `i`, which must be smaller than the length of `map`.
+    /// This allows iterating over all entries, in an unspecified order.
+    public fun borrow_at<Key, Value>(map: &SimpleMap<Key, Value>, i: u64): (&Key, &Value) {
+        assert!(i < Vector::length(&map.data), Errors::invalid_argument(EINDEX_OUT_OF_BOUNDS));
+        let element = Vector::borrow(&map.data, i);
+        (&element.key, &element.value)
+    }
+    spec borrow_at {
+        aborts_if i >= len(map.data) with Errors::INVALID_ARGUMENT;
+        ensures result_1 == map.data[i].key;
+        ensures result_2 == map.data[i].value;
+    }
+
+    /// Return the entry at position `i` with a mutable reference to its value.
+    public fun borrow_at_mut<Key, Value>(map: &mut SimpleMap<Key, Value>, i: u64): (&Key, &mut Value) {
+        assert!(i < Vector::length(&map.data), Errors::invalid_argument(EINDEX_OUT_OF_BOUNDS));
+        let element = Vector::borrow_mut(&mut map.data, i);
+        (&element.key, &mut element.value)
+    }
+    spec borrow_at_mut {
+        aborts_if i >= len(map.data) with Errors::INVALID_ARGUMENT;
+    }
+
+    /// Return all keys of `map`, in the same order as `values`.
+    public fun keys<Key: copy, Value>(map: &SimpleMap<Key, Value>): vector<Key> {
+        let keys = Vector::empty();
+        let i = 0;
+        let n = Vector::length(&map.data);
+        while ({
+            spec {
+                invariant i <= n;
+                invariant len(keys) == i;
+                invariant forall j in 0..i: keys[j] == map.data[j].key;
+            };
+            i < n
+        }) {
+            Vector::push_back(&mut keys, Vector::borrow(&map.data, i).key);
+            i = i + 1;
+        };
+        keys
+    }
+    spec keys {
+        aborts_if false;
+        ensures len(result) == len(map.data);
+        ensures forall i in 0..len(result): result[i] == map.data[i].key;
+    }
+
+    /// Return all values of `map`, in the same order as `keys`.
+    public fun values<Key, Value: copy>(map: &SimpleMap<Key, Value>): vector<Value> {
+        let values = Vector::empty();
+        let i = 0;
+        let n = Vector::length(&map.data);
+        while ({
+            spec {
+                invariant i <= n;
+                invariant len(values) == i;
+                invariant forall j in 0..i: values[j] == map.data[j].value;
+            };
+            i < n
+        }) {
+            Vector::push_back(&mut values, Vector::borrow(&map.data, i).value);
+            i = i + 1;
+        };
+        values
+    }
+    spec values {
+        aborts_if false;
+        ensures len(result) == len(map.data);
+        ensures forall i in 0..len(result): result[i] == map.data[i].value;
+    }
+
+    /// Return the position of the entry for `key`, if any.
+    fun find<Key, Value>(map: &SimpleMap<Key, Value>, key: &Key): Option<u64> {
+        let n = Vector::length(&map.data);
+        let i = 0;
+        while ({
+            spec {
+                invariant i <= n;
+                invariant forall j in 0..i: map.data[j].key != key;
+            };
+            i < n
+        }) {
+            if (&Vector::borrow(&map.data, i).key == key) {
+                return Option::some(i)
+            };
+            i = i + 1;
+        };
+        Option::none()
+    }
+    spec find {
+        aborts_if false;
+        ensures Option::is_none(result) ==> !spec_contains_key(map, key);
+        ensures Option::is_some(result) ==> map.data[Option::borrow(result)].key == key;
+    }
+
+    // **************** SPECIFICATIONS ****************
+
+    spec module {} // switch documentation context to module level
+
+    spec module {
+        /// Whether `map` contains an entry for `key`.
+        fun spec_contains_key<Key, Value>(map: SimpleMap<Key, Value>, key: Key): bool {
+            exists i in 0..len(map.data): map.data[i].key == key
+        }
+
+        /// The value stored for `key`, which must be contained in `map`.
+        fun spec_get<Key, Value>(map: SimpleMap<Key, Value>, key: Key): Value {
+            map.data[choose i in 0..len(map.data) where map.data[i].key == key].value
+        }
+    }
+}
@@ -0,0 +1,172 @@
+/// The `String` module defines the `String` type which represents UTF-8 encoded strings.
+///
+/// Validation of UTF-8 and searching are implemented as natives, which are described to the
+/// prover by uninterpreted specification functions.
+module Std::String {
+    use Std::Errors;
+    use Std::Option::{Self, Option};
+    use Std::Vector;
+
+    /// An invalid UTF-8 encoding.
+    const EINVALID_UTF8: u64 = 0;
+
+    /// Index out of range, or not at a character boundary.
+    const EINVALID_INDEX: u64 = 1;
+
+    /// A `String` holds a sequence of bytes which is guaranteed to be in UTF-8 format.
+    struct String has copy, drop, store {
+        bytes: vector<u8>,
+    }
+
+    /// Creates a new string from a sequence of bytes. Aborts if the bytes do not represent
+    /// valid UTF-8.
+    public fun utf8(bytes: vector<u8>): String {
+        assert!(internal_check_utf8(&bytes), Errors::invalid_argument(EINVALID_UTF8));
+        String { bytes }
+    }
+    spec utf8 {
+        aborts_if !spec_internal_check_utf8(bytes) with Errors::INVALID_ARGUMENT;
+        ensures result == String { bytes };
+    }
+
+    /// Tries to create a new string from a sequence of bytes. Returns `None` if the bytes do
+    /// not represent valid UTF-8.
+    public fun try_utf8(bytes: vector<u8>): Option<String> {
+        if (internal_check_utf8(&bytes)) {
+            Option::some(String { bytes })
+        } else {
+            Option::none()
+        }
+    }
+    spec try_utf8 {
+        aborts_if false;
+        ensures spec_internal_check_utf8(bytes) ==> result == Option::spec_some(String { bytes });
+        ensures !spec_internal_check_utf8(bytes) ==> result == Option::spec_none();
+    }
+
+    /// Returns a reference to the underlying byte vector.
+    public fun bytes(s: &String): &vector<u8> {
+        &s.bytes
+    }
+
+    /// Checks whether this string is empty.
+    public fun is_empty(s: &String): bool {
+        Vector::is_empty(&s.bytes)
+    }
+
+    /// Returns the length of this string, in bytes.
+    public fun length(s: &String): u64 {
+        Vector::length(&s.bytes)
+    }
+
+    /// Appends a string.
+    public fun append(s: &mut String, r: String) {
+        Vector::append(&mut s.bytes, r.bytes)
+    }
+    spec append {
+        aborts_if false;
+        ensures s.bytes == concat(old(s.bytes), r.bytes);
+    }
+
+    /// Appends bytes which must be in valid UTF-8 format.
+    public fun append_utf8(s: &mut String, bytes: vector<u8>) {
+        append(s, utf8(bytes))
+    }
+    spec append_utf8 {
+        aborts_if !spec_internal_check_utf8(bytes) with Errors::INVALID_ARGUMENT;
+        ensures s.bytes == concat(old(s.bytes), bytes);
+    }
+
+    /// Inserts the other string at the byte index `at`. The index must be at a valid UTF-8
+    /// character boundary.
+    public fun insert(s: &mut String, at: u64, o: String) {
+        let bytes = &s.bytes;
+        assert!(
+            at <= Vector::length(bytes) && internal_is_char_boundary(bytes, at),
+            Errors::invalid_argument(EINVALID_INDEX)
+        );
+        let l = length(s);
+        let front = sub_string(s, 0, at);
+        let end = sub_string(s, at, l);
+        append(&mut front, o);
+        append(&mut front, end);
+        *s = front;
+    }
+    spec insert {
+        aborts_if at > len(s.bytes) || !spec_internal_is_char_boundary(s.bytes, at)
+            with Errors::INVALID_ARGUMENT;
+        ensures s.bytes == concat(concat(old(s.bytes)[0..at], o.bytes), old(s.bytes)[at..len(old(s.bytes))]);
+    }
+
+    /// Returns a sub-string using the given byte indices, where `i` is the first byte position
+    /// and `j` is the start of the first byte not included (or the length of the string). The
+    /// indices must be at valid UTF-8 character boundaries, guaranteeing that the result is
+    /// valid UTF-8.
+    public fun sub_string(s: &String, i: u64, j: u64): String {
+        let bytes = &s.bytes;
+        let l = Vector::length(bytes);
+        assert!(
+            j <= l && i <= j && internal_is_char_boundary(bytes, i) && internal_is_char_boundary(bytes, j),
+            Errors::invalid_argument(EINVALID_INDEX)
+        );
+        String { bytes: internal_sub_string(bytes, i, j) }
+    }
+    spec sub_string {
+        aborts_if j > len(s.bytes) || i > j with Errors::INVALID_ARGUMENT;
+        aborts_if !spec_internal_is_char_boundary(s.bytes, i) with Errors::INVALID_ARGUMENT;
+        aborts_if !spec_internal_is_char_boundary(s.bytes, j) with Errors::INVALID_ARGUMENT;
+        ensures result.bytes == s.bytes[i..j];
+    }
+
+    /// Computes the byte index of the first occurrence of the `r` string. Returns the length
+    /// of `s` if no occurrence is found.
+    public fun index_of(s: &String, r: &String): u64 {
+        internal_index_of(&s.bytes, &r.bytes)
+    }
+    spec index_of {
+        aborts_if false;
+        ensures result == spec_internal_index_of(s.bytes, r.bytes);
+        ensures result <= len(s.bytes);
+    }
+
+    // Native API
+    native fun internal_check_utf8(v: &vector<u8>): bool;
+    native fun internal_is_char_boundary(v: &vector<u8>, i: u64): bool;
+    native fun internal_sub_string(v: &vector<u8>, i: u64, j: u64): vector<u8>;
+    native fun internal_index_of(v: &vector<u8>, r: &vector<u8>): u64;
+
+    spec internal_check_utf8 {
+        pragma opaque;
+        aborts_if false;
+        ensures result == spec_internal_check_utf8(v);
+    }
+    spec internal_is_char_boundary {
+        pragma opaque;
+        aborts_if false;
+        ensures result == spec_internal_is_char_boundary(v, i);
+    }
+    spec internal_sub_string {
+        pragma opaque;
+        aborts_if false;
+        ensures result == v[i..j];
+    }
+    spec internal_index_of {
+        pragma opaque;
+        aborts_if false;
+        ensures result == spec_internal_index_of(v, r);
+        ensures result <= len(v);
+    }
+
+    // ==============================
+    // Module Specification
+    spec module {} // switch to module documentation context
+
+    spec module {
+        /// Whether `v` is a valid UTF-8 encoding.
+        fun spec_internal_check_utf8(v: vector<u8>): bool;
+        /// Whether `i` is at a character boundary of the UTF-8 encoded `v`.
+        fun spec_internal_is_char_boundary(v: vector<u8>, i: u64): bool;
+        /// The byte index of the first occurrence of `r` in `v`, or the length of `v`.
+        fun spec_internal_index_of(v: vector<u8>, r: vector<u8>): u64;
+    }
+}
@@ -0,0 +1,122 @@
+/// A `VecSet` is a set backed by a vector. Elements keep the order in which they were
+/// inserted, and can be iterated by index. Lookups, insertions and removals take linear time,
+/// so this is best suited for small sets.
+module Std::VecSet {
+    use Std::Errors;
+    use Std::Vector;
+
+    /// The set already contains the element
+    const EELEMENT_ALREADY_EXISTS: u64 = 0;
+    /// The set does not contain the element
+    const EELEMENT_NOT_FOUND: u64 = 1;
+
+    struct VecSet<Element> has copy, drop, store {
+        contents: vector<Element>,
+    }
+    spec VecSet {
+        /// Elements are unique.
+        invariant forall i in 0..len(contents), j in 0..len(contents):
+            contents[i] == contents[j] ==> i == j;
+    }
+
+    /// Create an empty set.
+    public fun empty<Element>(): VecSet<Element> {
+        VecSet { contents: Vector::empty() }
+    }
+    spec empty {
+        aborts_if false;
+        ensures len(result.contents) == 0;
+    }
+
+    /// Create a set containing only `e`.
+    public fun singleton<Element>(e: Element): VecSet<Element> {
+        VecSet { contents: Vector::singleton(e) }
+    }
+    spec singleton {
+        aborts_if false;
+        ensures result.contents == vec(e);
+    }
+
+    /// Return the number of elements in `set`.
+    public fun length<Element>(set: &VecSet<Element>): u64 {
+        Vector::length(&set.contents)
+    }
+    spec length {
+        aborts_if false;
+        ensures result == len(set.contents);
+    }
+
+    /// Return true if `set` has no elements.
+    public fun is_empty<Element>(set: &VecSet<Element>): bool {
+        Vector::is_empty(&set.contents)
+    }
+
+    /// Return true if `set` contains `e`.
+    public fun contains<Element>(set: &VecSet<Element>, e: &Element): bool {
+        Vector::contains(&set.contents, e)
+    }
+    spec contains {
+        aborts_if false;
+        ensures result == spec_contains(set, e);
+    }
+
+    /// Insert `e` into `set`. Aborts if `set` already contains `e`.
+    public fun insert<Element>(set: &mut VecSet<Element>, e: Element) {
+        assert!(!Vector::contains(&set.contents, &e), Errors::invalid_argument(EELEMENT_ALREADY_EXISTS));
+        Vector::push_back(&mut set.contents, e);
+    }
+    spec insert {
+        aborts_if spec_contains(set, e) with Errors::INVALID_ARGUMENT;
+        ensures set.contents == concat(old(set.contents), vec(e));
+    }
+
+    /// Remove `e` from `set` and return the removed element. Aborts if `set` does not
+    /// contain `e`. The order of the remaining elements is preserved.
+    public fun remove<Element>(set: &mut VecSet<Element>, e: &Element): Element {
+        let (found, i) = Vector::index_of(&set.contents, e);
+        assert!(found, Errors::invalid_argument(EELEMENT_NOT_FOUND));
+        Vector::remove(&mut set.contents, i)
+    }
+    spec remove {
+        aborts_if !spec_contains(set, e) with Errors::INVALID_ARGUMENT;
+        ensures len(set.contents) == len(old(set.contents)) - 1;
+        ensures !spec_contains(set, e);
+        ensures result == e;
+    }
+
+    /// Return the element at position `i`, which must be smaller than the length of `set`.
+    /// This allows iterating over all elements in insertion order.
+    public fun borrow<Element>(set: &VecSet<Element>, i: u64): &Element {
+        Vector::borrow(&set.contents, i)
+    }
+    spec borrow {
+        aborts_if i >= len(set.contents);
+        ensures result == set.contents[i];
+    }
+
+    /// Return a reference to the elements of `set`, in insertion order.
+    public fun elements<Element>(set: &VecSet<Element>): &vector<Element> {
+        &set.contents
+    }
+
+    /// Unpack `set` into its elements, in insertion order.
+    public fun into_elements<Element>(set: VecSet<Element>): vector<Element> {
+        let VecSet { contents } = set;
+        contents
+    }
+    spec into_elements {
+        aborts_if false;
+        ensures result == set.contents;
+    }
+
+    // **************** SPECIFICATIONS ****************
+
+    spec module {} // switch documentation context to module level
+
+    spec module {
+        /// Whether `set` contains `e`.
+        fun spec_contains<Element>(set: VecSet<Element>, e: Element): bool {
+            exists i in 0..len(set.contents): set.contents[i] == e
+        }
+    }
+}
@@ -3,55 +3,58 @@
 
 use move_binary_format::errors::PartialVMResult;
 use move_core_types::gas_schedule::ONE_GAS_UNIT;
-use move_vm_runtime::native_functions::NativeContext;
+use move_vm_runtime::native_functions::{NativeContext, TypedNativeFunction, TypedNativeResult};
+use move_vm_types::loaded_data::runtime_types::Type;
 #[allow(unused_imports)]
 use move_vm_types::values::{values_impl::debug::print_reference, Reference};
-#[allow(unused_imports)]
-use move_vm_types::{
-    loaded_data::runtime_types::Type, natives::function::NativeResult, pop_arg, values::Value,
-};
-use smallvec::smallvec;
-use std::collections::VecDeque;
-
-#[allow(unused_mut)]
-#[allow(unused_variables)]
-pub fn native_print(
-    context: &mut NativeContext,
-    mut ty_args: Vec<Type>,
-    mut args: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(ty_args.len() == 1);
-    debug_assert!(args.len() == 1);
-
-    // No-op if the feature flag is not present.
-    #[cfg(feature = "testing")]
-    {
-        let ty = ty_args.pop().unwrap();
-        let r = pop_arg!(args, Reference);
-
-        let mut buf = String::new();
-        print_reference(&mut buf, &r)?;
-        println!("[debug] {}", buf);
-    }
 
-    Ok(NativeResult::ok(ONE_GAS_UNIT, smallvec![]))
+/// Rust implementation of Move's `native public fun print<T>(x: &T)`
+pub struct NativePrint;
+
+impl TypedNativeFunction for NativePrint {
+    const TYPE_PARAMETERS: usize = 1;
+
+    type Args = (Reference,);
+    type Return = ();
+
+    #[allow(unused_variables)]
+    fn execute(
+        context: &mut NativeContext,
+        ty_args: Vec<Type>,
+        (r,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<()>> {
+        // No-op if the feature flag is not present.
+        #[cfg(feature = "testing")]
+        {
+            let mut buf = String::new();
+            print_reference(&mut buf, &r)?;
+            println!("[debug] {}", buf);
+        }
+
+        Ok(TypedNativeResult::ok(ONE_GAS_UNIT, ()))
+    }
 }
 
-#[allow(unused_variables)]
-pub fn native_print_stack_trace(
-    context: &mut NativeContext,
-    ty_args: Vec<Type>,
-    args: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(ty_args.is_empty());
-    debug_assert!(args.is_empty());
-
-    #[cfg(feature = "testing")]
-    {
-        let mut s = String::new();
-        context.print_stack_trace(&mut s)?;
-        println!("{}", s);
+/// Rust implementation of Move's `native public fun print_stack_trace()`
+pub struct NativePrintStackTrace;
+
+impl TypedNativeFunction for NativePrintStackTrace {
+    type Args = ();
+    type Return = ();
+
+    #[allow(unused_variables)]
+    fn execute(
+        context: &mut NativeContext,
+        ty_args: Vec<Type>,
+        (): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<()>> {
+        #[cfg(feature = "testing")]
+        {
+            let mut s = String::new();
+            context.print_stack_trace(&mut s)?;
+            println!("{}", s);
+        }
+
+        Ok(TypedNativeResult::ok(ONE_GAS_UNIT, ()))
     }
-
-    Ok(NativeResult::ok(ONE_GAS_UNIT, smallvec![]))
 }
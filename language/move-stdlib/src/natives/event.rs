@@ -3,39 +3,63 @@
 
 use move_binary_format::errors::PartialVMResult;
 use move_core_types::gas_schedule::GasAlgebra;
-use move_vm_runtime::native_functions::NativeContext;
+use move_vm_runtime::native_functions::{NativeContext, TypedNativeFunction, TypedNativeResult};
 use move_vm_types::{
-    gas_schedule::NativeCostIndex,
-    loaded_data::runtime_types::Type,
-    natives::function::{native_gas, NativeResult},
-    pop_arg,
-    values::Value,
+    gas_schedule::NativeCostIndex, loaded_data::runtime_types::Type,
+    natives::function::NativeGasFormula, values::Value,
 };
-use smallvec::smallvec;
-use std::collections::VecDeque;
-
-pub fn write_to_event_store(
-    context: &mut NativeContext,
-    mut ty_args: Vec<Type>,
-    mut arguments: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(ty_args.len() == 1);
-    debug_assert!(arguments.len() == 3);
-
-    let ty = ty_args.pop().unwrap();
-    let msg = arguments.pop_back().unwrap();
-    let seq_num = pop_arg!(arguments, u64);
-    let guid = pop_arg!(arguments, Vec<u8>);
-
-    let cost = native_gas(
-        context.cost_table(),
-        NativeCostIndex::EMIT_EVENT,
-        msg.size().get() as usize,
-    );
-
-    if !context.save_event(guid, seq_num, ty, msg)? {
-        return Ok(NativeResult::err(cost, 0));
+
+/// Rust implementation of Move's
+/// `native fun write_to_event_store<T: drop + store>(guid: vector<u8>, count: u64, msg: T)`
+pub struct NativeWriteToEventStore;
+
+impl TypedNativeFunction for NativeWriteToEventStore {
+    const TYPE_PARAMETERS: usize = 1;
+
+    type Args = (Vec<u8>, u64, Value);
+    type Return = ();
+
+    fn execute(
+        context: &mut NativeContext,
+        mut ty_args: Vec<Type>,
+        (guid, seq_num, msg): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<()>> {
+        let ty = ty_args.pop().unwrap();
+
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::EMIT_EVENT)
+            .cost(context.cost_table(), msg.size().get() as usize);
+
+        if !context.save_event(guid, seq_num, ty, msg)? {
+            return Ok(TypedNativeResult::err(cost, 0));
+        }
+
+        Ok(TypedNativeResult::ok(cost, ()))
     }
+}
 
-    Ok(NativeResult::ok(cost, smallvec![]))
+/// Rust implementation of Move's `native fun emit<T: drop + store>(msg: T)`
+pub struct NativeEmit;
+
+impl TypedNativeFunction for NativeEmit {
+    const TYPE_PARAMETERS: usize = 1;
+
+    type Args = (Value,);
+    type Return = ();
+
+    fn execute(
+        context: &mut NativeContext,
+        mut ty_args: Vec<Type>,
+        (msg,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<()>> {
+        let ty = ty_args.pop().unwrap();
+
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::EMIT_EVENT)
+            .cost(context.cost_table(), msg.size().get() as usize);
+
+        if !context.save_module_event(ty, msg)? {
+            return Ok(TypedNativeResult::err(cost, 0));
+        }
+
+        Ok(TypedNativeResult::ok(cost, ()))
+    }
 }
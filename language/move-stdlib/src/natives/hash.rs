@@ -1,62 +1,118 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use blake2::{
+    digest::{Update, VariableOutput},
+    VarBlake2b,
+};
 use move_binary_format::errors::PartialVMResult;
-use move_vm_runtime::native_functions::NativeContext;
+use move_vm_runtime::native_functions::{NativeContext, TypedNativeFunction, TypedNativeResult};
 use move_vm_types::{
-    gas_schedule::NativeCostIndex,
-    loaded_data::runtime_types::Type,
-    natives::function::{native_gas, NativeResult},
-    pop_arg,
-    values::Value,
+    gas_schedule::NativeCostIndex, loaded_data::runtime_types::Type,
+    natives::function::NativeGasFormula,
 };
+use ripemd160::Ripemd160;
 use sha2::{Digest, Sha256};
-use sha3::Sha3_256;
-use smallvec::smallvec;
-use std::collections::VecDeque;
-
-pub fn native_sha2_256(
-    context: &mut NativeContext,
-    _ty_args: Vec<Type>,
-    mut arguments: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(_ty_args.is_empty());
-    debug_assert!(arguments.len() == 1);
-
-    let hash_arg = pop_arg!(arguments, Vec<u8>);
-
-    let cost = native_gas(
-        context.cost_table(),
-        NativeCostIndex::SHA2_256,
-        hash_arg.len(),
-    );
-
-    let hash_vec = Sha256::digest(hash_arg.as_slice()).to_vec();
-    Ok(NativeResult::ok(
-        cost,
-        smallvec![Value::vector_u8(hash_vec)],
-    ))
+use sha3::{Keccak256, Sha3_256};
+
+/// Rust implementation of Move's `native public fun sha2_256(data: vector<u8>): vector<u8>`
+pub struct NativeSha2_256;
+
+impl TypedNativeFunction for NativeSha2_256 {
+    type Args = (Vec<u8>,);
+    type Return = Vec<u8>;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (hash_arg,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Vec<u8>>> {
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::SHA2_256)
+            .cost(context.cost_table(), hash_arg.len());
+
+        let hash_vec = Sha256::digest(hash_arg.as_slice()).to_vec();
+        Ok(TypedNativeResult::ok(cost, hash_vec))
+    }
+}
+
+/// Rust implementation of Move's `native public fun sha3_256(data: vector<u8>): vector<u8>`
+pub struct NativeSha3_256;
+
+impl TypedNativeFunction for NativeSha3_256 {
+    type Args = (Vec<u8>,);
+    type Return = Vec<u8>;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (hash_arg,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Vec<u8>>> {
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::SHA3_256)
+            .cost(context.cost_table(), hash_arg.len());
+
+        let hash_vec = Sha3_256::digest(hash_arg.as_slice()).to_vec();
+        Ok(TypedNativeResult::ok(cost, hash_vec))
+    }
 }
 
-pub fn native_sha3_256(
-    context: &mut NativeContext,
-    _ty_args: Vec<Type>,
-    mut arguments: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(_ty_args.is_empty());
-    debug_assert!(arguments.len() == 1);
-
-    let hash_arg = pop_arg!(arguments, Vec<u8>);
-
-    let cost = native_gas(
-        context.cost_table(),
-        NativeCostIndex::SHA3_256,
-        hash_arg.len(),
-    );
-
-    let hash_vec = Sha3_256::digest(hash_arg.as_slice()).to_vec();
-    Ok(NativeResult::ok(
-        cost,
-        smallvec![Value::vector_u8(hash_vec)],
-    ))
+/// Rust implementation of Move's `native public fun keccak256(data: vector<u8>): vector<u8>`
+pub struct NativeKeccak256;
+
+impl TypedNativeFunction for NativeKeccak256 {
+    type Args = (Vec<u8>,);
+    type Return = Vec<u8>;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (hash_arg,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Vec<u8>>> {
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::KECCAK_256)
+            .cost(context.cost_table(), hash_arg.len());
+
+        let hash_vec = Keccak256::digest(hash_arg.as_slice()).to_vec();
+        Ok(TypedNativeResult::ok(cost, hash_vec))
+    }
+}
+
+/// Rust implementation of Move's `native public fun blake2b_256(data: vector<u8>): vector<u8>`
+pub struct NativeBlake2b256;
+
+impl TypedNativeFunction for NativeBlake2b256 {
+    type Args = (Vec<u8>,);
+    type Return = Vec<u8>;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (hash_arg,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Vec<u8>>> {
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::BLAKE2B_256)
+            .cost(context.cost_table(), hash_arg.len());
+
+        let mut hasher = VarBlake2b::new(32).expect("32 is a valid BLAKE2b output size");
+        hasher.update(hash_arg.as_slice());
+        let hash_vec = hasher.finalize_boxed().into_vec();
+        Ok(TypedNativeResult::ok(cost, hash_vec))
+    }
+}
+
+/// Rust implementation of Move's `native public fun ripemd160(data: vector<u8>): vector<u8>`
+pub struct NativeRipemd160;
+
+impl TypedNativeFunction for NativeRipemd160 {
+    type Args = (Vec<u8>,);
+    type Return = Vec<u8>;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (hash_arg,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Vec<u8>>> {
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::RIPEMD160)
+            .cost(context.cost_table(), hash_arg.len());
+
+        let hash_vec = Ripemd160::digest(hash_arg.as_slice()).to_vec();
+        Ok(TypedNativeResult::ok(cost, hash_vec))
+    }
 }
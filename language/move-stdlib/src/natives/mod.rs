@@ -4,7 +4,9 @@
 pub mod bcs;
 pub mod event;
 pub mod hash;
+pub mod signature;
 pub mod signer;
+pub mod string;
 pub mod vector;
 
 #[cfg(feature = "testing")]
@@ -14,40 +16,43 @@ pub mod unit_test;
 pub mod debug;
 
 use move_core_types::account_address::AccountAddress;
-use move_vm_runtime::{
-    native_functions,
-    native_functions::{NativeFunction, NativeFunctionTable},
-};
+use move_vm_runtime::native_functions::{NativeFunctionTable, NativeFunctionTableBuilder};
 
 pub fn all_natives(move_std_addr: AccountAddress) -> NativeFunctionTable {
-    const NATIVES: &[(&str, &str, NativeFunction)] = &[
-        ("BCS", "to_bytes", bcs::native_to_bytes),
-        ("Event", "write_to_event_store", event::write_to_event_store),
-        ("Hash", "sha2_256", hash::native_sha2_256),
-        ("Hash", "sha3_256", hash::native_sha3_256),
-        ("Signer", "borrow_address", signer::native_borrow_address),
-        ("Vector", "length", vector::native_length),
-        ("Vector", "empty", vector::native_empty),
-        ("Vector", "borrow", vector::native_borrow),
-        ("Vector", "borrow_mut", vector::native_borrow),
-        ("Vector", "push_back", vector::native_push_back),
-        ("Vector", "pop_back", vector::native_pop),
-        ("Vector", "destroy_empty", vector::native_destroy_empty),
-        ("Vector", "swap", vector::native_swap),
-        #[cfg(feature = "testing")]
-        ("Debug", "print", debug::native_print),
-        #[cfg(feature = "testing")]
-        (
-            "Debug",
-            "print_stack_trace",
-            debug::native_print_stack_trace,
-        ),
-        #[cfg(feature = "testing")]
-        (
+    let mut builder = NativeFunctionTableBuilder::new(move_std_addr);
+    builder
+        .add_typed::<bcs::NativeToBytes>("BCS", "to_bytes")
+        .add_typed::<event::NativeWriteToEventStore>("Event", "write_to_event_store")
+        .add_typed::<event::NativeEmit>("Event", "emit")
+        .add_typed::<hash::NativeSha2_256>("Hash", "sha2_256")
+        .add_typed::<hash::NativeSha3_256>("Hash", "sha3_256")
+        .add_typed::<hash::NativeKeccak256>("Hash", "keccak256")
+        .add_typed::<hash::NativeBlake2b256>("Hash", "blake2b_256")
+        .add_typed::<hash::NativeRipemd160>("Hash", "ripemd160")
+        .add_typed::<signature::NativeEd25519ValidatePubkey>("Signature", "ed25519_validate_pubkey")
+        .add_typed::<signature::NativeEd25519Verify>("Signature", "ed25519_verify")
+        .add_typed::<signature::NativeSecp256k1Verify>("Signature", "secp256k1_verify")
+        .add_typed::<signature::NativeSecp256k1Recover>("Signature", "secp256k1_recover_internal")
+        .add_typed::<signer::NativeBorrowAddress>("Signer", "borrow_address")
+        .add_typed::<string::NativeCheckUtf8>("String", "internal_check_utf8")
+        .add_typed::<string::NativeIsCharBoundary>("String", "internal_is_char_boundary")
+        .add_typed::<string::NativeSubString>("String", "internal_sub_string")
+        .add_typed::<string::NativeIndexOf>("String", "internal_index_of")
+        .add_typed::<vector::NativeLength>("Vector", "length")
+        .add_typed::<vector::NativeEmpty>("Vector", "empty")
+        .add_typed::<vector::NativeBorrow>("Vector", "borrow")
+        .add_typed::<vector::NativeBorrow>("Vector", "borrow_mut")
+        .add_typed::<vector::NativePushBack>("Vector", "push_back")
+        .add_typed::<vector::NativePop>("Vector", "pop_back")
+        .add_typed::<vector::NativeDestroyEmpty>("Vector", "destroy_empty")
+        .add_typed::<vector::NativeSwap>("Vector", "swap");
+    #[cfg(feature = "testing")]
+    builder
+        .add_typed::<debug::NativePrint>("Debug", "print")
+        .add_typed::<debug::NativePrintStackTrace>("Debug", "print_stack_trace")
+        .add_typed::<unit_test::NativeCreateSignersForTesting>(
             "UnitTest",
             "create_signers_for_testing",
-            unit_test::native_create_signers_for_testing,
-        ),
-    ];
-    native_functions::make_table(move_std_addr, NATIVES)
+        );
+    builder.build()
 }
@@ -0,0 +1,108 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+// The validation of keys and signatures is shared with the stackless bytecode interpreter, so
+// that the natives and the interpreter accept the same inputs.
+use bytecode_interpreter_crypto::{
+    ed25519_deserialize_public_key, ed25519_deserialize_signature, ed25519_verify_signature,
+    secp256k1_recover_public_key, secp256k1_verify_signature,
+};
+use move_binary_format::errors::PartialVMResult;
+use move_vm_runtime::native_functions::{NativeContext, TypedNativeFunction, TypedNativeResult};
+use move_vm_types::{
+    gas_schedule::NativeCostIndex, loaded_data::runtime_types::Type,
+    natives::function::NativeGasFormula,
+};
+
+/// Rust implementation of Move's `native public fun ed25519_validate_pubkey(public_key: vector<u8>):
+/// bool`
+pub struct NativeEd25519ValidatePubkey;
+
+impl TypedNativeFunction for NativeEd25519ValidatePubkey {
+    type Args = (Vec<u8>,);
+    type Return = bool;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (key,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<bool>> {
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::ED25519_VALIDATE_KEY)
+            .cost(context.cost_table(), key.len());
+
+        Ok(TypedNativeResult::ok(
+            cost,
+            ed25519_deserialize_public_key(&key).is_ok(),
+        ))
+    }
+}
+
+/// Rust implementation of Move's `native public fun ed25519_verify(signature: vector<u8>,
+/// public_key: vector<u8>, message: vector<u8>): bool`
+pub struct NativeEd25519Verify;
+
+impl TypedNativeFunction for NativeEd25519Verify {
+    type Args = (Vec<u8>, Vec<u8>, Vec<u8>);
+    type Return = bool;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (signature, key, msg): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<bool>> {
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::ED25519_VERIFY)
+            .cost(context.cost_table(), msg.len());
+
+        let verified = match (
+            ed25519_deserialize_signature(&signature),
+            ed25519_deserialize_public_key(&key),
+        ) {
+            (Ok(signature), Ok(key)) => ed25519_verify_signature(&key, &signature, &msg).is_ok(),
+            _ => false,
+        };
+        Ok(TypedNativeResult::ok(cost, verified))
+    }
+}
+
+/// Rust implementation of Move's `native public fun secp256k1_verify(signature: vector<u8>,
+/// public_key: vector<u8>, message_hash: vector<u8>): bool`
+pub struct NativeSecp256k1Verify;
+
+impl TypedNativeFunction for NativeSecp256k1Verify {
+    type Args = (Vec<u8>, Vec<u8>, Vec<u8>);
+    type Return = bool;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (signature, key, hash): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<bool>> {
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::SECP256K1_VERIFY)
+            .cost(context.cost_table(), hash.len());
+
+        let verified = secp256k1_verify_signature(&signature, &key, &hash).is_ok();
+        Ok(TypedNativeResult::ok(cost, verified))
+    }
+}
+
+/// Rust implementation of Move's `native fun secp256k1_recover_internal(message_hash: vector<u8>,
+/// recovery_id: u8, signature: vector<u8>): vector<u8>`
+pub struct NativeSecp256k1Recover;
+
+impl TypedNativeFunction for NativeSecp256k1Recover {
+    type Args = (Vec<u8>, u8, Vec<u8>);
+    type Return = Vec<u8>;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (hash, recovery_id, signature): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Vec<u8>>> {
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::SECP256K1_RECOVER)
+            .cost(context.cost_table(), hash.len());
+
+        // An empty vector signals that no public key could be recovered.
+        let key = secp256k1_recover_public_key(&hash, recovery_id, &signature).unwrap_or_default();
+        Ok(TypedNativeResult::ok(cost, key))
+    }
+}
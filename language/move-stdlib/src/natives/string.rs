@@ -0,0 +1,127 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::errors::{PartialVMError, PartialVMResult};
+use move_core_types::vm_status::StatusCode;
+use move_vm_runtime::native_functions::{NativeContext, TypedNativeFunction, TypedNativeResult};
+use move_vm_types::{
+    gas_schedule::NativeCostIndex, loaded_data::runtime_types::Type,
+    natives::function::NativeGasFormula, values::VectorRef,
+};
+
+/// Rust implementation of Move's `native fun internal_check_utf8(v: &vector<u8>): bool`
+pub struct NativeCheckUtf8;
+
+impl TypedNativeFunction for NativeCheckUtf8 {
+    type Args = (VectorRef,);
+    type Return = bool;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (s_arg,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<bool>> {
+        let s_ref = s_arg.as_bytes_ref()?;
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::STRING_CHECK_UTF8)
+            .cost(context.cost_table(), s_ref.len());
+
+        let ok = std::str::from_utf8(s_ref.as_slice()).is_ok();
+        Ok(TypedNativeResult::ok(cost, ok))
+    }
+}
+
+/// Rust implementation of Move's `native fun internal_is_char_boundary(v: &vector<u8>, i: u64): bool`
+pub struct NativeIsCharBoundary;
+
+impl TypedNativeFunction for NativeIsCharBoundary {
+    type Args = (VectorRef, u64);
+    type Return = bool;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (s_arg, i): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<bool>> {
+        let cost = NativeGasFormula::Constant(NativeCostIndex::STRING_IS_CHAR_BOUNDARY)
+            .cost(context.cost_table(), 1);
+
+        let s_ref = s_arg.as_bytes_ref()?;
+        let ok = is_char_boundary(s_ref.as_slice(), i as usize);
+        Ok(TypedNativeResult::ok(cost, ok))
+    }
+}
+
+/// Rust implementation of Move's
+/// `native fun internal_sub_string(v: &vector<u8>, i: u64, j: u64): vector<u8>`
+pub struct NativeSubString;
+
+impl TypedNativeFunction for NativeSubString {
+    type Args = (VectorRef, u64, u64);
+    type Return = Vec<u8>;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (s_arg, i, j): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Vec<u8>>> {
+        let s_ref = s_arg.as_bytes_ref()?;
+        let (i, j) = (i as usize, j as usize);
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::STRING_SUB_STRING)
+            .cost(context.cost_table(), j.saturating_sub(i));
+
+        // The Move side checks the bounds, so a violation here is an invariant breach.
+        let sub = s_ref.get(i..j).ok_or_else(|| {
+            PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR).with_message(
+                format!(
+                    "invalid sub string range {}..{} of a string of length {}",
+                    i,
+                    j,
+                    s_ref.len()
+                ),
+            )
+        })?;
+        Ok(TypedNativeResult::ok(cost, sub.to_vec()))
+    }
+}
+
+/// Rust implementation of Move's `native fun internal_index_of(v: &vector<u8>, r: &vector<u8>): u64`
+pub struct NativeIndexOf;
+
+impl TypedNativeFunction for NativeIndexOf {
+    type Args = (VectorRef, VectorRef);
+    type Return = u64;
+
+    fn execute(
+        context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (s_arg, r_arg): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<u64>> {
+        let s_ref = s_arg.as_bytes_ref()?;
+        let r_ref = r_arg.as_bytes_ref()?;
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::STRING_INDEX_OF)
+            .cost(context.cost_table(), s_ref.len() + r_ref.len());
+
+        let (s_str, r_str) = (as_str(s_ref.as_slice())?, as_str(r_ref.as_slice())?);
+        let pos = s_str.find(r_str).unwrap_or(s_str.len());
+        Ok(TypedNativeResult::ok(cost, pos as u64))
+    }
+}
+
+/// Mirrors `str::is_char_boundary` on bytes which are known to be valid UTF-8: `i` is a boundary
+/// if it is the start or end of `bytes`, or if the byte at `i` is not a continuation byte.
+fn is_char_boundary(bytes: &[u8], i: usize) -> bool {
+    match bytes.get(i) {
+        Some(b) => (*b as i8) >= -0x40,
+        None => i == bytes.len(),
+    }
+}
+
+/// Views the bytes of a Move string as a `str`. Strings are checked to be valid UTF-8 when they
+/// are created, so a failure here is an invariant breach. Searching in a `str` takes time linear
+/// in the length of both strings.
+fn as_str(bytes: &[u8]) -> PartialVMResult<&str> {
+    std::str::from_utf8(bytes).map_err(|e| {
+        PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+            .with_message(format!("string is not valid UTF-8: {}", e))
+    })
+}
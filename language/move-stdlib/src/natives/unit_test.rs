@@ -3,12 +3,11 @@
 
 use move_binary_format::errors::PartialVMResult;
 use move_core_types::gas_schedule::ONE_GAS_UNIT;
-use move_vm_runtime::native_functions::NativeContext;
+use move_vm_runtime::native_functions::{NativeContext, TypedNativeFunction, TypedNativeResult};
 use move_vm_types::{
-    loaded_data::runtime_types::Type, natives::function::NativeResult, pop_arg, values::Value,
+    loaded_data::runtime_types::Type,
+    values::{Value, Vector},
 };
-use smallvec::smallvec;
-use std::collections::VecDeque;
 
 use move_core_types::account_address::AccountAddress;
 
@@ -19,18 +18,24 @@ fn to_le_bytes(i: u64) -> [u8; AccountAddress::LENGTH] {
     result
 }
 
-pub fn native_create_signers_for_testing(
-    _context: &mut NativeContext,
-    ty_args: Vec<Type>,
-    mut args: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(ty_args.is_empty());
-    debug_assert!(args.len() == 1);
+/// Rust implementation of Move's
+/// `native public fun create_signers_for_testing(num_signers: u64): vector<signer>`
+pub struct NativeCreateSignersForTesting;
 
-    let num_signers = pop_arg!(args, u64);
-    let signers = Value::vector_for_testing_only(
-        (0..num_signers).map(|i| Value::signer(AccountAddress::new(to_le_bytes(i)))),
-    );
+impl TypedNativeFunction for NativeCreateSignersForTesting {
+    type Args = (u64,);
+    type Return = Vector;
 
-    Ok(NativeResult::ok(ONE_GAS_UNIT, smallvec![signers]))
+    fn execute(
+        _context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (num_signers,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Vector>> {
+        let signers = Value::vector_for_testing_only(
+            (0..num_signers).map(|i| Value::signer(AccountAddress::new(to_le_bytes(i)))),
+        )
+        .value_as::<Vector>()?;
+
+        Ok(TypedNativeResult::ok(ONE_GAS_UNIT, signers))
+    }
 }
@@ -3,124 +3,176 @@
 
 use move_binary_format::errors::{PartialVMError, PartialVMResult};
 use move_core_types::{gas_schedule::GasAlgebra, vm_status::StatusCode};
-use move_vm_runtime::native_functions::NativeContext;
+use move_vm_runtime::native_functions::{NativeContext, TypedNativeFunction, TypedNativeResult};
 use move_vm_types::{
     gas_schedule::NativeCostIndex,
     loaded_data::runtime_types::Type,
-    natives::function::{native_gas, NativeResult},
-    pop_arg,
-    values::{Value, Vector, VectorRef},
+    natives::function::NativeGasFormula,
+    values::{Reference, Value, Vector, VectorRef},
 };
 
-use std::collections::VecDeque;
-
-pub fn native_empty(
-    context: &mut NativeContext,
-    ty_args: Vec<Type>,
-    args: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(ty_args.len() == 1);
-    debug_assert!(args.is_empty());
-
-    let cost = native_gas(context.cost_table(), NativeCostIndex::EMPTY, 1);
-    NativeResult::map_partial_vm_result_one(cost, Vector::empty(&ty_args[0]))
+/// Rust implementation of Move's `native public fun empty<Element>(): vector<Element>`
+pub struct NativeEmpty;
+
+impl TypedNativeFunction for NativeEmpty {
+    const TYPE_PARAMETERS: usize = 1;
+
+    type Args = ();
+    type Return = Vector;
+
+    fn execute(
+        context: &mut NativeContext,
+        ty_args: Vec<Type>,
+        (): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Vector>> {
+        let cost = NativeGasFormula::Constant(NativeCostIndex::EMPTY).cost(context.cost_table(), 1);
+        TypedNativeResult::map_partial_vm_result(
+            cost,
+            Vector::empty(&ty_args[0]).and_then(Value::value_as::<Vector>),
+        )
+    }
 }
 
-pub fn native_length(
-    context: &mut NativeContext,
-    ty_args: Vec<Type>,
-    mut args: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(ty_args.len() == 1);
-    debug_assert!(args.len() == 1);
-
-    let r = pop_arg!(args, VectorRef);
-    let cost = native_gas(context.cost_table(), NativeCostIndex::LENGTH, 1);
-    NativeResult::map_partial_vm_result_one(cost, r.len(&ty_args[0]))
+/// Rust implementation of Move's `native public fun length<Element>(v: &vector<Element>): u64`
+pub struct NativeLength;
+
+impl TypedNativeFunction for NativeLength {
+    const TYPE_PARAMETERS: usize = 1;
+
+    type Args = (VectorRef,);
+    type Return = u64;
+
+    fn execute(
+        context: &mut NativeContext,
+        ty_args: Vec<Type>,
+        (r,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<u64>> {
+        let cost =
+            NativeGasFormula::Constant(NativeCostIndex::LENGTH).cost(context.cost_table(), 1);
+        TypedNativeResult::map_partial_vm_result(
+            cost,
+            r.len(&ty_args[0]).and_then(Value::value_as::<u64>),
+        )
+    }
 }
 
-pub fn native_push_back(
-    context: &mut NativeContext,
-    ty_args: Vec<Type>,
-    mut args: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(ty_args.len() == 1);
-    debug_assert!(args.len() == 2);
-
-    let e = args.pop_back().unwrap();
-    let r = pop_arg!(args, VectorRef);
-    let cost = native_gas(
-        context.cost_table(),
-        NativeCostIndex::PUSH_BACK,
-        e.size().get() as usize,
-    );
-    NativeResult::map_partial_vm_result_empty(cost, r.push_back(e, &ty_args[0]))
+/// Rust implementation of Move's
+/// `native public fun push_back<Element>(v: &mut vector<Element>, e: Element)`
+pub struct NativePushBack;
+
+impl TypedNativeFunction for NativePushBack {
+    const TYPE_PARAMETERS: usize = 1;
+
+    type Args = (VectorRef, Value);
+    type Return = ();
+
+    fn execute(
+        context: &mut NativeContext,
+        ty_args: Vec<Type>,
+        (r, e): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<()>> {
+        let cost = NativeGasFormula::PerUnit(NativeCostIndex::PUSH_BACK)
+            .cost(context.cost_table(), e.size().get() as usize);
+        TypedNativeResult::map_partial_vm_result(cost, r.push_back(e, &ty_args[0]))
+    }
 }
 
-pub fn native_borrow(
-    context: &mut NativeContext,
-    ty_args: Vec<Type>,
-    mut args: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(ty_args.len() == 1);
-    debug_assert!(args.len() == 2);
-
-    let idx = pop_arg!(args, u64) as usize;
-    let r = pop_arg!(args, VectorRef);
-    let cost = native_gas(context.cost_table(), NativeCostIndex::BORROW, 1);
-    NativeResult::map_partial_vm_result_one(
-        cost,
-        r.borrow_elem(idx, &ty_args[0])
-            .map_err(native_error_to_abort),
-    )
+/// Rust implementation of Move's
+/// `native public fun borrow<Element>(v: &vector<Element>, i: u64): &Element` and of its
+/// `borrow_mut` counterpart.
+pub struct NativeBorrow;
+
+impl TypedNativeFunction for NativeBorrow {
+    const TYPE_PARAMETERS: usize = 1;
+
+    type Args = (VectorRef, u64);
+    type Return = Reference;
+
+    fn execute(
+        context: &mut NativeContext,
+        ty_args: Vec<Type>,
+        (r, idx): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Reference>> {
+        let cost =
+            NativeGasFormula::Constant(NativeCostIndex::BORROW).cost(context.cost_table(), 1);
+        TypedNativeResult::map_partial_vm_result(
+            cost,
+            r.borrow_elem(idx as usize, &ty_args[0])
+                .map_err(native_error_to_abort)
+                .and_then(Value::value_as::<Reference>),
+        )
+    }
 }
 
-pub fn native_pop(
-    context: &mut NativeContext,
-    ty_args: Vec<Type>,
-    mut args: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(ty_args.len() == 1);
-    debug_assert!(args.len() == 1);
-
-    let r = pop_arg!(args, VectorRef);
-    let cost = native_gas(context.cost_table(), NativeCostIndex::POP_BACK, 1);
-    NativeResult::map_partial_vm_result_one(cost, r.pop(&ty_args[0]).map_err(native_error_to_abort))
+/// Rust implementation of Move's
+/// `native public fun pop_back<Element>(v: &mut vector<Element>): Element`
+pub struct NativePop;
+
+impl TypedNativeFunction for NativePop {
+    const TYPE_PARAMETERS: usize = 1;
+
+    type Args = (VectorRef,);
+    type Return = Value;
+
+    fn execute(
+        context: &mut NativeContext,
+        ty_args: Vec<Type>,
+        (r,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Value>> {
+        let cost =
+            NativeGasFormula::Constant(NativeCostIndex::POP_BACK).cost(context.cost_table(), 1);
+        TypedNativeResult::map_partial_vm_result(
+            cost,
+            r.pop(&ty_args[0]).map_err(native_error_to_abort),
+        )
+    }
 }
 
-pub fn native_destroy_empty(
-    context: &mut NativeContext,
-    ty_args: Vec<Type>,
-    mut args: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(ty_args.len() == 1);
-    debug_assert!(args.len() == 1);
-
-    let v = pop_arg!(args, Vector);
-    let cost = native_gas(context.cost_table(), NativeCostIndex::DESTROY_EMPTY, 1);
-    NativeResult::map_partial_vm_result_empty(
-        cost,
-        v.destroy_empty(&ty_args[0]).map_err(native_error_to_abort),
-    )
+/// Rust implementation of Move's `native public fun destroy_empty<Element>(v: vector<Element>)`
+pub struct NativeDestroyEmpty;
+
+impl TypedNativeFunction for NativeDestroyEmpty {
+    const TYPE_PARAMETERS: usize = 1;
+
+    type Args = (Vector,);
+    type Return = ();
+
+    fn execute(
+        context: &mut NativeContext,
+        ty_args: Vec<Type>,
+        (v,): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<()>> {
+        let cost = NativeGasFormula::Constant(NativeCostIndex::DESTROY_EMPTY)
+            .cost(context.cost_table(), 1);
+        TypedNativeResult::map_partial_vm_result(
+            cost,
+            v.destroy_empty(&ty_args[0]).map_err(native_error_to_abort),
+        )
+    }
 }
 
-pub fn native_swap(
-    context: &mut NativeContext,
-    ty_args: Vec<Type>,
-    mut args: VecDeque<Value>,
-) -> PartialVMResult<NativeResult> {
-    debug_assert!(ty_args.len() == 1);
-    debug_assert!(args.len() == 3);
-
-    let idx2 = pop_arg!(args, u64) as usize;
-    let idx1 = pop_arg!(args, u64) as usize;
-    let r = pop_arg!(args, VectorRef);
-    let cost = native_gas(context.cost_table(), NativeCostIndex::SWAP, 1);
-    NativeResult::map_partial_vm_result_empty(
-        cost,
-        r.swap(idx1, idx2, &ty_args[0])
-            .map_err(native_error_to_abort),
-    )
+/// Rust implementation of Move's
+/// `native public fun swap<Element>(v: &mut vector<Element>, i: u64, j: u64)`
+pub struct NativeSwap;
+
+impl TypedNativeFunction for NativeSwap {
+    const TYPE_PARAMETERS: usize = 1;
+
+    type Args = (VectorRef, u64, u64);
+    type Return = ();
+
+    fn execute(
+        context: &mut NativeContext,
+        ty_args: Vec<Type>,
+        (r, idx1, idx2): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<()>> {
+        let cost = NativeGasFormula::Constant(NativeCostIndex::SWAP).cost(context.cost_table(), 1);
+        TypedNativeResult::map_partial_vm_result(
+            cost,
+            r.swap(idx1 as usize, idx2 as usize, &ty_args[0])
+                .map_err(native_error_to_abort),
+        )
+    }
 }
 
 fn native_error_to_abort(err: PartialVMError) -> PartialVMError {
@@ -78,6 +78,18 @@ module Std::EventTests {
         event_129(&s);
     }
 
+    #[test]
+    fun test_emit_128() {
+        Event::emit(box127(true))
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 0)]
+    fun test_emit_129() {
+        // will abort
+        Event::emit(Box { x: box127(true) })
+    }
+
     // More detailed version of the above--test BCS compatibility between the old event
     // format and the new wrapper hack.
     // this test lives here because it is important for the correctness of GUIDWrapper;
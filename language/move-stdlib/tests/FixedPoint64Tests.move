@@ -0,0 +1,113 @@
+#[test_only]
+module Std::FixedPoint64Tests {
+    use Std::FixedPoint64;
+
+    #[test]
+    #[expected_failure(abort_code = 7)]
+    fun create_div_zero() {
+        // A denominator of zero should be rejected.
+        FixedPoint64::create_from_rational(2, 0);
+    }
+
+    #[test]
+    fun create_zero() {
+        let x = FixedPoint64::create_from_rational(0, 1);
+        assert!(FixedPoint64::is_zero(x), 0);
+    }
+
+    #[test]
+    fun create_extremes() {
+        // The smallest and largest ratios of two u64 values are representable.
+        let f = FixedPoint64::create_from_rational(1, 18446744073709551615);
+        assert!(FixedPoint64::get_raw_value(f) == 1, 0); // 2^-64, truncated
+        let f = FixedPoint64::create_from_rational(18446744073709551615, 1);
+        assert!(FixedPoint64::get_raw_value(f) == 340282366920938463444927863358058659840, 1);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 775)]
+    fun divide_by_zero() {
+        // Dividing by zero should be rejected.
+        let f = FixedPoint64::create_from_raw_value(0);
+        FixedPoint64::divide_u64(1, f);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 264)]
+    fun divide_overflow_small_divisor() {
+        let f = FixedPoint64::create_from_raw_value(1); // 2^-64
+        // Divide 1 by the minimum fractional value. This should overflow.
+        FixedPoint64::divide_u64(1, f);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 264)]
+    fun divide_overflow_large_numerator() {
+        let f = FixedPoint64::create_from_rational(1, 2); // 0.5
+        // Divide the maximum u64 value by 0.5. This should overflow.
+        FixedPoint64::divide_u64(18446744073709551615, f);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 520)]
+    fun multiply_overflow_small_multiplier() {
+        let f = FixedPoint64::create_from_rational(3, 2); // 1.5
+        // Multiply the maximum u64 value by 1.5. This should overflow.
+        FixedPoint64::multiply_u64(18446744073709551615, f);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 520)]
+    fun multiply_overflow_large_multiplier() {
+        let f = FixedPoint64::create_from_raw_value(340282366920938463463374607431768211455);
+        // Multiply 2 by the maximum fixed-point value. This should overflow.
+        FixedPoint64::multiply_u64(2, f);
+    }
+
+    #[test]
+    fun multiply_max_values() {
+        // Multiply the maximum u64 value by the largest fixed-point value below 1.0.
+        let f = FixedPoint64::create_from_raw_value(18446744073709551615);
+        let product = FixedPoint64::multiply_u64(18446744073709551615, f);
+        assert!(product == 18446744073709551614, 0);
+    }
+
+    #[test]
+    fun exact_multiply() {
+        let f = FixedPoint64::create_from_rational(3, 4); // 0.75
+        let nine = FixedPoint64::multiply_u64(12, f); // 12 * 0.75
+        assert!(nine == 9, 0);
+        let f = FixedPoint64::create_from_rational(5, 2); // 2.5
+        let thirty = FixedPoint64::multiply_u64(12, f); // 12 * 2.5
+        assert!(thirty == 30, 1);
+    }
+
+    #[test]
+    fun exact_divide() {
+        let f = FixedPoint64::create_from_rational(3, 4); // 0.75
+        let twelve = FixedPoint64::divide_u64(9, f); // 9 / 0.75
+        assert!(twelve == 12, 0);
+    }
+
+    #[test]
+    fun multiply_truncates() {
+        let f = FixedPoint64::create_from_rational(1, 3); // 0.333...
+        let not_three = FixedPoint64::multiply_u64(9, copy f); // 9 * 0.333...
+        // multiply_u64 does NOT round -- it truncates -- so values that
+        // are not perfectly representable in binary may be off by one.
+        assert!(not_three == 2, 0);
+
+        // Try again with a fraction slightly larger than 1/3.
+        let f = FixedPoint64::create_from_raw_value(FixedPoint64::get_raw_value(f) + 1);
+        let three = FixedPoint64::multiply_u64(9, f);
+        assert!(three == 3, 1);
+    }
+
+    #[test]
+    fun create_from_rational_max_numerator_denominator() {
+        // Test creating a 1.0 fraction from the maximum u64 value.
+        let f = FixedPoint64::create_from_rational(18446744073709551615, 18446744073709551615);
+        let one = FixedPoint64::get_raw_value(f);
+        assert!(one == 18446744073709551616, 0); // 0x1.0000000000000000
+    }
+}
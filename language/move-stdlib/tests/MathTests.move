@@ -0,0 +1,110 @@
+#[test_only]
+module Std::MathTests {
+    use Std::Math;
+    use Std::Option;
+
+    const MAX_U64: u64 = 18446744073709551615;
+
+    #[test]
+    fun test_min_max() {
+        assert!(Math::max(1, 2) == 2, 0);
+        assert!(Math::max(2, 1) == 2, 1);
+        assert!(Math::min(1, 2) == 1, 2);
+        assert!(Math::min(2, 1) == 1, 3);
+        assert!(Math::max(3, 3) == 3, 4);
+    }
+
+    #[test]
+    fun test_average() {
+        assert!(Math::average(1, 2) == 1, 0);
+        assert!(Math::average(2, 1) == 1, 1);
+        assert!(Math::average(3, 7) == 5, 2);
+        assert!(Math::average(MAX_U64, MAX_U64) == MAX_U64, 3);
+        assert!(Math::average(MAX_U64, MAX_U64 - 2) == MAX_U64 - 1, 4);
+    }
+
+    #[test]
+    fun test_mul_div() {
+        assert!(Math::mul_div(6, 4, 3) == 8, 0);
+        assert!(Math::mul_div(7, 1, 2) == 3, 1);
+        // The intermediate product does not fit into a u64.
+        assert!(Math::mul_div(MAX_U64, MAX_U64, MAX_U64) == MAX_U64, 2);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 7)]
+    fun test_mul_div_by_zero() {
+        Math::mul_div(1, 1, 0);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 264)]
+    fun test_mul_div_overflow() {
+        Math::mul_div(MAX_U64, 2, 1);
+    }
+
+    #[test]
+    fun test_pow() {
+        assert!(Math::pow(0, 0) == 1, 0);
+        assert!(Math::pow(0, 5) == 0, 1);
+        assert!(Math::pow(3, 1) == 3, 2);
+        assert!(Math::pow(3, 4) == 81, 3);
+        assert!(Math::pow(10, 19) == 10000000000000000000, 4);
+        assert!(Math::pow(2, 63) == 9223372036854775808, 5);
+    }
+
+    #[test]
+    #[expected_failure]
+    fun test_pow_overflow() {
+        Math::pow(2, 64);
+    }
+
+    #[test]
+    fun test_sqrt() {
+        assert!(Math::sqrt(0) == 0, 0);
+        assert!(Math::sqrt(1) == 1, 1);
+        assert!(Math::sqrt(3) == 1, 2);
+        assert!(Math::sqrt(4) == 2, 3);
+        assert!(Math::sqrt(99) == 9, 4);
+        assert!(Math::sqrt(100) == 10, 5);
+        assert!(Math::sqrt(MAX_U64) == 4294967295, 6);
+    }
+
+    #[test]
+    fun test_log2() {
+        assert!(Math::log2(1) == 0, 0);
+        assert!(Math::log2(2) == 1, 1);
+        assert!(Math::log2(3) == 1, 2);
+        assert!(Math::log2(1024) == 10, 3);
+        assert!(Math::log2(1025) == 10, 4);
+        assert!(Math::log2(MAX_U64) == 63, 5);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 519)]
+    fun test_log2_of_zero() {
+        Math::log2(0);
+    }
+
+    #[test]
+    fun test_checked() {
+        assert!(Math::checked_add(1, 2) == Option::some(3), 0);
+        assert!(Option::is_none(&Math::checked_add(MAX_U64, 1)), 1);
+        assert!(Math::checked_sub(3, 2) == Option::some(1), 2);
+        assert!(Option::is_none(&Math::checked_sub(2, 3)), 3);
+        assert!(Math::checked_mul(3, 2) == Option::some(6), 4);
+        assert!(Option::is_none(&Math::checked_mul(MAX_U64, 2)), 5);
+        assert!(Math::checked_div(7, 2) == Option::some(3), 6);
+        assert!(Option::is_none(&Math::checked_div(7, 0)), 7);
+    }
+
+    #[test]
+    fun test_saturating() {
+        assert!(Math::saturating_add(1, 2) == 3, 0);
+        assert!(Math::saturating_add(MAX_U64, 1) == MAX_U64, 1);
+        assert!(Math::saturating_sub(3, 2) == 1, 2);
+        assert!(Math::saturating_sub(2, 3) == 0, 3);
+        assert!(Math::saturating_mul(3, 2) == 6, 4);
+        assert!(Math::saturating_mul(MAX_U64, 2) == MAX_U64, 5);
+    }
+}
@@ -0,0 +1,53 @@
+#[test_only]
+module Std::SignatureTests {
+    use Std::Hash;
+    use Std::Option;
+    use Std::Signature;
+
+    // Test vector 2 of RFC 8032.
+    const ED25519_PUBLIC_KEY: vector<u8> = x"3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+    const ED25519_MESSAGE: vector<u8> = x"72";
+    const ED25519_SIGNATURE: vector<u8> = x"92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00";
+
+    // A signature of `sha2_256(b"abc")`, with recovery id 1.
+    const SECP256K1_PUBLIC_KEY: vector<u8> = x"4bc2a31265153f07e70e0bab08724e6b85e217f8cd628ceb62974247bb493382ce28cab79ad7119ee1ad3ebcdb98a16805211530ecc6cfefa1b88e6dff99232a";
+    const SECP256K1_SIGNATURE: vector<u8> = x"43f8c41498980a79e07b2f93aca5fb513b29ab1ad1dd328044e16d8eb883ba4268354c0ac2dff34948fc1fe1ca019bb7e93508f454c7d85803f4b1eafc6b1188";
+    // The same signature with the high `s`, `n - s`.
+    const SECP256K1_MALLEATED_SIGNATURE: vector<u8> = x"43f8c41498980a79e07b2f93aca5fb513b29ab1ad1dd328044e16d8eb883ba4297cab3f53d200cb6b703e01e35fe6446d179d3f25a80c7e3bbddaca1d3cb2fb9";
+
+    #[test]
+    fun ed25519_validate_pubkey() {
+        assert!(Signature::ed25519_validate_pubkey(ED25519_PUBLIC_KEY), 0);
+        // The identity point has small order.
+        assert!(!Signature::ed25519_validate_pubkey(x"0100000000000000000000000000000000000000000000000000000000000000"), 1);
+        assert!(!Signature::ed25519_validate_pubkey(x"3d4017c3"), 2);
+    }
+
+    #[test]
+    fun ed25519_verify() {
+        assert!(Signature::ed25519_verify(ED25519_SIGNATURE, ED25519_PUBLIC_KEY, ED25519_MESSAGE), 0);
+        assert!(!Signature::ed25519_verify(ED25519_SIGNATURE, ED25519_PUBLIC_KEY, x"73"), 1);
+        assert!(!Signature::ed25519_verify(x"92a009a9", ED25519_PUBLIC_KEY, ED25519_MESSAGE), 2);
+    }
+
+    #[test]
+    fun secp256k1_verify() {
+        let hash = Hash::sha2_256(b"abc");
+        assert!(Signature::secp256k1_verify(SECP256K1_SIGNATURE, SECP256K1_PUBLIC_KEY, hash), 0);
+        assert!(!Signature::secp256k1_verify(SECP256K1_SIGNATURE, SECP256K1_PUBLIC_KEY, Hash::sha2_256(b"abd")), 1);
+        assert!(!Signature::secp256k1_verify(SECP256K1_MALLEATED_SIGNATURE, SECP256K1_PUBLIC_KEY, hash), 2);
+        assert!(!Signature::secp256k1_verify(SECP256K1_SIGNATURE, SECP256K1_PUBLIC_KEY, b"abc"), 3);
+    }
+
+    #[test]
+    fun secp256k1_recover() {
+        let hash = Hash::sha2_256(b"abc");
+        let key = Signature::secp256k1_recover(hash, 1, SECP256K1_SIGNATURE);
+        assert!(Option::some(SECP256K1_PUBLIC_KEY) == key, 0);
+        // The other recovery id yields a different key.
+        let key = Signature::secp256k1_recover(hash, 0, SECP256K1_SIGNATURE);
+        assert!(Option::is_some(&key) && Option::some(SECP256K1_PUBLIC_KEY) != key, 1);
+        assert!(Option::is_none(&Signature::secp256k1_recover(hash, 4, SECP256K1_SIGNATURE)), 2);
+        assert!(Option::is_none(&Signature::secp256k1_recover(hash, 1, SECP256K1_MALLEATED_SIGNATURE)), 3);
+    }
+}
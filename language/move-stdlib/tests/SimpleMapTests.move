@@ -0,0 +1,98 @@
+#[test_only]
+module Std::SimpleMapTests {
+    use Std::SimpleMap;
+    use Std::Vector;
+
+    #[test]
+    fun add_remove_many() {
+        let map = SimpleMap::empty<u64, u64>();
+
+        assert!(SimpleMap::length(&map) == 0, 0);
+        assert!(!SimpleMap::contains_key(&map, &3), 1);
+        SimpleMap::add(&mut map, 3, 1);
+        assert!(SimpleMap::length(&map) == 1, 2);
+        assert!(SimpleMap::contains_key(&map, &3), 3);
+        assert!(SimpleMap::borrow(&map, &3) == &1, 4);
+        *SimpleMap::borrow_mut(&mut map, &3) = 2;
+        assert!(SimpleMap::borrow(&map, &3) == &2, 5);
+
+        assert!(!SimpleMap::contains_key(&map, &2), 6);
+        SimpleMap::add(&mut map, 2, 5);
+        assert!(SimpleMap::length(&map) == 2, 7);
+        assert!(SimpleMap::contains_key(&map, &2), 8);
+        assert!(SimpleMap::borrow(&map, &2) == &5, 9);
+
+        let (k, v) = SimpleMap::remove(&mut map, &2);
+        assert!(k == 2 && v == 5, 10);
+        assert!(SimpleMap::length(&map) == 1, 11);
+        assert!(!SimpleMap::contains_key(&map, &2), 12);
+        assert!(SimpleMap::contains_key(&map, &3), 13);
+
+        SimpleMap::remove(&mut map, &3);
+        assert!(SimpleMap::is_empty(&map), 14);
+        SimpleMap::destroy_empty(map);
+    }
+
+    #[test]
+    fun iterate() {
+        let map = SimpleMap::empty<u64, u64>();
+        SimpleMap::add(&mut map, 1, 10);
+        SimpleMap::add(&mut map, 2, 20);
+        SimpleMap::add(&mut map, 3, 30);
+
+        let sum = 0;
+        let i = 0;
+        while (i < SimpleMap::length(&map)) {
+            let (k, v) = SimpleMap::borrow_at_mut(&mut map, i);
+            *v = *v + *k;
+            sum = sum + *v;
+            i = i + 1;
+        };
+        assert!(sum == 66, 0);
+
+        let keys = SimpleMap::keys(&map);
+        let values = SimpleMap::values(&map);
+        assert!(Vector::length(&keys) == 3 && Vector::length(&values) == 3, 1);
+        let i = 0;
+        while (i < 3) {
+            let (k, v) = SimpleMap::borrow_at(&map, i);
+            assert!(Vector::borrow(&keys, i) == k, 2);
+            assert!(Vector::borrow(&values, i) == v, 3);
+            assert!(*v == *k * 11, 4);
+            i = i + 1;
+        };
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 7)]
+    fun add_twice() {
+        let map = SimpleMap::empty<u64, u64>();
+        SimpleMap::add(&mut map, 3, 1);
+        SimpleMap::add(&mut map, 3, 1);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 263)]
+    fun borrow_missing() {
+        let map = SimpleMap::empty<u64, u64>();
+        SimpleMap::add(&mut map, 3, 1);
+        SimpleMap::borrow(&map, &2);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 263)]
+    fun remove_twice() {
+        let map = SimpleMap::empty<u64, u64>();
+        SimpleMap::add(&mut map, 3, 1);
+        SimpleMap::remove(&mut map, &3);
+        SimpleMap::remove(&mut map, &3);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 519)]
+    fun borrow_at_out_of_bounds() {
+        let map = SimpleMap::empty<u64, u64>();
+        SimpleMap::add(&mut map, 3, 1);
+        SimpleMap::borrow_at(&map, 1);
+    }
+}
@@ -0,0 +1,97 @@
+#[test_only]
+module Std::StringTests {
+    use Std::String;
+    use Std::Option;
+
+    #[test]
+    fun test_valid_utf8() {
+        let sparkle_heart = vector[240, 159, 146, 150];
+        let s = String::utf8(sparkle_heart);
+        assert!(String::length(&s) == 4, 22);
+        assert!(*String::bytes(&s) == sparkle_heart, 23);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 7)]
+    fun test_invalid_utf8() {
+        let no_sparkle_heart = vector[0, 159, 146, 150];
+        let s = String::utf8(no_sparkle_heart);
+        assert!(String::length(&s) == 4, 22);
+    }
+
+    #[test]
+    fun test_try_utf8() {
+        assert!(Option::is_some(&String::try_utf8(b"abc")), 22);
+        assert!(Option::is_none(&String::try_utf8(vector[0, 159, 146, 150])), 23);
+        assert!(Option::is_none(&String::try_utf8(vector[192])), 24);
+    }
+
+    #[test]
+    fun test_sub_string() {
+        let s = String::utf8(b"abcd");
+        let sub = String::sub_string(&s, 2, 4);
+        assert!(sub == String::utf8(b"cd"), 22);
+        assert!(String::is_empty(&String::sub_string(&s, 4, 4)), 23);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 263)]
+    fun test_sub_string_invalid_boundary() {
+        let sparkle_heart = vector[240, 159, 146, 150];
+        let s = String::utf8(sparkle_heart);
+        let _sub = String::sub_string(&s, 1, 4);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 263)]
+    fun test_sub_string_invalid_index() {
+        let s = String::utf8(b"abcd");
+        let _sub = String::sub_string(&s, 4, 5);
+    }
+
+    #[test]
+    fun test_index_of() {
+        let s = String::utf8(b"abcd");
+        let r = String::utf8(b"bc");
+        let p = String::utf8(b"x");
+        assert!(String::index_of(&s, &r) == 1, 22);
+        assert!(String::index_of(&s, &p) == 4, 23);
+        assert!(String::index_of(&s, &String::utf8(b"")) == 0, 24);
+    }
+
+    #[test]
+    fun test_index_of_multi_byte() {
+        let s = String::utf8(vector[240, 159, 146, 150, 97]);
+        assert!(String::index_of(&s, &String::utf8(b"a")) == 4, 22);
+    }
+
+    #[test]
+    fun test_append() {
+        let s = String::utf8(b"abcd");
+        String::append(&mut s, String::utf8(b"ef"));
+        assert!(s == String::utf8(b"abcdef"), 22)
+    }
+
+    #[test]
+    fun test_append_utf8() {
+        let s = String::utf8(b"abcd");
+        String::append_utf8(&mut s, b"ef");
+        assert!(s == String::utf8(b"abcdef"), 22)
+    }
+
+    #[test]
+    fun test_insert() {
+        let s = String::utf8(b"abcd");
+        String::insert(&mut s, 1, String::utf8(b"xy"));
+        assert!(s == String::utf8(b"axybcd"), 22);
+        String::insert(&mut s, 6, String::utf8(b"z"));
+        assert!(s == String::utf8(b"axybcdz"), 23)
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 263)]
+    fun test_insert_invalid_boundary() {
+        let s = String::utf8(vector[240, 159, 146, 150]);
+        String::insert(&mut s, 2, String::utf8(b"xy"));
+    }
+}
@@ -0,0 +1,49 @@
+#[test_only]
+module Std::VecSetTests {
+    use Std::VecSet;
+
+    #[test]
+    fun insert_remove_many() {
+        let set = VecSet::empty<u8>();
+        assert!(VecSet::is_empty(&set), 0);
+        VecSet::insert(&mut set, 3);
+        VecSet::insert(&mut set, 1);
+        VecSet::insert(&mut set, 2);
+        assert!(VecSet::length(&set) == 3, 1);
+        assert!(VecSet::contains(&set, &1), 2);
+        assert!(!VecSet::contains(&set, &4), 3);
+
+        // Removal keeps the insertion order of the remaining elements.
+        assert!(VecSet::remove(&mut set, &3) == 3, 4);
+        assert!(VecSet::elements(&set) == &vector[1, 2], 5);
+        assert!(!VecSet::contains(&set, &3), 6);
+        assert!(VecSet::into_elements(set) == vector[1, 2], 7);
+    }
+
+    #[test]
+    fun iterate() {
+        let set = VecSet::singleton(5);
+        VecSet::insert(&mut set, 7);
+        let sum = 0;
+        let i = 0;
+        while (i < VecSet::length(&set)) {
+            sum = sum + *VecSet::borrow(&set, i);
+            i = i + 1;
+        };
+        assert!(sum == 12, 0);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 7)]
+    fun insert_twice() {
+        let set = VecSet::singleton(5);
+        VecSet::insert(&mut set, 5);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 263)]
+    fun remove_missing() {
+        let set = VecSet::singleton(5);
+        VecSet::remove(&mut set, &4);
+    }
+}
@@ -9,7 +9,7 @@ use move_core_types::{
     value::{serialize_values, MoveValue},
     vm_status::StatusType,
 };
-use move_vm_runtime::move_vm::MoveVM;
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
 use move_vm_test_utils::{BlankStorage, InMemoryStorage};
 use move_vm_types::gas_schedule::GasStatus;
 
@@ -17,7 +17,7 @@ const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGT
 
 #[test]
 fn call_non_existent_module() {
-    let vm = MoveVM::new(vec![]).unwrap();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
     let storage = BlankStorage;
 
     let mut sess = vm.new_session(&storage);
@@ -54,7 +54,7 @@ fn call_non_existent_function() {
     let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
     storage.publish_or_overwrite_module(module_id.clone(), blob);
 
-    let vm = MoveVM::new(vec![]).unwrap();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
     let mut sess = vm.new_session(&storage);
 
     let fun_name = Identifier::new("foo").unwrap();
@@ -12,7 +12,7 @@ use move_core_types::{
     value::{serialize_values, MoveValue},
     vm_status::{StatusCode, StatusType},
 };
-use move_vm_runtime::move_vm::MoveVM;
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
 use move_vm_test_utils::{DeltaStorage, InMemoryStorage};
 use move_vm_types::gas_schedule::GasStatus;
 
@@ -83,9 +83,10 @@ fn test_malformed_resource() {
     m.serialize(&mut blob).unwrap();
     storage.publish_or_overwrite_module(m.self_id(), blob);
 
-    let vm = MoveVM::new(move_stdlib::natives::all_natives(
-        AccountAddress::from_hex_literal("0x1").unwrap(),
-    ))
+    let vm = MoveVM::new(
+        move_stdlib::natives::all_natives(AccountAddress::from_hex_literal("0x1").unwrap()),
+        VMConfig::default(),
+    )
     .unwrap();
 
     let mut gas_status = GasStatus::new_unmetered();
@@ -102,7 +103,7 @@ fn test_malformed_resource() {
     )
     .map(|_| ())
     .unwrap();
-    let (changeset, _) = sess.finish().unwrap();
+    let (changeset, _, _) = sess.finish().unwrap();
     storage.apply(changeset).unwrap();
 
     // Execut the second script and make sure it succeeds. This script simply checks
@@ -177,7 +178,7 @@ fn test_malformed_module() {
     {
         let mut storage = InMemoryStorage::new();
         storage.publish_or_overwrite_module(m.self_id(), blob.clone());
-        let vm = MoveVM::new(vec![]).unwrap();
+        let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
         let mut sess = vm.new_session(&storage);
         sess.execute_function_bypass_visibility(
             &module_id,
@@ -202,7 +203,7 @@ fn test_malformed_module() {
         blob[3] = 0xef;
         let mut storage = InMemoryStorage::new();
         storage.publish_or_overwrite_module(m.self_id(), blob);
-        let vm = MoveVM::new(vec![]).unwrap();
+        let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
         let mut sess = vm.new_session(&storage);
         let err = sess
             .execute_function_bypass_visibility(
@@ -242,7 +243,7 @@ fn test_unverifiable_module() {
         m.serialize(&mut blob).unwrap();
         storage.publish_or_overwrite_module(m.self_id(), blob);
 
-        let vm = MoveVM::new(vec![]).unwrap();
+        let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
         let mut sess = vm.new_session(&storage);
 
         sess.execute_function_bypass_visibility(
@@ -266,7 +267,7 @@ fn test_unverifiable_module() {
         m.serialize(&mut blob).unwrap();
         storage.publish_or_overwrite_module(m.self_id(), blob);
 
-        let vm = MoveVM::new(vec![]).unwrap();
+        let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
         let mut sess = vm.new_session(&storage);
 
         let err = sess
@@ -319,7 +320,7 @@ fn test_missing_module_dependency() {
         storage.publish_or_overwrite_module(m.self_id(), blob_m);
         storage.publish_or_overwrite_module(n.self_id(), blob_n.clone());
 
-        let vm = MoveVM::new(vec![]).unwrap();
+        let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
         let mut sess = vm.new_session(&storage);
 
         sess.execute_function_bypass_visibility(
@@ -338,7 +339,7 @@ fn test_missing_module_dependency() {
         let mut storage = InMemoryStorage::new();
         storage.publish_or_overwrite_module(n.self_id(), blob_n);
 
-        let vm = MoveVM::new(vec![]).unwrap();
+        let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
         let mut sess = vm.new_session(&storage);
 
         let err = sess
@@ -391,7 +392,7 @@ fn test_malformed_module_denpency() {
         storage.publish_or_overwrite_module(m.self_id(), blob_m.clone());
         storage.publish_or_overwrite_module(n.self_id(), blob_n.clone());
 
-        let vm = MoveVM::new(vec![]).unwrap();
+        let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
         let mut sess = vm.new_session(&storage);
 
         sess.execute_function_bypass_visibility(
@@ -416,7 +417,7 @@ fn test_malformed_module_denpency() {
         storage.publish_or_overwrite_module(m.self_id(), blob_m);
         storage.publish_or_overwrite_module(n.self_id(), blob_n);
 
-        let vm = MoveVM::new(vec![]).unwrap();
+        let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
         let mut sess = vm.new_session(&storage);
 
         let err = sess
@@ -470,7 +471,7 @@ fn test_unverifiable_module_dependency() {
         storage.publish_or_overwrite_module(m.self_id(), blob_m);
         storage.publish_or_overwrite_module(n.self_id(), blob_n.clone());
 
-        let vm = MoveVM::new(vec![]).unwrap();
+        let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
         let mut sess = vm.new_session(&storage);
 
         sess.execute_function_bypass_visibility(
@@ -495,7 +496,7 @@ fn test_unverifiable_module_dependency() {
         storage.publish_or_overwrite_module(m.self_id(), blob_m);
         storage.publish_or_overwrite_module(n.self_id(), blob_n);
 
-        let vm = MoveVM::new(vec![]).unwrap();
+        let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
         let mut sess = vm.new_session(&storage);
 
         let err = sess
@@ -556,7 +557,7 @@ fn test_storage_returns_bogus_error_when_loading_module() {
         let storage = BogusStorage {
             bad_status_code: *error_code,
         };
-        let vm = MoveVM::new(vec![]).unwrap();
+        let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
         let mut sess = vm.new_session(&storage);
 
         let err = sess
@@ -623,9 +624,10 @@ fn test_storage_returns_bogus_error_when_loading_resource() {
         };
         let storage = DeltaStorage::new(&storage, &delta);
 
-        let vm = MoveVM::new(move_stdlib::natives::all_natives(
-            AccountAddress::from_hex_literal("0x1").unwrap(),
-        ))
+        let vm = MoveVM::new(
+            move_stdlib::natives::all_natives(AccountAddress::from_hex_literal("0x1").unwrap()),
+            VMConfig::default(),
+        )
         .unwrap();
         let mut sess = vm.new_session(&storage);
 
@@ -7,14 +7,14 @@ use crate::compiler::{as_module, compile_units};
 use move_binary_format::errors::VMResult;
 use move_core_types::{
     account_address::AccountAddress,
-    effects::{ChangeSet, Event},
+    effects::{ChangeSet, ContractEvent},
     gas_schedule::GasAlgebra,
     identifier::Identifier,
     language_storage::ModuleId,
     value::{serialize_values, MoveValue},
     vm_status::StatusCode,
 };
-use move_vm_runtime::{move_vm::MoveVM, session::SerializedReturnValues};
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM, session::SerializedReturnValues};
 use move_vm_test_utils::InMemoryStorage;
 use move_vm_types::gas_schedule::GasStatus;
 
@@ -85,7 +85,7 @@ fn run(
     arg_val0: MoveValue,
 ) -> (
     /* gas used */ u64,
-    VMResult<(ChangeSet, Vec<Event>, SerializedReturnValues)>,
+    VMResult<(ChangeSet, Vec<ContractEvent>, SerializedReturnValues)>,
 ) {
     let module_id = &module.0;
     let modules = vec![module.clone()];
@@ -104,7 +104,7 @@ fn run(
             &mut gas_status,
         )
         .and_then(|ret_values| {
-            let (change_set, events) = session.finish()?;
+            let (change_set, events, _) = session.finish()?;
             Ok((change_set, events, ret_values))
         });
     let gas_used = gas_start - gas_status.remaining_gas().get();
@@ -117,7 +117,7 @@ type ModuleCode = (ModuleId, String);
 fn setup_vm(modules: &[ModuleCode]) -> (MoveVM, InMemoryStorage) {
     let mut storage = InMemoryStorage::new();
     compile_modules(&mut storage, modules);
-    (MoveVM::new(vec![]).unwrap(), storage)
+    (MoveVM::new(vec![], VMConfig::default()).unwrap(), storage)
 }
 
 fn compile_modules(mut storage: &mut InMemoryStorage, modules: &[ModuleCode]) {
@@ -8,7 +8,7 @@ use move_core_types::{
     identifier::{IdentStr, Identifier},
     language_storage::ModuleId,
 };
-use move_vm_runtime::move_vm::MoveVM;
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
 use move_vm_test_utils::InMemoryStorage;
 use move_vm_types::gas_schedule::GasStatus;
 use std::{path::PathBuf, sync::Arc, thread};
@@ -48,7 +48,7 @@ impl Adapter {
         ];
         Self {
             store,
-            vm: Arc::new(MoveVM::new(vec![]).unwrap()),
+            vm: Arc::new(MoveVM::new(vec![], VMConfig::default()).unwrap()),
             functions,
         }
     }
@@ -65,7 +65,7 @@ impl Adapter {
                 .publish_module(binary, WORKING_ACCOUNT, &mut gas_status)
                 .unwrap_or_else(|_| panic!("failure publishing module: {:#?}", module));
         }
-        let (changeset, _) = session.finish().expect("failure getting write set");
+        let (changeset, _, _) = session.finish().expect("failure getting write set");
         self.store
             .apply(changeset)
             .expect("failure applying write set");
@@ -7,4 +7,10 @@ mod exec_func_effects_tests;
 mod function_arg_tests;
 mod loader_tests;
 mod mutated_accounts_tests;
+mod native_function_tests;
 mod return_value_tests;
+mod savepoint_tests;
+mod storage_usage_tests;
+#[cfg(feature = "table-extension")]
+mod table_change_set_tests;
+mod vm_config_tests;
@@ -8,7 +8,7 @@ use move_core_types::{
     language_storage::ModuleId,
     value::{serialize_values, MoveValue},
 };
-use move_vm_runtime::move_vm::MoveVM;
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
 use move_vm_test_utils::InMemoryStorage;
 use move_vm_types::gas_schedule::GasStatus;
 
@@ -42,7 +42,7 @@ fn mutated_accounts() {
     let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
     storage.publish_or_overwrite_module(module_id.clone(), blob);
 
-    let vm = MoveVM::new(vec![]).unwrap();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
     let mut sess = vm.new_session(&storage);
 
     let mut gas_status = GasStatus::new_unmetered();
@@ -88,7 +88,7 @@ fn mutated_accounts() {
     .unwrap();
     assert_eq!(sess.num_mutated_accounts(&TEST_ADDR), 2);
 
-    let (changes, _) = sess.finish().unwrap();
+    let (changes, _, _) = sess.finish().unwrap();
     storage.apply(changes).unwrap();
 
     let mut sess = vm.new_session(&storage);
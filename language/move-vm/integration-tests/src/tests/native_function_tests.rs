@@ -0,0 +1,317 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_binary_format::errors::{PartialVMResult, VMResult};
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_schedule::{GasAlgebra, InternalGasUnits},
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::{serialize_values, MoveValue},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::{
+    config::VMConfig,
+    move_vm::MoveVM,
+    native_functions::{
+        MissingNativesAllowList, NativeContext, NativeFunctionTable, NativeFunctionTableBuilder,
+        TypedNativeFunction, TypedNativeResult,
+    },
+};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::{gas_schedule::GasStatus, loaded_data::runtime_types::Type};
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+const EOVERFLOW: u64 = 7;
+
+struct NativeAdd;
+
+impl TypedNativeFunction for NativeAdd {
+    type Args = (u64, u64);
+    type Return = u64;
+
+    fn execute(
+        _context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (a, b): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<u64>> {
+        let cost = InternalGasUnits::new(1);
+        Ok(match a.checked_add(b) {
+            Some(sum) => TypedNativeResult::ok(cost, sum),
+            None => TypedNativeResult::err(cost, EOVERFLOW),
+        })
+    }
+}
+
+struct NativeSplit;
+
+impl TypedNativeFunction for NativeSplit {
+    type Args = (Vec<u8>, AccountAddress);
+    type Return = (u64, bool);
+
+    fn execute(
+        _context: &mut NativeContext,
+        _ty_args: Vec<Type>,
+        (bytes, addr): Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<(u64, bool)>> {
+        let cost = InternalGasUnits::new(bytes.len() as u64);
+        Ok(TypedNativeResult::ok(
+            cost,
+            (bytes.len() as u64, addr == AccountAddress::ZERO),
+        ))
+    }
+}
+
+fn natives() -> NativeFunctionTable {
+    NativeFunctionTableBuilder::new(TEST_ADDR)
+        .add_typed::<NativeAdd>("M", "add")
+        .add_typed::<NativeSplit>("M", "split")
+        .build()
+}
+
+fn compile(natives_decl: &str) -> Vec<u8> {
+    let code = format!(
+        r#"
+        module 0x{}::M {{
+            {}
+        }}
+    "#,
+        TEST_ADDR, natives_decl
+    );
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    m.serialize(&mut blob).unwrap();
+    blob
+}
+
+fn publish(natives_decl: &str) -> VMResult<()> {
+    let storage = InMemoryStorage::new();
+    let vm = MoveVM::new(natives(), VMConfig::default()).unwrap();
+    let mut sess = vm.new_session(&storage);
+    sess.publish_module(
+        compile(natives_decl),
+        TEST_ADDR,
+        &mut GasStatus::new_unmetered(),
+    )
+}
+
+fn call(name: &str, args: Vec<MoveValue>) -> VMResult<Vec<Vec<u8>>> {
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
+    storage.publish_or_overwrite_module(
+        module_id.clone(),
+        compile(
+            r#"
+            native fun add(a: u64, b: u64): u64;
+            native fun split(bytes: vector<u8>, addr: address): (u64, bool);
+
+            public fun call_add(a: u64, b: u64): u64 {
+                add(a, b)
+            }
+            public fun call_split(bytes: vector<u8>, addr: address): (u64, bool) {
+                split(bytes, addr)
+            }
+            "#,
+        ),
+    );
+
+    let vm = MoveVM::new(natives(), VMConfig::default()).unwrap();
+    let mut sess = vm.new_session(&storage);
+    let return_values = sess.execute_function_bypass_visibility(
+        &module_id,
+        &Identifier::new(name).unwrap(),
+        vec![],
+        serialize_values(&args),
+        &mut GasStatus::new_unmetered(),
+    )?;
+    Ok(return_values
+        .return_values
+        .into_iter()
+        .map(|(bytes, _layout)| bytes)
+        .collect())
+}
+
+#[test]
+fn typed_natives_marshal_arguments() {
+    let return_values = call("call_add", vec![MoveValue::U64(40), MoveValue::U64(2)]).unwrap();
+    assert_eq!(
+        return_values,
+        vec![MoveValue::U64(42).simple_serialize().unwrap()]
+    );
+
+    let return_values = call(
+        "call_split",
+        vec![
+            MoveValue::vector_u8(vec![1, 2, 3]),
+            MoveValue::Address(AccountAddress::ZERO),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        return_values,
+        vec![
+            MoveValue::U64(3).simple_serialize().unwrap(),
+            MoveValue::Bool(true).simple_serialize().unwrap(),
+        ]
+    );
+}
+
+#[test]
+fn typed_natives_abort() {
+    let err = call(
+        "call_add",
+        vec![MoveValue::U64(u64::MAX), MoveValue::U64(1)],
+    )
+    .unwrap_err();
+    assert_eq!(err.major_status(), StatusCode::ABORTED);
+    assert_eq!(err.sub_status(), Some(EOVERFLOW));
+}
+
+#[test]
+fn matching_declarations_are_accepted() {
+    publish(
+        r#"
+        native fun add(a: u64, b: u64): u64;
+        native fun split(bytes: vector<u8>, addr: address): (u64, bool);
+        "#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn mismatched_declarations_are_rejected() {
+    for decl in [
+        // wrong arity
+        "native fun add(a: u64): u64;",
+        // wrong parameter type
+        "native fun add(a: u64, b: u128): u64;",
+        // wrong return type
+        "native fun add(a: u64, b: u64): bool;",
+        // unexpected type parameter
+        "native fun add<T>(a: u64, b: u64): u64;",
+        // wrong number of return values
+        "native fun split(bytes: vector<u8>, addr: address): u64;",
+        // wrong vector element type
+        "native fun split(bytes: vector<u64>, addr: address): (u64, bool);",
+    ] {
+        let err = publish(decl).unwrap_err();
+        assert_eq!(
+            err.major_status(),
+            StatusCode::NATIVE_FUNCTION_SIGNATURE_MISMATCH,
+            "{}",
+            decl
+        );
+    }
+}
+
+const MISSING_NATIVE_MODULE: &str = r#"
+    native fun add(a: u64, b: u64): u64;
+    native fun missing(): u64;
+
+    public fun call_add(): u64 {
+        add(40, 2)
+    }
+    public fun call_missing(): u64 {
+        missing()
+    }
+"#;
+
+fn vm_with_missing_natives_allowed(missing_natives_allowed: MissingNativesAllowList) -> MoveVM {
+    let vm_config = VMConfig {
+        missing_natives_allowed,
+        ..VMConfig::default()
+    };
+    MoveVM::new(natives(), vm_config).unwrap()
+}
+
+fn publish_with_missing_native(missing_natives_allowed: MissingNativesAllowList) -> VMResult<()> {
+    let storage = InMemoryStorage::new();
+    let vm = vm_with_missing_natives_allowed(missing_natives_allowed);
+    let mut sess = vm.new_session(&storage);
+    sess.publish_module(
+        compile(MISSING_NATIVE_MODULE),
+        TEST_ADDR,
+        &mut GasStatus::new_unmetered(),
+    )
+}
+
+fn call_with_missing_native(
+    missing_natives_allowed: MissingNativesAllowList,
+    name: &str,
+) -> VMResult<Vec<Vec<u8>>> {
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
+    storage.publish_or_overwrite_module(module_id.clone(), compile(MISSING_NATIVE_MODULE));
+
+    let vm = vm_with_missing_natives_allowed(missing_natives_allowed);
+    let mut sess = vm.new_session(&storage);
+    let return_values = sess.execute_function_bypass_visibility(
+        &module_id,
+        &Identifier::new(name).unwrap(),
+        vec![],
+        Vec::<Vec<u8>>::new(),
+        &mut GasStatus::new_unmetered(),
+    )?;
+    Ok(return_values
+        .return_values
+        .into_iter()
+        .map(|(bytes, _layout)| bytes)
+        .collect())
+}
+
+fn assert_missing_native_rejected(missing_natives_allowed: MissingNativesAllowList) {
+    let err = publish_with_missing_native(missing_natives_allowed.clone()).unwrap_err();
+    assert_eq!(err.major_status(), StatusCode::MISSING_NATIVE_FUNCTION);
+    // Modules in storage are expected to verify, so the failure is reported as an unexpected
+    // verifier error when loading them
+    let err = call_with_missing_native(missing_natives_allowed, "call_add").unwrap_err();
+    assert_eq!(err.major_status(), StatusCode::UNEXPECTED_VERIFIER_ERROR);
+}
+
+#[test]
+fn missing_natives_are_rejected_by_default() {
+    assert_missing_native_rejected(MissingNativesAllowList::default());
+}
+
+#[test]
+fn allowlisted_missing_natives_are_loaded() {
+    let module_name = Identifier::new("M").unwrap();
+    let mut by_function = MissingNativesAllowList::default();
+    by_function.allow_function(
+        TEST_ADDR,
+        module_name.clone(),
+        Identifier::new("missing").unwrap(),
+    );
+    let mut by_module = MissingNativesAllowList::default();
+    by_module.allow_module(TEST_ADDR, module_name);
+
+    for allowed in [by_function, by_module, MissingNativesAllowList::all()] {
+        publish_with_missing_native(allowed.clone()).unwrap();
+        // The rest of the module is usable
+        let return_values = call_with_missing_native(allowed.clone(), "call_add").unwrap();
+        assert_eq!(
+            return_values,
+            vec![MoveValue::U64(42).simple_serialize().unwrap()]
+        );
+        // but calling the missing native fails
+        let err = call_with_missing_native(allowed, "call_missing").unwrap_err();
+        assert_eq!(err.major_status(), StatusCode::MISSING_NATIVE_FUNCTION);
+    }
+}
+
+#[test]
+fn allowlist_only_covers_the_listed_natives() {
+    let mut other_function = MissingNativesAllowList::default();
+    other_function.allow_function(
+        TEST_ADDR,
+        Identifier::new("M").unwrap(),
+        Identifier::new("other").unwrap(),
+    );
+    assert_missing_native_rejected(other_function);
+
+    let mut other_module = MissingNativesAllowList::default();
+    other_module.allow_module(TEST_ADDR, Identifier::new("N").unwrap());
+    assert_missing_native_rejected(other_module);
+}
@@ -0,0 +1,281 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::{ChangeSet, ContractEvent},
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag},
+    resolver::MoveResolver,
+    value::{serialize_values, MoveTypeLayout, MoveValue},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM, session::Session};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::{gas_schedule::GasStatus, loaded_data::runtime_types::Type, values::Value};
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+fn compile(code: &str) -> Vec<u8> {
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    m.serialize(&mut blob).unwrap();
+    blob
+}
+
+fn setup_storage() -> (InMemoryStorage, ModuleId) {
+    let blob = compile(
+        r#"
+        module {{ADDR}}::M {
+            struct R has key { a: u64 }
+
+            public fun publish(s: signer) {
+                move_to(&s, R { a: 0 })
+            }
+            public fun increment(addr: address) acquires R {
+                let r = borrow_global_mut<R>(addr);
+                r.a = r.a + 1;
+            }
+        }
+    "#,
+    );
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
+    storage.publish_or_overwrite_module(module_id.clone(), blob);
+    (storage, module_id)
+}
+
+fn call<S: MoveResolver>(sess: &mut Session<S>, module_id: &ModuleId, name: &str, arg: MoveValue) {
+    sess.execute_function_bypass_visibility(
+        module_id,
+        &Identifier::new(name).unwrap(),
+        vec![],
+        serialize_values(&vec![arg]),
+        &mut GasStatus::new_unmetered(),
+    )
+    .unwrap();
+}
+
+fn increment<S: MoveResolver>(sess: &mut Session<S>, module_id: &ModuleId, addr: AccountAddress) {
+    call(sess, module_id, "increment", MoveValue::Address(addr))
+}
+
+fn emit(sess: &mut Session<InMemoryStorage>, seq_num: u64) {
+    sess.get_data_store()
+        .emit_event(vec![0], seq_num, Type::U64, Value::u64(seq_num))
+        .unwrap();
+}
+
+fn resource_value(changes: &ChangeSet, module_id: &ModuleId, addr: AccountAddress) -> Option<u64> {
+    let tag = StructTag {
+        address: *module_id.address(),
+        module: module_id.name().to_owned(),
+        name: Identifier::new("R").unwrap(),
+        type_params: vec![],
+    };
+    let blob = changes
+        .accounts()
+        .get(&addr)?
+        .resources()
+        .get(&tag)?
+        .as_ref()?;
+    match MoveValue::simple_deserialize(blob, &MoveTypeLayout::U64).unwrap() {
+        MoveValue::U64(a) => Some(a),
+        _ => unreachable!(),
+    }
+}
+
+fn emit_module_event(sess: &mut Session<InMemoryStorage>, payload: u64) {
+    sess.get_data_store()
+        .emit_module_event(Type::U64, Value::u64(payload))
+        .unwrap();
+}
+
+// Describes each event by the handle sequence number it was emitted with, if any, and its payload
+fn describe_events(events: &[ContractEvent]) -> Vec<(Option<u64>, u64)> {
+    events
+        .iter()
+        .map(|event| {
+            let seq_num = match event {
+                ContractEvent::Handle { seq_num, .. } => Some(*seq_num),
+                ContractEvent::Module { .. } => None,
+            };
+            match MoveValue::simple_deserialize(event.data(), &MoveTypeLayout::U64).unwrap() {
+                MoveValue::U64(payload) => (seq_num, payload),
+                _ => unreachable!(),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn rollback_discards_resource_changes() {
+    let (storage, module_id) = setup_storage();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
+    let account = AccountAddress::random();
+    let mut sess = vm.new_session(&storage);
+
+    call(&mut sess, &module_id, "publish", MoveValue::Signer(account));
+    let sp = sess.savepoint().unwrap();
+    increment(&mut sess, &module_id, account);
+    increment(&mut sess, &module_id, account);
+    sess.rollback_to(sp).unwrap();
+
+    // The savepoint stays valid after a rollback.
+    increment(&mut sess, &module_id, account);
+    sess.rollback_to(sp).unwrap();
+    increment(&mut sess, &module_id, account);
+
+    let (changes, _, _) = sess.finish().unwrap();
+    assert_eq!(resource_value(&changes, &module_id, account), Some(1));
+}
+
+#[test]
+fn rollback_discards_resource_creation() {
+    let (storage, module_id) = setup_storage();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
+    let account = AccountAddress::random();
+    let mut sess = vm.new_session(&storage);
+
+    let sp = sess.savepoint().unwrap();
+    call(&mut sess, &module_id, "publish", MoveValue::Signer(account));
+    sess.rollback_to(sp).unwrap();
+
+    let (changes, _, usage) = sess.finish().unwrap();
+    assert_eq!(resource_value(&changes, &module_id, account), None);
+    assert_eq!(usage.num_written_resources(), 0);
+}
+
+#[test]
+fn release_keeps_changes() {
+    let (storage, module_id) = setup_storage();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
+    let account = AccountAddress::random();
+    let mut sess = vm.new_session(&storage);
+
+    call(&mut sess, &module_id, "publish", MoveValue::Signer(account));
+    let sp = sess.savepoint().unwrap();
+    increment(&mut sess, &module_id, account);
+    sess.release(sp).unwrap();
+
+    // A released savepoint can no longer be used.
+    assert!(sess.rollback_to(sp).is_err());
+    assert!(sess.release(sp).is_err());
+
+    let (changes, _, _) = sess.finish().unwrap();
+    assert_eq!(resource_value(&changes, &module_id, account), Some(1));
+}
+
+#[test]
+fn nested_savepoints() {
+    let (storage, module_id) = setup_storage();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
+    let account = AccountAddress::random();
+    let mut sess = vm.new_session(&storage);
+
+    call(&mut sess, &module_id, "publish", MoveValue::Signer(account));
+    let outer = sess.savepoint().unwrap();
+    increment(&mut sess, &module_id, account);
+    let inner = sess.savepoint().unwrap();
+    increment(&mut sess, &module_id, account);
+
+    // Rolling back the inner savepoint keeps the changes made before it.
+    sess.rollback_to(inner).unwrap();
+    sess.release(inner).unwrap();
+    let inner = sess.savepoint().unwrap();
+    increment(&mut sess, &module_id, account);
+
+    // Rolling back the outer savepoint discards the inner one.
+    sess.rollback_to(outer).unwrap();
+    assert!(sess.rollback_to(inner).is_err());
+    increment(&mut sess, &module_id, account);
+    sess.release(outer).unwrap();
+
+    let (changes, _, _) = sess.finish().unwrap();
+    assert_eq!(resource_value(&changes, &module_id, account), Some(1));
+}
+
+#[test]
+fn rollback_discards_events() {
+    let storage = InMemoryStorage::new();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
+    let mut sess = vm.new_session(&storage);
+
+    emit(&mut sess, 0);
+    emit_module_event(&mut sess, 10);
+    let sp = sess.savepoint().unwrap();
+    emit(&mut sess, 1);
+    emit_module_event(&mut sess, 11);
+    emit(&mut sess, 2);
+    sess.rollback_to(sp).unwrap();
+    emit_module_event(&mut sess, 12);
+    emit(&mut sess, 3);
+
+    let (_, events, _) = sess.finish().unwrap();
+    assert_eq!(
+        describe_events(&events),
+        vec![(Some(0), 0), (None, 10), (None, 12), (Some(3), 3)]
+    );
+}
+
+#[test]
+fn rollback_discards_published_modules() {
+    let storage = InMemoryStorage::new();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
+    let mut sess = vm.new_session(&storage);
+
+    let sp = sess.savepoint().unwrap();
+    sess.publish_module(
+        compile("module {{ADDR}}::N {}"),
+        TEST_ADDR,
+        &mut GasStatus::new_unmetered(),
+    )
+    .unwrap();
+    sess.rollback_to(sp).unwrap();
+
+    let (changes, _, usage) = sess.finish().unwrap();
+    assert!(changes.accounts().get(&TEST_ADDR).is_none());
+    assert_eq!(usage.module_bytes, 0);
+}
+
+#[test]
+fn rollback_evicts_loaded_modules() {
+    let storage = InMemoryStorage::new();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("N").unwrap());
+    let f = Identifier::new("f").unwrap();
+    let call_f = |sess: &mut Session<InMemoryStorage>| {
+        sess.execute_function_bypass_visibility(
+            &module_id,
+            &f,
+            vec![],
+            serialize_values(&vec![MoveValue::U64(1)]),
+            &mut GasStatus::new_unmetered(),
+        )
+    };
+    let mut sess = vm.new_session(&storage);
+
+    let sp = sess.savepoint().unwrap();
+    sess.publish_module(
+        compile("module {{ADDR}}::N { public fun f(_x: u64) {} }"),
+        TEST_ADDR,
+        &mut GasStatus::new_unmetered(),
+    )
+    .unwrap();
+    // Calling the module loads it into the cache of the VM.
+    call_f(&mut sess).unwrap();
+    sess.rollback_to(sp).unwrap();
+
+    // Neither this session nor a later one finds the module after the rollback.
+    let err = call_f(&mut sess).unwrap_err();
+    assert_eq!(err.major_status(), StatusCode::LINKER_ERROR);
+    let (changes, _, _) = sess.finish().unwrap();
+    assert!(changes.accounts().get(&TEST_ADDR).is_none());
+
+    let mut sess = vm.new_session(&storage);
+    let err = call_f(&mut sess).unwrap_err();
+    assert_eq!(err.major_status(), StatusCode::LINKER_ERROR);
+}
@@ -0,0 +1,305 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_binary_format::errors::VMResult;
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::{ChangeSet, ContractEvent},
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag},
+    value::{serialize_values, MoveValue},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::{
+    config::VMConfig,
+    data_cache::{ResourceChange, ResourceStorageUsage, StorageUsage, WriteSetLimits},
+    move_vm::MoveVM,
+};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas_schedule::GasStatus;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+fn setup_storage() -> (InMemoryStorage, ModuleId) {
+    let code = r#"
+        module {{ADDR}}::M {
+            struct Small has key { a: u64 }
+            struct Large has key { a: u128, b: u128 }
+
+            public fun publish(s: signer) {
+                move_to(&s, Small { a: 0 })
+            }
+            public fun publish_both(s: signer) {
+                move_to(&s, Small { a: 0 });
+                move_to(&s, Large { a: 0, b: 0 })
+            }
+            public fun read(addr: address): u64 acquires Small {
+                borrow_global<Small>(addr).a
+            }
+            public fun grow(s: signer, addr: address) acquires Small {
+                let Small { a } = move_from<Small>(addr);
+                move_to(&s, Large { a: (a as u128), b: 0 })
+            }
+            public fun increment(addr: address) acquires Small {
+                let r = borrow_global_mut<Small>(addr);
+                r.a = r.a + 1;
+            }
+            public fun remove(addr: address) acquires Small {
+                let Small { a: _ } = move_from<Small>(addr);
+            }
+        }
+    "#;
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    m.serialize(&mut blob).unwrap();
+
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
+    storage.publish_or_overwrite_module(module_id.clone(), blob);
+    (storage, module_id)
+}
+
+fn run(
+    vm: &MoveVM,
+    storage: &InMemoryStorage,
+    module_id: &ModuleId,
+    name: &str,
+    args: Vec<MoveValue>,
+) -> VMResult<(ChangeSet, Vec<ContractEvent>, StorageUsage)> {
+    let mut sess = vm.new_session(storage);
+    sess.execute_function_bypass_visibility(
+        module_id,
+        &Identifier::new(name).unwrap(),
+        vec![],
+        serialize_values(&args),
+        &mut GasStatus::new_unmetered(),
+    )?;
+    sess.finish()
+}
+
+fn resource_key(
+    module_id: &ModuleId,
+    addr: AccountAddress,
+    name: &str,
+) -> (AccountAddress, StructTag) {
+    let tag = StructTag {
+        address: *module_id.address(),
+        module: module_id.name().to_owned(),
+        name: Identifier::new(name).unwrap(),
+        type_params: vec![],
+    };
+    (addr, tag)
+}
+
+#[test]
+fn storage_usage_lifecycle() {
+    let (mut storage, module_id) = setup_storage();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
+    let account = AccountAddress::random();
+    let small = resource_key(&module_id, account, "Small");
+    let large = resource_key(&module_id, account, "Large");
+
+    // creation
+    let (changes, _, usage) = run(
+        &vm,
+        &storage,
+        &module_id,
+        "publish",
+        vec![MoveValue::Signer(account)],
+    )
+    .unwrap();
+    assert_eq!(
+        usage.resources[&small],
+        ResourceStorageUsage {
+            change: ResourceChange::Created,
+            read_bytes: 0,
+            written_bytes: 8,
+        }
+    );
+    assert_eq!(usage.byte_delta(), 8);
+    assert_eq!(usage.num_written_resources(), 1);
+    storage.apply(changes).unwrap();
+
+    // read only
+    let (_, _, usage) = run(
+        &vm,
+        &storage,
+        &module_id,
+        "read",
+        vec![MoveValue::Address(account)],
+    )
+    .unwrap();
+    assert_eq!(usage.resources[&small].change, ResourceChange::None);
+    assert_eq!(usage.read_bytes(), 8);
+    assert_eq!(usage.written_bytes(), 0);
+    assert_eq!(usage.num_written_resources(), 0);
+
+    // modification
+    let (changes, _, usage) = run(
+        &vm,
+        &storage,
+        &module_id,
+        "increment",
+        vec![MoveValue::Address(account)],
+    )
+    .unwrap();
+    assert_eq!(
+        usage.resources[&small],
+        ResourceStorageUsage {
+            change: ResourceChange::Modified,
+            read_bytes: 8,
+            written_bytes: 8,
+        }
+    );
+    assert_eq!(usage.byte_delta(), 0);
+    storage.apply(changes).unwrap();
+
+    // replacing a resource with a larger one
+    let (changes, _, usage) = run(
+        &vm,
+        &storage,
+        &module_id,
+        "grow",
+        vec![MoveValue::Signer(account), MoveValue::Address(account)],
+    )
+    .unwrap();
+    assert_eq!(usage.resources[&small].change, ResourceChange::Deleted);
+    assert_eq!(usage.resources[&small].byte_delta(), -8);
+    assert_eq!(usage.resources[&large].change, ResourceChange::Created);
+    assert_eq!(usage.resources[&large].byte_delta(), 32);
+    assert_eq!(usage.byte_delta(), 24);
+    assert_eq!(usage.num_written_resources(), 2);
+    storage.apply(changes).unwrap();
+
+    // the module is not published by the session
+    assert_eq!(usage.module_bytes, 0);
+    assert_eq!(usage.write_set_size(), 32);
+}
+
+#[test]
+fn storage_usage_deletion() {
+    let (mut storage, module_id) = setup_storage();
+    let vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
+    let account = AccountAddress::random();
+    let small = resource_key(&module_id, account, "Small");
+
+    let (changes, _, _) = run(
+        &vm,
+        &storage,
+        &module_id,
+        "publish",
+        vec![MoveValue::Signer(account)],
+    )
+    .unwrap();
+    storage.apply(changes).unwrap();
+
+    let (_, _, usage) = run(
+        &vm,
+        &storage,
+        &module_id,
+        "remove",
+        vec![MoveValue::Address(account)],
+    )
+    .unwrap();
+    assert_eq!(
+        usage.resources[&small],
+        ResourceStorageUsage {
+            change: ResourceChange::Deleted,
+            read_bytes: 8,
+            written_bytes: 0,
+        }
+    );
+    assert_eq!(usage.byte_delta(), -8);
+    assert_eq!(usage.write_set_size(), 0);
+}
+
+#[test]
+fn write_set_size_limit() {
+    let (storage, module_id) = setup_storage();
+    let account = AccountAddress::random();
+
+    let vm = MoveVM::new(
+        vec![],
+        VMConfig {
+            write_set_limits: WriteSetLimits {
+                max_write_set_size: Some(40),
+                max_written_resources: None,
+            },
+            ..VMConfig::default()
+        },
+    )
+    .unwrap();
+    run(
+        &vm,
+        &storage,
+        &module_id,
+        "publish_both",
+        vec![MoveValue::Signer(account)],
+    )
+    .unwrap();
+
+    let vm = MoveVM::new(
+        vec![],
+        VMConfig {
+            write_set_limits: WriteSetLimits {
+                max_write_set_size: Some(39),
+                max_written_resources: None,
+            },
+            ..VMConfig::default()
+        },
+    )
+    .unwrap();
+    let err = run(
+        &vm,
+        &storage,
+        &module_id,
+        "publish_both",
+        vec![MoveValue::Signer(account)],
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::VM_MAX_WRITE_SET_SIZE_REACHED
+    );
+}
+
+#[test]
+fn written_resources_limit() {
+    let (storage, module_id) = setup_storage();
+    let account = AccountAddress::random();
+
+    let vm = MoveVM::new(
+        vec![],
+        VMConfig {
+            write_set_limits: WriteSetLimits {
+                max_write_set_size: None,
+                max_written_resources: Some(1),
+            },
+            ..VMConfig::default()
+        },
+    )
+    .unwrap();
+    run(
+        &vm,
+        &storage,
+        &module_id,
+        "publish",
+        vec![MoveValue::Signer(account)],
+    )
+    .unwrap();
+    let err = run(
+        &vm,
+        &storage,
+        &module_id,
+        "publish_both",
+        vec![MoveValue::Signer(account)],
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::VM_MAX_WRITTEN_RESOURCES_REACHED
+    );
+}
@@ -0,0 +1,330 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_compiler::{
+    compiled_unit::AnnotatedCompiledUnit,
+    shared::{Flags, NumericalAddress},
+    Compiler,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_schedule::{GasCarrier, InternalGasUnits},
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::{serialize_values, MoveValue},
+};
+use move_table_extension::{
+    table_natives, NativeTableContext, TableChange, TableChangeSet, TableHandle, TableOperation,
+    TableResolver,
+};
+use move_vm_runtime::{
+    config::VMConfig, move_vm::MoveVM, native_extensions::NativeContextExtensions,
+};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas_schedule::GasStatus;
+use std::{collections::BTreeSet, fs::File, io::Write};
+use tempfile::tempdir;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+const TABLE_MODULE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../extensions/move-table-extension/sources/Table.move"
+);
+
+fn setup_storage() -> (InMemoryStorage, ModuleId) {
+    let code = format!(
+        r#"
+        module 0x{}::M {{
+            use Extensions::Table::{{Self, Table}};
+
+            struct Bytes has key {{ t: Table<u64, vector<u8>> }}
+            struct Nested has key {{ t: Table<u64, Table<u64, u64>> }}
+
+            public fun publish_bytes(s: signer) {{
+                let t = Table::new();
+                Table::add(&mut t, &1, b"ab");
+                Table::add(&mut t, &2, b"abc");
+                move_to(&s, Bytes {{ t }})
+            }}
+            public fun update_bytes(addr: address) acquires Bytes {{
+                let t = &mut borrow_global_mut<Bytes>(addr).t;
+                *Table::borrow_mut(t, &1) = b"abcd";
+                Table::remove(t, &2);
+            }}
+            public fun drop_new() {{
+                let t = Table::new<u64, u64>();
+                Table::add(&mut t, &1, 1);
+                Table::drop_unchecked(t)
+            }}
+            public fun destroy_new() {{
+                let t = Table::new<u64, u64>();
+                Table::add(&mut t, &1, 1);
+                Table::remove(&mut t, &1);
+                Table::destroy_empty(t)
+            }}
+            fun new_nested(): Table<u64, Table<u64, u64>> {{
+                let inner = Table::new();
+                Table::add(&mut inner, &1, 1);
+                let t = Table::new();
+                Table::add(&mut t, &1, inner);
+                Table::add(&mut t, &2, Table::new());
+                t
+            }}
+            public fun drop_new_nested() {{
+                Table::drop_unchecked(new_nested())
+            }}
+            public fun publish_nested(s: signer) {{
+                move_to(&s, Nested {{ t: new_nested() }})
+            }}
+            public fun drop_nested(addr: address) acquires Nested {{
+                let Nested {{ t }} = move_from<Nested>(addr);
+                Table::drop_unchecked(t)
+            }}
+            public fun publish_wide(s: signer) {{
+                let t = Table::new();
+                let i = 0;
+                while (i < 100) {{
+                    Table::add(&mut t, &i, Table::new());
+                    i = i + 1;
+                }};
+                move_to(&s, Nested {{ t }})
+            }}
+        }}
+    "#,
+        TEST_ADDR
+    );
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("modules.move");
+    writeln!(File::create(&file_path).unwrap(), "{}", code).unwrap();
+    let mut targets = move_stdlib::move_stdlib_files();
+    targets.push(TABLE_MODULE.to_string());
+    targets.push(file_path.to_str().unwrap().to_string());
+    let mut named_addresses = move_stdlib::move_stdlib_named_addresses();
+    named_addresses.insert(
+        "Extensions".to_string(),
+        NumericalAddress::parse_str("0x2").unwrap(),
+    );
+    // `Table::drop_unchecked` is only available in test mode
+    let (_, units) = Compiler::from_files(targets, vec![], named_addresses)
+        .set_flags(Flags::testing())
+        .build_and_report()
+        .unwrap();
+
+    let mut storage = InMemoryStorage::new();
+    for unit in units {
+        if let AnnotatedCompiledUnit::Module(annot_module) = unit {
+            let module = annot_module.named_module.module;
+            let mut blob = vec![];
+            module.serialize(&mut blob).unwrap();
+            storage.publish_or_overwrite_module(module.self_id(), blob);
+        }
+    }
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
+    (storage, module_id)
+}
+
+// A resolver which keeps the default `iter_table_entries`, and so cannot iterate tables
+struct NonIterableResolver<'a>(&'a InMemoryStorage);
+
+impl<'a> TableResolver for NonIterableResolver<'a> {
+    fn resolve_table_entry(
+        &self,
+        handle: &TableHandle,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        self.0.resolve_table_entry(handle, key)
+    }
+
+    fn operation_cost(
+        &self,
+        op: TableOperation,
+        key_size: usize,
+        val_size: usize,
+    ) -> InternalGasUnits<GasCarrier> {
+        self.0.operation_cost(op, key_size, val_size)
+    }
+}
+
+// Runs a function in its own session, applies its effects and returns its table change set
+fn run(
+    storage: &mut InMemoryStorage,
+    module_id: &ModuleId,
+    name: &str,
+    args: Vec<MoveValue>,
+) -> TableChangeSet {
+    run_with_resolver(storage, module_id, name, args, true)
+}
+
+fn run_with_resolver(
+    storage: &mut InMemoryStorage,
+    module_id: &ModuleId,
+    name: &str,
+    args: Vec<MoveValue>,
+    iterable: bool,
+) -> TableChangeSet {
+    let mut natives =
+        move_stdlib::natives::all_natives(AccountAddress::from_hex_literal("0x1").unwrap());
+    natives.append(&mut table_natives(
+        AccountAddress::from_hex_literal("0x2").unwrap(),
+    ));
+    let vm = MoveVM::new(natives, VMConfig::default()).unwrap();
+    let (changes, table_changes) = {
+        let mut extensions = NativeContextExtensions::default();
+        let non_iterable = NonIterableResolver(&*storage);
+        let resolver: &dyn TableResolver = if iterable { &*storage } else { &non_iterable };
+        extensions.add(NativeTableContext::new(0, resolver));
+        let mut sess = vm.new_session_with_extensions(&*storage, extensions);
+        sess.execute_function_bypass_visibility(
+            module_id,
+            &Identifier::new(name).unwrap(),
+            vec![],
+            serialize_values(&args),
+            &mut GasStatus::new_unmetered(),
+        )
+        .unwrap();
+        let (changes, _, _, mut extensions) = sess.finish_with_extensions().unwrap();
+        let table_changes = extensions
+            .remove::<NativeTableContext>()
+            .into_change_set()
+            .unwrap();
+        (changes, table_changes)
+    };
+    // The change set is consumed when applied, so apply a copy of it
+    let applied = TableChangeSet {
+        new_tables: table_changes.new_tables.clone(),
+        removed_tables: table_changes.removed_tables.clone(),
+        dropped_tables: table_changes.dropped_tables.clone(),
+        changes: table_changes
+            .changes
+            .iter()
+            .map(|(handle, change)| {
+                let change = TableChange {
+                    entries: change.entries.clone(),
+                    bytes_delta: change.bytes_delta,
+                };
+                (*handle, change)
+            })
+            .collect(),
+    };
+    storage.apply_extended(changes, applied).unwrap();
+    table_changes
+}
+
+fn bytes_deltas(change_set: &TableChangeSet) -> Vec<i64> {
+    change_set
+        .changes
+        .values()
+        .map(|change| change.bytes_delta)
+        .collect()
+}
+
+#[test]
+fn bytes_delta_counts_keys_and_values() {
+    let (mut storage, module_id) = setup_storage();
+
+    // Each entry is a u64 key of 8 bytes and a vector of 2 and 3 bytes plus its length.
+    let cs = run(
+        &mut storage,
+        &module_id,
+        "publish_bytes",
+        vec![MoveValue::Signer(TEST_ADDR)],
+    );
+    assert_eq!(cs.new_tables.len(), 1);
+    assert_eq!(bytes_deltas(&cs), vec![11 + 12]);
+
+    // Growing the first value by 2 bytes and deleting the second entry frees storage.
+    let cs = run(
+        &mut storage,
+        &module_id,
+        "update_bytes",
+        vec![MoveValue::Address(TEST_ADDR)],
+    );
+    assert!(cs.new_tables.is_empty());
+    assert_eq!(bytes_deltas(&cs), vec![2 - 12]);
+}
+
+#[test]
+fn dropped_tables_are_reported() {
+    let (mut storage, module_id) = setup_storage();
+
+    let cs = run(&mut storage, &module_id, "drop_new", vec![]);
+    assert_eq!(cs.dropped_tables, cs.new_tables);
+    assert!(cs.removed_tables.is_empty());
+
+    // A destroyed table is empty and not reported as dropped.
+    let cs = run(&mut storage, &module_id, "destroy_new", vec![]);
+    assert_eq!(cs.removed_tables, cs.new_tables);
+    assert!(cs.dropped_tables.is_empty());
+}
+
+#[test]
+fn nested_tables_are_dropped_with_their_table() {
+    let (mut storage, module_id) = setup_storage();
+
+    // All three tables are created and dropped in the same transaction.
+    let cs = run(&mut storage, &module_id, "drop_new_nested", vec![]);
+    assert_eq!(cs.new_tables.len(), 3);
+    assert_eq!(cs.dropped_tables, cs.new_tables);
+
+    // Nested tables which are only in storage are found by iterating the dropped table.
+    let cs = run(
+        &mut storage,
+        &module_id,
+        "publish_nested",
+        vec![MoveValue::Signer(TEST_ADDR)],
+    );
+    let created: BTreeSet<TableHandle> = cs.new_tables;
+    assert_eq!(created.len(), 3);
+    let cs = run(
+        &mut storage,
+        &module_id,
+        "drop_nested",
+        vec![MoveValue::Address(TEST_ADDR)],
+    );
+    assert_eq!(cs.dropped_tables, created);
+}
+
+#[test]
+fn nested_tables_are_found_page_by_page() {
+    let (mut storage, module_id) = setup_storage();
+
+    // The outer table has more entries than are read at a time.
+    let cs = run(
+        &mut storage,
+        &module_id,
+        "publish_wide",
+        vec![MoveValue::Signer(TEST_ADDR)],
+    );
+    let created: BTreeSet<TableHandle> = cs.new_tables;
+    assert_eq!(created.len(), 101);
+    let cs = run(
+        &mut storage,
+        &module_id,
+        "drop_nested",
+        vec![MoveValue::Address(TEST_ADDR)],
+    );
+    assert_eq!(cs.dropped_tables, created);
+}
+
+#[test]
+fn only_outer_table_is_dropped_without_iteration() {
+    let (mut storage, module_id) = setup_storage();
+
+    let cs = run(
+        &mut storage,
+        &module_id,
+        "publish_nested",
+        vec![MoveValue::Signer(TEST_ADDR)],
+    );
+    let created: BTreeSet<TableHandle> = cs.new_tables;
+    // The nested tables are only in storage, and cannot be found without iterating.
+    let cs = run_with_resolver(
+        &mut storage,
+        &module_id,
+        "drop_nested",
+        vec![MoveValue::Address(TEST_ADDR)],
+        false,
+    );
+    assert_eq!(cs.dropped_tables.len(), 1);
+    assert!(cs.dropped_tables.is_subset(&created));
+}
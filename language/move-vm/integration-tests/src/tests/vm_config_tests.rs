@@ -0,0 +1,378 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_binary_format::errors::VMResult;
+use move_bytecode_verifier::VerifierConfig;
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    value::{serialize_values, MoveValue},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas_schedule::GasStatus;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+fn compile(code: &str) -> Vec<u8> {
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    m.serialize(&mut blob).unwrap();
+    blob
+}
+
+fn publish(vm_config: VMConfig, code: &str) -> VMResult<()> {
+    let storage = InMemoryStorage::new();
+    let vm = MoveVM::new(vec![], vm_config).unwrap();
+    let mut sess = vm.new_session(&storage);
+    sess.publish_module(compile(code), TEST_ADDR, &mut GasStatus::new_unmetered())
+}
+
+fn run(
+    vm_config: VMConfig,
+    code: &str,
+    name: &str,
+    ty_args: Vec<TypeTag>,
+    args: Vec<MoveValue>,
+) -> VMResult<()> {
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
+    storage.publish_or_overwrite_module(module_id.clone(), compile(code));
+
+    let vm = MoveVM::new(vec![], vm_config).unwrap();
+    let mut sess = vm.new_session(&storage);
+    sess.execute_function_bypass_visibility(
+        &module_id,
+        &Identifier::new(name).unwrap(),
+        ty_args,
+        serialize_values(&args),
+        &mut GasStatus::new_unmetered(),
+    )?;
+    Ok(())
+}
+
+fn expect_status(result: VMResult<()>, status: StatusCode) {
+    assert_eq!(result.unwrap_err().major_status(), status);
+}
+
+fn vector_of(ty: TypeTag, depth: usize) -> TypeTag {
+    (0..depth).fold(ty, |ty, _| TypeTag::Vector(Box::new(ty)))
+}
+
+const CODE: &str = r#"
+    module {{ADDR}}::M {
+        struct Pair<A, B> has drop { a: A, b: B }
+        struct Nested has key { v: vector<vector<vector<u64>>> }
+
+        public fun sum(a: u64): u64 {
+            a + (a + (a + (a + a)))
+        }
+        public fun recurse(n: u64) {
+            if (n > 0) recurse(n - 1)
+        }
+        public fun nested(s: signer) {
+            move_to(&s, Nested { v: vector[vector[vector[1]]] })
+        }
+        public fun copy_nested(a: u64): bool {
+            let v = vector[vector[vector[a]]];
+            let w = copy v;
+            v == w
+        }
+        public fun pack(a: u64): vector<u64> {
+            vector[a, a, a]
+        }
+        public fun generic<T>() {}
+        public fun call_nested_generic() {
+            generic<Pair<u64, Pair<u64, u64>>>()
+        }
+    }
+"#;
+
+fn run_code(vm_config: VMConfig, name: &str, ty_args: Vec<TypeTag>) -> VMResult<()> {
+    run(vm_config, CODE, name, ty_args, vec![])
+}
+
+fn run_code_with_arg(vm_config: VMConfig, name: &str, arg: MoveValue) -> VMResult<()> {
+    run(vm_config, CODE, name, vec![], vec![arg])
+}
+
+#[test]
+fn operand_stack_size() {
+    run_code_with_arg(VMConfig::default(), "sum", MoveValue::U64(1)).unwrap();
+    expect_status(
+        run_code_with_arg(
+            VMConfig {
+                max_operand_stack_size: 4,
+                ..VMConfig::default()
+            },
+            "sum",
+            MoveValue::U64(1),
+        ),
+        StatusCode::EXECUTION_STACK_OVERFLOW,
+    );
+}
+
+#[test]
+fn call_stack_size() {
+    let vm_config = VMConfig {
+        max_call_stack_size: 10,
+        ..VMConfig::default()
+    };
+    run_code_with_arg(vm_config.clone(), "recurse", MoveValue::U64(5)).unwrap();
+    expect_status(
+        run_code_with_arg(vm_config, "recurse", MoveValue::U64(20)),
+        StatusCode::CALL_STACK_OVERFLOW,
+    );
+}
+
+#[test]
+fn value_depth() {
+    run_code_with_arg(VMConfig::default(), "nested", MoveValue::Signer(TEST_ADDR)).unwrap();
+    expect_status(
+        run_code_with_arg(
+            VMConfig {
+                max_value_depth: 3,
+                ..VMConfig::default()
+            },
+            "nested",
+            MoveValue::Signer(TEST_ADDR),
+        ),
+        StatusCode::VM_MAX_VALUE_DEPTH_REACHED,
+    );
+}
+
+#[test]
+fn value_depth_of_copies() {
+    let vm_config = VMConfig {
+        max_value_depth: 4,
+        ..VMConfig::default()
+    };
+    run_code_with_arg(vm_config, "copy_nested", MoveValue::U64(1)).unwrap();
+    expect_status(
+        run_code_with_arg(
+            VMConfig {
+                max_value_depth: 3,
+                ..VMConfig::default()
+            },
+            "copy_nested",
+            MoveValue::U64(1),
+        ),
+        StatusCode::VM_MAX_VALUE_DEPTH_REACHED,
+    );
+}
+
+fn savepoint_after_nested(max_value_depth: usize) -> VMResult<()> {
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
+    storage.publish_or_overwrite_module(module_id.clone(), compile(CODE));
+
+    let vm = MoveVM::new(
+        vec![],
+        VMConfig {
+            max_value_depth,
+            ..VMConfig::default()
+        },
+    )
+    .unwrap();
+    let mut sess = vm.new_session(&storage);
+    sess.execute_function_bypass_visibility(
+        &module_id,
+        &Identifier::new("nested").unwrap(),
+        vec![],
+        serialize_values(&vec![MoveValue::Signer(TEST_ADDR)]),
+        &mut GasStatus::new_unmetered(),
+    )?;
+    // Savepoints copy the resources loaded so far
+    sess.savepoint()?;
+    Ok(())
+}
+
+#[test]
+fn value_depth_of_savepoints() {
+    savepoint_after_nested(5).unwrap();
+    expect_status(
+        savepoint_after_nested(4),
+        StatusCode::VM_MAX_VALUE_DEPTH_REACHED,
+    );
+}
+
+#[test]
+fn vec_pack_length() {
+    run_code_with_arg(
+        VMConfig {
+            max_vec_pack_length: Some(3),
+            ..VMConfig::default()
+        },
+        "pack",
+        MoveValue::U64(1),
+    )
+    .unwrap();
+    expect_status(
+        run_code_with_arg(
+            VMConfig {
+                max_vec_pack_length: Some(2),
+                ..VMConfig::default()
+            },
+            "pack",
+            MoveValue::U64(1),
+        ),
+        StatusCode::VM_MAX_VECTOR_LENGTH_REACHED,
+    );
+}
+
+#[test]
+fn type_argument_depth() {
+    let vm_config = VMConfig {
+        max_type_argument_depth: 3,
+        ..VMConfig::default()
+    };
+    run_code(
+        vm_config.clone(),
+        "generic",
+        vec![vector_of(TypeTag::U64, 2)],
+    )
+    .unwrap();
+    expect_status(
+        run_code(vm_config, "generic", vec![vector_of(TypeTag::U64, 3)]),
+        StatusCode::VM_MAX_TYPE_DEPTH_REACHED,
+    );
+}
+
+#[test]
+fn type_instantiation_size() {
+    let pair = |a, b| {
+        TypeTag::Struct(StructTag {
+            address: TEST_ADDR,
+            module: Identifier::new("M").unwrap(),
+            name: Identifier::new("Pair").unwrap(),
+            type_params: vec![a, b],
+        })
+    };
+    let vm_config = VMConfig {
+        max_type_instantiation_size: Some(4),
+        ..VMConfig::default()
+    };
+
+    // type arguments of entry functions
+    run_code(
+        vm_config.clone(),
+        "generic",
+        vec![pair(TypeTag::U64, TypeTag::U64)],
+    )
+    .unwrap();
+    expect_status(
+        run_code(
+            vm_config.clone(),
+            "generic",
+            vec![pair(TypeTag::U64, pair(TypeTag::U64, TypeTag::U64))],
+        ),
+        StatusCode::VM_MAX_TYPE_NODES_REACHED,
+    );
+
+    // instantiations in code
+    run_code(VMConfig::default(), "call_nested_generic", vec![]).unwrap();
+    expect_status(
+        run_code(vm_config, "call_nested_generic", vec![]),
+        StatusCode::VM_MAX_TYPE_NODES_REACHED,
+    );
+}
+
+fn verifier_config(verifier: VerifierConfig) -> VMConfig {
+    VMConfig {
+        verifier,
+        ..VMConfig::default()
+    }
+}
+
+#[test]
+fn verifier_max_function_parameters() {
+    let code = "module {{ADDR}}::M { fun f(_a: u64, _b: u64, _c: u64) {} }";
+    publish(VMConfig::default(), code).unwrap();
+    expect_status(
+        publish(
+            verifier_config(VerifierConfig {
+                max_function_parameters: Some(2),
+                ..VerifierConfig::default()
+            }),
+            code,
+        ),
+        StatusCode::TOO_MANY_PARAMETERS,
+    );
+}
+
+#[test]
+fn verifier_max_generic_instantiation_length() {
+    let code = "module {{ADDR}}::M { fun f<A, B, C>() {} }";
+    publish(VMConfig::default(), code).unwrap();
+    expect_status(
+        publish(
+            verifier_config(VerifierConfig {
+                max_generic_instantiation_length: Some(2),
+                ..VerifierConfig::default()
+            }),
+            code,
+        ),
+        StatusCode::TOO_MANY_TYPE_PARAMETERS,
+    );
+}
+
+#[test]
+fn verifier_max_loop_depth() {
+    let code = r#"
+        module {{ADDR}}::M {
+            fun f(n: u64) {
+                let i = 0;
+                while (i < n) {
+                    let j = 0;
+                    while (j < n) { j = j + 1 };
+                    i = i + 1;
+                }
+            }
+        }
+    "#;
+    publish(
+        verifier_config(VerifierConfig {
+            max_loop_depth: Some(2),
+            ..VerifierConfig::default()
+        }),
+        code,
+    )
+    .unwrap();
+    expect_status(
+        publish(
+            verifier_config(VerifierConfig {
+                max_loop_depth: Some(1),
+                ..VerifierConfig::default()
+            }),
+            code,
+        ),
+        StatusCode::LOOP_MAX_DEPTH_REACHED,
+    );
+}
+
+#[test]
+fn verifier_max_basic_blocks() {
+    let code = r#"
+        module {{ADDR}}::M {
+            fun f(b: bool): u64 {
+                if (b) 1 else 2
+            }
+        }
+    "#;
+    publish(VMConfig::default(), code).unwrap();
+    expect_status(
+        publish(
+            verifier_config(VerifierConfig {
+                max_basic_blocks: Some(1),
+                ..VerifierConfig::default()
+            }),
+            code,
+        ),
+        StatusCode::TOO_MANY_BASIC_BLOCKS,
+    );
+}
@@ -0,0 +1,16 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    /// The scheduler or the multi-version data structures reached an inconsistent state. This
+    /// indicates a bug in the executor.
+    InvariantViolation,
+    /// A transaction of the block published a module. The code cache of the VM is not versioned,
+    /// hence such blocks can only be executed sequentially.
+    ModulePathReadWrite,
+    /// The execution of a transaction failed. No transaction after it has been committed.
+    UserError(E),
+}
+
+pub type Result<T, E> = ::std::result::Result<T, Error<E>>;
@@ -0,0 +1,257 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    errors::{Error, Result},
+    mvhashmap::MVHashMap,
+    scheduler::{Scheduler, SchedulerTask},
+    task::{ExecutorTask, TransactionOutput, Version},
+    view::{MVStateView, ReadDescriptor, SequentialStateView, StateKey},
+};
+use move_core_types::resolver::MoveResolver;
+use parking_lot::Mutex;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The last recorded execution of a transaction.
+struct TxnLastExecution<O, E> {
+    reads: Vec<ReadDescriptor>,
+    writes: HashSet<StateKey>,
+    result: Option<std::result::Result<O, E>>,
+}
+
+impl<O, E> Default for TxnLastExecution<O, E> {
+    fn default() -> Self {
+        Self {
+            reads: vec![],
+            writes: HashSet::new(),
+            result: None,
+        }
+    }
+}
+
+/// The state shared by all the workers executing a block.
+struct BlockExecution<'a, T: ExecutorTask, S> {
+    task: &'a T,
+    block: &'a [T::Txn],
+    base: &'a S,
+    scheduler: Scheduler,
+    versioned: MVHashMap<StateKey, Option<Vec<u8>>>,
+    last_executions: Vec<Mutex<TxnLastExecution<T::Output, T::Error>>>,
+    module_written: AtomicBool,
+}
+
+impl<'a, T: ExecutorTask, S: MoveResolver + Sync> BlockExecution<'a, T, S> {
+    fn new(task: &'a T, block: &'a [T::Txn], base: &'a S) -> Self {
+        Self {
+            task,
+            block,
+            base,
+            scheduler: Scheduler::new(block.len()),
+            versioned: MVHashMap::new(),
+            last_executions: (0..block.len())
+                .map(|_| Mutex::new(TxnLastExecution::default()))
+                .collect(),
+            module_written: AtomicBool::new(false),
+        }
+    }
+
+    fn work(&self) {
+        let mut scheduler_task = SchedulerTask::NoTask;
+        loop {
+            scheduler_task = match scheduler_task {
+                SchedulerTask::ExecutionTask(version) => self.execute(version),
+                SchedulerTask::ValidationTask(version) => self.validate(version),
+                SchedulerTask::NoTask => self.scheduler.next_task(),
+                SchedulerTask::Done => break,
+            }
+        }
+    }
+
+    fn execute(&self, version: Version) -> SchedulerTask {
+        let (txn_idx, incarnation) = version;
+        let view = MVStateView::new(self.base, &self.versioned, txn_idx);
+        let result = self.task.execute_transaction(&view, &self.block[txn_idx]);
+
+        if let Some(dep_idx) = view.dependency() {
+            // The execution observed an estimate and is meaningless: either wait for the
+            // blocking transaction or, if it already finished, try again right away.
+            return if self.scheduler.add_dependency(txn_idx, dep_idx) {
+                SchedulerTask::NoTask
+            } else {
+                SchedulerTask::ExecutionTask(version)
+            };
+        }
+
+        let mut writes = HashSet::new();
+        if let Ok(output) = &result {
+            for (key, blob) in StateKey::writes_of(output.change_set()) {
+                if let StateKey::Module(_) = key {
+                    self.module_written.store(true, Ordering::SeqCst);
+                    self.scheduler.halt();
+                    return SchedulerTask::Done;
+                }
+                self.versioned
+                    .write(&key, version, blob.map(|b| b.to_vec()));
+                writes.insert(key);
+            }
+        }
+
+        let mut last_execution = self.last_executions[txn_idx].lock();
+        for key in last_execution.writes.difference(&writes) {
+            self.versioned.delete(key, txn_idx);
+        }
+        let wrote_new_path = writes
+            .iter()
+            .any(|key| !last_execution.writes.contains(key));
+        *last_execution = TxnLastExecution {
+            reads: view.take_reads(),
+            writes,
+            result: Some(result),
+        };
+        drop(last_execution);
+
+        self.scheduler
+            .finish_execution(txn_idx, incarnation, wrote_new_path)
+    }
+
+    fn validate(&self, version: Version) -> SchedulerTask {
+        let (txn_idx, incarnation) = version;
+        let last_execution = self.last_executions[txn_idx].lock();
+        let valid = last_execution
+            .reads
+            .iter()
+            .all(|read| read.validate(&self.versioned, txn_idx));
+        let aborted = !valid && self.scheduler.try_validation_abort(txn_idx, incarnation);
+        if aborted {
+            for key in last_execution.writes.iter() {
+                self.versioned.mark_estimate(key, txn_idx);
+            }
+        }
+        drop(last_execution);
+
+        self.scheduler.finish_validation(txn_idx, aborted)
+    }
+
+    /// Collect the outputs of the block, stopping at the first failed transaction.
+    fn into_outputs(self) -> Result<Vec<T::Output>, T::Error> {
+        if self.module_written.load(Ordering::SeqCst) {
+            return Err(Error::ModulePathReadWrite);
+        }
+        let mut outputs = Vec::with_capacity(self.block.len());
+        for last_execution in self.last_executions {
+            match last_execution.into_inner().result {
+                Some(Ok(output)) => outputs.push(output),
+                Some(Err(err)) => return Err(Error::UserError(err)),
+                None => return Err(Error::InvariantViolation),
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+/// Executes blocks of transactions on a pool of worker threads, producing the same outputs as
+/// `execute_transactions_sequential`.
+pub struct ParallelTransactionExecutor {
+    thread_pool: ThreadPool,
+}
+
+impl ParallelTransactionExecutor {
+    /// Create an executor with as many worker threads as there are CPUs.
+    pub fn new() -> Self {
+        Self::with_concurrency_level(num_cpus::get())
+    }
+
+    /// Create an executor with the given number of worker threads.
+    pub fn with_concurrency_level(concurrency_level: usize) -> Self {
+        assert!(concurrency_level > 0, "concurrency level must be positive");
+        Self {
+            thread_pool: ThreadPoolBuilder::new()
+                .num_threads(concurrency_level)
+                .thread_name(|idx| format!("move-parallel-exec-{}", idx))
+                .build()
+                .expect("failed to build the thread pool of the parallel executor"),
+        }
+    }
+
+    pub fn concurrency_level(&self) -> usize {
+        self.thread_pool.current_num_threads()
+    }
+
+    /// Execute the block in parallel on top of `base`.
+    ///
+    /// Returns the outputs of all transactions in order, or the error of the first transaction
+    /// which failed. Blocks in which a transaction publishes a module cannot be executed in
+    /// parallel and result in `Error::ModulePathReadWrite`.
+    pub fn execute_transactions_parallel<T, S>(
+        &self,
+        task: &T,
+        block: &[T::Txn],
+        base: &S,
+    ) -> Result<Vec<T::Output>, T::Error>
+    where
+        T: ExecutorTask,
+        S: MoveResolver + Sync,
+    {
+        let execution = BlockExecution::new(task, block, base);
+        self.thread_pool.scope(|s| {
+            for _ in 0..self.concurrency_level() {
+                s.spawn(|_| execution.work());
+            }
+        });
+        execution.into_outputs()
+    }
+
+    /// Execute the block in parallel, falling back to sequential execution if it publishes
+    /// modules.
+    pub fn execute_block<T, S>(
+        &self,
+        task: &T,
+        block: &[T::Txn],
+        base: &S,
+    ) -> Result<Vec<T::Output>, T::Error>
+    where
+        T: ExecutorTask,
+        S: MoveResolver + Sync,
+    {
+        match self.execute_transactions_parallel(task, block, base) {
+            Err(Error::ModulePathReadWrite) => execute_transactions_sequential(task, block, base),
+            result => result,
+        }
+    }
+}
+
+impl Default for ParallelTransactionExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Execute the block one transaction after the other on top of `base`, each transaction
+/// observing the writes of the preceding ones.
+///
+/// Returns the outputs of all transactions in order, or the error of the first transaction
+/// which failed.
+pub fn execute_transactions_sequential<T, S>(
+    task: &T,
+    block: &[T::Txn],
+    base: &S,
+) -> Result<Vec<T::Output>, T::Error>
+where
+    T: ExecutorTask,
+    S: MoveResolver,
+{
+    let mut view = SequentialStateView::new(base);
+    let mut outputs = Vec::with_capacity(block.len());
+    for txn in block {
+        let output = task
+            .execute_transaction(&view, txn)
+            .map_err(Error::UserError)?;
+        view.apply(output.change_set());
+        outputs.push(output);
+    }
+    Ok(outputs)
+}
@@ -0,0 +1,30 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Parallel execution of blocks of transactions on top of the Move VM.
+//!
+//! The executor follows the Block-STM approach: transactions of a block are executed
+//! optimistically on several threads against a multi-version view of the state, in which every
+//! transaction observes the writes of the transactions preceding it in the block. The reads of
+//! every execution are recorded and validated once the execution finished; transactions which
+//! read a stale value are re-executed. The resulting outputs are identical to the ones obtained by
+//! executing the block sequentially, in order.
+//!
+//! The executor is agnostic of the transaction format. Clients provide an implementation of
+//! `ExecutorTask`, which typically opens a `Session` of a shared `MoveVM` on top of the state view
+//! passed in and returns the effects of the session.
+
+mod errors;
+pub mod executor;
+mod mvhashmap;
+mod scheduler;
+pub mod task;
+mod view;
+
+#[cfg(test)]
+mod unit_tests;
+
+pub use errors::{Error, Result};
+pub use view::{StateKey, StateViewError};
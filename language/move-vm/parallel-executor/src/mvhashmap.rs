@@ -0,0 +1,86 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::task::{Incarnation, TxnIndex, Version};
+use parking_lot::RwLock;
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    sync::Arc,
+};
+
+/// A value written by some incarnation of a transaction. Once the incarnation is aborted, its
+/// writes are marked as estimates of what the next incarnation will write.
+struct WriteCell<V> {
+    incarnation: Incarnation,
+    estimate: bool,
+    value: Arc<V>,
+}
+
+/// The result of reading a key at a given transaction index.
+pub(crate) enum MVRead<V> {
+    /// The value was written by the given version of a preceding transaction.
+    Version(Version, Arc<V>),
+    /// The closest preceding write belongs to an aborted transaction which is about to be
+    /// re-executed.
+    Dependency(TxnIndex),
+    /// No preceding transaction wrote the key, the value must be read from storage.
+    NotFound,
+}
+
+/// A multi-version data structure: for every key, it keeps the values written by each
+/// transaction of the block, ordered by transaction index.
+pub(crate) struct MVHashMap<K, V> {
+    data: RwLock<HashMap<K, BTreeMap<TxnIndex, WriteCell<V>>>>,
+}
+
+impl<K: Hash + Eq + Clone, V> MVHashMap<K, V> {
+    pub(crate) fn new() -> Self {
+        Self {
+            data: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record the value written to `key` by the given version.
+    pub(crate) fn write(&self, key: &K, version: Version, value: V) {
+        let (txn_idx, incarnation) = version;
+        let mut data = self.data.write();
+        data.entry(key.clone()).or_default().insert(
+            txn_idx,
+            WriteCell {
+                incarnation,
+                estimate: false,
+                value: Arc::new(value),
+            },
+        );
+    }
+
+    /// Mark the value written to `key` by `txn_idx` as an estimate.
+    pub(crate) fn mark_estimate(&self, key: &K, txn_idx: TxnIndex) {
+        let mut data = self.data.write();
+        if let Some(cell) = data.get_mut(key).and_then(|m| m.get_mut(&txn_idx)) {
+            cell.estimate = true;
+        }
+    }
+
+    /// Remove the value written to `key` by `txn_idx`.
+    pub(crate) fn delete(&self, key: &K, txn_idx: TxnIndex) {
+        let mut data = self.data.write();
+        if let Some(versions) = data.get_mut(key) {
+            versions.remove(&txn_idx);
+        }
+    }
+
+    /// Read the latest value written to `key` by a transaction preceding `txn_idx`.
+    pub(crate) fn read(&self, key: &K, txn_idx: TxnIndex) -> MVRead<V> {
+        let data = self.data.read();
+        match data
+            .get(key)
+            .and_then(|versions| versions.range(..txn_idx).next_back())
+        {
+            Some((idx, cell)) if cell.estimate => MVRead::Dependency(*idx),
+            Some((idx, cell)) => MVRead::Version((*idx, cell.incarnation), cell.value.clone()),
+            None => MVRead::NotFound,
+        }
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The collaborative scheduler of Block-STM.
+//!
+//! Execution and validation tasks are dispatched in the order of the block, lower transactions
+//! first. Two indices track the next transaction to execute and to validate. Whenever an
+//! execution invalidates the results of later transactions (because it aborted or wrote to a new
+//! location), the corresponding index is decreased so the affected transactions are
+//! revisited. The block is done when both indices passed the end of the block and no task is
+//! in flight.
+
+use crate::task::{Incarnation, TxnIndex, Version};
+use parking_lot::Mutex;
+use std::{
+    cmp::min,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum SchedulerTask {
+    ExecutionTask(Version),
+    ValidationTask(Version),
+    NoTask,
+    Done,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TransactionStatus {
+    ReadyToExecute(Incarnation),
+    Executing(Incarnation),
+    Executed(Incarnation),
+    Aborting(Incarnation),
+}
+
+pub(crate) struct Scheduler {
+    num_txns: usize,
+    /// The next transaction to try to execute.
+    execution_idx: AtomicUsize,
+    /// The next transaction to try to validate.
+    validation_idx: AtomicUsize,
+    /// Incremented every time one of the indices above is decreased, used to detect completion
+    /// without locking.
+    decrease_cnt: AtomicUsize,
+    /// The number of execution and validation tasks currently in flight.
+    num_active_tasks: AtomicUsize,
+    done_marker: AtomicBool,
+    /// For every transaction, the transactions waiting for it to finish executing.
+    txn_dependency: Vec<Mutex<Vec<TxnIndex>>>,
+    txn_status: Vec<Mutex<TransactionStatus>>,
+}
+
+impl Scheduler {
+    pub(crate) fn new(num_txns: usize) -> Self {
+        Self {
+            num_txns,
+            execution_idx: AtomicUsize::new(0),
+            validation_idx: AtomicUsize::new(0),
+            decrease_cnt: AtomicUsize::new(0),
+            num_active_tasks: AtomicUsize::new(0),
+            done_marker: AtomicBool::new(false),
+            txn_dependency: (0..num_txns).map(|_| Mutex::new(Vec::new())).collect(),
+            txn_status: (0..num_txns)
+                .map(|_| Mutex::new(TransactionStatus::ReadyToExecute(0)))
+                .collect(),
+        }
+    }
+
+    /// Return the next task to perform, validation taking precedence when it is lagging behind.
+    pub(crate) fn next_task(&self) -> SchedulerTask {
+        if self.done() {
+            return SchedulerTask::Done;
+        }
+        let idx_to_validate = self.validation_idx.load(Ordering::SeqCst);
+        let idx_to_execute = self.execution_idx.load(Ordering::SeqCst);
+        if idx_to_validate < idx_to_execute {
+            if let Some(version) = self.next_version_to_validate() {
+                return SchedulerTask::ValidationTask(version);
+            }
+        } else if let Some(version) = self.next_version_to_execute() {
+            return SchedulerTask::ExecutionTask(version);
+        }
+        SchedulerTask::NoTask
+    }
+
+    /// Record that `txn_idx` read an estimate written by `dep_txn_idx` and must wait for it to be
+    /// re-executed. Returns false if `dep_txn_idx` already finished executing, in which case the
+    /// caller can re-execute `txn_idx` right away.
+    pub(crate) fn add_dependency(&self, txn_idx: TxnIndex, dep_txn_idx: TxnIndex) -> bool {
+        {
+            let mut deps = self.txn_dependency[dep_txn_idx].lock();
+            if matches!(
+                *self.txn_status[dep_txn_idx].lock(),
+                TransactionStatus::Executed(_)
+            ) {
+                return false;
+            }
+            let mut status = self.txn_status[txn_idx].lock();
+            if let TransactionStatus::Executing(incarnation) = *status {
+                *status = TransactionStatus::Aborting(incarnation);
+            }
+            deps.push(txn_idx);
+        }
+        self.num_active_tasks.fetch_sub(1, Ordering::SeqCst);
+        true
+    }
+
+    /// Mark the execution of the given version as finished. If the transaction needs to be
+    /// validated right away, the validation task is handed back to the caller.
+    pub(crate) fn finish_execution(
+        &self,
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+        wrote_new_path: bool,
+    ) -> SchedulerTask {
+        *self.txn_status[txn_idx].lock() = TransactionStatus::Executed(incarnation);
+
+        let deps = std::mem::take(&mut *self.txn_dependency[txn_idx].lock());
+        self.resume_dependencies(deps);
+
+        if self.validation_idx.load(Ordering::SeqCst) > txn_idx {
+            if wrote_new_path {
+                // All transactions after this one may have read a stale value.
+                self.decrease_validation_idx(txn_idx);
+            } else {
+                return SchedulerTask::ValidationTask((txn_idx, incarnation));
+            }
+        }
+        self.num_active_tasks.fetch_sub(1, Ordering::SeqCst);
+        SchedulerTask::NoTask
+    }
+
+    /// Try to abort the given version after a failed validation. Returns false if another
+    /// validation already aborted it.
+    pub(crate) fn try_validation_abort(&self, txn_idx: TxnIndex, incarnation: Incarnation) -> bool {
+        let mut status = self.txn_status[txn_idx].lock();
+        if *status == TransactionStatus::Executed(incarnation) {
+            *status = TransactionStatus::Aborting(incarnation);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Mark the validation of a transaction as finished. If it was aborted, the re-execution
+    /// task may be handed back to the caller.
+    pub(crate) fn finish_validation(&self, txn_idx: TxnIndex, aborted: bool) -> SchedulerTask {
+        if aborted {
+            self.set_ready_status(txn_idx);
+            self.decrease_validation_idx(txn_idx + 1);
+            if self.execution_idx.load(Ordering::SeqCst) > txn_idx {
+                if let Some(incarnation) = self.try_incarnate(txn_idx) {
+                    return SchedulerTask::ExecutionTask((txn_idx, incarnation));
+                }
+            }
+        }
+        self.num_active_tasks.fetch_sub(1, Ordering::SeqCst);
+        SchedulerTask::NoTask
+    }
+
+    /// Stop the scheduling of the block; all subsequent calls to `next_task` return `Done`.
+    pub(crate) fn halt(&self) {
+        self.done_marker.store(true, Ordering::SeqCst);
+    }
+
+    pub(crate) fn done(&self) -> bool {
+        self.done_marker.load(Ordering::Acquire)
+    }
+
+    fn decrease_execution_idx(&self, target_idx: TxnIndex) {
+        self.execution_idx.fetch_min(target_idx, Ordering::SeqCst);
+        self.decrease_cnt.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn decrease_validation_idx(&self, target_idx: TxnIndex) {
+        self.validation_idx.fetch_min(target_idx, Ordering::SeqCst);
+        self.decrease_cnt.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn check_done(&self) {
+        let observed_cnt = self.decrease_cnt.load(Ordering::SeqCst);
+        let execution_idx = self.execution_idx.load(Ordering::SeqCst);
+        let validation_idx = self.validation_idx.load(Ordering::SeqCst);
+        if min(execution_idx, validation_idx) >= self.num_txns
+            && self.num_active_tasks.load(Ordering::SeqCst) == 0
+            && observed_cnt == self.decrease_cnt.load(Ordering::SeqCst)
+        {
+            self.done_marker.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Move a transaction which is ready to execute to the executing state, returning the
+    /// incarnation to execute.
+    fn try_incarnate(&self, txn_idx: TxnIndex) -> Option<Incarnation> {
+        if txn_idx >= self.num_txns {
+            return None;
+        }
+        let mut status = self.txn_status[txn_idx].lock();
+        if let TransactionStatus::ReadyToExecute(incarnation) = *status {
+            *status = TransactionStatus::Executing(incarnation);
+            Some(incarnation)
+        } else {
+            None
+        }
+    }
+
+    fn next_version_to_execute(&self) -> Option<Version> {
+        if self.execution_idx.load(Ordering::SeqCst) >= self.num_txns {
+            self.check_done();
+            return None;
+        }
+        self.num_active_tasks.fetch_add(1, Ordering::SeqCst);
+        let idx_to_execute = self.execution_idx.fetch_add(1, Ordering::SeqCst);
+        match self.try_incarnate(idx_to_execute) {
+            Some(incarnation) => Some((idx_to_execute, incarnation)),
+            None => {
+                self.num_active_tasks.fetch_sub(1, Ordering::SeqCst);
+                None
+            }
+        }
+    }
+
+    fn next_version_to_validate(&self) -> Option<Version> {
+        if self.validation_idx.load(Ordering::SeqCst) >= self.num_txns {
+            self.check_done();
+            return None;
+        }
+        self.num_active_tasks.fetch_add(1, Ordering::SeqCst);
+        let idx_to_validate = self.validation_idx.fetch_add(1, Ordering::SeqCst);
+        if idx_to_validate < self.num_txns {
+            if let TransactionStatus::Executed(incarnation) =
+                *self.txn_status[idx_to_validate].lock()
+            {
+                return Some((idx_to_validate, incarnation));
+            }
+        }
+        self.num_active_tasks.fetch_sub(1, Ordering::SeqCst);
+        None
+    }
+
+    fn set_ready_status(&self, txn_idx: TxnIndex) {
+        let mut status = self.txn_status[txn_idx].lock();
+        if let TransactionStatus::Aborting(incarnation) = *status {
+            *status = TransactionStatus::ReadyToExecute(incarnation + 1);
+        }
+    }
+
+    fn resume_dependencies(&self, deps: Vec<TxnIndex>) {
+        for dep in deps.iter() {
+            self.set_ready_status(*dep);
+        }
+        if let Some(min_dep) = deps.into_iter().min() {
+            self.decrease_execution_idx(min_dep);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_core_types::{
+    effects::{ChangeSet, ContractEvent},
+    resolver::MoveResolver,
+};
+use std::fmt::Debug;
+
+/// The position of a transaction in the block.
+pub type TxnIndex = usize;
+
+/// The number of times a transaction has been (re-)executed.
+pub type Incarnation = usize;
+
+/// A particular execution of a transaction.
+pub type Version = (TxnIndex, Incarnation);
+
+/// The output of a transaction, as returned by an `ExecutorTask`.
+pub trait TransactionOutput: Send {
+    /// The writes performed by the transaction. They become visible to all transactions
+    /// following this one in the block.
+    fn change_set(&self) -> &ChangeSet;
+}
+
+impl TransactionOutput for (ChangeSet, Vec<ContractEvent>) {
+    fn change_set(&self) -> &ChangeSet {
+        &self.0
+    }
+}
+
+/// The execution logic for a single transaction, usually implemented by an adapter on top of
+/// `MoveVM`.
+///
+/// The executor may run `execute_transaction` several times for the same transaction and on
+/// several threads at once, so implementations must be deterministic and must only access the
+/// state through the `view` passed in. Errors reported by the `view` must be propagated (e.g.
+/// as a failed `Session`) rather than being interpreted: they are used by the executor to abort
+/// executions which ran into a conflict.
+pub trait ExecutorTask: Sync {
+    /// The type of transactions executed by this task.
+    type Txn: Sync;
+    /// The output of a successfully executed transaction.
+    type Output: TransactionOutput;
+    /// An error which stops the execution of the block at the failing transaction.
+    type Error: Debug + Send;
+
+    fn execute_transaction<S: MoveResolver>(
+        &self,
+        view: &S,
+        txn: &Self::Txn,
+    ) -> Result<Self::Output, Self::Error>;
+}
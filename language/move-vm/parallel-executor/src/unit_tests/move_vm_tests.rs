@@ -0,0 +1,171 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parallel execution of Move transactions against sequential execution, on a block of
+//! transfers between a small number of accounts.
+
+use crate::{
+    executor::{execute_transactions_sequential, ParallelTransactionExecutor},
+    task::ExecutorTask,
+};
+use move_binary_format::errors::VMError;
+use move_compiler::{compiled_unit::AnnotatedCompiledUnit, Compiler};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::{ChangeSet, ContractEvent},
+    identifier::Identifier,
+    language_storage::ModuleId,
+    resolver::MoveResolver,
+    value::{serialize_values, MoveValue},
+};
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas_schedule::GasStatus;
+use std::{fs::File, io::Write};
+use tempfile::tempdir;
+
+const BANK_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+const NUM_ACCOUNTS: u8 = 10;
+
+const BANK: &str = r#"
+module 0x2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a::Bank {
+    struct Balance has key { value: u64 }
+
+    public fun open(account: signer, value: u64) {
+        move_to(&account, Balance { value })
+    }
+
+    public fun transfer(from: address, to: address, amount: u64) acquires Balance {
+        let from_balance = &mut borrow_global_mut<Balance>(from).value;
+        assert!(*from_balance >= amount, 1);
+        *from_balance = *from_balance - amount;
+        let to_balance = &mut borrow_global_mut<Balance>(to).value;
+        *to_balance = *to_balance + amount;
+    }
+}
+"#;
+
+#[derive(Debug)]
+enum BankTxn {
+    Open(AccountAddress, u64),
+    Transfer(AccountAddress, AccountAddress, u64),
+}
+
+struct BankTask {
+    vm: MoveVM,
+}
+
+impl ExecutorTask for BankTask {
+    type Txn = BankTxn;
+    type Output = (ChangeSet, Vec<ContractEvent>);
+    type Error = VMError;
+
+    fn execute_transaction<S: MoveResolver>(
+        &self,
+        view: &S,
+        txn: &BankTxn,
+    ) -> Result<Self::Output, VMError> {
+        let (function_name, args) = match txn {
+            BankTxn::Open(account, value) => (
+                "open",
+                vec![MoveValue::Signer(*account), MoveValue::U64(*value)],
+            ),
+            BankTxn::Transfer(from, to, amount) => (
+                "transfer",
+                vec![
+                    MoveValue::Address(*from),
+                    MoveValue::Address(*to),
+                    MoveValue::U64(*amount),
+                ],
+            ),
+        };
+        let mut session = self.vm.new_session(view);
+        let result = session.execute_function_bypass_visibility(
+            &ModuleId::new(BANK_ADDR, Identifier::new("Bank").unwrap()),
+            &Identifier::new(function_name).unwrap(),
+            vec![],
+            serialize_values(&args),
+            &mut GasStatus::new_unmetered(),
+        );
+        match result {
+            Ok(_) => session
+                .finish()
+                .map(|(change_set, events, _)| (change_set, events)),
+            // Failed transactions are kept in the block, without effects.
+            Err(_) => Ok((ChangeSet::new(), vec![])),
+        }
+    }
+}
+
+fn compile_bank() -> Vec<u8> {
+    let dir = tempdir().unwrap();
+    let file_path = dir.path().join("Bank.move");
+    writeln!(File::create(&file_path).unwrap(), "{}", BANK).unwrap();
+    let (_, units) = Compiler::from_files(
+        vec![file_path.to_str().unwrap().to_string()],
+        vec![],
+        std::collections::BTreeMap::<String, _>::new(),
+    )
+    .build_and_report()
+    .unwrap();
+    match units.into_iter().next().unwrap() {
+        AnnotatedCompiledUnit::Module(annot_module) => {
+            let mut blob = vec![];
+            annot_module
+                .named_module
+                .module
+                .serialize(&mut blob)
+                .unwrap();
+            blob
+        }
+        AnnotatedCompiledUnit::Script(_) => panic!("expected module got script"),
+    }
+}
+
+fn account(idx: u64) -> AccountAddress {
+    AccountAddress::from_hex_literal(&format!("0x{:x}", idx % NUM_ACCOUNTS as u64 + 1)).unwrap()
+}
+
+/// A deterministic block of transfers: accounts are opened first, then funds move around so that
+/// most transactions conflict with some of the preceding ones, and some transfers fail.
+fn bank_block(seed: u64, num_transfers: usize) -> Vec<BankTxn> {
+    let mut state = seed;
+    let mut next = || {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        state >> 33
+    };
+    let mut block: Vec<_> = (0..NUM_ACCOUNTS as u64)
+        .map(|idx| BankTxn::Open(account(idx), 100))
+        .collect();
+    for _ in 0..num_transfers {
+        block.push(BankTxn::Transfer(
+            account(next()),
+            account(next()),
+            next() % 60,
+        ));
+    }
+    block
+}
+
+#[test]
+fn parallel_bank_transfers() {
+    let mut storage = InMemoryStorage::new();
+    storage.publish_or_overwrite_module(
+        ModuleId::new(BANK_ADDR, Identifier::new("Bank").unwrap()),
+        compile_bank(),
+    );
+    let task = BankTask {
+        vm: MoveVM::new(vec![], VMConfig::default()).unwrap(),
+    };
+    let executor = ParallelTransactionExecutor::with_concurrency_level(4);
+    for seed in 0..8 {
+        let block = bank_block(seed, 300);
+        let sequential = execute_transactions_sequential(&task, &block, &storage).unwrap();
+        let parallel = executor
+            .execute_transactions_parallel(&task, &block, &storage)
+            .unwrap();
+        assert_eq!(parallel, sequential);
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A deterministic harness comparing parallel and sequential execution of randomly generated
+//! blocks. Transactions read and write a small set of resources and the values they write
+//! depend on everything they read, so any stale read shows up in the outputs.
+
+use crate::{
+    executor::{execute_transactions_sequential, ParallelTransactionExecutor},
+    task::ExecutorTask,
+    Error,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::{ChangeSet, ContractEvent},
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag},
+    resolver::MoveResolver,
+};
+use move_vm_test_utils::InMemoryStorage;
+use proptest::{collection::vec, prelude::*};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    hash::{Hash, Hasher},
+};
+
+const NUM_KEYS: u8 = 8;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Read(u8),
+    Write(u8),
+    Delete(u8),
+    /// Fail the transaction if the digest of everything read so far is divisible by the given
+    /// number.
+    FailIfDivisible(u64),
+    PublishModule,
+}
+
+#[derive(Clone, Debug)]
+struct Txn {
+    ops: Vec<Op>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum MockError {
+    Aborted,
+    Storage,
+}
+
+struct MockTask;
+
+fn key_address(key: u8) -> AccountAddress {
+    let mut addr = [0u8; AccountAddress::LENGTH];
+    addr[AccountAddress::LENGTH - 1] = key;
+    AccountAddress::new(addr)
+}
+
+fn resource_tag() -> StructTag {
+    StructTag {
+        address: AccountAddress::ONE,
+        module: Identifier::new("M").unwrap(),
+        name: Identifier::new("R").unwrap(),
+        type_params: vec![],
+    }
+}
+
+impl ExecutorTask for MockTask {
+    type Txn = Txn;
+    type Output = (ChangeSet, Vec<ContractEvent>);
+    type Error = MockError;
+
+    fn execute_transaction<S: MoveResolver>(
+        &self,
+        view: &S,
+        txn: &Txn,
+    ) -> Result<Self::Output, MockError> {
+        let mut digest = DefaultHasher::new();
+        let mut local_writes: BTreeMap<u8, Option<Vec<u8>>> = BTreeMap::new();
+        let mut change_set = ChangeSet::new();
+        for op in &txn.ops {
+            match op {
+                Op::Read(key) => {
+                    let value = match local_writes.get(key) {
+                        Some(value) => value.clone(),
+                        None => view
+                            .get_resource(&key_address(*key), &resource_tag())
+                            .map_err(|_| MockError::Storage)?,
+                    };
+                    value.hash(&mut digest);
+                }
+                Op::Write(key) => {
+                    let value = digest.finish().to_le_bytes().to_vec();
+                    local_writes.insert(*key, Some(value));
+                }
+                Op::Delete(key) => {
+                    local_writes.insert(*key, None);
+                }
+                Op::FailIfDivisible(n) => {
+                    if digest.finish() % n == 0 {
+                        return Err(MockError::Aborted);
+                    }
+                }
+                Op::PublishModule => {
+                    let module_id =
+                        ModuleId::new(AccountAddress::ONE, Identifier::new("N").unwrap());
+                    change_set.publish_or_overwrite_module(module_id, vec![digest.finish() as u8]);
+                }
+            }
+        }
+        for (key, value) in local_writes {
+            match value {
+                Some(blob) => {
+                    change_set.publish_or_overwrite_resource(key_address(key), resource_tag(), blob)
+                }
+                None => change_set
+                    .unpublish_resource(key_address(key), resource_tag())
+                    .unwrap(),
+            }
+        }
+        Ok((change_set, vec![]))
+    }
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    // Publishing a module prevents the whole block from being executed in parallel, so it is
+    // kept rare enough for most blocks not to publish any
+    prop_oneof![
+        300 => (0..NUM_KEYS).prop_map(Op::Read),
+        200 => (0..NUM_KEYS).prop_map(Op::Write),
+        50 => (0..NUM_KEYS).prop_map(Op::Delete),
+        50 => (10u64..40).prop_map(Op::FailIfDivisible),
+        1 => Just(Op::PublishModule),
+    ]
+}
+
+fn block_strategy() -> impl Strategy<Value = Vec<Txn>> {
+    vec(vec(op_strategy(), 0..8).prop_map(|ops| Txn { ops }), 0..100)
+}
+
+fn base_storage() -> InMemoryStorage {
+    let mut storage = InMemoryStorage::new();
+    for key in 0..NUM_KEYS / 2 {
+        storage.publish_or_overwrite_resource(key_address(key), resource_tag(), vec![key]);
+    }
+    storage
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn parallel_matches_sequential(block in block_strategy(), concurrency_level in 1usize..8) {
+        let storage = base_storage();
+        let executor = ParallelTransactionExecutor::with_concurrency_level(concurrency_level);
+        let sequential = execute_transactions_sequential(&MockTask, &block, &storage);
+        let publishes = block
+            .iter()
+            .any(|txn| txn.ops.iter().any(|op| matches!(op, Op::PublishModule)));
+        for _ in 0..4 {
+            match executor.execute_transactions_parallel(&MockTask, &block, &storage) {
+                // Even a speculative execution publishing a module stops parallel execution
+                Err(Error::ModulePathReadWrite) => prop_assert!(publishes),
+                parallel => prop_assert_eq!(&parallel, &sequential),
+            }
+        }
+        prop_assert_eq!(
+            &executor.execute_block(&MockTask, &block, &storage),
+            &sequential
+        );
+    }
+}
+
+#[test]
+fn read_write_chain() {
+    // Every transaction depends on the previous one.
+    let block: Vec<_> = (0..200)
+        .map(|_| Txn {
+            ops: vec![Op::Read(0), Op::Write(0)],
+        })
+        .collect();
+    let storage = base_storage();
+    let sequential = execute_transactions_sequential(&MockTask, &block, &storage);
+    let parallel = ParallelTransactionExecutor::with_concurrency_level(4)
+        .execute_transactions_parallel(&MockTask, &block, &storage);
+    assert_eq!(parallel, sequential);
+}
+
+#[test]
+fn module_publishing_falls_back_to_sequential() {
+    let block = vec![
+        Txn {
+            ops: vec![Op::Read(1), Op::Write(2)],
+        },
+        Txn {
+            ops: vec![Op::Read(2), Op::PublishModule],
+        },
+        Txn {
+            ops: vec![Op::Read(2), Op::Write(1)],
+        },
+    ];
+    let storage = base_storage();
+    let executor = ParallelTransactionExecutor::with_concurrency_level(2);
+    assert_eq!(
+        executor.execute_transactions_parallel(&MockTask, &block, &storage),
+        Err(Error::ModulePathReadWrite)
+    );
+    assert_eq!(
+        executor.execute_block(&MockTask, &block, &storage),
+        execute_transactions_sequential(&MockTask, &block, &storage)
+    );
+}
@@ -0,0 +1,211 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    mvhashmap::{MVHashMap, MVRead},
+    task::{TxnIndex, Version},
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::ChangeSet,
+    language_storage::{ModuleId, StructTag},
+    resolver::{ModuleResolver, MoveResolver, ResourceResolver},
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+/// A location in global storage which can be read or written by a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StateKey {
+    Module(ModuleId),
+    Resource(AccountAddress, StructTag),
+}
+
+impl StateKey {
+    /// Enumerate the writes of a change set, `None` standing for a deletion.
+    pub(crate) fn writes_of(
+        change_set: &ChangeSet,
+    ) -> impl Iterator<Item = (StateKey, Option<&[u8]>)> {
+        let modules = change_set
+            .modules()
+            .map(|(addr, name, blob)| (StateKey::Module(ModuleId::new(addr, name.clone())), blob));
+        let resources = change_set
+            .resources()
+            .map(|(addr, tag, blob)| (StateKey::Resource(addr, tag.clone()), blob));
+        modules.chain(resources)
+    }
+}
+
+/// Errors returned by the state views handed to an `ExecutorTask`.
+#[derive(Debug)]
+pub enum StateViewError<E> {
+    /// The transaction read a value written by a preceding transaction which is being
+    /// re-executed. The current execution is discarded by the executor.
+    Dependency(TxnIndex),
+    /// The underlying storage failed.
+    Storage(E),
+}
+
+/// How the value of a key was obtained by a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ReadOrigin {
+    /// Written by the given version of a preceding transaction.
+    Version(Version),
+    /// Not written by any preceding transaction, read from storage.
+    Storage,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ReadDescriptor {
+    pub(crate) key: StateKey,
+    pub(crate) origin: ReadOrigin,
+}
+
+impl ReadDescriptor {
+    /// Whether the read would still observe the same value in the current state of the
+    /// multi-version data structure.
+    pub(crate) fn validate(
+        &self,
+        versioned: &MVHashMap<StateKey, Option<Vec<u8>>>,
+        txn_idx: TxnIndex,
+    ) -> bool {
+        match versioned.read(&self.key, txn_idx) {
+            MVRead::Version(version, _) => self.origin == ReadOrigin::Version(version),
+            MVRead::NotFound => self.origin == ReadOrigin::Storage,
+            MVRead::Dependency(_) => false,
+        }
+    }
+}
+
+/// The view of the state observed by a transaction during parallel execution: the writes of the
+/// preceding transactions of the block on top of the base storage. All resource reads are
+/// captured for later validation.
+///
+/// Modules are read from the base storage directly, as blocks publishing modules are not
+/// executed in parallel.
+pub(crate) struct MVStateView<'a, S> {
+    base: &'a S,
+    versioned: &'a MVHashMap<StateKey, Option<Vec<u8>>>,
+    txn_idx: TxnIndex,
+    captured_reads: RefCell<Vec<ReadDescriptor>>,
+    dependency: Cell<Option<TxnIndex>>,
+}
+
+impl<'a, S: MoveResolver> MVStateView<'a, S> {
+    pub(crate) fn new(
+        base: &'a S,
+        versioned: &'a MVHashMap<StateKey, Option<Vec<u8>>>,
+        txn_idx: TxnIndex,
+    ) -> Self {
+        Self {
+            base,
+            versioned,
+            txn_idx,
+            captured_reads: RefCell::new(Vec::new()),
+            dependency: Cell::new(None),
+        }
+    }
+
+    /// The transaction whose estimate was read during execution, if any.
+    pub(crate) fn dependency(&self) -> Option<TxnIndex> {
+        self.dependency.get()
+    }
+
+    pub(crate) fn take_reads(&self) -> Vec<ReadDescriptor> {
+        self.captured_reads.take()
+    }
+}
+
+impl<'a, S: MoveResolver> ModuleResolver for MVStateView<'a, S> {
+    type Error = StateViewError<S::Err>;
+
+    fn get_module(&self, module_id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.base
+            .get_module(module_id)
+            .map_err(StateViewError::Storage)
+    }
+}
+
+impl<'a, S: MoveResolver> ResourceResolver for MVStateView<'a, S> {
+    type Error = StateViewError<S::Err>;
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> Result<Option<Vec<u8>>, Self::Error> {
+        let key = StateKey::Resource(*address, tag.clone());
+        match self.versioned.read(&key, self.txn_idx) {
+            MVRead::Version(version, value) => {
+                self.captured_reads.borrow_mut().push(ReadDescriptor {
+                    key,
+                    origin: ReadOrigin::Version(version),
+                });
+                Ok(value.as_ref().clone())
+            }
+            MVRead::NotFound => {
+                self.captured_reads.borrow_mut().push(ReadDescriptor {
+                    key,
+                    origin: ReadOrigin::Storage,
+                });
+                self.base
+                    .get_resource(address, tag)
+                    .map_err(StateViewError::Storage)
+            }
+            MVRead::Dependency(dep_idx) => {
+                self.dependency.set(Some(dep_idx));
+                Err(StateViewError::Dependency(dep_idx))
+            }
+        }
+    }
+}
+
+/// The view of the state observed by a transaction during sequential execution: the writes of
+/// all previously executed transactions on top of the base storage.
+pub(crate) struct SequentialStateView<'a, S> {
+    base: &'a S,
+    writes: HashMap<StateKey, Option<Vec<u8>>>,
+}
+
+impl<'a, S: MoveResolver> SequentialStateView<'a, S> {
+    pub(crate) fn new(base: &'a S) -> Self {
+        Self {
+            base,
+            writes: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn apply(&mut self, change_set: &ChangeSet) {
+        for (key, blob) in StateKey::writes_of(change_set) {
+            self.writes.insert(key, blob.map(|b| b.to_vec()));
+        }
+    }
+}
+
+impl<'a, S: MoveResolver> ModuleResolver for SequentialStateView<'a, S> {
+    type Error = S::Err;
+
+    fn get_module(&self, module_id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
+        match self.writes.get(&StateKey::Module(module_id.clone())) {
+            Some(blob) => Ok(blob.clone()),
+            None => self.base.get_module(module_id),
+        }
+    }
+}
+
+impl<'a, S: MoveResolver> ResourceResolver for SequentialStateView<'a, S> {
+    type Error = S::Err;
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> Result<Option<Vec<u8>>, Self::Error> {
+        match self.writes.get(&StateKey::Resource(*address, tag.clone())) {
+            Some(blob) => Ok(blob.clone()),
+            None => self.base.get_resource(address, tag),
+        }
+    }
+}
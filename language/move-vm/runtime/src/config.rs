@@ -0,0 +1,113 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{data_cache::WriteSetLimits, native_functions::MissingNativesAllowList};
+use move_binary_format::errors::{PartialVMError, PartialVMResult};
+use move_bytecode_verifier::VerifierConfig;
+use move_core_types::{value::VALUE_DEPTH_MAX, vm_status::StatusCode};
+use move_vm_types::loaded_data::runtime_types::{Type, TYPE_DEPTH_MAX};
+
+/// Limits and settings of a `MoveVM`. The default configuration preserves the limits the VM has
+/// always enforced.
+#[derive(Clone, Debug)]
+pub struct VMConfig {
+    /// Settings of the bytecode verifier, used when publishing or loading code.
+    pub verifier: VerifierConfig,
+    /// Maximum number of values on the operand stack. Exceeding it fails with
+    /// `EXECUTION_STACK_OVERFLOW`.
+    pub max_operand_stack_size: usize,
+    /// Maximum number of frames on the call stack. Exceeding it fails with `CALL_STACK_OVERFLOW`.
+    pub max_call_stack_size: usize,
+    /// Maximum nesting depth of values, applied when computing type layouts and when copying or
+    /// comparing values. Exceeding it fails with `VM_MAX_VALUE_DEPTH_REACHED`.
+    pub max_value_depth: usize,
+    /// Maximum number of elements of a vector created with `VecPack`. Exceeding it fails with
+    /// `VM_MAX_VECTOR_LENGTH_REACHED`.
+    pub max_vec_pack_length: Option<u64>,
+    /// Maximum number of nodes of a type obtained by instantiating a generic function or struct.
+    /// Exceeding it fails with `VM_MAX_TYPE_NODES_REACHED`.
+    pub max_type_instantiation_size: Option<usize>,
+    /// Maximum nesting depth of type arguments, at most `TYPE_DEPTH_MAX`. Exceeding it fails
+    /// with `VM_MAX_TYPE_DEPTH_REACHED`.
+    pub max_type_argument_depth: usize,
+    /// Limits on the effects of a session, checked when it finishes.
+    pub write_set_limits: WriteSetLimits,
+    /// Natives which modules may declare without the VM providing an implementation. See
+    /// `MissingNativesAllowList`.
+    pub missing_natives_allowed: MissingNativesAllowList,
+}
+
+impl Default for VMConfig {
+    fn default() -> Self {
+        Self {
+            verifier: VerifierConfig::default(),
+            max_operand_stack_size: 1024,
+            max_call_stack_size: 1024,
+            max_value_depth: VALUE_DEPTH_MAX,
+            max_vec_pack_length: None,
+            max_type_instantiation_size: None,
+            max_type_argument_depth: TYPE_DEPTH_MAX,
+            write_set_limits: WriteSetLimits::default(),
+            missing_natives_allowed: MissingNativesAllowList::default(),
+        }
+    }
+}
+
+impl VMConfig {
+    pub(crate) fn check_vec_pack_length(&self, len: u64) -> PartialVMResult<()> {
+        match self.max_vec_pack_length {
+            Some(max) if len > max => {
+                Err(
+                    PartialVMError::new(StatusCode::VM_MAX_VECTOR_LENGTH_REACHED).with_message(
+                        format!("vector of {} elements exceeds the limit of {}", len, max),
+                    ),
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks a type coming from an instantiation against `max_type_argument_depth` and
+    /// `max_type_instantiation_size`.
+    pub(crate) fn check_type(&self, ty: &Type) -> PartialVMResult<()> {
+        if self.max_type_argument_depth >= TYPE_DEPTH_MAX
+            && self.max_type_instantiation_size.is_none()
+        {
+            // `Type::subst` already enforces `TYPE_DEPTH_MAX`
+            return Ok(());
+        }
+        let mut nodes = 0;
+        self.check_type_impl(ty, 1, &mut nodes)
+    }
+
+    fn check_type_impl(&self, ty: &Type, depth: usize, nodes: &mut usize) -> PartialVMResult<()> {
+        if depth > self.max_type_argument_depth {
+            return Err(PartialVMError::new(StatusCode::VM_MAX_TYPE_DEPTH_REACHED));
+        }
+        *nodes += 1;
+        if let Some(max) = self.max_type_instantiation_size {
+            if *nodes > max {
+                return Err(PartialVMError::new(StatusCode::VM_MAX_TYPE_NODES_REACHED));
+            }
+        }
+        match ty {
+            Type::Bool
+            | Type::U8
+            | Type::U64
+            | Type::U128
+            | Type::Address
+            | Type::Signer
+            | Type::Struct(_)
+            | Type::TyParam(_) => Ok(()),
+            Type::Vector(ty) | Type::Reference(ty) | Type::MutableReference(ty) => {
+                self.check_type_impl(ty, depth + 1, nodes)
+            }
+            Type::StructInstantiation(_, ty_args) => {
+                for ty in ty_args {
+                    self.check_type_impl(ty, depth + 1, nodes)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -6,9 +6,9 @@ use crate::loader::Loader;
 use move_binary_format::errors::*;
 use move_core_types::{
     account_address::AccountAddress,
-    effects::{AccountChangeSet, ChangeSet, Event},
+    effects::{AccountChangeSet, ChangeSet, ContractEvent},
     identifier::Identifier,
-    language_storage::{ModuleId, TypeTag},
+    language_storage::{ModuleId, StructTag, TypeTag},
     resolver::MoveResolver,
     value::MoveTypeLayout,
     vm_status::StatusCode,
@@ -20,8 +20,127 @@ use move_vm_types::{
 };
 use std::collections::btree_map::BTreeMap;
 
+/// The kind of change made to a resource in a transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceChange {
+    /// The resource was only read.
+    None,
+    /// The resource did not exist in storage and has been published.
+    Created,
+    /// The resource existed in storage and has been overwritten.
+    Modified,
+    /// The resource existed in storage and has been removed.
+    Deleted,
+}
+
+/// Storage accounting for a single resource accessed by a transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResourceStorageUsage {
+    pub change: ResourceChange,
+    /// Size of the resource as read from storage, 0 if it did not exist.
+    pub read_bytes: u64,
+    /// Size of the resource written back to storage, 0 if it was not written or was deleted.
+    pub written_bytes: u64,
+}
+
+impl ResourceStorageUsage {
+    /// The growth of storage caused by the change to this resource. Deletions free the bytes
+    /// previously occupied by the resource.
+    pub fn byte_delta(&self) -> i64 {
+        match self.change {
+            ResourceChange::None => 0,
+            ResourceChange::Created | ResourceChange::Modified => {
+                self.written_bytes as i64 - self.read_bytes as i64
+            }
+            ResourceChange::Deleted => -(self.read_bytes as i64),
+        }
+    }
+}
+
+/// Storage accounting for all resources and modules accessed by a transaction, as computed when
+/// the session finishes. Embedders can use it to charge for storage growth.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageUsage {
+    pub resources: BTreeMap<(AccountAddress, StructTag), ResourceStorageUsage>,
+    /// Total size of the modules published by the transaction.
+    pub module_bytes: u64,
+}
+
+impl StorageUsage {
+    /// Total size of the resources read from storage.
+    pub fn read_bytes(&self) -> u64 {
+        self.resources.values().map(|usage| usage.read_bytes).sum()
+    }
+
+    /// Total size of the resources written to storage.
+    pub fn written_bytes(&self) -> u64 {
+        self.resources
+            .values()
+            .map(|usage| usage.written_bytes)
+            .sum()
+    }
+
+    /// The net growth of storage caused by the changes to resources.
+    pub fn byte_delta(&self) -> i64 {
+        self.resources
+            .values()
+            .map(|usage| usage.byte_delta())
+            .sum()
+    }
+
+    /// The number of resources created, modified or deleted.
+    pub fn num_written_resources(&self) -> u64 {
+        self.resources
+            .values()
+            .filter(|usage| usage.change != ResourceChange::None)
+            .count() as u64
+    }
+
+    /// Total size of the write set: written resources and published modules.
+    pub fn write_set_size(&self) -> u64 {
+        self.written_bytes() + self.module_bytes
+    }
+}
+
+/// Limits on the effects of a single transaction, checked when the session finishes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WriteSetLimits {
+    /// Maximum size, in bytes, of the write set. See `StorageUsage::write_set_size`.
+    pub max_write_set_size: Option<u64>,
+    /// Maximum number of resources created, modified or deleted.
+    pub max_written_resources: Option<u64>,
+}
+
+impl WriteSetLimits {
+    fn check(&self, usage: &StorageUsage) -> PartialVMResult<()> {
+        if let Some(max) = self.max_write_set_size {
+            let size = usage.write_set_size();
+            if size > max {
+                return Err(
+                    PartialVMError::new(StatusCode::VM_MAX_WRITE_SET_SIZE_REACHED).with_message(
+                        format!("write set size {} exceeds the limit of {}", size, max),
+                    ),
+                );
+            }
+        }
+        if let Some(max) = self.max_written_resources {
+            let num = usage.num_written_resources();
+            if num > max {
+                return Err(
+                    PartialVMError::new(StatusCode::VM_MAX_WRITTEN_RESOURCES_REACHED).with_message(
+                        format!("{} resources written, exceeding the limit of {}", num, max),
+                    ),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
 pub struct AccountDataCache {
-    data_map: BTreeMap<Type, (MoveTypeLayout, GlobalValue)>,
+    // For each resource, its layout, the cached value and the size of the blob read from storage
+    // (`None` if the resource did not exist).
+    data_map: BTreeMap<Type, (MoveTypeLayout, GlobalValue, Option<u64>)>,
     module_map: BTreeMap<Identifier, Vec<u8>>,
 }
 
@@ -32,6 +151,31 @@ impl AccountDataCache {
             module_map: BTreeMap::new(),
         }
     }
+
+    fn copy_value(&self, max_depth: usize) -> PartialVMResult<Self> {
+        let mut data_map = BTreeMap::new();
+        for (ty, (layout, gv, read_size)) in &self.data_map {
+            data_map.insert(
+                ty.clone(),
+                (
+                    layout.clone(),
+                    gv.copy_value_with_max_depth(max_depth)?,
+                    *read_size,
+                ),
+            );
+        }
+        Ok(Self {
+            data_map,
+            module_map: self.module_map.clone(),
+        })
+    }
+}
+
+/// The state of a `TransactionDataCache` recorded by a savepoint: a copy of the accounts
+/// touched so far and the number of events emitted.
+struct DataCacheSavepoint {
+    account_map: BTreeMap<AccountAddress, AccountDataCache>,
+    num_events: usize,
 }
 
 /// Transaction data cache. Keep updates within a transaction so they can all be published at
@@ -51,7 +195,8 @@ pub(crate) struct TransactionDataCache<'r, 'l, S> {
     remote: &'r S,
     loader: &'l Loader,
     account_map: BTreeMap<AccountAddress, AccountDataCache>,
-    event_data: Vec<(Vec<u8>, u64, Type, MoveTypeLayout, Value)>,
+    event_data: Vec<(Option<(Vec<u8>, u64)>, Type, MoveTypeLayout, Value)>,
+    savepoints: Vec<DataCacheSavepoint>,
 }
 
 impl<'r, 'l, S: MoveResolver> TransactionDataCache<'r, 'l, S> {
@@ -63,67 +208,155 @@ impl<'r, 'l, S: MoveResolver> TransactionDataCache<'r, 'l, S> {
             loader,
             account_map: BTreeMap::new(),
             event_data: vec![],
+            savepoints: vec![],
+        }
+    }
+
+    /// Record the current state on top of the stack of savepoints. This copies all data loaded
+    /// so far, so its cost is proportional to the size of the resources touched.
+    ///
+    /// Must not be called while references to global values are alive, i.e. during execution.
+    pub(crate) fn savepoint(&mut self) -> PartialVMResult<()> {
+        let max_value_depth = self.loader.vm_config().max_value_depth;
+        let mut account_map = BTreeMap::new();
+        for (addr, account_cache) in &self.account_map {
+            account_map.insert(*addr, account_cache.copy_value(max_value_depth)?);
+        }
+        self.savepoints.push(DataCacheSavepoint {
+            account_map,
+            num_events: self.event_data.len(),
+        });
+        Ok(())
+    }
+
+    /// Restore the state recorded by the topmost savepoint, which is kept on the stack.
+    pub(crate) fn rollback(&mut self) -> PartialVMResult<()> {
+        let savepoint = self.savepoints.last().ok_or_else(|| {
+            PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                .with_message("no savepoint to roll back to".to_string())
+        })?;
+        let max_value_depth = self.loader.vm_config().max_value_depth;
+        let mut account_map = BTreeMap::new();
+        for (addr, account_cache) in &savepoint.account_map {
+            account_map.insert(*addr, account_cache.copy_value(max_value_depth)?);
+        }
+        // Modules published since the savepoint may have been loaded, and must not be found by
+        // the loader after the rollback
+        let mut rolled_back_modules = vec![];
+        for (addr, account_cache) in &self.account_map {
+            for (name, blob) in &account_cache.module_map {
+                let saved_blob = savepoint
+                    .account_map
+                    .get(addr)
+                    .and_then(|saved_cache| saved_cache.module_map.get(name));
+                if saved_blob != Some(blob) {
+                    rolled_back_modules.push(ModuleId::new(*addr, name.clone()));
+                }
+            }
         }
+        self.loader.evict_modules(rolled_back_modules);
+        self.account_map = account_map;
+        self.event_data.truncate(savepoint.num_events);
+        Ok(())
+    }
+
+    /// Discard the topmost savepoint, keeping the current state.
+    pub(crate) fn release(&mut self) -> PartialVMResult<()> {
+        self.savepoints.pop().map(|_| ()).ok_or_else(|| {
+            PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                .with_message("no savepoint to release".to_string())
+        })
     }
 
     /// Make a write set from the updated (dirty, deleted) global resources along with
-    /// published modules.
+    /// published modules, together with the storage accounting of the transaction.
     ///
     /// Gives all proper guarantees on lifetime of global data as well.
-    pub(crate) fn into_effects(self) -> PartialVMResult<(ChangeSet, Vec<Event>)> {
+    pub(crate) fn into_effects(
+        self,
+    ) -> PartialVMResult<(ChangeSet, Vec<ContractEvent>, StorageUsage)> {
+        let max_value_depth = self.loader.vm_config().max_value_depth;
         let mut change_set = ChangeSet::new();
+        let mut usage = StorageUsage::default();
         for (addr, account_data_cache) in self.account_map.into_iter() {
             let mut modules = BTreeMap::new();
             for (module_name, module_blob) in account_data_cache.module_map {
+                usage.module_bytes += module_blob.len() as u64;
                 modules.insert(module_name, Some(module_blob));
             }
 
             let mut resources = BTreeMap::new();
-            for (ty, (layout, gv)) in account_data_cache.data_map {
-                match gv.into_effect()? {
-                    GlobalValueEffect::None => (),
+            for (ty, (layout, gv, read_size)) in account_data_cache.data_map {
+                let struct_tag = match self.loader.type_to_type_tag(&ty)? {
+                    TypeTag::Struct(struct_tag) => struct_tag,
+                    _ => return Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)),
+                };
+                let read_bytes = read_size.unwrap_or(0);
+                let resource_usage = match gv.into_effect()? {
+                    GlobalValueEffect::None => ResourceStorageUsage {
+                        change: ResourceChange::None,
+                        read_bytes,
+                        written_bytes: 0,
+                    },
                     GlobalValueEffect::Deleted => {
-                        let struct_tag = match self.loader.type_to_type_tag(&ty)? {
-                            TypeTag::Struct(struct_tag) => struct_tag,
-                            _ => return Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)),
-                        };
-                        resources.insert(struct_tag, None);
+                        resources.insert(struct_tag.clone(), None);
+                        ResourceStorageUsage {
+                            change: ResourceChange::Deleted,
+                            read_bytes,
+                            written_bytes: 0,
+                        }
                     }
                     GlobalValueEffect::Changed(val) => {
-                        let struct_tag = match self.loader.type_to_type_tag(&ty)? {
-                            TypeTag::Struct(struct_tag) => struct_tag,
-                            _ => return Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)),
-                        };
                         let resource_blob = val
-                            .simple_serialize(&layout)
+                            .simple_serialize_with_max_depth(&layout, max_value_depth)
                             .ok_or_else(|| PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR))?;
-                        resources.insert(struct_tag, Some(resource_blob));
+                        let written_bytes = resource_blob.len() as u64;
+                        resources.insert(struct_tag.clone(), Some(resource_blob));
+                        ResourceStorageUsage {
+                            change: if read_size.is_some() {
+                                ResourceChange::Modified
+                            } else {
+                                ResourceChange::Created
+                            },
+                            read_bytes,
+                            written_bytes,
+                        }
                     }
-                }
+                };
+                usage.resources.insert((addr, struct_tag), resource_usage);
             }
             change_set.publish_or_overwrite_account_change_set(
                 addr,
                 AccountChangeSet::from_modules_resources(modules, resources),
             );
         }
+        self.loader.vm_config().write_set_limits.check(&usage)?;
 
         let mut events = vec![];
-        for (guid, seq_num, ty, ty_layout, val) in self.event_data {
-            let ty_tag = self.loader.type_to_type_tag(&ty)?;
-            let blob = val
-                .simple_serialize(&ty_layout)
+        for (key, ty, ty_layout, val) in self.event_data {
+            let type_tag = self.loader.type_to_type_tag(&ty)?;
+            let data = val
+                .simple_serialize_with_max_depth(&ty_layout, max_value_depth)
                 .ok_or_else(|| PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR))?;
-            events.push((guid, seq_num, ty_tag, blob))
+            events.push(match key {
+                Some((guid, seq_num)) => ContractEvent::Handle {
+                    guid,
+                    seq_num,
+                    type_tag,
+                    data,
+                },
+                None => ContractEvent::Module { type_tag, data },
+            })
         }
 
-        Ok((change_set, events))
+        Ok((change_set, events, usage))
     }
 
     pub(crate) fn num_mutated_accounts(&self, sender: &AccountAddress) -> u64 {
         // The sender's account will always be mutated.
         let mut total_mutated_accounts: u64 = 1;
         for (addr, entry) in self.account_map.iter() {
-            if addr != sender && entry.data_map.values().any(|(_, v)| v.is_mutated()) {
+            if addr != sender && entry.data_map.values().any(|(_, v, _)| v.is_mutated()) {
                 total_mutated_accounts += 1;
             }
         }
@@ -168,9 +401,14 @@ impl<'r, 'l, S: MoveResolver> DataStore for TransactionDataCache<'r, 'l, S> {
             };
             let ty_layout = self.loader.type_to_type_layout(ty)?;
 
-            let gv = match self.remote.get_resource(&addr, &ty_tag) {
+            let (gv, read_size) = match self.remote.get_resource(&addr, &ty_tag) {
                 Ok(Some(blob)) => {
-                    let val = match Value::simple_deserialize(&blob, &ty_layout) {
+                    let max_value_depth = self.loader.vm_config().max_value_depth;
+                    let val = match Value::simple_deserialize_with_max_depth(
+                        &blob,
+                        &ty_layout,
+                        max_value_depth,
+                    ) {
                         Some(val) => val,
                         None => {
                             let msg =
@@ -182,9 +420,9 @@ impl<'r, 'l, S: MoveResolver> DataStore for TransactionDataCache<'r, 'l, S> {
                         }
                     };
 
-                    GlobalValue::cached(val)?
+                    (GlobalValue::cached(val)?, Some(blob.len() as u64))
                 }
-                Ok(None) => GlobalValue::none(),
+                Ok(None) => (GlobalValue::none(), None),
                 Err(err) => {
                     let msg = format!("Unexpected storage error: {:?}", err);
                     return Err(
@@ -194,13 +432,15 @@ impl<'r, 'l, S: MoveResolver> DataStore for TransactionDataCache<'r, 'l, S> {
                 }
             };
 
-            account_cache.data_map.insert(ty.clone(), (ty_layout, gv));
+            account_cache
+                .data_map
+                .insert(ty.clone(), (ty_layout, gv, read_size));
         }
 
         Ok(account_cache
             .data_map
             .get_mut(ty)
-            .map(|(_ty_layout, gv)| gv)
+            .map(|(_ty_layout, gv, _read_size)| gv)
             .expect("global value must exist"))
     }
 
@@ -262,10 +502,17 @@ impl<'r, 'l, S: MoveResolver> DataStore for TransactionDataCache<'r, 'l, S> {
         val: Value,
     ) -> PartialVMResult<()> {
         let ty_layout = self.loader.type_to_type_layout(&ty)?;
-        Ok(self.event_data.push((guid, seq_num, ty, ty_layout, val)))
+        Ok(self
+            .event_data
+            .push((Some((guid, seq_num)), ty, ty_layout, val)))
+    }
+
+    fn emit_module_event(&mut self, ty: Type, val: Value) -> PartialVMResult<()> {
+        let ty_layout = self.loader.type_to_type_layout(&ty)?;
+        Ok(self.event_data.push((None, ty, ty_layout, val)))
     }
 
-    fn events(&self) -> &Vec<(Vec<u8>, u64, Type, MoveTypeLayout, Value)> {
+    fn events(&self) -> &Vec<(Option<(Vec<u8>, u64)>, Type, MoveTypeLayout, Value)> {
         &self.event_data
     }
 }
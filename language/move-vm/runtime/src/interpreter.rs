@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    config::VMConfig,
     loader::{Function, Loader, Resolver},
     native_functions::NativeContext,
     trace,
@@ -81,7 +82,7 @@ impl Interpreter {
     ) -> VMResult<Vec<Value>> {
         // We count the intrinsic cost of the transaction here, since that needs to also cover the
         // setup of the function.
-        let mut interp = Self::new();
+        let mut interp = Self::new(loader.vm_config());
         interp.execute(
             loader, data_store, gas_status, extensions, function, ty_args, args,
         )
@@ -89,10 +90,10 @@ impl Interpreter {
 
     /// Create a new instance of an `Interpreter` in the context of a transaction with a
     /// given module cache and gas schedule.
-    fn new() -> Self {
+    fn new(config: &VMConfig) -> Self {
         Interpreter {
-            operand_stack: Stack::new(),
-            call_stack: CallStack::new(),
+            operand_stack: Stack::new(config.max_operand_stack_size),
+            call_stack: CallStack::new(config.max_call_stack_size),
         }
     }
 
@@ -151,7 +152,7 @@ impl Interpreter {
                         current_frame = frame;
                         current_frame.pc += 1; // advance past the Call instruction in the caller
                     } else {
-                        return Ok(mem::take(&mut self.operand_stack.0));
+                        return Ok(mem::take(&mut self.operand_stack.values));
                     }
                 }
                 ExitCode::Call(fh_idx) => {
@@ -508,11 +509,11 @@ impl Interpreter {
         loader: &Loader,
     ) -> PartialVMResult<()> {
         debug_writeln!(buf, "Call Stack:")?;
-        for (i, frame) in self.call_stack.0.iter().enumerate() {
+        for (i, frame) in self.call_stack.frames.iter().enumerate() {
             self.debug_print_frame(buf, loader, i, frame)?;
         }
         debug_writeln!(buf, "Operand Stack:")?;
-        for (idx, val) in self.operand_stack.0.iter().enumerate() {
+        for (idx, val) in self.operand_stack.values.iter().enumerate() {
             // TODO: Currently we do not know the types of the values on the operand stack.
             // Revisit.
             debug_write!(buf, "    [{}] ", idx)?;
@@ -530,7 +531,7 @@ impl Interpreter {
     /// of an execution.
     fn internal_state_str(&self, current_frame: &Frame) -> String {
         let mut internal_state = "Call stack:\n".to_string();
-        for (i, frame) in self.call_stack.0.iter().enumerate() {
+        for (i, frame) in self.call_stack.frames.iter().enumerate() {
             internal_state.push_str(
                 format!(
                     " frame #{}: {} [pc = {}]\n",
@@ -544,7 +545,7 @@ impl Interpreter {
         internal_state.push_str(
             format!(
                 "*frame #{}: {} [pc = {}]:\n",
-                self.call_stack.0.len(),
+                self.call_stack.frames.len(),
                 current_frame.function.pretty_string(),
                 current_frame.pc,
             )
@@ -562,7 +563,7 @@ impl Interpreter {
         }
         internal_state.push_str(format!("Locals:\n{}\n", current_frame.locals).as_str());
         internal_state.push_str("Operand Stack:\n");
-        for value in &self.operand_stack.0 {
+        for value in &self.operand_stack.values {
             internal_state.push_str(format!("{}\n", value).as_str());
         }
         internal_state
@@ -578,7 +579,7 @@ impl Interpreter {
         // is the last one)
         let stack_trace = self
             .call_stack
-            .0
+            .frames
             .iter()
             .rev()
             .map(|frame| {
@@ -593,24 +594,26 @@ impl Interpreter {
     }
 }
 
-// TODO Determine stack size limits based on gas limit
-const OPERAND_STACK_SIZE_LIMIT: usize = 1024;
-const CALL_STACK_SIZE_LIMIT: usize = 1024;
-
 /// The operand stack.
-struct Stack(Vec<Value>);
+struct Stack {
+    values: Vec<Value>,
+    max_size: usize,
+}
 
 impl Stack {
-    /// Create a new empty operand stack.
-    fn new() -> Self {
-        Stack(vec![])
+    /// Create a new empty operand stack holding at most `max_size` values.
+    fn new(max_size: usize) -> Self {
+        Stack {
+            values: vec![],
+            max_size,
+        }
     }
 
     /// Push a `Value` on the stack if the max stack size has not been reached. Abort execution
     /// otherwise.
     fn push(&mut self, value: Value) -> PartialVMResult<()> {
-        if self.0.len() < OPERAND_STACK_SIZE_LIMIT {
-            self.0.push(value);
+        if self.values.len() < self.max_size {
+            self.values.push(value);
             Ok(())
         } else {
             Err(PartialVMError::new(StatusCode::EXECUTION_STACK_OVERFLOW))
@@ -619,7 +622,7 @@ impl Stack {
 
     /// Pop a `Value` off the stack or abort execution if the stack is empty.
     fn pop(&mut self) -> PartialVMResult<Value> {
-        self.0
+        self.values
             .pop()
             .ok_or_else(|| PartialVMError::new(StatusCode::EMPTY_VALUE_STACK))
     }
@@ -636,29 +639,35 @@ impl Stack {
     /// Pop n values off the stack.
     fn popn(&mut self, n: u16) -> PartialVMResult<Vec<Value>> {
         let remaining_stack_size = self
-            .0
+            .values
             .len()
             .checked_sub(n as usize)
             .ok_or_else(|| PartialVMError::new(StatusCode::EMPTY_VALUE_STACK))?;
-        let args = self.0.split_off(remaining_stack_size);
+        let args = self.values.split_off(remaining_stack_size);
         Ok(args)
     }
 }
 
 /// A call stack.
 // #[derive(Debug)]
-struct CallStack(Vec<Frame>);
+struct CallStack {
+    frames: Vec<Frame>,
+    max_size: usize,
+}
 
 impl CallStack {
-    /// Create a new empty call stack.
-    fn new() -> Self {
-        CallStack(vec![])
+    /// Create a new empty call stack holding at most `max_size` frames.
+    fn new(max_size: usize) -> Self {
+        CallStack {
+            frames: vec![],
+            max_size,
+        }
     }
 
     /// Push a `Frame` on the call stack.
     fn push(&mut self, frame: Frame) -> ::std::result::Result<(), Frame> {
-        if self.0.len() < CALL_STACK_SIZE_LIMIT {
-            self.0.push(frame);
+        if self.frames.len() < self.max_size {
+            self.frames.push(frame);
             Ok(())
         } else {
             Err(frame)
@@ -667,11 +676,11 @@ impl CallStack {
 
     /// Pop a `Frame` off the call stack.
     fn pop(&mut self) -> Option<Frame> {
-        self.0.pop()
+        self.frames.pop()
     }
 
     fn current_location(&self) -> Location {
-        let location_opt = self.0.last().map(|frame| frame.location());
+        let location_opt = self.frames.last().map(|frame| frame.location());
         location_opt.unwrap_or(Location::Undefined)
     }
 }
@@ -730,6 +739,7 @@ impl Frame {
         gas_status: &mut GasStatus,
     ) -> PartialVMResult<ExitCode> {
         let code = self.function.code();
+        let max_value_depth = resolver.loader().vm_config().max_value_depth;
         loop {
             for instruction in &code[self.pc as usize..] {
                 trace!(
@@ -814,7 +824,9 @@ impl Frame {
                         interpreter.operand_stack.push(Value::bool(false))?;
                     }
                     Bytecode::CopyLoc(idx) => {
-                        let local = self.locals.copy_loc(*idx as usize)?;
+                        let local = self
+                            .locals
+                            .copy_loc_with_max_depth(*idx as usize, max_value_depth)?;
                         gas_status.charge_instr_with_size(Opcodes::COPY_LOC, local.size())?;
                         interpreter.operand_stack.push(local)?;
                     }
@@ -928,7 +940,7 @@ impl Frame {
                     }
                     Bytecode::ReadRef => {
                         let reference = interpreter.operand_stack.pop_as::<Reference>()?;
-                        let value = reference.read_ref()?;
+                        let value = reference.read_ref_with_max_depth(max_value_depth)?;
                         gas_status.charge_instr_with_size(Opcodes::READ_REF, value.size())?;
                         interpreter.operand_stack.push(value)?;
                     }
@@ -1053,18 +1065,18 @@ impl Frame {
                         let rhs = interpreter.operand_stack.pop()?;
                         gas_status
                             .charge_instr_with_size(Opcodes::EQ, lhs.size().add(rhs.size()))?;
-                        interpreter
-                            .operand_stack
-                            .push(Value::bool(lhs.equals(&rhs)?))?;
+                        interpreter.operand_stack.push(Value::bool(
+                            lhs.equals_with_max_depth(&rhs, max_value_depth)?,
+                        ))?;
                     }
                     Bytecode::Neq => {
                         let lhs = interpreter.operand_stack.pop()?;
                         let rhs = interpreter.operand_stack.pop()?;
                         gas_status
                             .charge_instr_with_size(Opcodes::NEQ, lhs.size().add(rhs.size()))?;
-                        interpreter
-                            .operand_stack
-                            .push(Value::bool(!lhs.equals(&rhs)?))?;
+                        interpreter.operand_stack.push(Value::bool(
+                            !lhs.equals_with_max_depth(&rhs, max_value_depth)?,
+                        ))?;
                     }
                     Bytecode::MutBorrowGlobal(sd_idx) | Bytecode::ImmBorrowGlobal(sd_idx) => {
                         let addr = interpreter.operand_stack.pop_as::<AccountAddress>()?;
@@ -1147,6 +1159,7 @@ impl Frame {
                         gas_status.charge_instr(Opcodes::NOP)?;
                     }
                     Bytecode::VecPack(si, num) => {
+                        resolver.loader().vm_config().check_vec_pack_length(*num)?;
                         let elements = interpreter.operand_stack.popn(*num as u16)?;
                         let size = AbstractMemorySize::new(*num);
                         gas_status.charge_instr_with_size(Opcodes::VEC_PACK, size)?;
@@ -9,6 +9,7 @@
 //! other blockchains can use it as well. The VM isn't there yet, but hopefully will be there
 //! soon.
 
+pub mod config;
 pub mod data_cache;
 mod interpreter;
 mod loader;
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    config::VMConfig,
     logging::expect_no_verification_errors,
     native_functions::{NativeFunction, NativeFunctions},
     session::LoadedFunctionInstantiation,
@@ -73,6 +74,12 @@ where
     fn get(&self, key: &K) -> Option<&Arc<V>> {
         self.id_map.get(key).and_then(|idx| self.binaries.get(*idx))
     }
+
+    // Removes the key from the cache, leaving the binary itself in place. Returns whether the
+    // key was in the cache.
+    fn remove(&mut self, key: &K) -> bool {
+        self.id_map.remove(key).is_some()
+    }
 }
 
 // A script cache is a map from the hash value of a script and the `Script` itself.
@@ -218,9 +225,17 @@ impl ModuleCache {
         let abilities = struct_handle.abilities;
         let name = module.identifier_at(struct_handle.name).to_owned();
         let type_parameters = struct_handle.type_parameters.clone();
+        let field_names = match &struct_def.field_information {
+            StructFieldInformation::Native => vec![],
+            StructFieldInformation::Declared(fields) => fields
+                .iter()
+                .map(|f| module.identifier_at(f.name).to_owned())
+                .collect(),
+        };
         let module = module.self_id();
         StructType {
             fields: vec![],
+            field_names,
             abilities,
             type_parameters,
             name,
@@ -382,6 +397,32 @@ impl ModuleCache {
         self.modules.id_map.contains_key(module_id)
     }
 
+    // Removes modules from the cache, together with the cached modules depending on them, so
+    // that they are loaded from storage again when used next. Their structs and functions stay
+    // in the global vectors, as values and other caches may still refer to them by index.
+    // Returns whether any module was removed.
+    fn evict(&mut self, module_ids: Vec<ModuleId>) -> bool {
+        let mut evicted = false;
+        let mut pending = module_ids;
+        while let Some(module_id) = pending.pop() {
+            // A module which is not cached has no cached dependents either
+            if !self.modules.remove(&module_id) {
+                continue;
+            }
+            evicted = true;
+            for (id, idx) in &self.modules.id_map {
+                if self.modules.binaries[*idx]
+                    .module()
+                    .immediate_dependencies()
+                    .contains(&module_id)
+                {
+                    pending.push(id.clone());
+                }
+            }
+        }
+        evicted
+    }
+
     // Given a ModuleId::struct_name, retrieve the `StructType` and the index associated.
     // Return and error if the type has not been loaded
     fn resolve_struct_by_name(
@@ -440,15 +481,30 @@ pub(crate) struct Loader {
     module_cache: RwLock<ModuleCache>,
     type_cache: RwLock<TypeCache>,
     natives: NativeFunctions,
+    vm_config: VMConfig,
 }
 
 impl Loader {
-    pub(crate) fn new(natives: NativeFunctions) -> Self {
+    pub(crate) fn new(natives: NativeFunctions, vm_config: VMConfig) -> Self {
         Self {
             scripts: RwLock::new(ScriptCache::new()),
             module_cache: RwLock::new(ModuleCache::new()),
             type_cache: RwLock::new(TypeCache::new()),
             natives,
+            vm_config,
+        }
+    }
+
+    pub(crate) fn vm_config(&self) -> &VMConfig {
+        &self.vm_config
+    }
+
+    /// Evicts modules which are no longer published, because the session which published them
+    /// rolled back to a savepoint, so that later loads do not find them in the cache.
+    pub(crate) fn evict_modules(&self, module_ids: Vec<ModuleId>) {
+        if self.module_cache.write().evict(module_ids) {
+            // Cached scripts are linked against the functions of the modules they call
+            *self.scripts.write() = ScriptCache::new();
         }
     }
 
@@ -544,7 +600,7 @@ impl Loader {
     // Script verification steps.
     // See `verify_module()` for module verification steps.
     fn verify_script(&self, script: &CompiledScript) -> VMResult<()> {
-        move_bytecode_verifier::verify_script(script)
+        move_bytecode_verifier::verify_script_with_config(&self.vm_config.verifier, script)
     }
 
     fn verify_script_dependencies(
@@ -669,7 +725,7 @@ impl Loader {
         // module will NOT show up in `module_cache`. In the module republishing case, it means
         // that the old module is still in the `module_cache`, unless a new Loader is created,
         // which means that a new MoveVM instance needs to be created.
-        move_bytecode_verifier::verify_module(module)?;
+        move_bytecode_verifier::verify_module_with_config(&self.vm_config.verifier, module)?;
         self.check_natives(module)?;
 
         let mut visited = BTreeSet::new();
@@ -739,31 +795,61 @@ impl Loader {
         )
     }
 
-    // All native functions must be known to the loader
+    // All native functions must be known to the loader, unless explicitly allowed to be missing
     fn check_natives(&self, module: &CompiledModule) -> VMResult<()> {
         fn check_natives_impl(loader: &Loader, module: &CompiledModule) -> PartialVMResult<()> {
             for (idx, native_function) in module
                 .function_defs()
                 .iter()
-                .filter(|fdv| fdv.is_native())
                 .enumerate()
+                .filter(|(_, fdv)| fdv.is_native())
             {
                 let fh = module.function_handle_at(native_function.function);
                 let mh = module.module_handle_at(fh.module);
-                loader
+                let addr = module.address_identifier_at(mh.address);
+                let module_name = module.identifier_at(mh.name).as_str();
+                let func_name = module.identifier_at(fh.name).as_str();
+                if loader
                     .natives
-                    .resolve(
-                        module.address_identifier_at(mh.address),
-                        module.identifier_at(mh.name).as_str(),
-                        module.identifier_at(fh.name).as_str(),
+                    .resolve(addr, module_name, func_name)
+                    .is_none()
+                {
+                    if loader
+                        .vm_config
+                        .missing_natives_allowed
+                        .allows(addr, module_name, func_name)
+                    {
+                        continue;
+                    }
+                    return Err(verification_error(
+                        StatusCode::MISSING_NATIVE_FUNCTION,
+                        IndexKind::FunctionDefinition,
+                        idx as TableIndex,
                     )
-                    .ok_or_else(|| {
-                        verification_error(
-                            StatusCode::MISSING_DEPENDENCY,
+                    .with_message(format!(
+                        "no implementation registered for native function 0x{}::{}::{}",
+                        addr.short_str_lossless(),
+                        module_name,
+                        func_name
+                    )));
+                }
+                // Typed natives must match their declaration
+                if let Some(signature) = loader.natives.signature(addr, module_name, func_name) {
+                    if !signature.matches(module, fh) {
+                        return Err(verification_error(
+                            StatusCode::NATIVE_FUNCTION_SIGNATURE_MISMATCH,
                             IndexKind::FunctionHandle,
-                            idx as TableIndex,
+                            native_function.function.0,
                         )
-                    })?;
+                        .with_message(format!(
+                            "expected 0x{}::{}::{} to match {:?}",
+                            addr.short_str_lossless(),
+                            module_name,
+                            func_name,
+                            signature
+                        )));
+                    }
+                }
             }
             // TODO: fix check and error code if we leave something around for native structs.
             // For now this generates the only error test cases care about...
@@ -894,7 +980,8 @@ impl Loader {
             .map_err(expect_no_verification_errors)?;
 
         // bytecode verifier checks that can be performed with the module itself
-        move_bytecode_verifier::verify_module(&module).map_err(expect_no_verification_errors)?;
+        move_bytecode_verifier::verify_module_with_config(&self.vm_config.verifier, &module)
+            .map_err(expect_no_verification_errors)?;
         self.check_natives(&module)
             .map_err(expect_no_verification_errors)?;
         Ok(module)
@@ -1094,6 +1181,7 @@ impl Loader {
             if !expected_k.is_subset(self.abilities(ty)?) {
                 return Err(PartialVMError::new(StatusCode::CONSTRAINT_NOT_SATISFIED));
             }
+            self.vm_config.check_type(ty)?;
         }
         Ok(())
     }
@@ -1130,6 +1218,18 @@ impl Loader {
         self.module_cache.read().structs.get(idx.0).map(Arc::clone)
     }
 
+    pub(crate) fn get_function_by_name(
+        &self,
+        module_id: &ModuleId,
+        func_name: &IdentStr,
+    ) -> Option<Arc<Function>> {
+        let module_cache = self.module_cache.read();
+        module_cache
+            .resolve_function_by_name(func_name, module_id)
+            .ok()
+            .map(|idx| module_cache.function_at(idx))
+    }
+
     fn abilities(&self, ty: &Type) -> PartialVMResult<AbilitySet> {
         match ty {
             Type::Bool | Type::U8 | Type::U64 | Type::U128 | Type::Address => {
@@ -1244,7 +1344,9 @@ impl<'a> Resolver<'a> {
         };
         let mut instantiation = vec![];
         for ty in &func_inst.instantiation {
-            instantiation.push(ty.subst(type_params)?);
+            let ty = ty.subst(type_params)?;
+            self.loader.vm_config.check_type(&ty)?;
+            instantiation.push(ty);
         }
         Ok(instantiation)
     }
@@ -1278,14 +1380,16 @@ impl<'a> Resolver<'a> {
             BinaryType::Module(module) => module.struct_instantiation_at(idx.0),
             BinaryType::Script(_) => unreachable!("Scripts cannot have type instructions"),
         };
-        Ok(Type::StructInstantiation(
+        let ty = Type::StructInstantiation(
             struct_inst.def,
             struct_inst
                 .instantiation
                 .iter()
                 .map(|ty| ty.subst(ty_args))
                 .collect::<PartialVMResult<_>>()?,
-        ))
+        );
+        self.loader.vm_config.check_type(&ty)?;
+        Ok(ty)
     }
 
     fn single_type_at(&self, idx: SignatureIndex) -> &Type {
@@ -1300,8 +1404,9 @@ impl<'a> Resolver<'a> {
         idx: SignatureIndex,
         ty_args: &[Type],
     ) -> PartialVMResult<Type> {
-        let ty = self.single_type_at(idx);
-        ty.subst(ty_args)
+        let ty = self.single_type_at(idx).subst(ty_args)?;
+        self.loader.vm_config.check_type(&ty)?;
+        Ok(ty)
     }
 
     //
@@ -1743,7 +1848,9 @@ impl Script {
         let type_parameters = script.type_parameters.clone();
         // TODO: main does not have a name. Revisit.
         let name = Identifier::new("main").unwrap();
-        let native = None; // Script entries cannot be native
+        // Script entries cannot be native
+        let is_native = false;
+        let native = None;
         let main: Arc<Function> = Arc::new(Function {
             file_format_version: script.version(),
             index: FunctionDefinitionIndex(0),
@@ -1752,6 +1859,7 @@ impl Script {
             return_,
             locals,
             type_parameters,
+            is_native,
             native,
             scope,
             name,
@@ -1843,6 +1951,7 @@ pub(crate) struct Function {
     return_: Signature,
     locals: Signature,
     type_parameters: Vec<AbilitySet>,
+    is_native: bool,
     native: Option<NativeFunction>,
     scope: Scope,
     name: Identifier,
@@ -1858,7 +1967,8 @@ impl Function {
         let handle = module.function_handle_at(def.function);
         let name = module.identifier_at(handle.name).to_owned();
         let module_id = module.self_id();
-        let native = if def.is_native() {
+        let is_native = def.is_native();
+        let native = if is_native {
             natives.resolve(
                 module_id.address(),
                 module_id.name().as_str(),
@@ -1894,6 +2004,7 @@ impl Function {
             return_,
             locals,
             type_parameters,
+            is_native,
             native,
             scope,
             name,
@@ -1967,13 +2078,14 @@ impl Function {
     }
 
     pub(crate) fn is_native(&self) -> bool {
-        self.native.is_some()
+        self.is_native
     }
 
     pub(crate) fn get_native(&self) -> PartialVMResult<NativeFunction> {
+        // Natives may only be missing if they were allowed to when the module was loaded
         self.native.ok_or_else(|| {
-            PartialVMError::new(StatusCode::UNREACHABLE)
-                .with_message("Missing Native Function".to_string())
+            PartialVMError::new(StatusCode::MISSING_NATIVE_FUNCTION)
+                .with_message(format!("Missing Native Function {}", self.name))
         })
     }
 }
@@ -2058,8 +2170,6 @@ impl TypeCache {
     }
 }
 
-const VALUE_DEPTH_MAX: usize = 128;
-
 impl Loader {
     fn struct_gidx_to_type_tag(
         &self,
@@ -2159,7 +2269,7 @@ impl Loader {
     }
 
     fn type_to_type_layout_impl(&self, ty: &Type, depth: usize) -> PartialVMResult<MoveTypeLayout> {
-        if depth > VALUE_DEPTH_MAX {
+        if depth > self.vm_config.max_value_depth {
             return Err(PartialVMError::new(StatusCode::VM_MAX_VALUE_DEPTH_REACHED));
         }
         Ok(match ty {
@@ -4,28 +4,28 @@
 use std::sync::Arc;
 
 use crate::{
-    data_cache::TransactionDataCache, native_extensions::NativeContextExtensions,
-    native_functions::NativeFunction, runtime::VMRuntime, session::Session,
+    config::VMConfig, data_cache::TransactionDataCache, native_extensions::NativeContextExtensions,
+    native_functions::NativeFunctionEntry, runtime::VMRuntime, session::Session,
 };
 use move_binary_format::{
     errors::{Location, VMResult},
     CompiledModule,
 };
-use move_core_types::{
-    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
-    resolver::MoveResolver,
-};
+use move_core_types::{language_storage::ModuleId, resolver::MoveResolver};
 
 pub struct MoveVM {
     runtime: VMRuntime,
 }
 
 impl MoveVM {
+    /// Create a new VM enforcing the limits of `vm_config`.
     pub fn new(
-        natives: impl IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
+        natives: impl IntoIterator<Item = NativeFunctionEntry>,
+        vm_config: VMConfig,
     ) -> VMResult<Self> {
         Ok(Self {
-            runtime: VMRuntime::new(natives).map_err(|err| err.finish(Location::Undefined))?,
+            runtime: VMRuntime::new(natives, vm_config)
+                .map_err(|err| err.finish(Location::Undefined))?,
         })
     }
 
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use better_any::{Tid, TidAble, TidExt};
+use move_binary_format::errors::PartialVMResult;
 use std::{any::TypeId, collections::HashMap};
 
 /// A data type to represent a heterogeneous collection of extensions which are available to
@@ -12,9 +13,33 @@ use std::{any::TypeId, collections::HashMap};
 /// avoids that extensions need to have `'static` lifetime, which `Any` requires. In order to make a
 /// struct suitable to be a 'Tid', use `#[derive(Tid)]` in the struct declaration. (See also
 /// tests at the end of this module.)
+///
+/// Extensions which keep state that must follow the session's savepoints (see
+/// `Session::savepoint`) implement `SessionExtension` and are registered with
+/// `add_with_savepoints`. The state of extensions registered with `add` is not affected by
+/// rollbacks.
 #[derive(Default)]
 pub struct NativeContextExtensions<'a> {
     map: HashMap<TypeId, Box<dyn Tid<'a>>>,
+    savepoint_aware: HashMap<TypeId, AsSessionExtension<'a>>,
+}
+
+/// An extension whose state can be saved and restored along with the session's savepoints.
+///
+/// Savepoints form a stack: `savepoint` pushes the current state, `rollback` restores the state
+/// on top of the stack while keeping it there, and `release` pops it without restoring it.
+pub trait SessionExtension {
+    fn savepoint(&mut self) -> PartialVMResult<()>;
+    fn rollback(&mut self) -> PartialVMResult<()>;
+    fn release(&mut self) -> PartialVMResult<()>;
+}
+
+type AsSessionExtension<'a> = for<'b> fn(&'b mut dyn Tid<'a>) -> &'b mut dyn SessionExtension;
+
+fn as_session_extension<'a, 'b, T: TidAble<'a> + SessionExtension>(
+    ext: &'b mut dyn Tid<'a>,
+) -> &'b mut dyn SessionExtension {
+    ext.downcast_mut::<T>().unwrap()
 }
 
 impl<'a> NativeContextExtensions<'a> {
@@ -25,6 +50,37 @@ impl<'a> NativeContextExtensions<'a> {
         )
     }
 
+    /// Adds an extension whose state is saved and restored along with the session's savepoints.
+    pub fn add_with_savepoints<T: TidAble<'a> + SessionExtension>(&mut self, ext: T) {
+        self.add(ext);
+        self.savepoint_aware
+            .insert(T::id(), as_session_extension::<T>);
+    }
+
+    pub(crate) fn savepoint(&mut self) -> PartialVMResult<()> {
+        self.for_each_session_extension(|ext| ext.savepoint())
+    }
+
+    pub(crate) fn rollback(&mut self) -> PartialVMResult<()> {
+        self.for_each_session_extension(|ext| ext.rollback())
+    }
+
+    pub(crate) fn release(&mut self) -> PartialVMResult<()> {
+        self.for_each_session_extension(|ext| ext.release())
+    }
+
+    fn for_each_session_extension(
+        &mut self,
+        f: impl Fn(&mut dyn SessionExtension) -> PartialVMResult<()>,
+    ) -> PartialVMResult<()> {
+        for (id, cast) in &self.savepoint_aware {
+            if let Some(ext) = self.map.get_mut(id) {
+                f(cast(ext.as_mut()))?
+            }
+        }
+        Ok(())
+    }
+
     pub fn get<T: TidAble<'a>>(&self) -> &T {
         self.map
             .get(&T::id())
@@ -44,6 +100,7 @@ impl<'a> NativeContextExtensions<'a> {
     }
 
     pub fn remove<T: TidAble<'a>>(&mut self) -> T {
+        self.savepoint_aware.remove(&T::id());
         // can't use expect below because it requires `T: Debug`.
         match self
             .map
@@ -4,50 +4,123 @@
 use crate::{
     interpreter::Interpreter, loader::Resolver, native_extensions::NativeContextExtensions,
 };
-use move_binary_format::errors::{PartialVMError, PartialVMResult};
+use move_binary_format::{
+    access::ModuleAccess,
+    errors::{PartialVMError, PartialVMResult},
+    file_format::{FunctionHandle, SignatureToken},
+    CompiledModule,
+};
 use move_core_types::{
     account_address::AccountAddress,
-    gas_schedule::CostTable,
-    identifier::Identifier,
-    language_storage::TypeTag,
+    gas_schedule::{CostTable, GasCarrier, InternalGasUnits},
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, TypeTag},
     value::MoveTypeLayout,
     vm_status::{StatusCode, StatusType},
 };
 use move_vm_types::{
-    data_store::DataStore, gas_schedule::GasStatus, loaded_data::runtime_types::Type,
-    natives::function::NativeResult, values::Value,
+    data_store::DataStore,
+    gas_schedule::GasStatus,
+    loaded_data::runtime_types::Type,
+    natives::function::NativeResult,
+    values::{Reference, SignerRef, Struct, StructRef, Value, Vector, VectorRef},
 };
+use smallvec::{smallvec, SmallVec};
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeSet, HashMap, VecDeque},
     fmt::Write,
 };
 
 pub type NativeFunction =
     fn(&mut NativeContext, Vec<Type>, VecDeque<Value>) -> PartialVMResult<NativeResult>;
 
-pub type NativeFunctionTable = Vec<(AccountAddress, Identifier, Identifier, NativeFunction)>;
+/// A native function along with the Move function it implements.
+#[derive(Clone)]
+pub struct NativeFunctionEntry {
+    pub address: AccountAddress,
+    pub module_name: Identifier,
+    pub function_name: Identifier,
+    pub function: NativeFunction,
+    /// The signature expected for the Move declaration of the native, checked when the module
+    /// declaring it is loaded. Untyped natives do not declare any.
+    pub signature: Option<NativeSignature>,
+}
+
+pub type NativeFunctionTable = Vec<NativeFunctionEntry>;
 
 pub fn make_table(
     addr: AccountAddress,
     elems: &[(&str, &str, NativeFunction)],
 ) -> NativeFunctionTable {
-    elems
-        .iter()
-        .cloned()
-        .map(|(module_name, func_name, func)| {
-            (
-                addr,
-                Identifier::new(module_name).unwrap(),
-                Identifier::new(func_name).unwrap(),
-                func,
-            )
-        })
-        .collect()
-}
-
-pub(crate) struct NativeFunctions(
-    HashMap<AccountAddress, HashMap<String, HashMap<String, NativeFunction>>>,
-);
+    let mut builder = NativeFunctionTableBuilder::new(addr);
+    for (module_name, func_name, func) in elems.iter().cloned() {
+        builder.add(module_name, func_name, func);
+    }
+    builder.build()
+}
+
+/// Builds the table of natives defined at a given address, mixing typed natives (see
+/// `TypedNativeFunction`) and untyped ones.
+pub struct NativeFunctionTableBuilder {
+    addr: AccountAddress,
+    table: NativeFunctionTable,
+}
+
+impl NativeFunctionTableBuilder {
+    pub fn new(addr: AccountAddress) -> Self {
+        Self {
+            addr,
+            table: vec![],
+        }
+    }
+
+    /// Add an untyped native, whose declaration is not checked at load time.
+    pub fn add(&mut self, module_name: &str, func_name: &str, func: NativeFunction) -> &mut Self {
+        self.push(module_name, func_name, func, None)
+    }
+
+    /// Add a typed native. Its Move declaration must match the Rust signature of `F`.
+    pub fn add_typed<F: TypedNativeFunction>(
+        &mut self,
+        module_name: &str,
+        func_name: &str,
+    ) -> &mut Self {
+        self.push(
+            module_name,
+            func_name,
+            typed_native_function::<F>,
+            Some(NativeSignature::of::<F>()),
+        )
+    }
+
+    pub fn build(&mut self) -> NativeFunctionTable {
+        std::mem::take(&mut self.table)
+    }
+
+    fn push(
+        &mut self,
+        module_name: &str,
+        func_name: &str,
+        function: NativeFunction,
+        signature: Option<NativeSignature>,
+    ) -> &mut Self {
+        self.table.push(NativeFunctionEntry {
+            address: self.addr,
+            module_name: Identifier::new(module_name).unwrap(),
+            function_name: Identifier::new(func_name).unwrap(),
+            function,
+            signature,
+        });
+        self
+    }
+}
+
+pub(crate) struct NativeFunctions {
+    natives: HashMap<
+        AccountAddress,
+        HashMap<String, HashMap<String, (NativeFunction, Option<NativeSignature>)>>,
+    >,
+}
 
 impl NativeFunctions {
     pub fn resolve(
@@ -56,26 +129,438 @@ impl NativeFunctions {
         module_name: &str,
         func_name: &str,
     ) -> Option<NativeFunction> {
-        self.0.get(addr)?.get(module_name)?.get(func_name).cloned()
+        self.get(addr, module_name, func_name)
+            .map(|(func, _)| *func)
+    }
+
+    /// The signature declared by the native, if it is known and typed.
+    pub fn signature(
+        &self,
+        addr: &AccountAddress,
+        module_name: &str,
+        func_name: &str,
+    ) -> Option<&NativeSignature> {
+        self.get(addr, module_name, func_name)?.1.as_ref()
+    }
+
+    fn get(
+        &self,
+        addr: &AccountAddress,
+        module_name: &str,
+        func_name: &str,
+    ) -> Option<&(NativeFunction, Option<NativeSignature>)> {
+        self.natives.get(addr)?.get(module_name)?.get(func_name)
     }
 
     pub fn new<I>(natives: I) -> PartialVMResult<Self>
     where
-        I: IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
+        I: IntoIterator<Item = NativeFunctionEntry>,
     {
         let mut map = HashMap::new();
-        for (addr, module_name, func_name, func) in natives.into_iter() {
-            let modules = map.entry(addr).or_insert_with(HashMap::new);
+        for entry in natives.into_iter() {
+            let modules = map.entry(entry.address).or_insert_with(HashMap::new);
             let funcs = modules
-                .entry(module_name.into_string())
+                .entry(entry.module_name.into_string())
                 .or_insert_with(HashMap::new);
 
-            if funcs.insert(func_name.into_string(), func).is_some() {
+            if funcs
+                .insert(
+                    entry.function_name.into_string(),
+                    (entry.function, entry.signature),
+                )
+                .is_some()
+            {
                 return Err(PartialVMError::new(StatusCode::DUPLICATE_NATIVE_FUNCTION));
             }
         }
-        Ok(Self(map))
+        Ok(Self { natives: map })
+    }
+}
+
+/// Native function declarations which can be published and loaded even though no implementation
+/// is registered for them, e.g. while developing a package against a VM lacking some natives.
+/// Calling such a native fails with `MISSING_NATIVE_FUNCTION`. See
+/// `VMConfig::missing_natives_allowed`.
+#[derive(Clone, Debug, Default)]
+pub struct MissingNativesAllowList {
+    all: bool,
+    modules: BTreeSet<(AccountAddress, Identifier)>,
+    functions: BTreeSet<(AccountAddress, Identifier, Identifier)>,
+}
+
+impl MissingNativesAllowList {
+    /// Allow any native to be missing.
+    pub fn all() -> Self {
+        Self {
+            all: true,
+            ..Self::default()
+        }
+    }
+
+    /// Allow all the natives declared by the given module to be missing.
+    pub fn allow_module(&mut self, addr: AccountAddress, module_name: Identifier) -> &mut Self {
+        self.modules.insert((addr, module_name));
+        self
+    }
+
+    /// Allow the given native to be missing.
+    pub fn allow_function(
+        &mut self,
+        addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+    ) -> &mut Self {
+        self.functions.insert((addr, module_name, func_name));
+        self
+    }
+
+    pub fn allows(&self, addr: &AccountAddress, module_name: &str, func_name: &str) -> bool {
+        self.all
+            || self
+                .modules
+                .iter()
+                .any(|(a, m)| a == addr && m.as_str() == module_name)
+            || self
+                .functions
+                .iter()
+                .any(|(a, m, f)| a == addr && m.as_str() == module_name && f.as_str() == func_name)
+    }
+}
+
+/// The Move types a typed native accepts for one of its parameters or return values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NativeType {
+    Bool,
+    U8,
+    U64,
+    U128,
+    Address,
+    Signer,
+    Vector(Box<NativeType>),
+    /// Any struct type.
+    Struct,
+    /// A reference, mutable or not, to the given type.
+    Reference(Box<NativeType>),
+    /// A mutable reference to the given type.
+    MutableReference(Box<NativeType>),
+    /// Any type, typically a type parameter of the native.
+    Any,
+}
+
+impl NativeType {
+    fn matches(&self, token: &SignatureToken) -> bool {
+        use NativeType as N;
+        use SignatureToken as S;
+
+        match (self, token) {
+            (N::Any, _) => true,
+            (N::Bool, S::Bool)
+            | (N::U8, S::U8)
+            | (N::U64, S::U64)
+            | (N::U128, S::U128)
+            | (N::Address, S::Address)
+            | (N::Signer, S::Signer)
+            | (N::Struct, S::Struct(_))
+            | (N::Struct, S::StructInstantiation(_, _)) => true,
+            (N::Vector(ty), S::Vector(tok))
+            | (N::Reference(ty), S::Reference(tok))
+            | (N::Reference(ty), S::MutableReference(tok))
+            | (N::MutableReference(ty), S::MutableReference(tok)) => ty.matches(tok),
+            _ => false,
+        }
+    }
+}
+
+/// The signature of a typed native, as derived from its Rust types.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativeSignature {
+    pub type_parameters: usize,
+    pub parameters: Vec<NativeType>,
+    pub return_: Vec<NativeType>,
+}
+
+impl NativeSignature {
+    pub fn of<F: TypedNativeFunction>() -> Self {
+        Self {
+            type_parameters: F::TYPE_PARAMETERS,
+            parameters: F::Args::native_types(),
+            return_: F::Return::native_types(),
+        }
+    }
+
+    /// Whether the Move declaration `handle` of `module` is compatible with this signature.
+    pub(crate) fn matches(&self, module: &CompiledModule, handle: &FunctionHandle) -> bool {
+        fn matches_all(types: &[NativeType], tokens: &[SignatureToken]) -> bool {
+            types.len() == tokens.len() && types.iter().zip(tokens).all(|(ty, tok)| ty.matches(tok))
+        }
+
+        handle.type_parameters.len() == self.type_parameters
+            && matches_all(&self.parameters, &module.signature_at(handle.parameters).0)
+            && matches_all(&self.return_, &module.signature_at(handle.return_).0)
+    }
+}
+
+/// A Rust type a typed native can take as a parameter.
+pub trait NativeArgument: Sized {
+    fn native_type() -> NativeType;
+
+    fn from_value(value: Value) -> PartialVMResult<Self>;
+}
+
+/// A Rust type a typed native can return.
+pub trait NativeReturnValue {
+    fn native_type() -> NativeType;
+
+    fn into_value(self) -> Value;
+}
+
+macro_rules! impl_native_argument {
+    ($ty:ty, $native_ty:expr) => {
+        impl NativeArgument for $ty {
+            fn native_type() -> NativeType {
+                $native_ty
+            }
+
+            fn from_value(value: Value) -> PartialVMResult<Self> {
+                value.value_as::<$ty>()
+            }
+        }
+    };
+}
+
+macro_rules! impl_native_return_value {
+    ($ty:ty, $native_ty:expr, $ctor:path) => {
+        impl NativeReturnValue for $ty {
+            fn native_type() -> NativeType {
+                $native_ty
+            }
+
+            fn into_value(self) -> Value {
+                $ctor(self)
+            }
+        }
+    };
+}
+
+impl_native_argument!(u8, NativeType::U8);
+impl_native_argument!(u64, NativeType::U64);
+impl_native_argument!(u128, NativeType::U128);
+impl_native_argument!(bool, NativeType::Bool);
+impl_native_argument!(AccountAddress, NativeType::Address);
+impl_native_argument!(Vec<u8>, NativeType::Vector(Box::new(NativeType::U8)));
+impl_native_argument!(Struct, NativeType::Struct);
+impl_native_argument!(Vector, NativeType::Vector(Box::new(NativeType::Any)));
+impl_native_argument!(
+    SignerRef,
+    NativeType::Reference(Box::new(NativeType::Signer))
+);
+impl_native_argument!(
+    StructRef,
+    NativeType::Reference(Box::new(NativeType::Struct))
+);
+impl_native_argument!(
+    VectorRef,
+    NativeType::Reference(Box::new(NativeType::Vector(Box::new(NativeType::Any))))
+);
+impl_native_argument!(Reference, NativeType::Reference(Box::new(NativeType::Any)));
+
+impl NativeArgument for Value {
+    fn native_type() -> NativeType {
+        NativeType::Any
     }
+
+    fn from_value(value: Value) -> PartialVMResult<Self> {
+        Ok(value)
+    }
+}
+
+impl_native_return_value!(u8, NativeType::U8, Value::u8);
+impl_native_return_value!(u64, NativeType::U64, Value::u64);
+impl_native_return_value!(u128, NativeType::U128, Value::u128);
+impl_native_return_value!(bool, NativeType::Bool, Value::bool);
+impl_native_return_value!(AccountAddress, NativeType::Address, Value::address);
+impl_native_return_value!(
+    Vec<u8>,
+    NativeType::Vector(Box::new(NativeType::U8)),
+    Value::vector_u8
+);
+impl_native_return_value!(
+    Vector,
+    NativeType::Vector(Box::new(NativeType::Any)),
+    Value::from_vector
+);
+impl_native_return_value!(
+    Reference,
+    NativeType::Reference(Box::new(NativeType::Any)),
+    Value::from_reference
+);
+impl_native_return_value!(Value, NativeType::Any, std::convert::identity);
+
+/// The parameters of a typed native: a tuple of `NativeArgument`s.
+pub trait NativeArguments: Sized {
+    /// The number of parameters.
+    const ARITY: usize;
+
+    fn native_types() -> Vec<NativeType>;
+
+    /// Convert the arguments, passed with the first argument at position 0.
+    fn from_values(args: VecDeque<Value>) -> PartialVMResult<Self>;
+}
+
+/// The return values of a typed native: a single `NativeReturnValue` or a tuple of them.
+pub trait NativeReturnValues {
+    fn native_types() -> Vec<NativeType>;
+
+    fn into_values(self) -> SmallVec<[Value; 1]>;
+}
+
+macro_rules! impl_native_tuples {
+    ($($t:ident),*) => {
+        impl<$($t: NativeArgument),*> NativeArguments for ($($t,)*) {
+            const ARITY: usize = <[&str]>::len(&[$(stringify!($t)),*]);
+
+            fn native_types() -> Vec<NativeType> {
+                vec![$($t::native_type()),*]
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn from_values(mut args: VecDeque<Value>) -> PartialVMResult<Self> {
+                Ok(($($t::from_value(args.pop_front().ok_or_else(|| {
+                    PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                })?)?,)*))
+            }
+        }
+
+        impl<$($t: NativeReturnValue),*> NativeReturnValues for ($($t,)*) {
+            fn native_types() -> Vec<NativeType> {
+                vec![$($t::native_type()),*]
+            }
+
+            #[allow(non_snake_case)]
+            fn into_values(self) -> SmallVec<[Value; 1]> {
+                let ($($t,)*) = self;
+                smallvec![$($t.into_value()),*]
+            }
+        }
+    };
+}
+
+impl_native_tuples!();
+impl_native_tuples!(A);
+impl_native_tuples!(A, B);
+impl_native_tuples!(A, B, C);
+impl_native_tuples!(A, B, C, D);
+
+macro_rules! impl_native_return_values {
+    ($($ty:ty),*) => {
+        $(
+            impl NativeReturnValues for $ty {
+                fn native_types() -> Vec<NativeType> {
+                    vec![<$ty as NativeReturnValue>::native_type()]
+                }
+
+                fn into_values(self) -> SmallVec<[Value; 1]> {
+                    smallvec![self.into_value()]
+                }
+            }
+        )*
+    };
+}
+
+impl_native_return_values!(
+    u8,
+    u64,
+    u128,
+    bool,
+    AccountAddress,
+    Vec<u8>,
+    Vector,
+    Reference,
+    Value
+);
+
+/// The result of a typed native, see `NativeResult`.
+pub struct TypedNativeResult<R> {
+    pub cost: InternalGasUnits<GasCarrier>,
+    pub result: Result<R, u64>,
+}
+
+impl<R> TypedNativeResult<R> {
+    pub fn ok(cost: InternalGasUnits<GasCarrier>, value: R) -> Self {
+        Self {
+            cost,
+            result: Ok(value),
+        }
+    }
+
+    pub fn err(cost: InternalGasUnits<GasCarrier>, abort_code: u64) -> Self {
+        Self {
+            cost,
+            result: Err(abort_code),
+        }
+    }
+
+    /// Convert a `PartialVMResult<R>` into a `PartialVMResult<TypedNativeResult<R>>`, turning
+    /// `ABORTED` errors into aborts of the native.
+    pub fn map_partial_vm_result(
+        cost: InternalGasUnits<GasCarrier>,
+        res: PartialVMResult<R>,
+    ) -> PartialVMResult<Self> {
+        match res {
+            Ok(value) => Ok(Self::ok(cost, value)),
+            Err(err) if err.major_status() == StatusCode::ABORTED => {
+                let (_, abort_code, _, _, _, _) = err.all_data();
+                Ok(Self::err(
+                    cost,
+                    abort_code.unwrap_or(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR as u64),
+                ))
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// A native function with typed parameters and return values.
+///
+/// The arguments are converted before `execute` is called and the Move declaration of the
+/// native is checked against the Rust types when the declaring module is loaded. Typed natives
+/// are registered with `NativeFunctionTableBuilder::add_typed`.
+pub trait TypedNativeFunction {
+    /// The number of type parameters of the native.
+    const TYPE_PARAMETERS: usize = 0;
+
+    type Args: NativeArguments;
+    type Return: NativeReturnValues;
+
+    fn execute(
+        context: &mut NativeContext,
+        ty_args: Vec<Type>,
+        args: Self::Args,
+    ) -> PartialVMResult<TypedNativeResult<Self::Return>>;
+}
+
+fn typed_native_function<F: TypedNativeFunction>(
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    if ty_args.len() != F::TYPE_PARAMETERS || args.len() != F::Args::ARITY {
+        return Err(
+            PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR).with_message(
+                format!(
+                    "native called with {} type arguments and {} arguments",
+                    ty_args.len(),
+                    args.len()
+                ),
+            ),
+        );
+    }
+    let args = F::Args::from_values(args)?;
+    let TypedNativeResult { cost, result } = F::execute(context, ty_args, args)?;
+    Ok(NativeResult {
+        cost,
+        result: result.map(NativeReturnValues::into_values),
+    })
 }
 
 pub struct NativeContext<'a, 'b> {
@@ -128,7 +613,17 @@ impl<'a, 'b> NativeContext<'a, 'b> {
         }
     }
 
-    pub fn events(&self) -> &Vec<(Vec<u8>, u64, Type, MoveTypeLayout, Value)> {
+    /// Save an event which is not associated with an event handle. Like `save_event`, returns
+    /// false if the event could not be recorded.
+    pub fn save_module_event(&mut self, ty: Type, val: Value) -> PartialVMResult<bool> {
+        match self.data_store.emit_module_event(ty, val) {
+            Ok(()) => Ok(true),
+            Err(e) if e.major_status().status_type() == StatusType::InvariantViolation => Err(e),
+            Err(_) => Ok(false),
+        }
+    }
+
+    pub fn events(&self) -> &Vec<(Option<(Vec<u8>, u64)>, Type, MoveTypeLayout, Value)> {
         self.data_store.events()
     }
 
@@ -144,6 +639,39 @@ impl<'a, 'b> NativeContext<'a, 'b> {
         }
     }
 
+    /// Returns the names of the fields of the struct type `ty`, as declared in its module.
+    pub fn struct_field_names(&self, ty: &Type) -> PartialVMResult<Vec<Identifier>> {
+        let idx = match ty {
+            Type::Struct(idx) | Type::StructInstantiation(idx, _) => *idx,
+            _ => {
+                return Err(
+                    PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                        .with_message(format!("type `{:?}` is not a struct", ty)),
+                )
+            }
+        };
+        match self.resolver.loader().get_struct_type(idx) {
+            Some(struct_type) => Ok(struct_type.field_names.clone()),
+            None => Err(
+                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                    .with_message(format!("unknown struct type index `{}`", idx.0)),
+            ),
+        }
+    }
+
+    /// Returns the number of parameters of a function, or `None` if its module has not been
+    /// loaded or does not define the function.
+    pub fn function_parameter_count(
+        &self,
+        module_id: &ModuleId,
+        function_name: &IdentStr,
+    ) -> Option<usize> {
+        self.resolver
+            .loader()
+            .get_function_by_name(module_id, function_name)
+            .map(|function| function.arg_count())
+    }
+
     pub fn extensions(&self) -> &NativeContextExtensions<'b> {
         self.extensions
     }
@@ -2,11 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    config::VMConfig,
     data_cache::TransactionDataCache,
     interpreter::Interpreter,
     loader::{Function, Loader},
     native_extensions::NativeContextExtensions,
-    native_functions::{NativeFunction, NativeFunctions},
+    native_functions::{NativeFunctionEntry, NativeFunctions},
     session::{LoadedFunctionInstantiation, SerializedReturnValues, Session},
 };
 use move_binary_format::{
@@ -19,21 +20,30 @@ use move_binary_format::{
 use move_bytecode_verifier::script_signature;
 use move_core_types::{
     account_address::AccountAddress,
-    identifier::{IdentStr, Identifier},
+    identifier::IdentStr,
     language_storage::{ModuleId, TypeTag},
     resolver::MoveResolver,
-    value::MoveTypeLayout,
+    transaction_argument::{is_ascii_string, is_option, is_valid_ascii},
+    value::{MoveTypeLayout, MoveValue},
     vm_status::StatusCode,
 };
 use move_vm_types::{
     data_store::DataStore,
     gas_schedule::GasStatus,
     loaded_data::runtime_types::Type,
-    values::{Locals, Reference, VMValueCast, Value},
+    values::{Locals, Reference, Struct, VMValueCast, Value, Vector},
 };
 use std::{borrow::Borrow, collections::BTreeSet, sync::Arc};
 use tracing::warn;
 
+fn contains_struct(ty: &Type) -> bool {
+    match ty {
+        Type::Vector(ty) => contains_struct(ty),
+        Type::Struct(_) | Type::StructInstantiation(_, _) => true,
+        _ => false,
+    }
+}
+
 /// An instantiation of the MoveVM.
 pub(crate) struct VMRuntime {
     loader: Loader,
@@ -41,10 +51,11 @@ pub(crate) struct VMRuntime {
 
 impl VMRuntime {
     pub(crate) fn new(
-        natives: impl IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
+        natives: impl IntoIterator<Item = NativeFunctionEntry>,
+        vm_config: VMConfig,
     ) -> PartialVMResult<Self> {
         Ok(VMRuntime {
-            loader: Loader::new(NativeFunctions::new(natives)?),
+            loader: Loader::new(NativeFunctions::new(natives)?, vm_config),
         })
     }
 
@@ -53,6 +64,8 @@ impl VMRuntime {
             runtime: self,
             data_cache: TransactionDataCache::new(remote, &self.loader),
             native_extensions: NativeContextExtensions::default(),
+            savepoints: vec![],
+            next_savepoint: 0,
         }
     }
 
@@ -65,6 +78,8 @@ impl VMRuntime {
             runtime: self,
             data_cache: TransactionDataCache::new(remote, &self.loader),
             native_extensions,
+            savepoints: vec![],
+            next_savepoint: 0,
         }
     }
 
@@ -196,9 +211,17 @@ impl VMRuntime {
         Ok(())
     }
 
-    fn deserialize_value(&self, ty: &Type, arg: impl Borrow<[u8]>) -> PartialVMResult<Value> {
+    fn deserialize_value(
+        &self,
+        ty: &Type,
+        arg: impl Borrow<[u8]>,
+        bypass_visibility: bool,
+    ) -> PartialVMResult<Value> {
         let layout = match self.loader.type_to_type_layout(ty) {
             Ok(layout) => layout,
+            Err(err) if err.major_status() == StatusCode::VM_MAX_VALUE_DEPTH_REACHED => {
+                return Err(err)
+            }
             Err(_err) => {
                 warn!("[VM] failed to get layout from type");
                 return Err(PartialVMError::new(
@@ -207,7 +230,28 @@ impl VMRuntime {
             }
         };
 
-        match Value::simple_deserialize(arg.borrow(), &layout) {
+        let max_value_depth = self.loader.vm_config().max_value_depth;
+        if contains_struct(ty) {
+            if !bypass_visibility {
+                self.check_struct_argument_type(ty)?;
+            }
+            // Structs are deserialized as `MoveValue`s, whose contents can be checked against
+            // the invariants of their type, and then turned into VM values
+            return match MoveValue::simple_deserialize_with_max_depth(
+                arg.borrow(),
+                &layout,
+                max_value_depth,
+            ) {
+                Ok(value) => self.struct_argument_value(ty, &value),
+                Err(_) => {
+                    warn!("[VM] failed to deserialize argument");
+                    Err(PartialVMError::new(
+                        StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT,
+                    ))
+                }
+            };
+        }
+        match Value::simple_deserialize_with_max_depth(arg.borrow(), &layout, max_value_depth) {
             Some(val) => Ok(val),
             None => {
                 warn!("[VM] failed to deserialize argument");
@@ -218,10 +262,117 @@ impl VMRuntime {
         }
     }
 
+    /// Checks that the only structs an entry point argument of type `ty` can hold are the
+    /// standard library structs accepted as arguments, `0x1::ASCII::String` and
+    /// `0x1::Option::Option`.
+    fn check_struct_argument_type(&self, ty: &Type) -> PartialVMResult<()> {
+        let (gidx, ty_args) = match ty {
+            Type::Vector(ty) => return self.check_struct_argument_type(ty),
+            Type::Struct(gidx) => (*gidx, &[][..]),
+            Type::StructInstantiation(gidx, ty_args) => (*gidx, &ty_args[..]),
+            _ => return Ok(()),
+        };
+        let struct_type = self.loader.get_struct_type(gidx).ok_or_else(|| {
+            PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                .with_message("struct type of an argument is not loaded".to_string())
+        })?;
+        let (address, module, name) = (
+            struct_type.module.address(),
+            struct_type.module.name(),
+            struct_type.name.as_ident_str(),
+        );
+        if !is_ascii_string(address, module, name) && !is_option(address, module, name) {
+            return Err(
+                PartialVMError::new(StatusCode::INVALID_PARAM_TYPE_FOR_DESERIALIZATION)
+                    .with_message(format!(
+                        "struct {}::{}::{} cannot be passed as an argument",
+                        address.short_str_lossless(),
+                        module,
+                        name
+                    )),
+            );
+        }
+        ty_args
+            .iter()
+            .try_for_each(|ty| self.check_struct_argument_type(ty))
+    }
+
+    /// Turns an argument holding structs into a VM value, checking the invariants of the
+    /// standard library structs accepted as arguments, which deserialization alone does not
+    /// enforce.
+    fn struct_argument_value(&self, ty: &Type, value: &MoveValue) -> PartialVMResult<Value> {
+        let (gidx, ty_args, fields) = match (ty, value) {
+            (Type::Bool, MoveValue::Bool(b)) => return Ok(Value::bool(*b)),
+            (Type::U8, MoveValue::U8(x)) => return Ok(Value::u8(*x)),
+            (Type::U64, MoveValue::U64(x)) => return Ok(Value::u64(*x)),
+            (Type::U128, MoveValue::U128(x)) => return Ok(Value::u128(*x)),
+            (Type::Address, MoveValue::Address(addr)) => return Ok(Value::address(*addr)),
+            (Type::Signer, MoveValue::Signer(addr)) => return Ok(Value::signer(*addr)),
+            (Type::Vector(ty), MoveValue::Vector(elems)) => {
+                let elems = elems
+                    .iter()
+                    .map(|elem| self.struct_argument_value(ty, elem))
+                    .collect::<PartialVMResult<_>>()?;
+                return Vector::pack(ty, elems);
+            }
+            (Type::Struct(gidx), MoveValue::Struct(s)) => (*gidx, &[][..], s.fields()),
+            (Type::StructInstantiation(gidx, ty_args), MoveValue::Struct(s)) => {
+                (*gidx, &ty_args[..], s.fields())
+            }
+            _ => {
+                return Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)
+                    .with_message("argument does not match its type".to_string()))
+            }
+        };
+        let struct_type = self.loader.get_struct_type(gidx).ok_or_else(|| {
+            PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                .with_message("struct type of an argument is not loaded".to_string())
+        })?;
+        let (address, module, name) = (
+            struct_type.module.address(),
+            struct_type.module.name(),
+            struct_type.name.as_ident_str(),
+        );
+        match fields {
+            [MoveValue::Vector(bytes)] if is_ascii_string(address, module, name) => {
+                let bytes = bytes
+                    .iter()
+                    .map(|byte| match byte {
+                        MoveValue::U8(byte) => Some(*byte),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>();
+                if !bytes.map_or(false, |bytes| is_valid_ascii(&bytes)) {
+                    return Err(
+                        PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT)
+                            .with_message("invalid ASCII string argument".to_string()),
+                    );
+                }
+            }
+            [MoveValue::Vector(elems)] if is_option(address, module, name) => {
+                if elems.len() > 1 {
+                    return Err(
+                        PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT)
+                            .with_message("option argument with more than one element".to_string()),
+                    );
+                }
+            }
+            _ => (),
+        }
+        let fields = struct_type
+            .fields
+            .iter()
+            .zip(fields)
+            .map(|(field_ty, field)| self.struct_argument_value(&field_ty.subst(ty_args)?, field))
+            .collect::<PartialVMResult<Vec<_>>>()?;
+        Ok(Value::struct_(Struct::pack(fields)))
+    }
+
     fn deserialize_args(
         &self,
         arg_tys: Vec<Type>,
         serialized_args: Vec<impl Borrow<[u8]>>,
+        bypass_visibility: bool,
     ) -> PartialVMResult<(Locals, Vec<Value>)> {
         if arg_tys.len() != serialized_args.len() {
             return Err(
@@ -245,10 +396,13 @@ impl VMRuntime {
             .enumerate()
             .map(|(idx, (arg_ty, arg_bytes))| match &arg_ty {
                 Type::MutableReference(inner_t) | Type::Reference(inner_t) => {
-                    dummy_locals.store_loc(idx, self.deserialize_value(inner_t, arg_bytes)?)?;
+                    dummy_locals.store_loc(
+                        idx,
+                        self.deserialize_value(inner_t, arg_bytes, bypass_visibility)?,
+                    )?;
                     dummy_locals.borrow_loc(idx)
                 }
-                _ => self.deserialize_value(&arg_ty, arg_bytes),
+                _ => self.deserialize_value(&arg_ty, arg_bytes, bypass_visibility),
             })
             .collect::<PartialVMResult<Vec<_>>>()?;
         Ok((dummy_locals, deserialized_args))
@@ -266,21 +420,28 @@ impl VMRuntime {
                         "non reference value given for a reference typed return value".to_string(),
                     )
                 })?;
-                let inner_value = ref_value.read_ref()?;
+                let inner_value =
+                    ref_value.read_ref_with_max_depth(self.loader.vm_config().max_value_depth)?;
                 (&**inner, inner_value)
             }
             _ => (ty, value),
         };
 
-        let layout = self.loader.type_to_type_layout(ty).map_err(|_err| {
+        let layout = self.loader.type_to_type_layout(ty).map_err(|err| {
+            if err.major_status() == StatusCode::VM_MAX_VALUE_DEPTH_REACHED {
+                return err;
+            }
             PartialVMError::new(StatusCode::VERIFICATION_ERROR).with_message(
                 "entry point functions cannot have non-serializable return types".to_string(),
             )
         })?;
-        let bytes = value.simple_serialize(&layout).ok_or_else(|| {
-            PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
-                .with_message("failed to serialize return values".to_string())
-        })?;
+        let max_value_depth = self.loader.vm_config().max_value_depth;
+        let bytes = value
+            .simple_serialize_with_max_depth(&layout, max_value_depth)
+            .ok_or_else(|| {
+                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                    .with_message("failed to serialize return values".to_string())
+            })?;
         Ok((bytes, layout))
     }
 
@@ -318,6 +479,7 @@ impl VMRuntime {
         data_store: &mut impl DataStore,
         gas_status: &mut GasStatus,
         extensions: &mut NativeContextExtensions,
+        bypass_visibility: bool,
     ) -> VMResult<SerializedReturnValues> {
         let arg_types = param_types
             .into_iter()
@@ -333,7 +495,7 @@ impl VMRuntime {
             })
             .collect::<Vec<_>>();
         let (mut dummy_locals, deserialized_args) = self
-            .deserialize_args(arg_types, serialized_args)
+            .deserialize_args(arg_types, serialized_args, bypass_visibility)
             .map_err(|e| e.finish(Location::Undefined))?;
         let return_types = return_types
             .into_iter()
@@ -436,6 +598,7 @@ impl VMRuntime {
             data_store,
             gas_status,
             extensions,
+            bypass_visibility,
         )
     }
 
@@ -460,6 +623,7 @@ impl VMRuntime {
         ) = self
             .loader
             .load_script(script.borrow(), &ty_args, data_store)?;
+        let bypass_visibility = false;
         // execute the function
         self.execute_function_impl(
             func,
@@ -470,6 +634,7 @@ impl VMRuntime {
             data_store,
             gas_status,
             extensions,
+            bypass_visibility,
         )
     }
 
@@ -2,17 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    data_cache::TransactionDataCache, native_extensions::NativeContextExtensions,
+    data_cache::{StorageUsage, TransactionDataCache},
+    native_extensions::NativeContextExtensions,
     runtime::VMRuntime,
 };
 use move_binary_format::{errors::*, file_format::LocalIndex};
 use move_core_types::{
     account_address::AccountAddress,
-    effects::{ChangeSet, Event},
+    effects::{ChangeSet, ContractEvent},
     identifier::IdentStr,
     language_storage::{ModuleId, TypeTag},
     resolver::MoveResolver,
     value::MoveTypeLayout,
+    vm_status::StatusCode,
 };
 use move_vm_types::{
     data_store::DataStore,
@@ -25,8 +27,14 @@ pub struct Session<'r, 'l, S> {
     pub(crate) runtime: &'l VMRuntime,
     pub(crate) data_cache: TransactionDataCache<'r, 'l, S>,
     pub(crate) native_extensions: NativeContextExtensions<'r>,
+    pub(crate) savepoints: Vec<Savepoint>,
+    pub(crate) next_savepoint: u64,
 }
 
+/// A savepoint of a session, see `Session::savepoint`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Savepoint(u64);
+
 /// Serialized return values from function/script execution
 /// Simple struct is designed just to convey meaning behind serialized values
 #[derive(Debug)]
@@ -184,16 +192,80 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
             .publish_module_bundle(modules, sender, &mut self.data_cache, gas_status)
     }
 
+    /// Record the current state of the session, so that the effects of subsequent calls can be
+    /// undone with `rollback_to` without discarding the session. This covers resources, published
+    /// modules, emitted events and the state of native extensions registered with
+    /// `NativeContextExtensions::add_with_savepoints`.
+    ///
+    /// Savepoints nest: rolling back to or releasing a savepoint also discards all savepoints
+    /// taken after it.
+    ///
+    /// Taking a savepoint copies the data loaded by the session so far.
+    pub fn savepoint(&mut self) -> VMResult<Savepoint> {
+        self.data_cache
+            .savepoint()
+            .and_then(|()| self.native_extensions.savepoint())
+            .map_err(|e| e.finish(Location::Undefined))?;
+        let savepoint = Savepoint(self.next_savepoint);
+        self.next_savepoint += 1;
+        self.savepoints.push(savepoint);
+        Ok(savepoint)
+    }
+
+    /// Undo all effects produced since `savepoint` was taken. The savepoint remains valid and
+    /// can be rolled back to again.
+    ///
+    /// Modules published since the savepoint are also evicted from the loader of the VM, so
+    /// neither this session nor later ones resolve them unless they are in storage.
+    pub fn rollback_to(&mut self, savepoint: Savepoint) -> VMResult<()> {
+        let pos = self.savepoint_position(savepoint)?;
+        self.pop_savepoints(pos + 1)?;
+        self.data_cache
+            .rollback()
+            .and_then(|()| self.native_extensions.rollback())
+            .map_err(|e| e.finish(Location::Undefined))
+    }
+
+    /// Discard `savepoint`, keeping all effects produced since it was taken.
+    pub fn release(&mut self, savepoint: Savepoint) -> VMResult<()> {
+        let pos = self.savepoint_position(savepoint)?;
+        self.pop_savepoints(pos)
+    }
+
+    fn savepoint_position(&self, savepoint: Savepoint) -> VMResult<usize> {
+        self.savepoints
+            .iter()
+            .position(|sp| *sp == savepoint)
+            .ok_or_else(|| {
+                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                    .with_message(format!("unknown savepoint {:?}", savepoint))
+                    .finish(Location::Undefined)
+            })
+    }
+
+    fn pop_savepoints(&mut self, len: usize) -> VMResult<()> {
+        while self.savepoints.len() > len {
+            self.data_cache
+                .release()
+                .and_then(|()| self.native_extensions.release())
+                .map_err(|e| e.finish(Location::Undefined))?;
+            self.savepoints.pop();
+        }
+        Ok(())
+    }
+
     pub fn num_mutated_accounts(&self, sender: &AccountAddress) -> u64 {
         self.data_cache.num_mutated_accounts(sender)
     }
 
-    /// Finish up the session and produce the side effects.
+    /// Finish up the session and produce the side effects, along with the storage accounting of
+    /// the resources and modules touched by the session.
     ///
-    /// This function should always succeed with no user errors returned, barring invariant violations.
+    /// This function should always succeed with no user errors returned, barring invariant
+    /// violations and effects exceeding the `WriteSetLimits` of the VM.
     ///
     /// This MUST NOT be called if there is a previous invocation that failed with an invariant violation.
-    pub fn finish(self) -> VMResult<(ChangeSet, Vec<Event>)> {
+    pub fn finish(self) -> VMResult<(ChangeSet, Vec<ContractEvent>, StorageUsage)> {
         self.data_cache
             .into_effects()
             .map_err(|e| e.finish(Location::Undefined))
@@ -202,16 +274,21 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
     /// Same like `finish`, but also extracts the native context extensions from the session.
     pub fn finish_with_extensions(
         self,
-    ) -> VMResult<(ChangeSet, Vec<Event>, NativeContextExtensions<'r>)> {
+    ) -> VMResult<(
+        ChangeSet,
+        Vec<ContractEvent>,
+        StorageUsage,
+        NativeContextExtensions<'r>,
+    )> {
         let Session {
             data_cache,
             native_extensions,
             ..
         } = self;
-        let (change_set, events) = data_cache
+        let (change_set, events, usage) = data_cache
             .into_effects()
             .map_err(|e| e.finish(Location::Undefined))?;
-        Ok((change_set, events, native_extensions))
+        Ok((change_set, events, usage, native_extensions))
     }
 
     /// Load a script and all of its types into cache
@@ -253,6 +330,14 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
             .get_type_layout(type_tag, &self.data_cache)
     }
 
+    /// Convert a type loaded into cache back into its type tag.
+    pub fn get_type_tag(&self, ty: &Type) -> VMResult<TypeTag> {
+        self.runtime
+            .loader()
+            .type_to_type_tag(ty)
+            .map_err(|e| e.finish(Location::Undefined))
+    }
+
     /// Fetch a struct type from cache, if the index is in bounds
     /// Helpful when paired with load_type, or any other API that returns 'Type'
     pub fn get_struct_type(&self, index: CachedStructIndex) -> Option<Arc<StructType>> {
@@ -262,6 +347,11 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
     pub fn get_data_store(&mut self) -> &mut dyn DataStore {
         &mut self.data_cache
     }
+
+    /// Get the native context extensions of this session.
+    pub fn get_native_extensions(&mut self) -> &mut NativeContextExtensions<'r> {
+        &mut self.native_extensions
+    }
 }
 
 pub struct LoadedFunctionInstantiation {
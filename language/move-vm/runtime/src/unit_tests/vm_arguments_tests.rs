@@ -3,7 +3,7 @@
 
 use std::collections::HashMap;
 
-use crate::move_vm::MoveVM;
+use crate::{config::VMConfig, move_vm::MoveVM};
 use move_binary_format::{
     errors::{VMError, VMResult},
     file_format::{
@@ -11,15 +11,15 @@ use move_binary_format::{
         CompiledScript, FieldDefinition, FunctionDefinition, FunctionHandle, FunctionHandleIndex,
         IdentifierIndex, ModuleHandle, ModuleHandleIndex, Signature, SignatureIndex,
         SignatureToken, StructDefinition, StructFieldInformation, StructHandle, StructHandleIndex,
-        TableIndex, TypeSignature, Visibility,
+        StructTypeParameter, TableIndex, TypeSignature, Visibility,
     },
 };
 use move_core_types::{
     account_address::AccountAddress,
     identifier::{IdentStr, Identifier},
-    language_storage::{ModuleId, StructTag, TypeTag},
+    language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS},
     resolver::{ModuleResolver, ResourceResolver},
-    value::{serialize_values, MoveValue},
+    value::{serialize_values, MoveStruct, MoveValue},
     vm_status::{StatusCode, StatusType},
 };
 use move_vm_types::gas_schedule::GasStatus;
@@ -216,6 +216,93 @@ fn make_script_function(signature: Signature) -> (CompiledModule, Identifier) {
     make_module_with_function(Visibility::Script, signature, Signature(vec![]), vec![])
 }
 
+// make a module `0x1::<module_name>` declaring a struct `<struct_name>` with a single field of
+// type `field` and, if `generic` is set, a single type parameter. The struct and module mimic the
+// standard library structs accepted as arguments, `0x1::ASCII::String` and `0x1::Option::Option`.
+// The module has a script function whose parameters are the given signature.
+fn make_stdlib_module_with_function(
+    module_name: &str,
+    struct_name: &str,
+    generic: bool,
+    field: SignatureToken,
+    parameters: Signature,
+) -> (CompiledModule, Identifier) {
+    let function_name = Identifier::new("foo").unwrap();
+    let mut identifiers = vec![Identifier::new(module_name).unwrap()];
+    if struct_name != module_name {
+        identifiers.push(Identifier::new(struct_name).unwrap());
+    }
+    identifiers.push(function_name.clone());
+    identifiers.push(Identifier::new("field").unwrap());
+    let ident_idx = |name: &str| {
+        IdentifierIndex(
+            identifiers
+                .iter()
+                .position(|ident| ident.as_str() == name)
+                .unwrap() as TableIndex,
+        )
+    };
+    let type_parameters = if generic {
+        vec![StructTypeParameter {
+            constraints: AbilitySet::EMPTY,
+            is_phantom: false,
+        }]
+    } else {
+        vec![]
+    };
+    let module = CompiledModule {
+        version: move_binary_format::file_format_common::VERSION_MAX,
+        self_module_handle_idx: ModuleHandleIndex(0),
+        module_handles: vec![ModuleHandle {
+            address: AddressIdentifierIndex(0),
+            name: ident_idx(module_name),
+        }],
+        struct_handles: vec![StructHandle {
+            module: ModuleHandleIndex(0),
+            name: ident_idx(struct_name),
+            abilities: AbilitySet::EMPTY,
+            type_parameters,
+        }],
+        function_handles: vec![FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: ident_idx("foo"),
+            parameters: SignatureIndex(1),
+            return_: SignatureIndex(0),
+            type_parameters: vec![],
+        }],
+        field_handles: vec![],
+        friend_decls: vec![],
+
+        struct_def_instantiations: vec![],
+        function_instantiations: vec![],
+        field_instantiations: vec![],
+
+        signatures: vec![Signature(vec![]), parameters],
+
+        address_identifiers: vec![CORE_CODE_ADDRESS],
+        constant_pool: vec![],
+
+        struct_defs: vec![StructDefinition {
+            struct_handle: StructHandleIndex(0),
+            field_information: StructFieldInformation::Declared(vec![FieldDefinition {
+                name: ident_idx("field"),
+                signature: TypeSignature(field),
+            }]),
+        }],
+        function_defs: vec![FunctionDefinition {
+            function: FunctionHandleIndex(0),
+            visibility: Visibility::Script,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals: SignatureIndex(0),
+                code: vec![Bytecode::LdU64(0), Bytecode::Abort],
+            }),
+        }],
+        identifiers,
+    };
+    (module, function_name)
+}
+
 struct RemoteStore {
     modules: HashMap<ModuleId, Vec<u8>>,
 }
@@ -271,7 +358,7 @@ fn call_script_with_args_ty_args_signers(
     ty_args: Vec<TypeTag>,
     signers: Vec<AccountAddress>,
 ) -> VMResult<()> {
-    let move_vm = MoveVM::new(vec![]).unwrap();
+    let move_vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
     let remote_view = RemoteStore::new();
     let mut session = move_vm.new_session(&remote_view);
     let mut gas_status = GasStatus::new_unmetered();
@@ -296,7 +383,7 @@ fn call_script_function_with_args_ty_args_signers(
     ty_args: Vec<TypeTag>,
     signers: Vec<AccountAddress>,
 ) -> VMResult<()> {
-    let move_vm = MoveVM::new(vec![]).unwrap();
+    let move_vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
     let mut remote_view = RemoteStore::new();
     let id = module.self_id();
     remote_view.add_module(module);
@@ -765,13 +852,201 @@ fn check_script_function() {
     );
 }
 
+fn bytes_struct(bytes: &[u8]) -> MoveValue {
+    MoveValue::Struct(MoveStruct::new(vec![MoveValue::vector_u8(bytes.to_vec())]))
+}
+
+fn option_struct(elems: Vec<MoveValue>) -> MoveValue {
+    MoveValue::Struct(MoveStruct::new(vec![MoveValue::Vector(elems)]))
+}
+
+#[test]
+fn check_stdlib_struct_arguments() {
+    let string_ty = SignatureToken::Struct(StructHandleIndex(0));
+    let string_field = SignatureToken::Vector(Box::new(SignatureToken::U8));
+    let option_ty =
+        SignatureToken::StructInstantiation(StructHandleIndex(0), vec![SignatureToken::U64]);
+    let option_field = SignatureToken::Vector(Box::new(SignatureToken::TypeParameter(0)));
+
+    let cases = vec![
+        // valid ASCII string
+        (
+            ("ASCII", "String", false, string_field.clone()),
+            string_ty.clone(),
+            bytes_struct(b"hello"),
+            StatusCode::ABORTED,
+        ),
+        // non-ASCII string
+        (
+            ("ASCII", "String", false, string_field.clone()),
+            string_ty.clone(),
+            bytes_struct(&[b'a', 0x80]),
+            StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT,
+        ),
+        // vector of strings with a non-ASCII element
+        (
+            ("ASCII", "String", false, string_field.clone()),
+            SignatureToken::Vector(Box::new(string_ty.clone())),
+            MoveValue::Vector(vec![bytes_struct(b"a"), bytes_struct(&[0xff])]),
+            StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT,
+        ),
+        // non-ASCII bytes in a struct which is not a string
+        (
+            ("ASCII", "Bytes", false, string_field),
+            string_ty,
+            bytes_struct(&[0xff]),
+            StatusCode::ABORTED,
+        ),
+        // none
+        (
+            ("Option", "Option", true, option_field.clone()),
+            option_ty.clone(),
+            option_struct(vec![]),
+            StatusCode::ABORTED,
+        ),
+        // some
+        (
+            ("Option", "Option", true, option_field.clone()),
+            option_ty.clone(),
+            option_struct(vec![MoveValue::U64(1)]),
+            StatusCode::ABORTED,
+        ),
+        // more than one element
+        (
+            ("Option", "Option", true, option_field.clone()),
+            option_ty.clone(),
+            option_struct(vec![MoveValue::U64(1), MoveValue::U64(2)]),
+            StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT,
+        ),
+        // vector of options with more than one element
+        (
+            ("Option", "Option", true, option_field.clone()),
+            SignatureToken::Vector(Box::new(option_ty.clone())),
+            MoveValue::Vector(vec![
+                option_struct(vec![]),
+                option_struct(vec![MoveValue::U64(1), MoveValue::U64(2)]),
+            ]),
+            StatusCode::FAILED_TO_DESERIALIZE_ARGUMENT,
+        ),
+        // more than one element in a struct which is not an option
+        (
+            ("Option", "Vector", true, option_field),
+            option_ty,
+            option_struct(vec![MoveValue::U64(1), MoveValue::U64(2)]),
+            StatusCode::ABORTED,
+        ),
+    ];
+    for ((module_name, struct_name, generic, field), param, arg, expected_status) in cases {
+        let (module, function_name) = make_stdlib_module_with_function(
+            module_name,
+            struct_name,
+            generic,
+            field,
+            Signature(vec![param]),
+        );
+        assert_eq!(
+            call_script_function(module, function_name, serialize_values(&[arg]))
+                .err()
+                .unwrap()
+                .major_status(),
+            expected_status
+        );
+    }
+}
+
+fn call_entry_function(
+    module: CompiledModule,
+    function_name: Identifier,
+    args: Vec<Vec<u8>>,
+) -> VMResult<()> {
+    let move_vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
+    let mut remote_view = RemoteStore::new();
+    let id = module.self_id();
+    remote_view.add_module(module);
+    let mut session = move_vm.new_session(&remote_view);
+    let mut gas_status = GasStatus::new_unmetered();
+    session.execute_entry_function(
+        &id,
+        function_name.as_ident_str(),
+        vec![],
+        args,
+        &mut gas_status,
+    )?;
+    Ok(())
+}
+
+#[test]
+fn check_entry_point_struct_arguments() {
+    let string_field = SignatureToken::Vector(Box::new(SignatureToken::U8));
+    let option_field = SignatureToken::Vector(Box::new(SignatureToken::TypeParameter(0)));
+    let cases = vec![
+        // string
+        (
+            ("ASCII", "String", false, string_field.clone()),
+            SignatureToken::Struct(StructHandleIndex(0)),
+            bytes_struct(b"hello"),
+            StatusCode::ABORTED,
+        ),
+        // option
+        (
+            ("Option", "Option", true, option_field.clone()),
+            SignatureToken::StructInstantiation(StructHandleIndex(0), vec![SignatureToken::U64]),
+            option_struct(vec![MoveValue::U64(1)]),
+            StatusCode::ABORTED,
+        ),
+        // struct which is not accepted as an argument
+        (
+            ("ASCII", "Bytes", false, string_field.clone()),
+            SignatureToken::Struct(StructHandleIndex(0)),
+            bytes_struct(b"hello"),
+            StatusCode::INVALID_PARAM_TYPE_FOR_DESERIALIZATION,
+        ),
+        // empty vector of structs which are not accepted as arguments
+        (
+            ("ASCII", "Bytes", false, string_field),
+            SignatureToken::Vector(Box::new(SignatureToken::Struct(StructHandleIndex(0)))),
+            MoveValue::Vector(vec![]),
+            StatusCode::INVALID_PARAM_TYPE_FOR_DESERIALIZATION,
+        ),
+        // option of a struct which is not accepted as an argument
+        (
+            ("Option", "Option", true, option_field),
+            SignatureToken::StructInstantiation(
+                StructHandleIndex(0),
+                vec![SignatureToken::StructInstantiation(
+                    StructHandleIndex(0),
+                    vec![SignatureToken::U64],
+                )],
+            ),
+            option_struct(vec![]),
+            StatusCode::ABORTED,
+        ),
+    ];
+    for ((module_name, struct_name, generic, field), param, arg, expected_status) in cases {
+        let (module, function_name) = make_stdlib_module_with_function(
+            module_name,
+            struct_name,
+            generic,
+            field,
+            Signature(vec![param]),
+        );
+        assert_eq!(
+            call_entry_function(module, function_name, serialize_values(&[arg]))
+                .err()
+                .unwrap()
+                .major_status(),
+            expected_status
+        );
+    }
+}
+
 #[test]
 fn call_missing_item() {
     let module = empty_module();
     let id = &module.self_id();
     let function_name = IdentStr::new("foo").unwrap();
     // mising module
-    let move_vm = MoveVM::new(vec![]).unwrap();
+    let move_vm = MoveVM::new(vec![], VMConfig::default()).unwrap();
     let mut gas_status = GasStatus::new_unmetered();
     let mut remote_view = RemoteStore::new();
     let mut session = move_vm.new_session(&remote_view);
@@ -4,5 +4,7 @@
 #![allow(clippy::new_without_default)]
 
 mod storage;
+#[cfg(test)]
+mod unit_tests;
 
 pub use storage::{BlankStorage, DeltaStorage, InMemoryStorage};
@@ -16,6 +16,8 @@ use std::collections::{btree_map, BTreeMap};
 
 #[cfg(feature = "table-extension")]
 use move_table_extension::{TableChangeSet, TableHandle, TableOperation, TableResolver};
+#[cfg(feature = "table-extension")]
+use std::ops::Bound;
 
 /// A dummy storage containing no modules or resources.
 #[derive(Debug, Clone)]
@@ -57,6 +59,15 @@ impl TableResolver for BlankStorage {
         Ok(None)
     }
 
+    fn iter_table_entries(
+        &self,
+        _handle: &TableHandle,
+        _start_key: Option<&[u8]>,
+        _limit: usize,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Error> {
+        Ok(vec![])
+    }
+
     fn operation_cost(
         &self,
         _op: TableOperation,
@@ -73,6 +84,8 @@ impl TableResolver for BlankStorage {
 pub struct DeltaStorage<'a, 'b, S> {
     base: &'a S,
     delta: &'b ChangeSet,
+    #[cfg(feature = "table-extension")]
+    table_delta: Option<&'b TableChangeSet>,
 }
 
 impl<'a, 'b, S: ModuleResolver> ModuleResolver for DeltaStorage<'a, 'b, S> {
@@ -114,10 +127,76 @@ impl<'a, 'b, S: TableResolver> TableResolver for DeltaStorage<'a, 'b, S> {
         handle: &TableHandle,
         key: &[u8],
     ) -> std::result::Result<Option<Vec<u8>>, Error> {
-        // TODO: No support for table deltas
+        let table_delta = match self.table_delta {
+            Some(table_delta) => table_delta,
+            None => return self.base.resolve_table_entry(handle, key),
+        };
+        if table_delta.removed_tables.contains(handle)
+            || table_delta.dropped_tables.contains(handle)
+        {
+            return Ok(None);
+        }
+        if let Some(val_opt) = table_delta
+            .changes
+            .get(handle)
+            .and_then(|change| change.entries.get(key))
+        {
+            return Ok(val_opt.clone());
+        }
+        if table_delta.new_tables.contains(handle) {
+            return Ok(None);
+        }
         self.base.resolve_table_entry(handle, key)
     }
 
+    fn iter_table_entries(
+        &self,
+        handle: &TableHandle,
+        start_key: Option<&[u8]>,
+        limit: usize,
+    ) -> std::result::Result<Vec<(Vec<u8>, Vec<u8>)>, Error> {
+        let table_delta = match self.table_delta {
+            Some(table_delta) => table_delta,
+            None => return self.base.iter_table_entries(handle, start_key, limit),
+        };
+        if table_delta.removed_tables.contains(handle)
+            || table_delta.dropped_tables.contains(handle)
+        {
+            return Ok(vec![]);
+        }
+        let start = match start_key {
+            Some(key) => Bound::Included(key.to_vec()),
+            None => Bound::Unbounded,
+        };
+        let changes: Vec<_> = match table_delta.changes.get(handle) {
+            Some(change) => change.entries.range((start, Bound::Unbounded)).collect(),
+            None => vec![],
+        };
+        let mut entries: BTreeMap<Vec<u8>, Vec<u8>> = if table_delta.new_tables.contains(handle) {
+            BTreeMap::new()
+        } else {
+            // Each deleted entry may hide one entry of the base, so fetch as many more. As the
+            // page still has `limit` entries from the base, entries added beyond the last key
+            // fetched from the base are cut off below.
+            let deletions = changes.iter().filter(|(_, val)| val.is_none()).count();
+            self.base
+                .iter_table_entries(handle, start_key, limit.saturating_add(deletions))?
+                .into_iter()
+                .collect()
+        };
+        for (key, val_opt) in changes {
+            match val_opt {
+                Some(val) => {
+                    entries.insert(key.clone(), val.clone());
+                }
+                None => {
+                    entries.remove(key);
+                }
+            }
+        }
+        Ok(entries.into_iter().take(limit).collect())
+    }
+
     fn operation_cost(
         &self,
         op: TableOperation,
@@ -131,7 +210,26 @@ impl<'a, 'b, S: TableResolver> TableResolver for DeltaStorage<'a, 'b, S> {
 
 impl<'a, 'b, S: MoveResolver> DeltaStorage<'a, 'b, S> {
     pub fn new(base: &'a S, delta: &'b ChangeSet) -> Self {
-        Self { base, delta }
+        Self {
+            base,
+            delta,
+            #[cfg(feature = "table-extension")]
+            table_delta: None,
+        }
+    }
+
+    /// Stacks the table changes `table_delta` on top of the tables of `base` as well.
+    #[cfg(feature = "table-extension")]
+    pub fn new_with_table_changes(
+        base: &'a S,
+        delta: &'b ChangeSet,
+        table_delta: &'b TableChangeSet,
+    ) -> Self {
+        Self {
+            base,
+            delta,
+            table_delta: Some(table_delta),
+        }
     }
 }
 
@@ -242,6 +340,7 @@ impl InMemoryStorage {
         let TableChangeSet {
             new_tables,
             removed_tables,
+            dropped_tables,
             changes,
         } = changes;
         self.tables.retain(|h, _| !removed_tables.contains(h));
@@ -261,6 +360,8 @@ impl InMemoryStorage {
                 }
             }
         }
+        // Entries of dropped tables are unreachable, so collect them right away.
+        self.tables.retain(|h, _| !dropped_tables.contains(h));
     }
 
     pub fn new() -> Self {
@@ -335,6 +436,27 @@ impl TableResolver for InMemoryStorage {
         Ok(self.tables.get(handle).and_then(|t| t.get(key).cloned()))
     }
 
+    fn iter_table_entries(
+        &self,
+        handle: &TableHandle,
+        start_key: Option<&[u8]>,
+        limit: usize,
+    ) -> std::result::Result<Vec<(Vec<u8>, Vec<u8>)>, Error> {
+        let table = match self.tables.get(handle) {
+            Some(table) => table,
+            None => return Ok(vec![]),
+        };
+        let start = match start_key {
+            Some(key) => Bound::Included(key.to_vec()),
+            None => Bound::Unbounded,
+        };
+        Ok(table
+            .range((start, Bound::Unbounded))
+            .take(limit)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+
     fn operation_cost(
         &self,
         _op: TableOperation,
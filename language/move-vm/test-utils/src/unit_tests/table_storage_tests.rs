@@ -0,0 +1,130 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{DeltaStorage, InMemoryStorage};
+use move_core_types::effects::ChangeSet;
+use move_table_extension::{TableChange, TableChangeSet, TableHandle, TableResolver};
+
+fn table_changes(
+    new_tables: Vec<TableHandle>,
+    changes: Vec<(TableHandle, Vec<(u8, Option<u8>)>)>,
+) -> TableChangeSet {
+    let mut change_set = TableChangeSet::default();
+    change_set.new_tables.extend(new_tables);
+    for (handle, entries) in changes {
+        let entries = entries
+            .into_iter()
+            .map(|(key, val)| (vec![key], val.map(|val| vec![val])))
+            .collect();
+        change_set.changes.insert(
+            handle,
+            TableChange {
+                entries,
+                bytes_delta: 0,
+            },
+        );
+    }
+    change_set
+}
+
+fn entries(entries: Vec<(u8, u8)>) -> Vec<(Vec<u8>, Vec<u8>)> {
+    entries
+        .into_iter()
+        .map(|(key, val)| (vec![key], vec![val]))
+        .collect()
+}
+
+fn storage_with_table(handle: TableHandle) -> InMemoryStorage {
+    let mut storage = InMemoryStorage::new();
+    storage
+        .apply_extended(
+            ChangeSet::new(),
+            table_changes(
+                vec![handle],
+                vec![(handle, (0..6).map(|i| (i, Some(i))).collect())],
+            ),
+        )
+        .unwrap();
+    storage
+}
+
+#[test]
+fn in_memory_iter_table_entries() {
+    let handle = TableHandle(1);
+    let storage = storage_with_table(handle);
+
+    assert_eq!(
+        storage.iter_table_entries(&handle, None, 2).unwrap(),
+        entries(vec![(0, 0), (1, 1)])
+    );
+    assert_eq!(
+        storage.iter_table_entries(&handle, Some(&[4]), 10).unwrap(),
+        entries(vec![(4, 4), (5, 5)])
+    );
+    assert_eq!(
+        storage.iter_table_entries(&handle, Some(&[6]), 10).unwrap(),
+        vec![]
+    );
+    assert_eq!(
+        storage
+            .iter_table_entries(&TableHandle(2), None, 10)
+            .unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+fn delta_iter_table_entries() {
+    let handle = TableHandle(1);
+    let new_handle = TableHandle(2);
+    let storage = storage_with_table(handle);
+    let change_set = ChangeSet::new();
+    let table_delta = table_changes(
+        vec![new_handle],
+        vec![
+            (
+                handle,
+                vec![(0, None), (1, None), (2, Some(20)), (7, Some(7))],
+            ),
+            (new_handle, vec![(0, Some(0))]),
+        ],
+    );
+    let delta = DeltaStorage::new_with_table_changes(&storage, &change_set, &table_delta);
+
+    // Deleted entries do not shorten the page, and entries added beyond it are not included.
+    assert_eq!(
+        delta.iter_table_entries(&handle, None, 2).unwrap(),
+        entries(vec![(2, 20), (3, 3)])
+    );
+    assert_eq!(
+        delta.iter_table_entries(&handle, Some(&[4]), 10).unwrap(),
+        entries(vec![(4, 4), (5, 5), (7, 7)])
+    );
+    assert_eq!(
+        delta.iter_table_entries(&new_handle, None, 10).unwrap(),
+        entries(vec![(0, 0)])
+    );
+    assert_eq!(delta.resolve_table_entry(&handle, &[0]).unwrap(), None);
+    assert_eq!(
+        delta.resolve_table_entry(&handle, &[2]).unwrap(),
+        Some(vec![20])
+    );
+    assert_eq!(
+        delta.resolve_table_entry(&handle, &[3]).unwrap(),
+        Some(vec![3])
+    );
+
+    // Removed and dropped tables have no entries.
+    let mut table_delta = TableChangeSet::default();
+    table_delta.dropped_tables.insert(handle);
+    let delta = DeltaStorage::new_with_table_changes(&storage, &change_set, &table_delta);
+    assert_eq!(delta.iter_table_entries(&handle, None, 10).unwrap(), vec![]);
+    assert_eq!(delta.resolve_table_entry(&handle, &[3]).unwrap(), None);
+
+    // Without table changes, the tables of the base are visible.
+    let delta = DeltaStorage::new(&storage, &change_set);
+    assert_eq!(
+        delta.iter_table_entries(&handle, Some(&[5]), 10).unwrap(),
+        entries(vec![(5, 5)])
+    );
+}
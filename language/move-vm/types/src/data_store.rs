@@ -51,5 +51,10 @@ pub trait DataStore {
         val: Value,
     ) -> PartialVMResult<()>;
 
-    fn events(&self) -> &Vec<(Vec<u8>, u64, Type, MoveTypeLayout, Value)>;
+    /// Emit an event which is not associated with an event handle to the EventStore
+    fn emit_module_event(&mut self, ty: Type, val: Value) -> PartialVMResult<()>;
+
+    /// The events emitted so far, in emission order. Events emitted to a handle carry
+    /// the guid and sequence number of the handle.
+    fn events(&self) -> &Vec<(Option<(Vec<u8>, u64)>, Type, MoveTypeLayout, Value)>;
 }
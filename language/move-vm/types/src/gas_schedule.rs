@@ -280,9 +280,9 @@ pub fn zero_cost_schedule(num_of_native_funcs: usize) -> CostTable {
         (VecUnpack(SignatureIndex::new(0), 0), GasCost::new(0, 0)),
         (VecSwap(SignatureIndex::new(0)), GasCost::new(0, 0)),
     ];
-    // length of native_table vector should be at least 18 due to the fact that there's a
-    // builtin native function cost EMIT_EVENT which indexed 17 in the vector
-    let num_of_native_funcs = max(num_of_native_funcs, 18);
+    // length of native_table vector should be at least 27 due to the fact that there's a
+    // builtin native function cost SECP256K1_RECOVER which indexed 26 in the vector
+    let num_of_native_funcs = max(num_of_native_funcs, 27);
     let native_table = (0..num_of_native_funcs)
         .map(|_| GasCost::new(0, 0))
         .collect::<Vec<GasCost>>();
@@ -431,6 +431,15 @@ pub static INITIAL_COST_SCHEDULE: Lazy<CostTable> = Lazy::new(|| {
         (N::CREATE_SIGNER, GasCost::new(24, 1)),
         (N::DESTROY_SIGNER, GasCost::new(212, 1)),
         (N::EMIT_EVENT, GasCost::new(52, 1)),
+        (N::STRING_CHECK_UTF8, GasCost::new(4, 1)),
+        (N::STRING_IS_CHAR_BOUNDARY, GasCost::new(28, 1)),
+        (N::STRING_SUB_STRING, GasCost::new(6, 1)),
+        (N::STRING_INDEX_OF, GasCost::new(11, 1)),
+        (N::KECCAK_256, GasCost::new(64, 1)),
+        (N::BLAKE2B_256, GasCost::new(15, 1)),
+        (N::RIPEMD160, GasCost::new(35, 1)),
+        (N::SECP256K1_VERIFY, GasCost::new(96, 1)),
+        (N::SECP256K1_RECOVER, GasCost::new(112, 1)),
     ];
     native_table.sort_by_key(|cost| cost.0 as u64);
     let raw_native_table = native_table
@@ -480,6 +489,15 @@ pub enum NativeCostIndex {
     CREATE_SIGNER = 15,
     DESTROY_SIGNER = 16,
     EMIT_EVENT = 17,
+    STRING_CHECK_UTF8 = 18,
+    STRING_IS_CHAR_BOUNDARY = 19,
+    STRING_SUB_STRING = 20,
+    STRING_INDEX_OF = 21,
+    KECCAK_256 = 22,
+    BLAKE2B_256 = 23,
+    RIPEMD160 = 24,
+    SECP256K1_VERIFY = 25,
+    SECP256K1_RECOVER = 26,
 }
 
 impl From<NativeCostIndex> for u8 {
@@ -16,7 +16,7 @@
 //! This module contains the declarations and utilities to implement a native
 //! function.
 
-use crate::values::Value;
+use crate::{gas_schedule::NativeCostIndex, values::Value};
 use move_core_types::gas_schedule::{
     AbstractMemorySize, CostTable, GasAlgebra, GasCarrier, InternalGasUnits,
 };
@@ -117,6 +117,29 @@ pub fn native_gas(
     gas_amt.total().mul(memory_size)
 }
 
+/// How the cost of a native function is derived from the `CostTable`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NativeGasFormula {
+    /// A fixed amount of gas, independent of the `CostTable`.
+    Fixed(GasCarrier),
+    /// The cost of the native entry, charged once.
+    Constant(NativeCostIndex),
+    /// The cost of the native entry, charged for every unit (usually a byte) of the data
+    /// processed by the native, and at least once.
+    PerUnit(NativeCostIndex),
+}
+
+impl NativeGasFormula {
+    /// Compute the cost of an execution processing `size` units of data.
+    pub fn cost(self, table: &CostTable, size: usize) -> InternalGasUnits<GasCarrier> {
+        match self {
+            NativeGasFormula::Fixed(amount) => InternalGasUnits::new(amount),
+            NativeGasFormula::Constant(idx) => native_gas(table, idx, 1),
+            NativeGasFormula::PerUnit(idx) => native_gas(table, idx, size),
+        }
+    }
+}
+
 /// Return the argument at the top of the stack.
 ///
 /// Arguments are passed to a native as a stack with first arg at the bottom of the stack.
@@ -3,6 +3,12 @@
 
 use crate::values::*;
 use move_binary_format::errors::*;
+use move_core_types::{
+    gas_schedule::GasAlgebra,
+    value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue, VALUE_DEPTH_MAX},
+    vm_status::StatusCode,
+};
+use proptest::prelude::*;
 
 #[test]
 fn locals() -> PartialVMResult<()> {
@@ -120,3 +126,99 @@ fn global_value_non_struct() -> PartialVMResult<()> {
 
     Ok(())
 }
+
+// Builds a value nested `shape.len() + 1` levels deep, wrapping a u64 in a struct or a vector
+// at each level, together with its layout and the equivalent `MoveValue`.
+fn nested_value(shape: &[bool]) -> (Value, MoveTypeLayout, MoveValue) {
+    let leaf = (Value::u64(0), MoveTypeLayout::U64, MoveValue::U64(0));
+    shape
+        .iter()
+        .fold(leaf, |(val, layout, move_val), is_struct| {
+            if *is_struct {
+                (
+                    Value::struct_(Struct::pack(vec![val])),
+                    MoveTypeLayout::Struct(MoveStructLayout::new(vec![layout])),
+                    MoveValue::Struct(MoveStruct::new(vec![move_val])),
+                )
+            } else {
+                let val = match layout {
+                    MoveTypeLayout::U64 => Value::vector_u64(vec![0]),
+                    _ => Value::vector_for_testing_only(vec![val]),
+                };
+                (
+                    val,
+                    MoveTypeLayout::Vector(Box::new(layout)),
+                    MoveValue::Vector(vec![move_val]),
+                )
+            }
+        })
+}
+
+fn is_depth_error<T>(res: PartialVMResult<T>) -> bool {
+    matches!(res, Err(err) if err.major_status() == StatusCode::VM_MAX_VALUE_DEPTH_REACHED)
+}
+
+#[test]
+fn deeply_nested_values() -> PartialVMResult<()> {
+    let (val, layout, move_val) = nested_value(&[true, false].repeat(500));
+
+    assert!(is_depth_error(val.copy_value()));
+    assert!(is_depth_error(val.equals(&val)));
+    assert!(val.size().get() > 0);
+    assert!(val.simple_serialize(&layout).is_none());
+    assert!(move_val.simple_serialize().is_none());
+
+    // The layout alone is enough to reject the input.
+    let blob = Value::u64(0)
+        .simple_serialize(&MoveTypeLayout::U64)
+        .unwrap();
+    assert!(Value::simple_deserialize(&blob, &layout).is_none());
+    assert!(MoveValue::simple_deserialize(&blob, &layout).is_err());
+
+    let mut locals = Locals::new(1);
+    locals.store_loc(0, val)?;
+    assert!(is_depth_error(locals.copy_loc(0)));
+    let r: Reference = locals.borrow_loc(0)?.value_as()?;
+    assert!(is_depth_error(r.read_ref()));
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn value_depth_is_bounded(
+        shape in proptest::collection::vec(any::<bool>(), 0..VALUE_DEPTH_MAX),
+        max_depth in 1..VALUE_DEPTH_MAX,
+    ) {
+        let (val, layout, move_val) = nested_value(&shape);
+        let within_depth = shape.len() < max_depth;
+
+        let copy = val.copy_value_with_max_depth(max_depth);
+        prop_assert_eq!(copy.is_ok(), within_depth);
+        prop_assert!(within_depth || is_depth_error(copy));
+        let eq = val.equals_with_max_depth(&val, max_depth);
+        prop_assert_eq!(eq.is_ok(), within_depth);
+        prop_assert!(within_depth || is_depth_error(eq));
+
+        let blob = val.simple_serialize(&layout).expect("must serialize");
+        prop_assert_eq!(move_val.simple_serialize(), Some(blob.clone()));
+
+        let bounded_blob = val.simple_serialize_with_max_depth(&layout, max_depth);
+        prop_assert_eq!(bounded_blob.is_some(), within_depth);
+        let bounded_move_blob = move_val.simple_serialize_with_max_depth(max_depth);
+        prop_assert_eq!(bounded_move_blob.is_some(), within_depth);
+
+        let deserialized = Value::simple_deserialize_with_max_depth(&blob, &layout, max_depth);
+        prop_assert_eq!(deserialized.is_some(), within_depth);
+        let move_deserialized =
+            MoveValue::simple_deserialize_with_max_depth(&blob, &layout, max_depth);
+        prop_assert_eq!(move_deserialized.is_ok(), within_depth);
+        if within_depth {
+            prop_assert_eq!(move_deserialized.unwrap(), move_val);
+            prop_assert_eq!(
+                deserialized.unwrap().simple_serialize(&layout),
+                Some(blob)
+            );
+        }
+    }
+}
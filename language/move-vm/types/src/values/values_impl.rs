@@ -12,11 +12,11 @@ use move_core_types::{
         AbstractMemorySize, GasAlgebra, GasCarrier, CONST_SIZE, MIN_EXISTS_DATA_SIZE,
         REFERENCE_SIZE, STRUCT_SIZE,
     },
-    value::{MoveStructLayout, MoveTypeLayout},
+    value::{MoveStructLayout, MoveTypeLayout, VALUE_DEPTH_MAX},
     vm_status::{sub_status::NFE_VECTOR_ERROR_BASE, StatusCode},
 };
 use std::{
-    cell::RefCell,
+    cell::{Ref, RefCell},
     fmt::{self, Debug, Display},
     iter,
     mem::size_of,
@@ -229,6 +229,18 @@ impl Container {
         }
     }
 
+    // Whether this is a vector storing its primitive elements unboxed.
+    fn is_specialized_vec(&self) -> bool {
+        match self {
+            Self::VecU8(_)
+            | Self::VecU64(_)
+            | Self::VecU128(_)
+            | Self::VecBool(_)
+            | Self::VecAddress(_) => true,
+            Self::Locals(_) | Self::Vec(_) | Self::Struct(_) => false,
+        }
+    }
+
     fn rc_count(&self) -> usize {
         match self {
             Self::Locals(r) | Self::Struct(r) | Self::Vec(r) => Rc::strong_count(r),
@@ -328,10 +340,18 @@ impl ValueImpl {
  *   surprising behaviors from happening.
  *
  **************************************************************************************/
+fn check_depth(depth: usize, max_depth: usize) -> PartialVMResult<()> {
+    if depth > max_depth {
+        return Err(PartialVMError::new(StatusCode::VM_MAX_VALUE_DEPTH_REACHED));
+    }
+    Ok(())
+}
+
 impl ValueImpl {
-    fn copy_value(&self) -> PartialVMResult<Self> {
+    fn copy_value(&self, depth: usize, max_depth: usize) -> PartialVMResult<Self> {
         use ValueImpl::*;
 
+        check_depth(depth, max_depth)?;
         Ok(match self {
             Invalid => Invalid,
 
@@ -346,22 +366,26 @@ impl ValueImpl {
 
             // When cloning a container, we need to make sure we make a deep
             // copy of the data instead of a shallow copy of the Rc.
-            Container(c) => Container(c.copy_value()?),
+            Container(c) => Container(c.copy_value(depth, max_depth)?),
         })
     }
 }
 
 impl Container {
-    fn copy_value(&self) -> PartialVMResult<Self> {
+    fn copy_value(&self, depth: usize, max_depth: usize) -> PartialVMResult<Self> {
         let copy_rc_ref_vec_val = |r: &Rc<RefCell<Vec<ValueImpl>>>| {
             Ok(Rc::new(RefCell::new(
                 r.borrow()
                     .iter()
-                    .map(|v| v.copy_value())
+                    .map(|v| v.copy_value(depth + 1, max_depth))
                     .collect::<PartialVMResult<_>>()?,
             )))
         };
 
+        // Primitive elements sit one level below the vector, as in its layout.
+        if self.is_specialized_vec() && self.len() > 0 {
+            check_depth(depth + 1, max_depth)?;
+        }
         Ok(match self {
             Self::Vec(r) => Self::Vec(copy_rc_ref_vec_val(r)?),
             Self::Struct(r) => Self::Struct(copy_rc_ref_vec_val(r)?),
@@ -418,7 +442,13 @@ impl ContainerRef {
 
 impl Value {
     pub fn copy_value(&self) -> PartialVMResult<Self> {
-        Ok(Self(self.0.copy_value()?))
+        self.copy_value_with_max_depth(VALUE_DEPTH_MAX)
+    }
+
+    /// Copies the value, failing with `VM_MAX_VALUE_DEPTH_REACHED` if it is nested deeper than
+    /// `max_depth`.
+    pub fn copy_value_with_max_depth(&self, max_depth: usize) -> PartialVMResult<Self> {
+        Ok(Self(self.0.copy_value(1, max_depth)?))
     }
 }
 
@@ -440,9 +470,10 @@ impl Value {
  **************************************************************************************/
 
 impl ValueImpl {
-    fn equals(&self, other: &Self) -> PartialVMResult<bool> {
+    fn equals(&self, other: &Self, depth: usize, max_depth: usize) -> PartialVMResult<bool> {
         use ValueImpl::*;
 
+        check_depth(depth, max_depth)?;
         let res = match (self, other) {
             (U8(l), U8(r)) => l == r,
             (U64(l), U64(r)) => l == r,
@@ -450,10 +481,10 @@ impl ValueImpl {
             (Bool(l), Bool(r)) => l == r,
             (Address(l), Address(r)) => l == r,
 
-            (Container(l), Container(r)) => l.equals(r)?,
+            (Container(l), Container(r)) => l.equals(r, depth, max_depth)?,
 
-            (ContainerRef(l), ContainerRef(r)) => l.equals(r)?,
-            (IndexedRef(l), IndexedRef(r)) => l.equals(r)?,
+            (ContainerRef(l), ContainerRef(r)) => l.equals(r, depth, max_depth)?,
+            (IndexedRef(l), IndexedRef(r)) => l.equals(r, depth, max_depth)?,
 
             _ => {
                 return Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)
@@ -466,9 +497,12 @@ impl ValueImpl {
 }
 
 impl Container {
-    fn equals(&self, other: &Self) -> PartialVMResult<bool> {
+    fn equals(&self, other: &Self, depth: usize, max_depth: usize) -> PartialVMResult<bool> {
         use Container::*;
 
+        if self.is_specialized_vec() && self.len() > 0 {
+            check_depth(depth + 1, max_depth)?;
+        }
         let res = match (self, other) {
             (Vec(l), Vec(r)) | (Struct(l), Struct(r)) => {
                 let l = &*l.borrow();
@@ -478,7 +512,7 @@ impl Container {
                     return Ok(false);
                 }
                 for (v1, v2) in l.iter().zip(r.iter()) {
-                    if !v1.equals(v2)? {
+                    if !v1.equals(v2, depth + 1, max_depth)? {
                         return Ok(false);
                     }
                 }
@@ -512,13 +546,13 @@ impl Container {
 }
 
 impl ContainerRef {
-    fn equals(&self, other: &Self) -> PartialVMResult<bool> {
-        self.container().equals(other.container())
+    fn equals(&self, other: &Self, depth: usize, max_depth: usize) -> PartialVMResult<bool> {
+        self.container().equals(other.container(), depth, max_depth)
     }
 }
 
 impl IndexedRef {
-    fn equals(&self, other: &Self) -> PartialVMResult<bool> {
+    fn equals(&self, other: &Self, depth: usize, max_depth: usize) -> PartialVMResult<bool> {
         use Container::*;
 
         let res = match (
@@ -534,7 +568,9 @@ impl IndexedRef {
             | (Struct(r1), Locals(r2))
             | (Locals(r1), Vec(r2))
             | (Locals(r1), Struct(r2))
-            | (Locals(r1), Locals(r2)) => r1.borrow()[self.idx].equals(&r2.borrow()[other.idx])?,
+            | (Locals(r1), Locals(r2)) => {
+                r1.borrow()[self.idx].equals(&r2.borrow()[other.idx], depth, max_depth)?
+            }
 
             (VecU8(r1), VecU8(r2)) => r1.borrow()[self.idx] == r2.borrow()[other.idx],
             (VecU64(r1), VecU64(r2)) => r1.borrow()[self.idx] == r2.borrow()[other.idx],
@@ -595,7 +631,13 @@ impl IndexedRef {
 
 impl Value {
     pub fn equals(&self, other: &Self) -> PartialVMResult<bool> {
-        self.0.equals(&other.0)
+        self.equals_with_max_depth(other, VALUE_DEPTH_MAX)
+    }
+
+    /// Compares two values, failing with `VM_MAX_VALUE_DEPTH_REACHED` if they are nested deeper
+    /// than `max_depth`.
+    pub fn equals_with_max_depth(&self, other: &Self, max_depth: usize) -> PartialVMResult<bool> {
+        self.0.equals(&other.0, 1, max_depth)
     }
 }
 
@@ -608,17 +650,19 @@ impl Value {
  **************************************************************************************/
 
 impl ContainerRef {
-    fn read_ref(self) -> PartialVMResult<Value> {
-        Ok(Value(ValueImpl::Container(self.container().copy_value()?)))
+    fn read_ref(self, max_depth: usize) -> PartialVMResult<Value> {
+        Ok(Value(ValueImpl::Container(
+            self.container().copy_value(1, max_depth)?,
+        )))
     }
 }
 
 impl IndexedRef {
-    fn read_ref(self) -> PartialVMResult<Value> {
+    fn read_ref(self, max_depth: usize) -> PartialVMResult<Value> {
         use Container::*;
 
         let res = match &*self.container_ref.container() {
-            Locals(r) | Vec(r) | Struct(r) => r.borrow()[self.idx].copy_value()?,
+            Locals(r) | Vec(r) | Struct(r) => r.borrow()[self.idx].copy_value(1, max_depth)?,
             VecU8(r) => ValueImpl::U8(r.borrow()[self.idx]),
             VecU64(r) => ValueImpl::U64(r.borrow()[self.idx]),
             VecU128(r) => ValueImpl::U128(r.borrow()[self.idx]),
@@ -631,23 +675,35 @@ impl IndexedRef {
 }
 
 impl ReferenceImpl {
-    fn read_ref(self) -> PartialVMResult<Value> {
+    fn read_ref(self, max_depth: usize) -> PartialVMResult<Value> {
         match self {
-            Self::ContainerRef(r) => r.read_ref(),
-            Self::IndexedRef(r) => r.read_ref(),
+            Self::ContainerRef(r) => r.read_ref(max_depth),
+            Self::IndexedRef(r) => r.read_ref(max_depth),
         }
     }
 }
 
 impl StructRef {
     pub fn read_ref(self) -> PartialVMResult<Value> {
-        self.0.read_ref()
+        self.read_ref_with_max_depth(VALUE_DEPTH_MAX)
+    }
+
+    /// Reads the referenced struct, failing with `VM_MAX_VALUE_DEPTH_REACHED` if it is nested
+    /// deeper than `max_depth`.
+    pub fn read_ref_with_max_depth(self, max_depth: usize) -> PartialVMResult<Value> {
+        self.0.read_ref(max_depth)
     }
 }
 
 impl Reference {
     pub fn read_ref(self) -> PartialVMResult<Value> {
-        self.0.read_ref()
+        self.read_ref_with_max_depth(VALUE_DEPTH_MAX)
+    }
+
+    /// Reads the referenced value, failing with `VM_MAX_VALUE_DEPTH_REACHED` if it is nested
+    /// deeper than `max_depth`.
+    pub fn read_ref_with_max_depth(self, max_depth: usize) -> PartialVMResult<Value> {
+        self.0.read_ref(max_depth)
     }
 }
 
@@ -899,13 +955,19 @@ impl Locals {
     }
 
     pub fn copy_loc(&self, idx: usize) -> PartialVMResult<Value> {
+        self.copy_loc_with_max_depth(idx, VALUE_DEPTH_MAX)
+    }
+
+    /// Copies the local at `idx`, failing with `VM_MAX_VALUE_DEPTH_REACHED` if it is nested
+    /// deeper than `max_depth`.
+    pub fn copy_loc_with_max_depth(&self, idx: usize, max_depth: usize) -> PartialVMResult<Value> {
         let v = self.0.borrow();
         match v.get(idx) {
             Some(ValueImpl::Invalid) => Err(PartialVMError::new(
                 StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR,
             )
             .with_message(format!("cannot copy invalid value at index {}", idx))),
-            Some(v) => Ok(Value(v.copy_value()?)),
+            Some(v) => Ok(Value(v.copy_value(1, max_depth)?)),
             None => Err(
                 PartialVMError::new(StatusCode::VERIFIER_INVARIANT_VIOLATION).with_message(
                     format!("local index out of bounds: got {}, len: {}", idx, v.len()),
@@ -998,6 +1060,17 @@ impl Value {
         ))))
     }
 
+    pub fn from_vector(v: Vector) -> Self {
+        Self(ValueImpl::Container(v.0))
+    }
+
+    pub fn from_reference(r: Reference) -> Self {
+        match r.0 {
+            ReferenceImpl::IndexedRef(r) => Self(ValueImpl::IndexedRef(r)),
+            ReferenceImpl::ContainerRef(r) => Self(ValueImpl::ContainerRef(r)),
+        }
+    }
+
     // TODO: consider whether we want to replace these with fn vector(v: Vec<Value>).
     pub fn vector_u8(it: impl IntoIterator<Item = u8>) -> Self {
         Self(ValueImpl::Container(Container::VecU8(Rc::new(
@@ -1680,6 +1753,17 @@ impl VectorRef {
         self.0.mark_dirty();
         Ok(())
     }
+
+    /// Borrow the bytes of a `&vector<u8>` without copying them.
+    pub fn as_bytes_ref(&self) -> PartialVMResult<Ref<'_, Vec<u8>>> {
+        match self.0.container() {
+            Container::VecU8(r) => Ok(r.borrow()),
+            _ => Err(
+                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                    .with_message("expected vector<u8>".to_string()),
+            ),
+        }
+    }
 }
 
 impl Vector {
@@ -1798,23 +1882,31 @@ impl Vector {
  **************************************************************************************/
 
 impl Container {
+    // Nested containers are visited with an explicit worklist rather than by recursion, so the
+    // size of arbitrarily deep values can be computed without exhausting the stack.
     fn size(&self) -> AbstractMemorySize<GasCarrier> {
-        match self {
-            Self::Locals(r) | Self::Vec(r) | Self::Struct(r) => Struct::size_impl(&*r.borrow()),
-            Self::VecU8(r) => AbstractMemorySize::new((r.borrow().len() * size_of::<u8>()) as u64),
-            Self::VecU64(r) => {
-                AbstractMemorySize::new((r.borrow().len() * size_of::<u64>()) as u64)
-            }
-            Self::VecU128(r) => {
-                AbstractMemorySize::new((r.borrow().len() * size_of::<u128>()) as u64)
-            }
-            Self::VecBool(r) => {
-                AbstractMemorySize::new((r.borrow().len() * size_of::<bool>()) as u64)
-            }
-            Self::VecAddress(r) => {
-                AbstractMemorySize::new((r.borrow().len() * size_of::<AccountAddress>()) as u64)
-            }
+        let mut size = 0;
+        let mut worklist = vec![self.copy_by_ref()];
+        while let Some(container) = worklist.pop() {
+            size += match &container {
+                Self::Locals(r) | Self::Vec(r) | Self::Struct(r) => {
+                    let mut size = STRUCT_SIZE.get();
+                    for v in r.borrow().iter() {
+                        match v {
+                            ValueImpl::Container(c) => worklist.push(c.copy_by_ref()),
+                            v => size += v.size().get(),
+                        }
+                    }
+                    size
+                }
+                Self::VecU8(r) => (r.borrow().len() * size_of::<u8>()) as u64,
+                Self::VecU64(r) => (r.borrow().len() * size_of::<u64>()) as u64,
+                Self::VecU128(r) => (r.borrow().len() * size_of::<u128>()) as u64,
+                Self::VecBool(r) => (r.borrow().len() * size_of::<bool>()) as u64,
+                Self::VecAddress(r) => (r.borrow().len() * size_of::<AccountAddress>()) as u64,
+            };
         }
+        AbstractMemorySize::new(size)
     }
 }
 
@@ -1846,15 +1938,11 @@ impl ValueImpl {
 }
 
 impl Struct {
-    fn size_impl(fields: &[ValueImpl]) -> AbstractMemorySize<GasCarrier> {
-        fields
+    pub fn size(&self) -> AbstractMemorySize<GasCarrier> {
+        self.fields
             .iter()
             .fold(STRUCT_SIZE, |acc, v| acc.map2(v.size(), Add::add))
     }
-
-    pub fn size(&self) -> AbstractMemorySize<GasCarrier> {
-        Self::size_impl(&self.fields)
-    }
 }
 
 impl Value {
@@ -2012,6 +2100,27 @@ impl GlobalValueImpl {
         })
     }
 
+    fn copy_value(&self, max_depth: usize) -> PartialVMResult<Self> {
+        let copy_fields =
+            |fields: &Rc<RefCell<Vec<ValueImpl>>>| match Container::Struct(Rc::clone(fields))
+                .copy_value(1, max_depth)?
+            {
+                Container::Struct(fields) => Ok(fields),
+                _ => unreachable!(),
+            };
+        Ok(match self {
+            Self::None => Self::None,
+            Self::Deleted => Self::Deleted,
+            Self::Fresh { fields } => Self::Fresh {
+                fields: copy_fields(fields)?,
+            },
+            Self::Cached { fields, status } => Self::Cached {
+                fields: copy_fields(fields)?,
+                status: Rc::new(RefCell::new(*status.borrow())),
+            },
+        })
+    }
+
     fn is_mutated(&self) -> bool {
         match self {
             Self::None => false,
@@ -2064,6 +2173,18 @@ impl GlobalValue {
     pub fn is_mutated(&self) -> bool {
         self.0.is_mutated()
     }
+
+    /// Makes a deep copy of the slot, including the status of the resource relative to
+    /// storage. References taken from the original do not alias the copy.
+    pub fn copy_value(&self) -> PartialVMResult<Self> {
+        self.copy_value_with_max_depth(VALUE_DEPTH_MAX)
+    }
+
+    /// Like `copy_value`, but fails with `VM_MAX_VALUE_DEPTH_REACHED` if the resource is nested
+    /// deeper than `max_depth`.
+    pub fn copy_value_with_max_depth(&self, max_depth: usize) -> PartialVMResult<Self> {
+        Ok(Self(self.0.copy_value(max_depth)?))
+    }
 }
 
 /***************************************************************************************
@@ -2197,7 +2318,16 @@ pub mod debug {
         debug_write!(buf, "{}", x)
     }
 
-    fn print_value_impl<B: Write>(buf: &mut B, val: &ValueImpl) -> PartialVMResult<()> {
+    // Values nested deeper than `VALUE_DEPTH_MAX` are elided rather than printed, so that printing
+    // cannot exhaust the stack.
+    fn print_value_impl<B: Write>(
+        buf: &mut B,
+        val: &ValueImpl,
+        depth: usize,
+    ) -> PartialVMResult<()> {
+        if depth > VALUE_DEPTH_MAX {
+            return debug_write!(buf, "..");
+        }
         match val {
             ValueImpl::Invalid => print_invalid(buf),
 
@@ -2207,7 +2337,7 @@ pub mod debug {
             ValueImpl::Bool(x) => print_bool(buf, x),
             ValueImpl::Address(x) => print_address(buf, x),
 
-            ValueImpl::Container(c) => print_container(buf, c),
+            ValueImpl::Container(c) => print_container(buf, c, depth),
 
             ValueImpl::ContainerRef(r) => print_container_ref(buf, r),
             ValueImpl::IndexedRef(r) => print_indexed_ref(buf, r),
@@ -2240,13 +2370,12 @@ pub mod debug {
         Ok(())
     }
 
-    fn print_container<B: Write>(buf: &mut B, c: &Container) -> PartialVMResult<()> {
+    fn print_container<B: Write>(buf: &mut B, c: &Container, depth: usize) -> PartialVMResult<()> {
+        let print_elem = |buf: &mut B, v: &ValueImpl| print_value_impl(buf, v, depth + 1);
         match c {
-            Container::Vec(r) => print_list(buf, "[", r.borrow().iter(), print_value_impl, "]"),
+            Container::Vec(r) => print_list(buf, "[", r.borrow().iter(), print_elem, "]"),
 
-            Container::Struct(r) => {
-                print_list(buf, "{ ", r.borrow().iter(), print_value_impl, " }")
-            }
+            Container::Struct(r) => print_list(buf, "{ ", r.borrow().iter(), print_elem, " }"),
 
             Container::VecU8(r) => print_list(buf, "[", r.borrow().iter(), print_u8, "]"),
             Container::VecU64(r) => print_list(buf, "[", r.borrow().iter(), print_u64, "]"),
@@ -2263,7 +2392,7 @@ pub mod debug {
 
     fn print_container_ref<B: Write>(buf: &mut B, r: &ContainerRef) -> PartialVMResult<()> {
         debug_write!(buf, "(&) ")?;
-        print_container(buf, r.container())
+        print_container(buf, r.container(), 1)
     }
 
     fn print_slice_elem<B, X, F>(buf: &mut B, v: &[X], idx: usize, print: F) -> PartialVMResult<()>
@@ -2284,7 +2413,7 @@ pub mod debug {
         let idx = r.idx;
         match r.container_ref.container() {
             Container::Locals(r) | Container::Vec(r) | Container::Struct(r) => {
-                print_slice_elem(buf, &*r.borrow(), idx, print_value_impl)
+                print_slice_elem(buf, &*r.borrow(), idx, |buf, v| print_value_impl(buf, v, 1))
             }
 
             Container::VecU8(r) => print_slice_elem(buf, &*r.borrow(), idx, print_u8),
@@ -2306,14 +2435,14 @@ pub mod debug {
         // REVIEW: The number of spaces in the indent is currently hard coded.
         for (idx, val) in locals.0.borrow().iter().enumerate() {
             debug_write!(buf, "            [{}] ", idx)?;
-            print_value_impl(buf, val)?;
+            print_value_impl(buf, val, 1)?;
             debug_writeln!(buf)?;
         }
         Ok(())
     }
 
     pub fn print_value<B: Write>(buf: &mut B, val: &Value) -> PartialVMResult<()> {
-        print_value_impl(buf, &val.0)
+        print_value_impl(buf, &val.0, 1)
     }
 }
 
@@ -2342,12 +2471,38 @@ use serde::{
     Deserialize,
 };
 
+// Both directions recurse along the layout, one level per level of nesting of the layout, so
+// bounding the depth of the layout bounds the recursion regardless of the value or the input.
 impl Value {
     pub fn simple_deserialize(blob: &[u8], layout: &MoveTypeLayout) -> Option<Value> {
+        Self::simple_deserialize_with_max_depth(blob, layout, VALUE_DEPTH_MAX)
+    }
+
+    /// Like `simple_deserialize`, but fails for layouts nested deeper than `max_depth`.
+    pub fn simple_deserialize_with_max_depth(
+        blob: &[u8],
+        layout: &MoveTypeLayout,
+        max_depth: usize,
+    ) -> Option<Value> {
+        if !layout.is_within_depth(max_depth) {
+            return None;
+        }
         bcs::from_bytes_seed(SeedWrapper { layout }, blob).ok()
     }
 
     pub fn simple_serialize(&self, layout: &MoveTypeLayout) -> Option<Vec<u8>> {
+        self.simple_serialize_with_max_depth(layout, VALUE_DEPTH_MAX)
+    }
+
+    /// Like `simple_serialize`, but fails for layouts nested deeper than `max_depth`.
+    pub fn simple_serialize_with_max_depth(
+        &self,
+        layout: &MoveTypeLayout,
+        max_depth: usize,
+    ) -> Option<Vec<u8>> {
+        if !layout.is_within_depth(max_depth) {
+            return None;
+        }
         bcs::to_bytes(&AnnotatedValue {
             layout,
             val: &self.0,
@@ -2358,10 +2513,16 @@ impl Value {
 
 impl Struct {
     pub fn simple_deserialize(blob: &[u8], layout: &MoveStructLayout) -> Option<Struct> {
+        if !layout.is_within_depth(VALUE_DEPTH_MAX) {
+            return None;
+        }
         bcs::from_bytes_seed(SeedWrapper { layout }, blob).ok()
     }
 
     pub fn simple_serialize(&self, layout: &MoveStructLayout) -> Option<Vec<u8>> {
+        if !layout.is_within_depth(VALUE_DEPTH_MAX) {
+            return None;
+        }
         bcs::to_bytes(&AnnotatedValue {
             layout,
             val: &self.fields,
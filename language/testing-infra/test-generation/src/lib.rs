@@ -33,7 +33,7 @@ use move_core_types::{
     value::MoveValue,
     vm_status::{StatusCode, VMStatus},
 };
-use move_vm_runtime::move_vm::MoveVM;
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
 use move_vm_test_utils::{DeltaStorage, InMemoryStorage};
 use move_vm_types::gas_schedule::GasStatus;
 use once_cell::sync::Lazy;
@@ -123,9 +123,10 @@ fn execute_function_in_module(
         module.identifier_at(entry_name_idx)
     };
     {
-        let vm = MoveVM::new(move_stdlib::natives::all_natives(
-            AccountAddress::from_hex_literal("0x1").unwrap(),
-        ))
+        let vm = MoveVM::new(
+            move_stdlib::natives::all_natives(AccountAddress::from_hex_literal("0x1").unwrap()),
+            VMConfig::default(),
+        )
         .unwrap();
 
         let mut changeset = ChangeSet::new();
@@ -29,6 +29,7 @@ use move_resource_viewer::MoveValueAnnotator;
 use move_stdlib::move_stdlib_named_addresses;
 use move_symbol_pool::Symbol;
 use move_vm_runtime::{
+    config::VMConfig,
     move_vm::MoveVM,
     session::{SerializedReturnValues, Session},
 };
@@ -283,7 +284,11 @@ impl<'a> SimpleVMTestAdapter<'a> {
         f: impl FnOnce(&mut Session<InMemoryStorage>, &mut GasStatus) -> VMResult<Ret>,
     ) -> VMResult<Ret> {
         // start session
-        let vm = MoveVM::new(move_stdlib::natives::all_natives(STD_ADDR)).unwrap();
+        let vm = MoveVM::new(
+            move_stdlib::natives::all_natives(STD_ADDR),
+            VMConfig::default(),
+        )
+        .unwrap();
         let (mut session, mut gas_status) = {
             let gas_status = move_cli::sandbox::utils::get_gas_status(
                 &move_vm_types::gas_schedule::INITIAL_COST_SCHEDULE,
@@ -299,7 +304,7 @@ impl<'a> SimpleVMTestAdapter<'a> {
 
         // save changeset
         // TODO support events
-        let (changeset, _events) = session.finish()?;
+        let (changeset, _events, _) = session.finish()?;
         self.storage.apply(changeset).unwrap();
         Ok(res)
     }
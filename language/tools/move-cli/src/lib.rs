@@ -19,14 +19,13 @@ const BCS_EXTENSION: &str = "bcs";
 
 use anyhow::Result;
 use clap::Parser;
-use move_core_types::{
-    account_address::AccountAddress, errmap::ErrorMapping, gas_schedule::CostTable,
-    identifier::Identifier,
-};
-use move_vm_runtime::native_functions::NativeFunction;
+#[cfg(feature = "table-extension")]
+use move_core_types::account_address::AccountAddress;
+use move_core_types::{errmap::ErrorMapping, gas_schedule::CostTable};
+use move_vm_runtime::native_functions::NativeFunctionEntry;
 use std::path::PathBuf;
 
-type NativeFunctionRecord = (AccountAddress, Identifier, Identifier, NativeFunction);
+type NativeFunctionRecord = NativeFunctionEntry;
 
 #[derive(Parser)]
 #[clap(author, version, about)]
@@ -48,6 +47,16 @@ pub struct Move {
     /// Package build options
     #[clap(flatten)]
     build_config: BuildConfig,
+
+    /// Enable the table extension, registering its natives at this address. Tables created by
+    /// `sandbox run` are stored under the storage directory.
+    #[cfg(feature = "table-extension")]
+    #[clap(
+        long = "table-extension",
+        global = true,
+        parse(try_from_str = AccountAddress::from_hex_literal)
+    )]
+    table_extension: Option<AccountAddress>,
 }
 
 /// MoveCLI is the CLI that will be executed by the `move-cli` command
@@ -100,6 +109,14 @@ pub fn run_cli(
     move_args: &Move,
     cmd: &Command,
 ) -> Result<()> {
+    #[cfg(feature = "table-extension")]
+    let natives = match move_args.table_extension {
+        Some(addr) => natives
+            .into_iter()
+            .chain(move_table_extension::table_natives(addr))
+            .collect(),
+        None => natives,
+    };
     match cmd {
         Command::Sandbox { storage_dir, cmd } => cmd.handle_command(
             natives,
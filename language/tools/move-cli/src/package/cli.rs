@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     fmt,
     fs::{create_dir_all, read_to_string},
     io::Write,
@@ -23,6 +23,8 @@ compile_error!("Unsupported OS, currently we only support windows and unix famil
 use anyhow::{bail, Result};
 
 use clap::Parser;
+use colored::Colorize;
+use move_binary_format::access::ModuleAccess;
 use move_command_line_common::files::{FileHash, MOVE_COVERAGE_MAP_EXTENSION};
 use move_compiler::{
     compiled_unit::{CompiledUnit, NamedCompiledModule},
@@ -39,7 +41,11 @@ use move_coverage::{
 };
 use move_disassembler::disassembler::Disassembler;
 use move_package::{
-    compilation::{build_plan::BuildPlan, compiled_package::CompiledUnitWithSource},
+    compilation::{
+        build_plan::BuildPlan,
+        compiled_package::{CompiledPackage, CompiledUnitWithSource},
+    },
+    registry::{filesystem::FileSystemRegistry, publish_package},
     source_package::layout::SourcePackageLayout,
     Architecture, ModelConfig,
 };
@@ -184,6 +190,16 @@ pub enum PackageCommand {
         #[clap(long = "name")]
         module_or_script_name: String,
     },
+    /// Publish the package to the registry given by `--registry`, by adding its archive and an
+    /// entry for its version to the registry index. All dependencies of the package must be
+    /// registry dependencies.
+    #[clap(name = "publish-registry")]
+    PublishRegistry,
+    /// Copy all dependencies of the package, including transitive and dev dependencies, into its
+    /// `vendor` directory. Later builds resolve the dependencies from there, so that the package
+    /// can be built offline.
+    #[clap(name = "vendor")]
+    Vendor,
 }
 
 #[derive(Parser, Debug)]
@@ -277,7 +293,7 @@ impl CoverageSummaryOptions {
 
 pub fn handle_package_commands(
     path: &Path,
-    config: move_package::BuildConfig,
+    mut config: move_package::BuildConfig,
     cmd: &PackageCommand,
     natives: Vec<NativeFunctionRecord>,
 ) -> Result<()> {
@@ -289,6 +305,11 @@ pub fn handle_package_commands(
         return Ok(());
     }
 
+    // The registry path is relative to the current directory, not to the package root.
+    if let Some(registry) = &config.registry {
+        config.registry = Some(std::env::current_dir()?.join(registry));
+    }
+
     // Always root ourselves to the package root, and then compile relative to that.
     let rooted_path = SourcePackageLayout::try_find_root(&path.canonicalize()?)?;
     std::env::set_current_dir(&rooted_path).unwrap();
@@ -299,9 +320,17 @@ pub fn handle_package_commands(
         PackageCommand::Build => {
             let architecture = config.architecture.unwrap_or(Architecture::Move);
 
+            // All members are built if the package is a workspace
             match architecture {
-                Architecture::Move | Architecture::AsyncMove => {
-                    config.compile_package(&rerooted_path, &mut std::io::stderr())?;
+                Architecture::Move => {
+                    let packages =
+                        config.compile_workspace(&rerooted_path, &mut std::io::stderr())?;
+                    warn_about_missing_natives(&packages, &natives, architecture)?;
+                }
+
+                // Natives of async packages are provided by the async runtime
+                Architecture::AsyncMove => {
+                    config.compile_workspace(&rerooted_path, &mut std::io::stderr())?;
                 }
 
                 Architecture::Ethereum => {
@@ -442,6 +471,20 @@ pub fn handle_package_commands(
         PackageCommand::CoverageReport { options } => {
             options.handle_command(config, &rerooted_path)?;
         }
+        PackageCommand::PublishRegistry => {
+            let registry = match config.registry {
+                Some(registry) => FileSystemRegistry::new(registry),
+                None => bail!("No package registry was given. Use `--registry` to specify one"),
+            };
+            let (package_name, entry) = publish_package(&rerooted_path, &registry)?;
+            println!("PUBLISHED {} {}", package_name, entry.version);
+        }
+        PackageCommand::Vendor => {
+            let vendor_info = config.vendor_package(&rerooted_path)?;
+            for package_name in vendor_info.packages.keys() {
+                println!("VENDORED {}", package_name);
+            }
+        }
         PackageCommand::New { .. } => {
             panic!("Hit a package new command after it should have been handled -- this should never happen")
         }
@@ -449,6 +492,53 @@ pub fn handle_package_commands(
     Ok(())
 }
 
+/// Warn about the native functions declared in the packages or their dependencies for which
+/// `natives` has no implementation. Modules declaring them cannot be published.
+fn warn_about_missing_natives(
+    packages: &[CompiledPackage],
+    natives: &[NativeFunctionRecord],
+    architecture: Architecture,
+) -> Result<()> {
+    let registered: BTreeSet<_> = natives
+        .iter()
+        .map(|native| {
+            (
+                native.address,
+                native.module_name.as_str(),
+                native.function_name.as_str(),
+            )
+        })
+        .collect();
+    let mut stderr = std::io::stderr();
+    // Dependencies shared by several packages are only reported once
+    let mut reported = BTreeSet::new();
+    for unit in packages.iter().flat_map(|package| package.all_modules()) {
+        let module = match &unit.unit {
+            CompiledUnit::Module(NamedCompiledModule { module, .. }) => module,
+            CompiledUnit::Script(_) => continue,
+        };
+        for def in module.function_defs().iter().filter(|def| def.is_native()) {
+            let handle = module.function_handle_at(def.function);
+            let key = (
+                *module.address(),
+                module.name().as_str(),
+                module.identifier_at(handle.name).as_str(),
+            );
+            if !registered.contains(&key) && reported.insert(key) {
+                writeln!(
+                    stderr,
+                    "warning: native function 0x{}::{}::{} is not implemented for the `{}` architecture",
+                    key.0.short_str_lossless(),
+                    key.1,
+                    key.2,
+                    architecture,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn run_move_unit_tests(
     pkg_path: &Path,
     mut build_config: move_package::BuildConfig,
@@ -456,7 +546,7 @@ pub fn run_move_unit_tests(
     natives: Vec<NativeFunctionRecord>,
     compute_coverage: bool,
 ) -> Result<UnitTestResult> {
-    let mut test_plan = None;
+    let mut test_plans = None;
     build_config.test_mode = true;
     build_config.dev_mode = true;
 
@@ -475,11 +565,16 @@ pub fn run_move_unit_tests(
         })
         .collect();
 
+    let is_workspace = resolution_graph.is_workspace();
+    let members = resolution_graph.workspace_members();
+    let root_name = resolution_graph.root_package.package.name;
+
     // Get the source files for all modules. We need this in order to report source-mapped error
-    // messages.
+    // messages. The root package of a workspace has no sources.
     let dep_file_map: HashMap<_, _> = resolution_graph
         .package_table
         .iter()
+        .filter(|(name, _)| !is_workspace || **name != root_name)
         .flat_map(|(_, rpkg)| {
             rpkg.get_sources(&resolution_graph.build_options)
                 .unwrap()
@@ -492,19 +587,33 @@ pub fn run_move_unit_tests(
                 .collect::<HashMap<_, _>>()
         })
         .collect();
-    let root_package = resolution_graph.root_package.package.name;
+    if compute_coverage && is_workspace {
+        bail!(
+            "Test coverage cannot be collected for a workspace. Run the tests of its members \
+             individually to collect coverage"
+        )
+    }
     let build_plan = BuildPlan::create(resolution_graph)?;
     // Compile the package. We need to intercede in the compilation, process being performed by the
     // Move package system, to first grab the compilation env, construct the test plan from it, and
     // then save it, before resuming the rest of the compilation and returning the results and
-    // control back to the Move package system.
-    build_plan.compile_with_driver(&mut std::io::stdout(), |compiler| {
+    // control back to the Move package system. All members of a workspace are compiled together,
+    // and a test plan is constructed for each of them.
+    build_plan.compile_workspace_with_driver(&mut std::io::stdout(), |compiler| {
         let (files, comments_and_compiler_res) = compiler.run::<PASS_CFGIR>().unwrap();
         let (_, compiler) =
             diagnostics::unwrap_or_report_diagnostics(&files, comments_and_compiler_res);
         let (mut compiler, cfgir) = compiler.into_ast();
         let compilation_env = compiler.compilation_env();
-        let built_test_plan = construct_test_plan(compilation_env, Some(root_package), &cfgir);
+        let built_test_plans = members
+            .iter()
+            .map(|member| {
+                (
+                    *member,
+                    construct_test_plan(compilation_env, Some(*member), &cfgir),
+                )
+            })
+            .collect::<Vec<_>>();
         if let Err(diags) = compilation_env.check_diags_at_or_above_severity(Severity::Warning) {
             diagnostics::report_diagnostics(&files, diags);
         }
@@ -512,15 +621,12 @@ pub fn run_move_unit_tests(
         let compilation_result = compiler.at_cfgir(cfgir).build();
 
         let (units, _) = diagnostics::unwrap_or_report_diagnostics(&files, compilation_result);
-        test_plan = Some((built_test_plan, files.clone(), units.clone()));
+        test_plans = Some((built_test_plans, files.clone(), units.clone()));
         Ok((files, units))
     })?;
 
-    let (test_plan, mut files, units) = test_plan.unwrap();
+    let (test_plans, mut files, units) = test_plans.unwrap();
     files.extend(dep_file_map);
-    let test_plan = test_plan.unwrap();
-    let no_tests = test_plan.is_empty();
-    let test_plan = TestPlan::new(test_plan, files, units);
 
     let trace_path = pkg_path.join(".trace");
     let coverage_map_path = pkg_path
@@ -540,13 +646,41 @@ pub fn run_move_unit_tests(
         std::env::set_var("MOVE_VM_TRACE", &trace_path);
     }
 
-    // Run the tests. If any of the tests fail, then we don't produce a coverage report, so cleanup
-    // the trace files.
-    if !unit_test_config
-        .run_and_report_unit_tests(test_plan, Some(natives), std::io::stdout())
-        .unwrap()
-        .1
-    {
+    // Run the tests of every member, and report the aggregated results for a workspace
+    let mut no_tests = true;
+    let mut failed_members = vec![];
+    for (member, test_plan) in test_plans {
+        let test_plan = test_plan.unwrap();
+        no_tests &= test_plan.is_empty();
+        if is_workspace {
+            println!("{} {}", "TESTING".bold().green(), member);
+        }
+        let test_plan = TestPlan::new(test_plan, files.clone(), units.clone());
+        if !unit_test_config
+            .run_and_report_unit_tests(test_plan, Some(natives.clone()), std::io::stdout())
+            .unwrap()
+            .1
+        {
+            failed_members.push(member);
+        }
+    }
+    if is_workspace {
+        println!(
+            "Workspace test result: {}. Total packages: {}; passed: {}; failed: {}",
+            if failed_members.is_empty() {
+                "OK".bold().bright_green()
+            } else {
+                "FAILED".bold().bright_red()
+            },
+            members.len(),
+            members.len() - failed_members.len(),
+            failed_members.len()
+        );
+    }
+
+    // If any of the tests fail, then we don't produce a coverage report, so cleanup the trace
+    // files.
+    if !failed_members.is_empty() {
         cleanup_trace();
         return Ok(UnitTestResult::Failure);
     }
@@ -7,7 +7,11 @@ use crate::package::cli;
 use anyhow::bail;
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
 use colored::Colorize;
-use move_package::{BuildConfig, ModelConfig};
+use move_model::model::GlobalEnv;
+use move_package::{
+    compilation::model_builder::ModelBuilder, source_package::manifest_parser::is_workspace,
+    BuildConfig, ModelConfig,
+};
 use move_prover::run_move_prover_with_model;
 use std::{
     io::Write,
@@ -113,6 +117,69 @@ pub fn run_move_prover(
     // Always run the prover in dev mode, so addresses get default assignments
     config.dev_mode = true;
 
+    if is_workspace(path)? {
+        return run_move_prover_on_workspace(config, path, target_filter, for_test, options);
+    }
+    let options = prover_options(path, for_test, options)?;
+    let now = Instant::now();
+    let model = config.move_model_for_package(
+        path,
+        ModelConfig {
+            all_files_as_targets: false,
+            target_filter: target_filter.clone(),
+        },
+    )?;
+    prove_model(&model, path, for_test, options, now)
+}
+
+/// Runs the prover on every member of the workspace at `path`, using the `Prover.toml` of the
+/// member if it has one. Fails if the verification of any member fails.
+///
+/// The workspace is resolved once, but a separate model is built for every member, as the prover
+/// verifies the modules of one model with one set of options. Dependencies shared by several
+/// members are therefore compiled once per member.
+fn run_move_prover_on_workspace(
+    config: BuildConfig,
+    path: &Path,
+    target_filter: &Option<String>,
+    for_test: bool,
+    options: &[String],
+) -> anyhow::Result<()> {
+    let resolution_graph = config.resolution_graph_for_package(path)?;
+    let mut message_writer = StandardStream::stdout(ColorChoice::Auto);
+    let mut failed_members = vec![];
+    for member in resolution_graph.workspace_members() {
+        let member_path = resolution_graph.get_package(&member).package_path.clone();
+        writeln!(message_writer, "{} {}", "PROVING".bold().green(), member)?;
+        let options = prover_options(&member_path, for_test, options)?;
+        let now = Instant::now();
+        let model = ModelBuilder::create_for_package(
+            resolution_graph.clone(),
+            member,
+            ModelConfig {
+                all_files_as_targets: false,
+                target_filter: target_filter.clone(),
+            },
+        )
+        .build_model()?;
+        if prove_model(&model, &member_path, for_test, options, now).is_err() {
+            failed_members.push(member.to_string());
+        }
+    }
+    if !failed_members.is_empty() {
+        bail!(
+            "exiting with verification errors in workspace members: {}",
+            failed_members.join(", ")
+        )
+    }
+    Ok(())
+}
+
+fn prover_options(
+    path: &Path,
+    for_test: bool,
+    options: &[String],
+) -> anyhow::Result<move_prover::cli::Options> {
     let mut args = vec!["package".to_string()];
     let prover_toml = Path::new(&path).join("Prover.toml");
     if prover_toml.exists() {
@@ -139,23 +206,24 @@ pub fn run_move_prover(
                      by the package system"
         );
     }
-
-    let mut message_writer = StandardStream::stdout(ColorChoice::Auto);
-    let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
     if for_test {
         options.set_quiet();
         options.setup_logging_for_test();
     } else {
         options.setup_logging();
     }
-    let now = Instant::now();
-    let model = config.move_model_for_package(
-        path,
-        ModelConfig {
-            all_files_as_targets: false,
-            target_filter: target_filter.clone(),
-        },
-    )?;
+    Ok(options)
+}
+
+fn prove_model(
+    model: &GlobalEnv,
+    path: &Path,
+    for_test: bool,
+    mut options: move_prover::cli::Options,
+    now: Instant,
+) -> anyhow::Result<()> {
+    let mut message_writer = StandardStream::stdout(ColorChoice::Auto);
+    let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
     let _temp_dir_holder = if for_test {
         // Need to ensure a distinct output.bpl file for concurrent execution. In non-test
         // mode, we actually want to use the static output.bpl for debugging purposes
@@ -170,7 +238,7 @@ pub fn run_move_prover(
     } else {
         None
     };
-    let res = run_move_prover_with_model(&model, &mut error_writer, options, Some(now));
+    let res = run_move_prover_with_model(model, &mut error_writer, options, Some(now));
     if for_test {
         let basedir = path
             .file_name()
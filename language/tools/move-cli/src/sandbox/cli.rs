@@ -70,8 +70,11 @@ pub enum SandboxCommand {
         /// bool literals (true, false),
         /// u64 literals (e.g., 10, 58),
         /// address literals (e.g., 0x12, 0x0000000000000000000000000000000f),
-        /// hexadecimal strings (e.g., x"0012" will parse as the vector<u8> value [00, 12]), and
-        /// ASCII strings (e.g., 'b"hi" will parse as the vector<u8> value [68, 69]).
+        /// hexadecimal strings (e.g., x"0012" will parse as the vector<u8> value [00, 12]),
+        /// ASCII strings (e.g., 'b"hi" will parse as the vector<u8> value [68, 69]),
+        /// typed vectors (e.g., vector<u64>[1,2], vector<vector<address>>[[0x1],[]]),
+        /// `0x1::ASCII::String` literals (e.g., '"hi"'), and
+        /// `0x1::Option::Option` literals (e.g., some(1u64), none).
         #[clap(
             long = "args",
             parse(try_from_str = parser::parse_transaction_argument),
@@ -80,6 +83,12 @@ pub enum SandboxCommand {
             multiple_occurrences(true)
         )]
         args: Vec<TransactionArgument>,
+        /// Arguments passed to the transaction as a JSON array, using the canonical JSON
+        /// representation of Move values (e.g., `'["10", "0x1", "0x0012", [true, false]]'` for
+        /// `main(a: u64, b: address, c: vector<u8>, d: vector<bool>)`). Cannot be combined with
+        /// `args`.
+        #[clap(long = "json-args", conflicts_with = "args")]
+        json_args: Option<String>,
         /// Possibly-empty list of type arguments passed to the transaction (e.g., `T` in
         /// `main<T>()`). Must match the type arguments kinds expected by `script_file`.
         #[clap(
@@ -118,6 +127,9 @@ pub enum SandboxCommand {
         /// Path to a resource, events file, or module stored on disk.
         #[clap(name = "file", parse(from_os_str))]
         file: PathBuf,
+        /// Print resources and events in their canonical JSON representation.
+        #[clap(long = "json")]
+        json: bool,
     },
     /// Delete all resources, events, and modules stored on disk under `storage-dir`.
     /// Does *not* delete anything in `src`.
@@ -195,6 +207,7 @@ impl SandboxCommand {
                     *no_republish,
                     *ignore_breaking_changes,
                     override_ordering.as_ref().map(|o| o.as_slice()),
+                    move_args.build_config.dev_mode,
                     move_args.verbose,
                 )
             }
@@ -203,6 +216,7 @@ impl SandboxCommand {
                 script_name,
                 signers,
                 args,
+                json_args,
                 type_args,
                 gas_budget,
                 dry_run,
@@ -220,9 +234,11 @@ impl SandboxCommand {
                     script_name,
                     signers,
                     args,
+                    json_args.as_deref(),
                     type_args.to_vec(),
                     *gas_budget,
                     *dry_run,
+                    move_args.build_config.dev_mode,
                     move_args.verbose,
                 )
             }
@@ -235,10 +251,10 @@ impl SandboxCommand {
                 *use_temp_dir,
                 *track_cov,
             ),
-            SandboxCommand::View { file } => {
+            SandboxCommand::View { file, json } => {
                 let state = PackageContext::new(&move_args.package_path, &move_args.build_config)?
                     .prepare_state(storage_dir)?;
-                sandbox::commands::view(&state, file)
+                sandbox::commands::view(&state, file, *json)
             }
             SandboxCommand::Clean {} => {
                 // delete storage
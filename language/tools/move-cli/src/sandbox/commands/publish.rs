@@ -3,7 +3,7 @@
 
 use crate::{
     sandbox::utils::{
-        explain_publish_changeset, explain_publish_error, get_gas_status, module,
+        explain_publish_changeset, explain_publish_error, get_gas_status, module, new_vm,
         on_disk_state_view::OnDiskStateView,
     },
     NativeFunctionRecord,
@@ -11,7 +11,6 @@ use crate::{
 use anyhow::{bail, Result};
 use move_core_types::gas_schedule::CostTable;
 use move_package::compilation::compiled_package::CompiledPackage;
-use move_vm_runtime::move_vm::MoveVM;
 use std::collections::BTreeMap;
 
 pub fn publish(
@@ -22,6 +21,7 @@ pub fn publish(
     no_republish: bool,
     ignore_breaking_changes: bool,
     override_ordering: Option<&[String]>,
+    allow_missing_natives: bool,
     verbose: bool,
 ) -> Result<()> {
     if verbose {
@@ -53,7 +53,7 @@ pub fn publish(
 
     // use the the publish_module API from the VM if we do not allow breaking changes
     if !ignore_breaking_changes {
-        let vm = MoveVM::new(natives).unwrap();
+        let vm = new_vm(natives, allow_missing_natives);
         let mut gas_status = get_gas_status(cost_table, None)?;
         let mut session = vm.new_session(state);
 
@@ -114,7 +114,7 @@ pub fn publish(
         }
 
         if !has_error {
-            let (changeset, events) = session.finish().map_err(|e| e.into_vm_status())?;
+            let (changeset, events, _) = session.finish().map_err(|e| e.into_vm_status())?;
             assert!(events.is_empty());
             if verbose {
                 explain_publish_changeset(&changeset, state);
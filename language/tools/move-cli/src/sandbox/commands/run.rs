@@ -3,8 +3,11 @@
 
 use crate::{
     sandbox::utils::{
-        contains_module, explain_execution_effects, explain_execution_error, get_gas_status,
-        is_bytecode_file, maybe_commit_effects, on_disk_state_view::OnDiskStateView,
+        contains_module, convert_json_args, explain_execution_effects, explain_execution_error,
+        extensions::{into_change_sets, new_extensions},
+        get_gas_status, is_bytecode_file, maybe_commit_effects, new_vm,
+        on_disk_state_view::OnDiskStateView,
+        script_parameter_types,
     },
     NativeFunctionRecord,
 };
@@ -20,7 +23,6 @@ use move_core_types::{
     value::MoveValue,
 };
 use move_package::compilation::compiled_package::CompiledPackage;
-use move_vm_runtime::move_vm::MoveVM;
 use std::{fs, path::Path};
 
 pub fn run(
@@ -33,9 +35,11 @@ pub fn run(
     script_name_opt: &Option<String>,
     signers: &[String],
     txn_args: &[TransactionArgument],
+    json_args: Option<&str>,
     vm_type_args: Vec<TypeTag>,
     gas_budget: Option<u64>,
     dry_run: bool,
+    allow_missing_natives: bool,
     verbose: bool,
 ) -> Result<()> {
     if !script_path.exists() {
@@ -69,11 +73,17 @@ move run` must be applied to a module inside `storage/`",
         .map(|s| AccountAddress::from_hex_literal(s))
         .collect::<Result<Vec<AccountAddress>, _>>()?;
     // TODO: parse Value's directly instead of going through the indirection of TransactionArgument?
-    let vm_args: Vec<Vec<u8>> = convert_txn_args(txn_args);
+    let vm_args: Vec<Vec<u8>> = match json_args {
+        Some(json_args) => {
+            let param_types = script_parameter_types(&bytecode, script_name_opt, &vm_type_args)?;
+            convert_json_args(state, &param_types, json_args)?
+        }
+        None => convert_txn_args(txn_args),
+    };
 
-    let vm = MoveVM::new(natives).unwrap();
+    let vm = new_vm(natives, allow_missing_natives);
     let mut gas_status = get_gas_status(cost_table, gas_budget)?;
-    let mut session = vm.new_session(state);
+    let mut session = vm.new_session_with_extensions(state, new_extensions(state)?);
 
     let script_type_parameters = vec![];
     let script_parameters = vec![];
@@ -120,10 +130,14 @@ move run` must be applied to a module inside `storage/`",
             txn_args,
         )
     } else {
-        let (changeset, events) = session.finish().map_err(|e| e.into_vm_status())?;
+        let (changeset, events, _, extensions) = session
+            .finish_with_extensions()
+            .map_err(|e| e.into_vm_status())?;
+        let extension_change_sets = into_change_sets(extensions)?;
         if verbose {
-            explain_execution_effects(&changeset, &events, state)?
+            explain_execution_effects(&changeset, &events, state)?;
+            extension_change_sets.explain();
         }
-        maybe_commit_effects(!dry_run, changeset, events, state)
+        maybe_commit_effects(!dry_run, changeset, events, extension_change_sets, state)
     }
 }
@@ -7,13 +7,42 @@ use crate::sandbox::utils::{
 
 use anyhow::{bail, Result};
 use std::path::Path;
-/// Print a module or resource stored in `file`
-pub fn view(state: &OnDiskStateView, path: &Path) -> Result<()> {
-    if state.is_resource_path(path) {
+/// Print a module or resource stored in `file`. If `json` is set, resources and events are
+/// printed in their canonical JSON representation.
+pub fn view(state: &OnDiskStateView, path: &Path, json: bool) -> Result<()> {
+    // Table entries carry no type information, so they are printed as raw bytes regardless of
+    // `json`.
+    #[cfg(feature = "table-extension")]
+    if state.is_table_path(path) {
+        let entries = state.view_table(path)?;
+        if entries.is_empty() {
+            println!("Table is empty.")
+        } else {
+            for (key, value) in entries {
+                println!("0x{} := 0x{}", hex::encode(key), hex::encode(value))
+            }
+        }
+        return Ok(());
+    }
+    if state.is_resource_path(path) && json {
+        match state.view_resource_json(path)? {
+            Some(resource) => println!("{}", resource),
+            None => println!("Resource not found."),
+        }
+    } else if state.is_resource_path(path) {
         match state.view_resource(path)? {
             Some(resource) => println!("{}", resource),
             None => println!("Resource not found."),
         }
+    } else if state.is_event_path(path) && json {
+        let events = state.view_events_json(path)?;
+        if events.is_empty() {
+            println!("Events not found.")
+        } else {
+            for event in events {
+                println!("{}", event)
+            }
+        }
     } else if state.is_event_path(path) {
         let events = state.view_events(path)?;
         if events.is_empty() {
@@ -0,0 +1,99 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module manages native extensions supported by sandbox execution. Such extensions are
+//! enabled by cfg features; their effects are explained and committed to disk together with
+//! the resources and events of a transaction.
+
+use crate::sandbox::utils::on_disk_state_view::OnDiskStateView;
+use anyhow::Result;
+use move_vm_runtime::native_extensions::NativeContextExtensions;
+
+#[cfg(feature = "table-extension")]
+use move_binary_format::errors::Location;
+#[cfg(feature = "table-extension")]
+use move_table_extension::{NativeTableContext, TableChangeSet};
+
+/// The changes made to native extensions by a transaction.
+pub(crate) struct ExtensionChangeSets {
+    #[cfg(feature = "table-extension")]
+    tables: TableChangeSet,
+}
+
+/// Create all available native context extensions for a session executing against `state`.
+#[allow(unused_mut, unused_variables)]
+pub(crate) fn new_extensions(state: &OnDiskStateView) -> Result<NativeContextExtensions<'_>> {
+    let mut e = NativeContextExtensions::default();
+    #[cfg(feature = "table-extension")]
+    e.add_with_savepoints(NativeTableContext::new(state.table_txn_hash()?, state));
+    Ok(e)
+}
+
+/// Extract the change sets of all available native context extensions.
+#[allow(unused_mut, unused_variables)]
+pub(crate) fn into_change_sets(
+    mut extensions: NativeContextExtensions,
+) -> Result<ExtensionChangeSets> {
+    Ok(ExtensionChangeSets {
+        #[cfg(feature = "table-extension")]
+        tables: extensions
+            .remove::<NativeTableContext>()
+            .into_change_set()
+            .map_err(|e| e.finish(Location::Undefined).into_vm_status())?,
+    })
+}
+
+impl ExtensionChangeSets {
+    pub(crate) fn is_empty(&self) -> bool {
+        #[cfg(feature = "table-extension")]
+        if !(self.tables.new_tables.is_empty()
+            && self.tables.removed_tables.is_empty()
+            && self.tables.dropped_tables.is_empty()
+            && self.tables.changes.is_empty())
+        {
+            return false;
+        }
+        true
+    }
+
+    /// Print the changes in the style of `explain_execution_effects`.
+    pub(crate) fn explain(&self) {
+        #[cfg(feature = "table-extension")]
+        explain_table_changes(&self.tables);
+    }
+
+    /// Commit the changes to `state`.
+    #[allow(unused_variables)]
+    pub(crate) fn commit(self, state: &OnDiskStateView) -> Result<()> {
+        #[cfg(feature = "table-extension")]
+        state.save_table_change_set(self.tables)?;
+        Ok(())
+    }
+}
+
+// =============================================================================================
+// Table Extension
+
+#[cfg(feature = "table-extension")]
+fn explain_table_changes(changes: &TableChangeSet) {
+    for handle in &changes.new_tables {
+        println!("Created table {:032x}", handle.0);
+    }
+    for (handle, change) in &changes.changes {
+        let written = change.entries.values().filter(|v| v.is_some()).count();
+        let deleted = change.entries.len() - written;
+        println!(
+            "Changed table {:032x}: wrote {:?} entries, deleted {:?} entries ({:+} bytes)",
+            handle.0, written, deleted, change.bytes_delta
+        );
+    }
+    for handle in &changes.removed_tables {
+        println!("Removed table {:032x}", handle.0);
+    }
+    for handle in &changes.dropped_tables {
+        println!(
+            "Dropped table {:032x}; its entries are garbage collected",
+            handle.0
+        );
+    }
+}
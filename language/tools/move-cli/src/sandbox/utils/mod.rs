@@ -1,18 +1,24 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::sandbox::utils::on_disk_state_view::OnDiskStateView;
-use anyhow::{bail, Result};
+use crate::{
+    sandbox::utils::{extensions::ExtensionChangeSets, on_disk_state_view::OnDiskStateView},
+    NativeFunctionRecord,
+};
+use anyhow::{anyhow, bail, Result};
 use colored::Colorize;
 use difference::{Changeset, Difference};
 use move_binary_format::{
-    access::ModuleAccess,
+    access::{ModuleAccess, ScriptAccess},
+    binary_views::BinaryIndexedView,
     compatibility::Compatibility,
     errors::VMError,
-    file_format::{AbilitySet, CompiledModule, FunctionDefinitionIndex, SignatureToken},
+    file_format::{
+        AbilitySet, CompiledModule, CompiledScript, FunctionDefinitionIndex, SignatureToken,
+    },
     normalized, IndexKind,
 };
-use move_bytecode_utils::Modules;
+use move_bytecode_utils::{layout::TypeLayoutBuilder, Modules};
 use move_command_line_common::files::{FileHash, MOVE_COMPILED_EXTENSION};
 use move_compiler::{
     compiled_unit::{CompiledUnit, NamedCompiledModule},
@@ -20,16 +26,20 @@ use move_compiler::{
 };
 use move_core_types::{
     account_address::AccountAddress,
-    effects::{ChangeSet, Event},
+    effects::{ChangeSet, ContractEvent},
     errmap::ErrorMapping,
     gas_schedule::{GasAlgebra, GasUnits},
-    language_storage::{ModuleId, TypeTag},
+    language_storage::{ModuleId, StructTag, TypeTag},
     transaction_argument::TransactionArgument,
+    value::MoveValue,
     vm_status::{AbortLocation, StatusCode, VMStatus},
 };
 use move_ir_types::location::Loc;
 use move_package::compilation::compiled_package::CompiledUnitWithSource;
 use move_resource_viewer::{AnnotatedMoveStruct, MoveValueAnnotator};
+use move_vm_runtime::{
+    config::VMConfig, move_vm::MoveVM, native_functions::MissingNativesAllowList,
+};
 use move_vm_types::gas_schedule::GasStatus;
 use std::{
     collections::{BTreeMap, HashMap},
@@ -37,6 +47,7 @@ use std::{
     path::Path,
 };
 
+pub mod extensions;
 pub mod on_disk_state_view;
 pub mod package_context;
 
@@ -45,6 +56,19 @@ use move_core_types::gas_schedule::CostTable;
 pub use on_disk_state_view::*;
 pub use package_context::*;
 
+/// Create the VM used by sandbox commands. In dev mode, modules declaring natives which the VM
+/// does not implement can be published and loaded.
+pub fn new_vm(
+    natives: impl IntoIterator<Item = NativeFunctionRecord>,
+    allow_missing_natives: bool,
+) -> MoveVM {
+    let mut vm_config = VMConfig::default();
+    if allow_missing_natives {
+        vm_config.missing_natives_allowed = MissingNativesAllowList::all();
+    }
+    MoveVM::new(natives, vm_config).unwrap()
+}
+
 pub fn get_gas_status(cost_table: &CostTable, gas_budget: Option<u64>) -> Result<GasStatus> {
     let gas_status = if let Some(gas_budget) = gas_budget {
         let max_gas_budget = u64::MAX
@@ -147,7 +171,7 @@ fn print_struct_diff_with_indent(
 
 pub(crate) fn explain_execution_effects(
     changeset: &ChangeSet,
-    events: &[Event],
+    events: &[ContractEvent],
     state: &OnDiskStateView,
 ) -> Result<()> {
     // execution effects should contain no modules
@@ -155,11 +179,21 @@ pub(crate) fn explain_execution_effects(
     if !events.is_empty() {
         println!("Emitted {:?} events:", events.len());
         // TODO: better event printing
-        for (event_key, event_sequence_number, _event_type, event_data) in events {
-            println!(
-                "Emitted {:?} as the {}th event to stream {:?}",
-                event_data, event_sequence_number, event_key
-            )
+        for event in events {
+            match event {
+                ContractEvent::Handle {
+                    guid,
+                    seq_num,
+                    data,
+                    ..
+                } => println!(
+                    "Emitted {:?} as the {}th event to stream {:?}",
+                    data, seq_num, guid
+                ),
+                ContractEvent::Module { type_tag, data } => {
+                    println!("Emitted {:?} as an event of type {}", data, type_tag)
+                }
+            }
         }
     }
     if !changeset.accounts().is_empty() {
@@ -242,11 +276,12 @@ pub(crate) fn explain_execution_effects(
     Ok(())
 }
 
-/// Commit the resources and events modified by a transaction to disk
+/// Commit the resources, events and native extension data modified by a transaction to disk
 pub(crate) fn maybe_commit_effects(
     commit: bool,
     changeset: ChangeSet,
-    events: Vec<Event>,
+    events: Vec<ContractEvent>,
+    extension_change_sets: ExtensionChangeSets,
     state: &OnDiskStateView,
 ) -> Result<()> {
     // similar to explain effects, all module publishing happens via save_modules(), so effects
@@ -261,10 +296,15 @@ pub(crate) fn maybe_commit_effects(
             }
         }
 
-        for (event_key, event_sequence_number, event_type, event_data) in events {
-            state.save_event(&event_key, event_sequence_number, event_type, event_data)?
+        for event in events {
+            state.save_event(event)?
         }
-    } else if !(changeset.resources().next().is_none() && events.is_empty()) {
+
+        extension_change_sets.commit(state)?;
+    } else if !(changeset.resources().next().is_none()
+        && events.is_empty()
+        && extension_change_sets.is_empty())
+    {
         println!("Discarding changes; re-run without --dry-run if you would like to keep them.")
     }
 
@@ -405,11 +445,11 @@ pub(crate) fn explain_publish_error(
             }
             println!("Re-run with --ignore-breaking-changes to publish anyway.")
         }
-        VMStatus::Error(MISSING_DEPENDENCY) => {
+        VMStatus::Error(MISSING_NATIVE_FUNCTION) => {
             let err_indices = error_clone.indices();
             let mut diags = Diagnostics::new();
             for (ind_kind, table_ind) in err_indices {
-                if let IndexKind::FunctionHandle = ind_kind {
+                if let IndexKind::FunctionDefinition = ind_kind {
                     let native_function = &(module.function_defs())[*table_ind as usize];
                     let fh = module.function_handle_at(native_function.function);
                     let mh = module.module_handle_at(fh.module);
@@ -568,3 +608,128 @@ pub(crate) fn contains_module(path: &Path) -> bool {
             Err(_) => false,
         }
 }
+
+/// Return the types of the non-signer parameters of the script in `bytecode`, or of the function
+/// `script_name` if `bytecode` holds a module, with type parameters instantiated by `type_args`
+pub(crate) fn script_parameter_types(
+    bytecode: &[u8],
+    script_name_opt: &Option<String>,
+    type_args: &[TypeTag],
+) -> Result<Vec<TypeTag>> {
+    let script;
+    let module;
+    let (view, params) = match script_name_opt {
+        Some(script_name) => {
+            module = CompiledModule::deserialize(bytecode)
+                .map_err(|e| anyhow!("Error deserializing module: {:?}", e))?;
+            // Only functions defined by the module can be run, not the ones it imports
+            let handle = module
+                .function_defs()
+                .iter()
+                .map(|def| module.function_handle_at(def.function))
+                .find(|handle| module.identifier_at(handle.name).as_str() == script_name)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Function {} not found in module {}",
+                        script_name,
+                        module.self_id()
+                    )
+                })?;
+            (
+                BinaryIndexedView::Module(&module),
+                module.signature_at(handle.parameters),
+            )
+        }
+        None => {
+            script = CompiledScript::deserialize(bytecode)
+                .map_err(|e| anyhow!("Error deserializing script: {:?}", e))?;
+            (
+                BinaryIndexedView::Script(&script),
+                script.signature_at(script.parameters),
+            )
+        }
+    };
+    let mut param_types = vec![];
+    for (idx, ty) in params.0.iter().enumerate() {
+        match ty {
+            // Signers are passed with --signers
+            SignatureToken::Signer => (),
+            SignatureToken::Reference(inner) if **inner == SignatureToken::Signer => (),
+            SignatureToken::Reference(inner) | SignatureToken::MutableReference(inner) => bail!(
+                "Parameter {} is a reference to {} and cannot be passed as a JSON argument",
+                idx,
+                signature_token_to_type_tag(&view, inner, type_args)?
+            ),
+            _ => param_types.push(signature_token_to_type_tag(&view, ty, type_args)?),
+        }
+    }
+    Ok(param_types)
+}
+
+fn signature_token_to_type_tag(
+    view: &BinaryIndexedView,
+    ty: &SignatureToken,
+    type_args: &[TypeTag],
+) -> Result<TypeTag> {
+    use SignatureToken::*;
+    let struct_tag = |idx, type_params| {
+        let handle = view.struct_handle_at(idx);
+        let module = view.module_handle_at(handle.module);
+        StructTag {
+            address: *view.address_identifier_at(module.address),
+            module: view.identifier_at(module.name).to_owned(),
+            name: view.identifier_at(handle.name).to_owned(),
+            type_params,
+        }
+    };
+    Ok(match ty {
+        Bool => TypeTag::Bool,
+        U8 => TypeTag::U8,
+        U64 => TypeTag::U64,
+        U128 => TypeTag::U128,
+        Address => TypeTag::Address,
+        Signer => TypeTag::Signer,
+        Vector(ty) => TypeTag::Vector(Box::new(signature_token_to_type_tag(view, ty, type_args)?)),
+        Struct(idx) => TypeTag::Struct(struct_tag(*idx, vec![])),
+        StructInstantiation(idx, tys) => TypeTag::Struct(struct_tag(
+            *idx,
+            tys.iter()
+                .map(|ty| signature_token_to_type_tag(view, ty, type_args))
+                .collect::<Result<_>>()?,
+        )),
+        TypeParameter(idx) => match type_args.get(*idx as usize) {
+            Some(ty) => ty.clone(),
+            None => bail!("Missing type argument for type parameter {}", idx),
+        },
+        Reference(_) | MutableReference(_) => bail!("Unexpected reference parameter"),
+    })
+}
+
+/// Parse `json_args`, a JSON array holding one value of each type in `param_types` in the
+/// canonical JSON representation of Move values, into BCS-serialized transaction arguments
+pub(crate) fn convert_json_args(
+    state: &OnDiskStateView,
+    param_types: &[TypeTag],
+    json_args: &str,
+) -> Result<Vec<Vec<u8>>> {
+    let args: Vec<serde_json::Value> = serde_json::from_str(json_args)
+        .map_err(|e| anyhow!("Expected a JSON array of arguments: {}", e))?;
+    if args.len() != param_types.len() {
+        bail!(
+            "Expected {} argument(s), but found {}",
+            param_types.len(),
+            args.len()
+        )
+    }
+    param_types
+        .iter()
+        .zip(args)
+        .map(|(ty, arg)| {
+            let layout = TypeLayoutBuilder::build_with_fields(ty, &state)?;
+            MoveValue::from_json(&arg, &layout)
+                .map_err(|e| anyhow!("Invalid argument {} of type {}: {}", arg, ty, e))?
+                .simple_serialize()
+                .ok_or_else(|| anyhow!("Failed to serialize argument {}", arg))
+        })
+        .collect()
+}
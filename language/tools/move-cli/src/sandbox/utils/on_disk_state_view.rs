@@ -8,32 +8,47 @@ use move_binary_format::{
     binary_views::BinaryIndexedView,
     file_format::{CompiledModule, CompiledScript, FunctionDefinitionIndex},
 };
-use move_bytecode_utils::module_cache::GetModule;
+use move_bytecode_utils::{layout::TypeLayoutBuilder, module_cache::GetModule};
 use move_command_line_common::files::MOVE_COMPILED_EXTENSION;
+#[cfg(feature = "table-extension")]
+use move_core_types::gas_schedule::{GasAlgebra, GasCarrier, InternalGasUnits};
 use move_core_types::{
     account_address::AccountAddress,
+    effects::ContractEvent,
     identifier::Identifier,
+    json::JsonMoveValue,
     language_storage::{ModuleId, StructTag, TypeTag},
     parser,
     resolver::{ModuleResolver, ResourceResolver},
+    value::MoveValue,
 };
 use move_disassembler::disassembler::Disassembler;
 use move_ir_types::location::Spanned;
 use move_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue, MoveValueAnnotator};
+#[cfg(feature = "table-extension")]
+use move_table_extension::{TableChangeSet, TableHandle, TableOperation, TableResolver};
 use std::{
     convert::{TryFrom, TryInto},
     fs,
     path::{Path, PathBuf},
 };
 
-type Event = (Vec<u8>, u64, TypeTag, Vec<u8>);
+/// The format events emitted to a handle were stored in before `ContractEvent`: the event key,
+/// the sequence number, the type and the data of each event
+type LegacyEvent = (Vec<u8>, u64, TypeTag, Vec<u8>);
 
 /// subdirectory of `DEFAULT_STORAGE_DIR`/<addr> where resources are stored
 pub const RESOURCES_DIR: &str = "resources";
 /// subdirectory of `DEFAULT_STORAGE_DIR`/<addr> where modules are stored
 pub const MODULES_DIR: &str = "modules";
-/// subdirectory of `DEFAULT_STORAGE_DIR`/<addr> where events are stored
+/// subdirectory of `DEFAULT_STORAGE_DIR`/<addr> where events emitted to a handle are stored, and
+/// of `DEFAULT_STORAGE_DIR` where events emitted without a handle are stored
 pub const EVENTS_DIR: &str = "events";
+/// subdirectory of `DEFAULT_STORAGE_DIR` where tables of the table extension are stored, with
+/// one directory per table handle holding a file per entry
+pub const TABLES_DIR: &str = "tables";
+/// file under `DEFAULT_STORAGE_DIR`/`TABLES_DIR` counting the transactions which created tables
+pub const TABLE_TXN_COUNTER_FILE: &str = "txn_counter";
 
 /// file under `DEFAULT_BUILD_DIR` where a registry of generated struct layouts are stored
 pub const STRUCT_LAYOUTS_FILE: &str = "struct_layouts.yaml";
@@ -97,6 +112,11 @@ impl OnDiskStateView {
         self.is_data_path(p, MODULES_DIR)
     }
 
+    #[cfg(feature = "table-extension")]
+    pub fn is_table_path(&self, p: &Path) -> bool {
+        p.is_dir() && self.is_data_path(p, TABLES_DIR)
+    }
+
     fn get_addr_path(&self, addr: &AccountAddress) -> PathBuf {
         let mut path = self.storage_dir.clone();
         path.push(format!("0x{}", addr));
@@ -110,8 +130,8 @@ impl OnDiskStateView {
         path.with_extension(BCS_EXTENSION)
     }
 
-    // Events are stored under address/handle creation number
-    fn get_event_path(&self, key: &[u8]) -> PathBuf {
+    // Events emitted to a handle are stored under address/handle creation number
+    fn get_handle_event_path(&self, key: &[u8]) -> PathBuf {
         // TODO: this is a hacky way to get the account address and creation number from the event key.
         // The root problem here is that the move-cli is using the Diem-specific event format.
         // We will deal this later when we make events more generic in the Move VM.
@@ -124,6 +144,49 @@ impl OnDiskStateView {
         path.with_extension(BCS_EXTENSION)
     }
 
+    // Events emitted without a handle are stored under their type
+    fn get_module_event_path(&self, type_tag: &TypeTag) -> PathBuf {
+        let mut path = self.storage_dir.clone();
+        path.push(EVENTS_DIR);
+        path.push(TypeID(type_tag.clone()).to_string());
+        path.with_extension(BCS_EXTENSION)
+    }
+
+    #[cfg(feature = "table-extension")]
+    fn get_table_path(&self, handle: &TableHandle) -> PathBuf {
+        let mut path = self.storage_dir.clone();
+        path.push(TABLES_DIR);
+        path.push(format!("{:032x}", handle.0));
+        path
+    }
+
+    #[cfg(feature = "table-extension")]
+    fn get_table_txn_counter_path(&self) -> PathBuf {
+        let mut path = self.storage_dir.clone();
+        path.push(TABLES_DIR);
+        path.push(TABLE_TXN_COUNTER_FILE);
+        path
+    }
+
+    /// Returns the transaction hash from which handles of tables created by the next transaction
+    /// are derived. This changes whenever a transaction creating tables is committed, so handles
+    /// never collide.
+    #[cfg(feature = "table-extension")]
+    pub fn table_txn_hash(&self) -> Result<u128> {
+        Ok(match Self::get_bytes(&self.get_table_txn_counter_path())? {
+            Some(bytes) => String::from_utf8(bytes)?.trim().parse()?,
+            None => 0,
+        })
+    }
+
+    // Table entries are stored under the hex encoding of their BCS-serialized key
+    #[cfg(feature = "table-extension")]
+    fn get_table_entry_path(&self, handle: &TableHandle, key: &[u8]) -> PathBuf {
+        let mut path = self.get_table_path(handle);
+        path.push(hex::encode(key));
+        path.with_extension(BCS_EXTENSION)
+    }
+
     fn get_module_path(&self, module_id: &ModuleId) -> PathBuf {
         let mut path = self.get_addr_path(module_id.address());
         path.push(MODULES_DIR);
@@ -190,9 +253,8 @@ impl OnDiskStateView {
         })
     }
 
-    /// Returns a deserialized representation of the resource value stored at `resource_path`.
-    /// Returns Err if the path does not hold a resource value or the resource cannot be deserialized
-    pub fn view_resource(&self, resource_path: &Path) -> Result<Option<AnnotatedMoveStruct>> {
+    /// Returns the type of the resource stored at `resource_path`.
+    fn get_resource_tag(resource_path: &Path) -> Result<StructTag> {
         if resource_path.is_dir() {
             bail!(
                 "Bad resource path {:?}. Needed file, found directory",
@@ -204,25 +266,48 @@ impl OnDiskStateView {
                 "Bad resource path {:?}; last component must be a file",
                 resource_path
             ),
-            Some(name) => Ok({
-                let id = match parser::parse_type_tag(&name.to_string_lossy())? {
-                    TypeTag::Struct(s) => s,
-                    t => bail!("Expected to parse struct tag, but got {}", t),
-                };
-                match Self::get_bytes(resource_path)? {
-                    Some(resource_data) => {
-                        Some(MoveValueAnnotator::new(self).view_resource(&id, &resource_data)?)
-                    }
-                    None => None,
-                }
+            Some(name) => Ok(match parser::parse_type_tag(&name.to_string_lossy())? {
+                TypeTag::Struct(s) => s,
+                t => bail!("Expected to parse struct tag, but got {}", t),
             }),
         }
     }
 
-    fn get_events(&self, events_path: &Path) -> Result<Vec<Event>> {
+    /// Returns a deserialized representation of the resource value stored at `resource_path`.
+    /// Returns Err if the path does not hold a resource value or the resource cannot be deserialized
+    pub fn view_resource(&self, resource_path: &Path) -> Result<Option<AnnotatedMoveStruct>> {
+        let id = Self::get_resource_tag(resource_path)?;
+        Ok(match Self::get_bytes(resource_path)? {
+            Some(resource_data) => {
+                Some(MoveValueAnnotator::new(self).view_resource(&id, &resource_data)?)
+            }
+            None => None,
+        })
+    }
+
+    /// Returns the canonical JSON representation of the resource value stored at `resource_path`.
+    /// Returns Err if the path does not hold a resource value or the resource cannot be deserialized
+    pub fn view_resource_json(&self, resource_path: &Path) -> Result<Option<String>> {
+        let id = Self::get_resource_tag(resource_path)?;
+        Ok(match Self::get_bytes(resource_path)? {
+            Some(resource_data) => Some(self.view_json(&TypeTag::Struct(id), &resource_data)?),
+            None => None,
+        })
+    }
+
+    /// Returns the canonical JSON representation of the BCS-serialized value `blob` of type `ty`
+    fn view_json(&self, ty: &TypeTag, blob: &[u8]) -> Result<String> {
+        let layout = TypeLayoutBuilder::build_with_fields(ty, &self)?;
+        let value = MoveValue::simple_deserialize(blob, &layout)?;
+        Ok(serde_json::to_string_pretty(&JsonMoveValue::new(
+            &value, &layout,
+        ))?)
+    }
+
+    fn get_events(&self, events_path: &Path) -> Result<Vec<ContractEvent>> {
         Ok(if events_path.exists() {
             match Self::get_bytes(events_path)? {
-                Some(events_data) => bcs::from_bytes::<Vec<Event>>(&events_data)?,
+                Some(events_data) => Self::deserialize_events(&events_data)?,
                 None => vec![],
             }
         } else {
@@ -230,14 +315,97 @@ impl OnDiskStateView {
         })
     }
 
+    fn deserialize_events(events_data: &[u8]) -> Result<Vec<ContractEvent>> {
+        match bcs::from_bytes::<Vec<ContractEvent>>(events_data) {
+            Ok(events) => Ok(events),
+            // Event logs written in the legacy format cannot be mistaken for `ContractEvent`s:
+            // where the variant index would be they hold the length of the event key, which is
+            // more than one byte long
+            Err(err) => match bcs::from_bytes::<Vec<LegacyEvent>>(events_data) {
+                Ok(events) => Ok(events
+                    .into_iter()
+                    .map(|(guid, seq_num, type_tag, data)| ContractEvent::Handle {
+                        guid,
+                        seq_num,
+                        type_tag,
+                        data,
+                    })
+                    .collect()),
+                Err(_) => Err(err.into()),
+            },
+        }
+    }
+
     pub fn view_events(&self, events_path: &Path) -> Result<Vec<AnnotatedMoveValue>> {
         let annotator = MoveValueAnnotator::new(self);
         self.get_events(events_path)?
             .iter()
-            .map(|(_, _, event_type, event_data)| annotator.view_value(event_type, event_data))
+            .map(|event| annotator.view_value(event.type_tag(), event.data()))
+            .collect()
+    }
+
+    pub fn view_events_json(&self, events_path: &Path) -> Result<Vec<String>> {
+        self.get_events(events_path)?
+            .iter()
+            .map(|event| self.view_json(event.type_tag(), event.data()))
+            .collect()
+    }
+
+    /// Returns up to `limit` entries of the table `handle`, ordered by their keys, starting with
+    /// the first key which is greater or equal to `start_key`. Only the values of the returned
+    /// entries are read.
+    #[cfg(feature = "table-extension")]
+    fn get_table_entries(
+        &self,
+        handle: &TableHandle,
+        start_key: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let table_path = self.get_table_path(handle);
+        if !table_path.exists() {
+            return Ok(vec![]);
+        }
+        let mut keys = vec![];
+        for entry in fs::read_dir(table_path)? {
+            let path = entry?.path();
+            let key = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(stem) => {
+                    hex::decode(stem).map_err(|_| anyhow!("Bad table entry path {:?}", path))?
+                }
+                None => bail!("Bad table entry path {:?}", path),
+            };
+            let in_range = match start_key {
+                Some(start) => key.as_slice() >= start,
+                None => true,
+            };
+            if in_range {
+                keys.push(key);
+            }
+        }
+        keys.sort();
+        keys.into_iter()
+            .take(limit)
+            .map(|key| {
+                let value = fs::read(self.get_table_entry_path(handle, &key))?;
+                Ok((key, value))
+            })
             .collect()
     }
 
+    /// Returns the BCS-serialized keys and values of the table stored at `table_path`, ordered
+    /// by their keys. The table does not record its key and value types, so entries are raw bytes.
+    #[cfg(feature = "table-extension")]
+    pub fn view_table(&self, table_path: &Path) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let handle = match table_path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => TableHandle(
+                u128::from_str_radix(name, 16)
+                    .map_err(|_| anyhow!("Bad table path {:?}", table_path))?,
+            ),
+            None => bail!("Bad table path {:?}", table_path),
+        };
+        self.get_table_entries(&handle, None, usize::MAX)
+    }
+
     fn view_bytecode(path: &Path, is_module: bool) -> Result<Option<String>> {
         if path.is_dir() {
             bail!("Bad bytecode path {:?}. Needed file, found directory", path)
@@ -299,29 +467,67 @@ impl OnDiskStateView {
         Ok(fs::write(path, bcs_bytes)?)
     }
 
-    pub fn save_event(
-        &self,
-        event_key: &[u8],
-        event_sequence_number: u64,
-        event_type: TypeTag,
-        event_data: Vec<u8>,
-    ) -> Result<()> {
-        // save event data in handle_address/EVENTS_DIR/handle_number
-        let path = self.get_event_path(event_key);
+    pub fn save_event(&self, event: ContractEvent) -> Result<()> {
+        // save event data in handle_address/EVENTS_DIR/handle_number for events emitted to a
+        // handle, and in EVENTS_DIR/type otherwise
+        let path = match &event {
+            ContractEvent::Handle { guid, .. } => self.get_handle_event_path(guid),
+            ContractEvent::Module { type_tag, .. } => self.get_module_event_path(type_tag),
+        };
         if !path.exists() {
             fs::create_dir_all(path.parent().unwrap())?;
         }
         // grab the old event log (if any) and append this event to it
         let mut event_log = self.get_events(&path)?;
-        event_log.push((
-            event_key.to_vec(),
-            event_sequence_number,
-            event_type,
-            event_data,
-        ));
+        event_log.push(event);
         Ok(fs::write(path, &bcs::to_bytes(&event_log)?)?)
     }
 
+    /// Apply `change_set` to the tables stored on disk under `TABLES_DIR`/handle
+    #[cfg(feature = "table-extension")]
+    pub fn save_table_change_set(&self, change_set: TableChangeSet) -> Result<()> {
+        let TableChangeSet {
+            new_tables,
+            removed_tables,
+            dropped_tables,
+            changes,
+        } = change_set;
+        if !new_tables.is_empty() {
+            let counter_path = self.get_table_txn_counter_path();
+            fs::create_dir_all(counter_path.parent().unwrap())?;
+            fs::write(counter_path, (self.table_txn_hash()? + 1).to_string())?;
+        }
+        for handle in new_tables {
+            fs::create_dir_all(self.get_table_path(&handle))?;
+        }
+        for (handle, change) in changes {
+            for (key, value) in change.entries {
+                let path = self.get_table_entry_path(&handle, &key);
+                match value {
+                    Some(bytes) => {
+                        if !path.exists() {
+                            fs::create_dir_all(path.parent().unwrap())?;
+                        }
+                        fs::write(path, bytes)?
+                    }
+                    None => {
+                        if path.exists() {
+                            fs::remove_file(path)?
+                        }
+                    }
+                }
+            }
+        }
+        // entries of dropped tables are unreachable, so they are garbage collected right away
+        for handle in removed_tables.into_iter().chain(dropped_tables) {
+            let path = self.get_table_path(&handle);
+            if path.exists() {
+                fs::remove_dir_all(path)?
+            }
+        }
+        Ok(())
+    }
+
     /// Save `module` on disk under the path `module.address()`/`module.name()`
     pub fn save_module(&self, module_id: &ModuleId, module_bytes: &[u8]) -> Result<()> {
         let path = self.get_module_path(module_id);
@@ -387,6 +593,11 @@ impl OnDiskStateView {
         self.iter_paths(move |p| self.is_event_path(p))
     }
 
+    #[cfg(feature = "table-extension")]
+    pub fn table_paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.iter_paths(move |p| self.is_table_path(p))
+    }
+
     /// Build all modules in the self.storage_dir.
     /// Returns an Err if a module does not deserialize.
     pub fn get_all_modules(&self) -> Result<Vec<CompiledModule>> {
@@ -418,6 +629,35 @@ impl ResourceResolver for OnDiskStateView {
     }
 }
 
+#[cfg(feature = "table-extension")]
+impl TableResolver for OnDiskStateView {
+    fn resolve_table_entry(
+        &self,
+        handle: &TableHandle,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        Self::get_bytes(&self.get_table_entry_path(handle, key))
+    }
+
+    fn iter_table_entries(
+        &self,
+        handle: &TableHandle,
+        start_key: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, anyhow::Error> {
+        self.get_table_entries(handle, start_key, limit)
+    }
+
+    fn operation_cost(
+        &self,
+        _op: TableOperation,
+        _key_size: usize,
+        _val_size: usize,
+    ) -> InternalGasUnits<GasCarrier> {
+        InternalGasUnits::new(1)
+    }
+}
+
 impl GetModule for &OnDiskStateView {
     type Error = anyhow::Error;
     type Item = CompiledModule;
@@ -0,0 +1,7 @@
+module 0x42::M {
+    native fun missing(): u64;
+
+    public fun call_missing(): u64 {
+        missing()
+    }
+}
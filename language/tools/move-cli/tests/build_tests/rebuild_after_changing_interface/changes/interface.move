@@ -0,0 +1,9 @@
+module A::Bar {
+    public fun bar(): u64 {
+        43
+    }
+
+    public fun baz(): u64 {
+        44
+    }
+}
@@ -0,0 +1,7 @@
+module A::Foo {
+    use A::Bar;
+
+    public fun foo(): u64 {
+        Bar::bar()
+    }
+}
@@ -0,0 +1,7 @@
+module A::A {
+    use A::Foo;
+
+    fun f(): u64 {
+        Foo::foo()
+    }
+}
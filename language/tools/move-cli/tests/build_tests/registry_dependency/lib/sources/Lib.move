@@ -0,0 +1,3 @@
+module Lib::Lib {
+    public fun lib(): u64 { 0 }
+}
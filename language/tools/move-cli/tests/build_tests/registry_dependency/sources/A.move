@@ -0,0 +1,7 @@
+module A::A {
+    use Lib::Lib;
+
+    fun f(): u64 {
+        Lib::lib()
+    }
+}
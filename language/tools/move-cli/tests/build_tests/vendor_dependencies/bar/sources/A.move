@@ -0,0 +1,5 @@
+module A::Bar {
+    public fun bar(): u64 {
+        42
+    }
+}
@@ -0,0 +1,7 @@
+module A::A {
+    use Shared::S;
+
+    public fun a(): u64 {
+        S::s()
+    }
+}
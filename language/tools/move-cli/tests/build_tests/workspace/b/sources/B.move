@@ -0,0 +1,8 @@
+module B::B {
+    use A::A;
+    use Shared::S;
+
+    fun b(): u64 {
+        A::a() + S::s()
+    }
+}
@@ -0,0 +1,5 @@
+module Shared::S {
+    public fun s(): u64 {
+        42
+    }
+}
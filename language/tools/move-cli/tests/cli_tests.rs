@@ -1,9 +1,11 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use move_cli::sandbox::commands::test;
-
-use std::path::PathBuf;
+use move_cli::sandbox::{commands::test, utils::on_disk_state_view::OnDiskStateView};
+use move_core_types::{
+    account_address::AccountAddress, effects::ContractEvent, language_storage::TypeTag,
+};
+use std::{fs, path::PathBuf};
 
 pub const CLI_METATEST_PATH: [&str; 3] = ["tests", "metatests", "args.txt"];
 
@@ -52,3 +54,93 @@ fn cross_process_locking_git_deps() {
         .expect("Package2 failed");
     handle.join().unwrap();
 }
+
+#[test]
+fn legacy_event_logs_are_read_and_extended() {
+    let dir = tempfile::tempdir().unwrap();
+    let state =
+        OnDiskStateView::create(dir.path().join("build"), dir.path().join("storage")).unwrap();
+    let mut key = 0u64.to_le_bytes().to_vec();
+    key.extend(AccountAddress::ONE.to_vec());
+
+    // Write an event log in the format used before `ContractEvent`, then append to it
+    state
+        .save_event(ContractEvent::Handle {
+            guid: key.clone(),
+            seq_num: 0,
+            type_tag: TypeTag::U64,
+            data: bcs::to_bytes(&7u64).unwrap(),
+        })
+        .unwrap();
+    let events_path = state.event_paths().next().unwrap();
+    let legacy_log = vec![(
+        key.clone(),
+        0u64,
+        TypeTag::U64,
+        bcs::to_bytes(&7u64).unwrap(),
+    )];
+    fs::write(&events_path, bcs::to_bytes(&legacy_log).unwrap()).unwrap();
+    state
+        .save_event(ContractEvent::Handle {
+            guid: key,
+            seq_num: 1,
+            type_tag: TypeTag::U64,
+            data: bcs::to_bytes(&8u64).unwrap(),
+        })
+        .unwrap();
+
+    let events: Vec<_> = state
+        .view_events(&events_path)
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(events, vec!["7", "8"]);
+}
+
+#[cfg(feature = "table-extension")]
+#[test]
+fn table_entries_are_iterated_in_key_order() {
+    use move_table_extension::{TableChange, TableChangeSet, TableHandle, TableResolver};
+
+    let dir = tempfile::tempdir().unwrap();
+    let state =
+        OnDiskStateView::create(dir.path().join("build"), dir.path().join("storage")).unwrap();
+    let handle = TableHandle(1);
+    let keys = vec![vec![2], vec![0], vec![1, 0], vec![1], vec![3]];
+    let mut change_set = TableChangeSet::default();
+    change_set.new_tables.insert(handle);
+    change_set.changes.insert(
+        handle,
+        TableChange {
+            entries: keys
+                .into_iter()
+                .map(|key| (key.clone(), Some(key)))
+                .collect(),
+            bytes_delta: 0,
+        },
+    );
+    state.save_table_change_set(change_set).unwrap();
+
+    let entries = |keys: Vec<Vec<u8>>| -> Vec<(Vec<u8>, Vec<u8>)> {
+        keys.into_iter().map(|key| (key.clone(), key)).collect()
+    };
+    assert_eq!(
+        state.iter_table_entries(&handle, None, 3).unwrap(),
+        entries(vec![vec![0], vec![1], vec![1, 0]])
+    );
+    assert_eq!(
+        state
+            .iter_table_entries(&handle, Some(&[1, 0]), 10)
+            .unwrap(),
+        entries(vec![vec![1, 0], vec![2], vec![3]])
+    );
+    assert_eq!(
+        state.iter_table_entries(&handle, Some(&[4]), 10).unwrap(),
+        vec![]
+    );
+    assert_eq!(
+        state.iter_table_entries(&TableHandle(2), None, 10).unwrap(),
+        vec![]
+    );
+}
@@ -0,0 +1,10 @@
+module A::A {
+    public fun one(): u64 {
+        1
+    }
+
+    #[test]
+    fun test_one() {
+        assert!(one() == 1, 0);
+    }
+}
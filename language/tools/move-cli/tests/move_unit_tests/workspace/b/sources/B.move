@@ -0,0 +1,14 @@
+module B::B {
+    use A::A;
+
+    #[test]
+    fun test_two() {
+        assert!(A::one() + A::one() == 2, 0);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 0)]
+    fun test_three() {
+        assert!(A::one() == 3, 0);
+    }
+}
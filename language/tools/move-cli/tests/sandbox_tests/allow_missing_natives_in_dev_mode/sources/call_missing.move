@@ -0,0 +1,5 @@
+script {
+    fun call_missing() {
+        0x42::M::missing()
+    }
+}
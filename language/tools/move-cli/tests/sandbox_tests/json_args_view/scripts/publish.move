@@ -0,0 +1,5 @@
+script {
+    fun main(account: signer, small: u8, big: u128, nums: vector<u64>, flags: vector<bool>, bytes: vector<vector<u8>>) {
+        0x2::M::publish(account, small, big, nums, flags, bytes)
+    }
+}
@@ -0,0 +1,32 @@
+module 0x2::M {
+    use Std::Event;
+    use Std::Vector;
+
+    struct Inner has copy, drop, store { flag: bool, bytes: vector<u8> }
+    struct R has key { small: u8, big: u128, owner: address, nums: vector<u64>, inner: vector<Inner> }
+
+    public(script) fun publish(
+        account: signer,
+        small: u8,
+        big: u128,
+        nums: vector<u64>,
+        flags: vector<bool>,
+        bytes: vector<vector<u8>>,
+    ) {
+        let inner = vector[];
+        let i = 0;
+        while (i < Vector::length(&flags)) {
+            Vector::push_back(&mut inner, Inner {
+                flag: *Vector::borrow(&flags, i),
+                bytes: *Vector::borrow(&bytes, i),
+            });
+            i = i + 1;
+        };
+        Event::emit(copy inner);
+        move_to(&account, R { small, big, owner: @0x2, nums, inner })
+    }
+
+    public fun read(small: &u8): u8 {
+        *small
+    }
+}
@@ -0,0 +1,7 @@
+script {
+    use 0x2::Events;
+
+    fun emit(i: u64) {
+        Events::emit(i)
+    }
+}
@@ -0,0 +1,14 @@
+address 0x2 {
+module Events {
+    use Std::Event;
+
+    struct AnEvent has copy, drop, store { i: u64 }
+    struct Generic<T> has copy, drop, store { t: T }
+
+    public fun emit(i: u64) {
+        Event::emit(AnEvent { i });
+        Event::emit(Generic { t: i });
+        Event::emit(i)
+    }
+}
+}
@@ -0,0 +1,46 @@
+module 0xA::Registry {
+    use Std::Signer;
+    use Extensions::Table::{Self, Table};
+
+    struct Registry has key {
+        entries: Table<u64, u64>,
+    }
+
+    public(script) fun create(account: signer) {
+        move_to(&account, Registry { entries: Table::new() })
+    }
+
+    public(script) fun set(account: signer, key: u64, value: u64) acquires Registry {
+        let entries = &mut borrow_global_mut<Registry>(Signer::address_of(&account)).entries;
+        *Table::borrow_mut_with_default(entries, &key, 0) = value;
+    }
+
+    public(script) fun remove(account: signer, key: u64) acquires Registry {
+        let entries = &mut borrow_global_mut<Registry>(Signer::address_of(&account)).entries;
+        Table::remove(entries, &key);
+    }
+
+    public(script) fun check(account: signer, key: u64, value: u64) acquires Registry {
+        let entries = &borrow_global<Registry>(Signer::address_of(&account)).entries;
+        assert!(*Table::borrow(entries, &key) == value, 42);
+    }
+
+    #[test(account = @0xA)]
+    fun set_and_remove(account: signer) acquires Registry {
+        move_to(&account, Registry { entries: Table::new() });
+        let entries = &mut borrow_global_mut<Registry>(@0xA).entries;
+        Table::add(entries, &1, 10);
+        *Table::borrow_mut_with_default(entries, &1, 0) = 11;
+        assert!(*Table::borrow(entries, &1) == 11, 0);
+        assert!(Table::remove(entries, &1) == 11, 1);
+        assert!(!Table::contains(entries, &1), 2);
+    }
+
+    #[test(account = @0xA)]
+    #[expected_failure]
+    fun borrow_missing_entry(account: signer) acquires Registry {
+        move_to(&account, Registry { entries: Table::new() });
+        let entries = &borrow_global<Registry>(@0xA).entries;
+        assert!(*Table::borrow(entries, &1) == 10, 0);
+    }
+}
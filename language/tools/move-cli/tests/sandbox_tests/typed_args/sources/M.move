@@ -0,0 +1,25 @@
+module 0x2::M {
+    use Std::ASCII::String;
+    use Std::Option::Option;
+
+    struct R has key {
+        owners: vector<address>,
+        nums: vector<vector<u64>>,
+        name: String,
+        names: vector<String>,
+        limit: Option<u64>,
+        tags: vector<Option<vector<u8>>>,
+    }
+
+    public(script) fun publish(
+        account: signer,
+        owners: vector<address>,
+        nums: vector<vector<u64>>,
+        name: String,
+        names: vector<String>,
+        limit: Option<u64>,
+        tags: vector<Option<vector<u8>>>,
+    ) {
+        move_to(&account, R { owners, nums, name, names, limit, tags })
+    }
+}
@@ -2,11 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    compilation::compiled_package::CompiledPackage, resolution::resolution_graph::ResolvedGraph,
+    compilation::compiled_package::CompiledPackage,
+    resolution::resolution_graph::{ResolvedGraph, ResolvedPackage, ResolvedTable},
     source_package::parsed_manifest::PackageName,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use move_compiler::{compiled_unit::AnnotatedCompiledUnit, diagnostics::FilesSourceText, Compiler};
+use move_symbol_pool::Symbol;
 use petgraph::algo::toposort;
 use std::{collections::BTreeSet, io::Write, path::Path};
 
@@ -97,26 +99,121 @@ impl BuildPlan {
         })
     }
 
+    /// Compiles the root package, only recompiling the modules and scripts of the previous build
+    /// which are affected by the changes made since.
     pub fn compile<W: Write>(&self, writer: &mut W) -> Result<CompiledPackage> {
-        self.compile_with_driver(writer, |compiler| compiler.build_and_report())
+        self.compile_root(writer, true, |compiler| compiler.build_and_report())
     }
 
+    /// Compiles the root package with `compiler_driver`. Since the driver may need to see the
+    /// compilation of all sources, these are always recompiled.
     pub fn compile_with_driver<W: Write>(
         &self,
         writer: &mut W,
-        mut compiler_driver: impl FnMut(
+        compiler_driver: impl FnMut(
             Compiler,
         )
             -> anyhow::Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
     ) -> Result<CompiledPackage> {
-        let root_package = &self.resolution_graph.package_table[&self.root];
+        self.compile_root(writer, false, compiler_driver)
+    }
+
+    /// Compiles all members of the workspace, or only the root package if it is not the root of a
+    /// workspace. Like `compile`, this only recompiles what changed since the previous build.
+    pub fn compile_workspace<W: Write>(&self, writer: &mut W) -> Result<Vec<CompiledPackage>> {
+        self.compile_packages(
+            writer,
+            self.resolution_graph.workspace_members(),
+            true,
+            |compiler| compiler.build_and_report(),
+        )
+    }
+
+    pub fn compile_workspace_with_driver<W: Write>(
+        &self,
+        writer: &mut W,
+        compiler_driver: impl FnMut(
+            Compiler,
+        )
+            -> anyhow::Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
+    ) -> Result<Vec<CompiledPackage>> {
+        self.compile_packages(
+            writer,
+            self.resolution_graph.workspace_members(),
+            false,
+            compiler_driver,
+        )
+    }
+
+    fn compile_root<W: Write>(
+        &self,
+        writer: &mut W,
+        incremental: bool,
+        compiler_driver: impl FnMut(
+            Compiler,
+        )
+            -> anyhow::Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
+    ) -> Result<CompiledPackage> {
+        if self.resolution_graph.is_workspace() {
+            bail!(
+                "Unable to compile the workspace at '{}' as a single package. Compile the \
+                 members of the workspace instead",
+                self.resolution_graph.root_package_path.to_string_lossy()
+            )
+        }
+        let mut compiled = self.compile_packages(
+            writer,
+            BTreeSet::from([self.root]),
+            incremental,
+            compiler_driver,
+        )?;
+        Ok(compiled.pop().unwrap())
+    }
+
+    fn compile_packages<W: Write>(
+        &self,
+        writer: &mut W,
+        package_names: BTreeSet<PackageName>,
+        incremental: bool,
+        mut compiler_driver: impl FnMut(
+            Compiler,
+        )
+            -> anyhow::Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
+    ) -> Result<Vec<CompiledPackage>> {
         let project_root = match &self.resolution_graph.build_options.install_dir {
             Some(under_path) => under_path.clone(),
             None => self.resolution_graph.root_package_path.clone(),
         };
-        let immediate_dependencies_names =
-            root_package.immediate_dependencies(&self.resolution_graph);
-        let transitive_dependencies = root_package
+        let packages = package_names
+            .into_iter()
+            .map(|package_name| {
+                let package = &self.resolution_graph.package_table[&package_name];
+                (package.clone(), self.transitive_dependencies(package))
+            })
+            .collect();
+
+        let compiled = CompiledPackage::build_all(
+            writer,
+            &project_root,
+            packages,
+            &self.resolution_graph,
+            incremental,
+            &mut compiler_driver,
+        )?;
+
+        Self::clean(
+            &project_root.join(CompiledPackageLayout::Root.path()),
+            self.sorted_deps.iter().copied().collect(),
+        )?;
+        Ok(compiled)
+    }
+
+    fn transitive_dependencies(
+        &self,
+        package: &ResolvedPackage,
+    ) -> Vec<(PackageName, bool, Vec<Symbol>, &ResolvedTable)> {
+        let immediate_dependencies_names = package.immediate_dependencies(&self.resolution_graph);
+        package
             .transitive_dependencies(&self.resolution_graph)
             .into_iter()
             .map(|package_name| {
@@ -135,26 +232,14 @@ impl BuildPlan {
                     &dep_package.resolution_table,
                 )
             })
-            .collect();
-
-        let compiled = CompiledPackage::build_all(
-            writer,
-            &project_root,
-            root_package.clone(),
-            transitive_dependencies,
-            &self.resolution_graph,
-            &mut compiler_driver,
-        )?;
-
-        Self::clean(
-            &project_root.join(CompiledPackageLayout::Root.path()),
-            self.sorted_deps.iter().copied().collect(),
-        )?;
-        Ok(compiled)
+            .collect()
     }
 
     #[cfg(feature = "evm-backend")]
     pub fn compile_evm<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.resolution_graph.is_workspace() {
+            bail!("Workspaces cannot be compiled to EVM bytecode. Compile their members instead")
+        }
         let root_package = &self.resolution_graph.package_table[&self.root];
         let project_root = match &self.resolution_graph.build_options.install_dir {
             Some(under_path) => under_path.clone(),
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    compilation::package_layout::CompiledPackageLayout,
+    compilation::{
+        module_graph::{compile_incrementally, ModuleGraph, PreviousBuild, RebuildReason},
+        package_layout::CompiledPackageLayout,
+    },
     resolution::resolution_graph::{Renaming, ResolvedGraph, ResolvedPackage, ResolvedTable},
     source_package::{
         layout::{SourcePackageLayout, REFERENCE_TEMPLATE_FILENAME},
@@ -21,10 +24,10 @@ use move_command_line_common::files::{
 };
 use move_compiler::{
     compiled_unit::{
-        self, AnnotatedCompiledUnit, CompiledUnit, NamedCompiledModule, NamedCompiledScript,
+        AnnotatedCompiledUnit, CompiledUnit, NamedCompiledModule, NamedCompiledScript,
     },
     diagnostics::FilesSourceText,
-    shared::{Flags, NamedAddressMap, NumericalAddress, PackagePaths},
+    shared::{NamedAddressMap, NumericalAddress, PackagePaths},
     Compiler,
 };
 use move_docgen::{Docgen, DocgenOptions};
@@ -105,6 +108,24 @@ pub struct OnDiskCompiledPackage {
     pub package: OnDiskPackage,
 }
 
+/// A package which is built by `CompiledPackage::build_all`, together with the inputs and outputs
+/// of its compilation.
+struct PackageBuild {
+    resolved_package: ResolvedPackage,
+    immediate_dependencies: Vec<PackageName>,
+    /// The transitive dependencies of the package
+    dependencies: BTreeSet<PackageName>,
+    sources_package_paths: PackagePaths,
+    deps_package_paths: Vec<PackagePaths>,
+    root_compiled_units: Vec<CompiledUnitWithSource>,
+    deps_compiled_units: Vec<(PackageName, CompiledUnitWithSource)>,
+    /// Docs and ABIs reused from the previous build
+    compiled_docs: Option<Vec<(String, String)>>,
+    compiled_abis: Option<Vec<(String, Vec<u8>)>>,
+    /// The part of the module graph of the build which concerns the package
+    module_graph: ModuleGraph,
+}
+
 impl CompilationCachingStatus {
     /// Returns `true` if this package and all dependencies are cached
     pub fn is_cached(&self) -> bool {
@@ -149,9 +170,10 @@ impl OnDiskCompiledPackage {
             }
         }
 
+        // Docs and ABIs are named relative to the directory they are saved in, like when they are
+        // generated
         let docs_path = self
             .root_path
-            .join(self.package.compiled_package_info.package_name.as_str())
             .join(CompiledPackageLayout::CompiledDocs.path());
         let compiled_docs = if docs_path.is_dir() {
             Some(
@@ -161,7 +183,7 @@ impl OnDiskCompiledPackage {
                 .into_iter()
                 .map(|path| {
                     let contents = std::fs::read_to_string(&path).unwrap();
-                    (path, contents)
+                    (Self::relative_name(&path, &docs_path), contents)
                 })
                 .collect(),
             )
@@ -171,7 +193,6 @@ impl OnDiskCompiledPackage {
 
         let abi_path = self
             .root_path
-            .join(self.package.compiled_package_info.package_name.as_str())
             .join(CompiledPackageLayout::CompiledABIs.path());
         let compiled_abis = if abi_path.is_dir() {
             Some(
@@ -181,7 +202,7 @@ impl OnDiskCompiledPackage {
                 .into_iter()
                 .map(|path| {
                     let contents = std::fs::read(&path).unwrap();
-                    (path, contents)
+                    (Self::relative_name(&path, &abi_path), contents)
                 })
                 .collect(),
             )
@@ -198,6 +219,13 @@ impl OnDiskCompiledPackage {
         })
     }
 
+    fn relative_name(path: &str, dir: &Path) -> String {
+        Path::new(path)
+            .strip_prefix(dir)
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.to_string())
+    }
+
     fn decode_unit(
         &self,
         package_name: Symbol,
@@ -275,7 +303,6 @@ impl OnDiskCompiledPackage {
         std::fs::write(path_to_save, bytes).map_err(|err| err.into())
     }
 
-    #[allow(unused)]
     pub(crate) fn has_source_changed_since_last_compile(
         &self,
         resolved_package: &ResolvedPackage,
@@ -287,30 +314,36 @@ impl OnDiskCompiledPackage {
         }
     }
 
-    #[allow(unused)]
-    pub(crate) fn are_build_flags_different(&self, build_config: &BuildConfig) -> bool {
-        build_config != &self.package.compiled_package_info.build_flags
-    }
-
     fn get_compiled_units_paths(&self, package_name: Symbol) -> Result<Vec<String>> {
-        let package_dir = if self.package.compiled_package_info.package_name == package_name {
-            self.root_path.clone()
+        // The units of dependencies are saved under the `dependencies` directory of each category
+        let is_root_package = self.package.compiled_package_info.package_name == package_name;
+        let package_dir = if is_root_package {
+            PathBuf::new()
         } else {
-            self.root_path
-                .join(CompiledPackageLayout::Dependencies.path())
+            CompiledPackageLayout::Dependencies
+                .path()
                 .join(package_name.as_str())
         };
         let mut compiled_unit_paths = vec![];
-        let module_path = package_dir.join(CompiledPackageLayout::CompiledModules.path());
+        let module_path = self
+            .root_path
+            .join(CompiledPackageLayout::CompiledModules.path())
+            .join(&package_dir);
         if module_path.exists() {
             compiled_unit_paths.push(module_path);
         }
-        let script_path = package_dir.join(CompiledPackageLayout::CompiledScripts.path());
+        let script_path = self
+            .root_path
+            .join(CompiledPackageLayout::CompiledScripts.path())
+            .join(&package_dir);
         if script_path.exists() {
             compiled_unit_paths.push(script_path);
         }
         find_filenames(&compiled_unit_paths, |path| {
             extension_equals(path, MOVE_COMPILED_EXTENSION)
+                && !(is_root_package
+                    && CompiledPackageLayout::path_to_file_after_category(path)
+                        .starts_with(CompiledPackageLayout::Dependencies.path()))
         })
     }
 
@@ -455,55 +488,79 @@ impl CompiledPackage {
             .filter(|unit| matches!(unit.unit, CompiledUnit::Script(_)))
     }
 
-    #[allow(unused)]
-    fn can_load_cached(
-        package: &OnDiskCompiledPackage,
-        resolution_graph: &ResolvedGraph,
-        resolved_package: &ResolvedPackage,
-        is_root_package: bool,
-    ) -> bool {
-        // TODO: add more tests for the different caching cases
-        !(package.has_source_changed_since_last_compile(resolved_package) // recompile if source has changed
-            // Recompile if the flags are different
-                || package.are_build_flags_different(&resolution_graph.build_options)
-                // Force root package recompilation in test mode
-                || resolution_graph.build_options.test_mode && is_root_package
-                // Recompile if force recompilation is set
-                || resolution_graph.build_options.force_recompilation) &&
-                // Dive deeper to make sure that instantiations haven't changed since that
-                // can be changed by other packages above us in the dependency graph possibly
-                package.package.compiled_package_info.address_alias_instantiation
-                    == resolved_package.resolution_table
-    }
-
+    /// Builds `packages` together with all of their transitive dependencies in a single
+    /// compilation, so that dependencies which are shared between the packages are only compiled
+    /// once. Returns the compiled packages in the order in which they were given.
+    ///
+    /// If `incremental` is set, the previous build of the packages is reused, and only the modules
+    /// and scripts which are affected by the changes made since are recompiled. The compiler
+    /// driver may then be invoked several times, or not at all.
     pub(crate) fn build_all<W: Write>(
         w: &mut W,
         project_root: &Path,
-        resolved_package: ResolvedPackage,
-        transitive_dependencies: Vec<(
-            /* name */ Symbol,
-            /* is immediate */ bool,
-            /* source paths */ Vec<Symbol>,
-            /* address mapping */ &ResolvedTable,
+        packages: Vec<(
+            ResolvedPackage,
+            /* transitive dependencies */
+            Vec<(
+                /* name */ Symbol,
+                /* is immediate */ bool,
+                /* source paths */ Vec<Symbol>,
+                /* address mapping */ &ResolvedTable,
+            )>,
         )>,
         resolution_graph: &ResolvedGraph,
-        mut compiler_driver: impl FnMut(
-            Compiler,
-        )
-            -> Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
-    ) -> Result<CompiledPackage> {
-        let immediate_dependencies = transitive_dependencies
+        incremental: bool,
+        compiler_driver: impl FnMut(Compiler) -> Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
+    ) -> Result<Vec<CompiledPackage>> {
+        let package_names = packages
             .iter()
-            .filter(|(_, is_immediate, _, _)| *is_immediate)
-            .map(|(name, _, _, _)| *name)
-            .collect::<Vec<_>>();
-        let transitive_dependencies = transitive_dependencies
-            .into_iter()
-            .map(|(name, _is_immediate, source_paths, address_mapping)| {
-                (name, source_paths, address_mapping)
-            })
-            .collect::<Vec<_>>();
-        for (dep_package_name, _, _) in &transitive_dependencies {
+            .map(|(package, _)| package.source_package.package.name)
+            .collect::<BTreeSet<_>>();
+        let mut builds = vec![];
+        let mut shared_deps_package_paths = BTreeMap::new();
+        for (resolved_package, transitive_dependencies) in packages {
+            let immediate_dependencies = transitive_dependencies
+                .iter()
+                .filter(|(_, is_immediate, _, _)| *is_immediate)
+                .map(|(name, _, _, _)| *name)
+                .collect::<Vec<_>>();
+            let dependencies = transitive_dependencies
+                .iter()
+                .map(|(name, _, _, _)| *name)
+                .collect::<BTreeSet<_>>();
+            let transitive_dependencies = transitive_dependencies
+                .into_iter()
+                .map(|(name, _is_immediate, source_paths, address_mapping)| {
+                    (name, source_paths, address_mapping)
+                })
+                .collect::<Vec<_>>();
+            // gather source/dep files with their address mappings
+            let (sources_package_paths, deps_package_paths) = make_source_and_deps_for_compiler(
+                resolution_graph,
+                &resolved_package,
+                transitive_dependencies,
+            )?;
+            // Packages which are built are compiled as sources, even if others depend on them
+            for dep_package_paths in &deps_package_paths {
+                let dep_name = dep_package_paths.name.unwrap();
+                if !package_names.contains(&dep_name) {
+                    shared_deps_package_paths.insert(dep_name, dep_package_paths.clone());
+                }
+            }
+            builds.push(PackageBuild {
+                resolved_package,
+                immediate_dependencies,
+                dependencies,
+                sources_package_paths,
+                deps_package_paths,
+                root_compiled_units: vec![],
+                deps_compiled_units: vec![],
+                compiled_docs: None,
+                compiled_abis: None,
+                module_graph: ModuleGraph::default(),
+            });
+        }
+        for dep_package_name in shared_deps_package_paths.keys() {
             writeln!(
                 w,
                 "{} {}",
@@ -511,89 +568,81 @@ impl CompiledPackage {
                 dep_package_name
             )?;
         }
-        let root_package_name = resolved_package.source_package.package.name;
-        writeln!(w, "{} {}", "BUILDING".bold().green(), root_package_name)?;
+        for build in &builds {
+            writeln!(
+                w,
+                "{} {}",
+                "BUILDING".bold().green(),
+                build.resolved_package.source_package.package.name
+            )?;
+        }
 
-        // gather source/dep files with their address mappings
-        let (sources_package_paths, deps_package_paths) = make_source_and_deps_for_compiler(
-            resolution_graph,
-            &resolved_package,
-            transitive_dependencies,
-        )?;
-        let flags = if resolution_graph.build_options.test_mode {
-            Flags::testing()
+        let build_root = project_root.join(CompiledPackageLayout::Root.path());
+        let previous = if incremental {
+            PreviousBuild::load(&build_root, &package_names).ok_or(RebuildReason::NoPreviousBuild)
         } else {
-            Flags::empty()
+            Err(RebuildReason::NotIncremental)
         };
         // invoke the compiler
-        let paths = {
-            let mut v = deps_package_paths.clone();
-            v.push(sources_package_paths.clone());
-            v
-        };
-        let compiler = Compiler::from_package_paths(paths, vec![]).set_flags(flags);
-        let (file_map, all_compiled_units) = compiler_driver(compiler)?;
-        let mut root_compiled_units = vec![];
-        let mut deps_compiled_units = vec![];
-        for annot_unit in all_compiled_units {
-            let source_path = PathBuf::from(file_map[&annot_unit.loc().file_hash()].0.as_str());
-            let package_name = match &annot_unit {
-                compiled_unit::CompiledUnitEnum::Module(m) => m.named_module.package_name.unwrap(),
-                compiled_unit::CompiledUnitEnum::Script(s) => s.named_script.package_name.unwrap(),
-            };
-            let unit = CompiledUnitWithSource {
-                unit: annot_unit.into_compiled_unit(),
-                source_path,
-            };
-            if package_name == root_package_name {
-                root_compiled_units.push(unit)
-            } else {
-                deps_compiled_units.push((package_name, unit))
-            }
+        let paths = shared_deps_package_paths
+            .into_values()
+            .chain(
+                builds
+                    .iter()
+                    .map(|build| build.sources_package_paths.clone()),
+            )
+            .collect();
+        let compilation = compile_incrementally(
+            w,
+            resolution_graph,
+            paths,
+            previous.as_ref().map_err(Clone::clone),
+            compiler_driver,
+        )?;
+        for build in &builds {
+            build.check_used_packages(&compilation.graph)?;
         }
-
-        let mut compiled_docs = None;
-        let mut compiled_abis = None;
-        if resolution_graph.build_options.generate_docs
-            || resolution_graph.build_options.generate_abis
-        {
-            let model = run_model_builder_with_options(
-                vec![sources_package_paths],
-                deps_package_paths,
-                ModelBuilderOptions::default(),
-            )?;
-
-            if resolution_graph.build_options.generate_docs {
-                compiled_docs = Some(Self::build_docs(
-                    resolved_package.source_package.package.name,
-                    &model,
-                    &resolved_package.package_path,
-                    &immediate_dependencies,
-                    &resolution_graph.build_options.install_dir,
-                ));
+        for (package_name, unit) in compilation.units {
+            for build in &mut builds {
+                if package_name == build.resolved_package.source_package.package.name {
+                    build.root_compiled_units.push(unit.clone())
+                } else if build.dependencies.contains(&package_name) {
+                    build.deps_compiled_units.push((package_name, unit.clone()))
+                }
             }
+        }
 
-            if resolution_graph.build_options.generate_abis {
-                compiled_abis = Some(Self::build_abis(&model, &root_compiled_units));
+        let build_options = &resolution_graph.build_options;
+        for build in &mut builds {
+            let package_name = build.resolved_package.source_package.package.name;
+            let mut package_names = build.dependencies.clone();
+            package_names.insert(package_name);
+            build.module_graph = compilation.graph.for_packages(&package_names);
+            // Docs and ABIs are only reused if nothing in the package or its dependencies was
+            // recompiled
+            let previous_package = match &previous {
+                Ok(previous) => previous.packages.get(&package_name),
+                Err(_) => None,
+            };
+            if let Some((on_disk_package, previous_package)) = previous_package {
+                if package_names.is_disjoint(&compilation.recompiled_packages)
+                    && !on_disk_package
+                        .has_source_changed_since_last_compile(&build.resolved_package)
+                {
+                    if build_options.generate_docs {
+                        build.compiled_docs = previous_package.compiled_docs.clone();
+                    }
+                    if build_options.generate_abis {
+                        build.compiled_abis = previous_package.compiled_abis.clone();
+                    }
+                }
             }
-        };
-
-        let compiled_package = CompiledPackage {
-            compiled_package_info: CompiledPackageInfo {
-                package_name: resolved_package.source_package.package.name,
-                address_alias_instantiation: resolved_package.resolution_table,
-                source_digest: Some(resolved_package.source_digest),
-                build_flags: resolution_graph.build_options.clone(),
-            },
-            root_compiled_units,
-            deps_compiled_units,
-            compiled_docs,
-            compiled_abis,
-        };
-
-        compiled_package.save_to_disk(project_root.join(CompiledPackageLayout::Root.path()))?;
+        }
 
-        Ok(compiled_package)
+        builds
+            .into_iter()
+            .map(|build| build.into_compiled_package(project_root, resolution_graph))
+            .collect()
     }
 
     // We take the (restrictive) view that all filesystems are case insensitive to maximize
@@ -791,6 +840,103 @@ impl CompiledPackage {
     }
 }
 
+impl PackageBuild {
+    /// Checks that the units of the package only use modules of the package and its dependencies.
+    /// All packages which are built together are compiled as sources of the same compilation, so
+    /// the compiler itself does not reject uses of the modules of another of these packages.
+    fn check_used_packages(&self, graph: &ModuleGraph) -> Result<()> {
+        let package_name = self.resolved_package.source_package.package.name;
+        let module_packages = graph
+            .units
+            .iter()
+            .filter_map(|unit| Some((unit.module_id.as_ref()?, unit.package_name)))
+            .collect::<BTreeMap<_, _>>();
+        for unit in graph
+            .units
+            .iter()
+            .filter(|unit| unit.package_name == package_name)
+        {
+            for module_id in &unit.dependencies {
+                let used_package = match module_packages.get(module_id) {
+                    Some(used_package) => *used_package,
+                    None => continue,
+                };
+                ensure!(
+                    used_package == package_name || self.dependencies.contains(&used_package),
+                    "'{}' in package '{}' uses module '{}' of package '{}', which is not a \
+                     dependency of '{}'. Add '{}' to the dependencies of '{}'",
+                    unit.name,
+                    package_name,
+                    module_id,
+                    used_package,
+                    package_name,
+                    used_package,
+                    package_name
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn into_compiled_package(
+        self,
+        project_root: &Path,
+        resolution_graph: &ResolvedGraph,
+    ) -> Result<CompiledPackage> {
+        let resolved_package = self.resolved_package;
+        let mut compiled_docs = self.compiled_docs;
+        let mut compiled_abis = self.compiled_abis;
+        let generate_docs = resolution_graph.build_options.generate_docs && compiled_docs.is_none();
+        let generate_abis = resolution_graph.build_options.generate_abis && compiled_abis.is_none();
+        if generate_docs || generate_abis {
+            let model = run_model_builder_with_options(
+                vec![self.sources_package_paths],
+                self.deps_package_paths,
+                ModelBuilderOptions::default(),
+            )?;
+
+            if generate_docs {
+                compiled_docs = Some(CompiledPackage::build_docs(
+                    resolved_package.source_package.package.name,
+                    &model,
+                    &resolved_package.package_path,
+                    &self.immediate_dependencies,
+                    &resolution_graph.build_options.install_dir,
+                ));
+            }
+
+            if generate_abis {
+                compiled_abis = Some(CompiledPackage::build_abis(
+                    &model,
+                    &self.root_compiled_units,
+                ));
+            }
+        };
+
+        let compiled_package = CompiledPackage {
+            compiled_package_info: CompiledPackageInfo {
+                package_name: resolved_package.source_package.package.name,
+                address_alias_instantiation: resolved_package.resolution_table,
+                source_digest: Some(resolved_package.source_digest),
+                build_flags: resolution_graph.build_options.clone(),
+            },
+            root_compiled_units: self.root_compiled_units,
+            deps_compiled_units: self.deps_compiled_units,
+            compiled_docs,
+            compiled_abis,
+        };
+
+        let on_disk_package =
+            compiled_package.save_to_disk(project_root.join(CompiledPackageLayout::Root.path()))?;
+        on_disk_package.save_under(
+            CompiledPackageLayout::ModuleGraph.path(),
+            serde_yaml::to_string(&self.module_graph)?.as_bytes(),
+        )?;
+
+        Ok(compiled_package)
+    }
+}
+
 pub(crate) fn named_address_mapping_for_compiler(
     resolution_table: &ResolvedTable,
 ) -> BTreeMap<Symbol, NumericalAddress> {
@@ -841,8 +987,19 @@ pub(crate) fn make_source_and_deps_for_compiler(
     /* sources */ PackagePaths,
     /* deps */ Vec<PackagePaths>,
 )> {
-    let deps_package_paths = deps
-        .into_iter()
+    let deps_package_paths = make_deps_for_compiler(deps)?;
+    let source_package_paths = make_source_for_compiler(resolution_graph, root)?;
+    Ok((source_package_paths, deps_package_paths))
+}
+
+pub(crate) fn make_deps_for_compiler(
+    deps: Vec<(
+        /* name */ Symbol,
+        /* source paths */ Vec<Symbol>,
+        /* address mapping */ &ResolvedTable,
+    )>,
+) -> Result<Vec<PackagePaths>> {
+    deps.into_iter()
         .map(|(name, source_paths, resolved_table)| {
             let paths = source_paths
                 .into_iter()
@@ -856,17 +1013,22 @@ pub(crate) fn make_source_and_deps_for_compiler(
                 named_address_map,
             })
         })
-        .collect::<Result<Vec<_>>>()?;
+        .collect()
+}
+
+pub(crate) fn make_source_for_compiler(
+    resolution_graph: &ResolvedGraph,
+    root: &ResolvedPackage,
+) -> Result<PackagePaths> {
     let root_named_addrs = apply_named_address_renaming(
         root.source_package.package.name,
         named_address_mapping_for_compiler(&root.resolution_table),
         &root.renaming,
     );
     let sources = root.get_sources(&resolution_graph.build_options)?;
-    let source_package_paths = PackagePaths {
+    Ok(PackagePaths {
         name: Some(root.source_package.package.name),
         paths: sources,
         named_address_map: root_named_addrs,
-    };
-    Ok((source_package_paths, deps_package_paths))
+    })
 }
@@ -4,4 +4,5 @@
 pub mod build_plan;
 pub mod compiled_package;
 pub mod model_builder;
+pub mod module_graph;
 pub mod package_layout;
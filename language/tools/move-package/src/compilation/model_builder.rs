@@ -2,24 +2,47 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    compilation::compiled_package::make_source_and_deps_for_compiler,
-    resolution::resolution_graph::ResolvedGraph, ModelConfig,
+    compilation::compiled_package::{make_deps_for_compiler, make_source_for_compiler},
+    resolution::resolution_graph::ResolvedGraph,
+    source_package::parsed_manifest::PackageName,
+    ModelConfig,
 };
 use anyhow::Result;
 use move_compiler::shared::PackagePaths;
 use move_model::{model::GlobalEnv, options::ModelBuilderOptions, run_model_builder_with_options};
+use std::collections::BTreeSet;
 
 #[derive(Debug, Clone)]
 pub struct ModelBuilder {
     resolution_graph: ResolvedGraph,
     model_config: ModelConfig,
+    /// The packages whose files are targets
+    target_packages: BTreeSet<PackageName>,
 }
 
 impl ModelBuilder {
+    /// Creates a builder for the model of the root package, or of all members of the workspace if
+    /// the root package is the root of a workspace.
     pub fn create(resolution_graph: ResolvedGraph, model_config: ModelConfig) -> Self {
+        let target_packages = resolution_graph.workspace_members();
         Self {
             resolution_graph,
             model_config,
+            target_packages,
+        }
+    }
+
+    /// Creates a builder for the model of `package` in the package graph, such as a single member
+    /// of a workspace.
+    pub fn create_for_package(
+        resolution_graph: ResolvedGraph,
+        package: PackageName,
+        model_config: ModelConfig,
+    ) -> Self {
+        Self {
+            resolution_graph,
+            model_config,
+            target_packages: BTreeSet::from([package]),
         }
     }
 
@@ -40,35 +63,43 @@ impl ModelBuilder {
             }
         }
 
-        // Targets are all files in the root package
+        // Targets are all files in the target packages
         let root_name = &self.resolution_graph.root_package.package.name;
-        let root_package = self.resolution_graph.get_package(root_name).clone();
+        let targets = self
+            .target_packages
+            .iter()
+            .map(|name| {
+                make_source_for_compiler(
+                    &self.resolution_graph,
+                    self.resolution_graph.get_package(name),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
         let deps_source_info = self
             .resolution_graph
             .package_table
             .iter()
             .filter_map(|(nm, pkg)| {
-                if nm == root_name {
+                // The root package of a workspace has no sources
+                if self.target_packages.contains(nm)
+                    || (self.resolution_graph.is_workspace() && nm == root_name)
+                {
                     return None;
                 }
                 let dep_source_paths = pkg
                     .get_sources(&self.resolution_graph.build_options)
                     .unwrap();
-                Some(Ok((*nm, dep_source_paths, &pkg.resolution_table)))
+                Some((*nm, dep_source_paths, &pkg.resolution_table))
             })
-            .collect::<Result<Vec<_>>>()?;
+            .collect::<Vec<_>>();
+        let deps = make_deps_for_compiler(deps_source_info)?;
 
-        let (target, deps) = make_source_and_deps_for_compiler(
-            &self.resolution_graph,
-            &root_package,
-            deps_source_info,
-        )?;
         let (all_targets, all_deps) = if self.model_config.all_files_as_targets {
-            let mut targets = vec![target];
+            let mut targets = targets;
             targets.extend(deps.into_iter());
             (targets, vec![])
         } else {
-            (vec![target], deps)
+            (targets, deps)
         };
         let (all_targets, all_deps) = match &self.model_config.target_filter {
             Some(filter) => {
@@ -0,0 +1,611 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    compilation::{
+        compiled_package::{CompiledPackage, CompiledUnitWithSource, OnDiskCompiledPackage},
+        package_layout::CompiledPackageLayout,
+    },
+    resolution::resolution_graph::ResolvedGraph,
+    source_package::parsed_manifest::{FileName, PackageName},
+};
+use anyhow::Result;
+use colored::Colorize;
+use move_binary_format::access::{ModuleAccess, ScriptAccess};
+use move_compiler::{
+    compiled_unit::{
+        AnnotatedCompiledUnit, CompiledUnit, CompiledUnitEnum, NamedCompiledModule,
+        NamedCompiledScript,
+    },
+    diagnostics::FilesSourceText,
+    interface_generator::write_module_to_string,
+    shared::{Flags, PackagePaths},
+    Compiler,
+};
+use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
+use move_symbol_pool::Symbol;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Identifies a compiled unit by its package, its name and whether it is a module. These are
+/// unique within a build, as they determine where the unit is saved on disk.
+type UnitKey = (PackageName, Symbol, bool);
+
+/// The modules and scripts produced by a build, together with the sources they were compiled from,
+/// the modules they depend on and the interfaces of the modules. It is saved with each compiled
+/// package, and used by the next build to only recompile what is affected by the changes made to
+/// the sources since.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModuleGraph {
+    /// Whether the units were compiled in test mode
+    pub test_mode: bool,
+    /// The sources of each package that were compiled
+    pub packages: BTreeMap<PackageName, CompiledSources>,
+    /// The compiled modules and scripts, in dependency order
+    pub units: Vec<CompiledUnitInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompiledSources {
+    /// The values of the named addresses that the sources were compiled with
+    pub named_addresses: BTreeMap<Symbol, AccountAddress>,
+    /// The hash of the contents of each source file
+    pub source_digests: BTreeMap<FileName, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompiledUnitInfo {
+    /// The package which declares the unit
+    pub package_name: PackageName,
+    /// The name of the module or script
+    pub name: Symbol,
+    /// The source file which declares the unit
+    pub source_path: FileName,
+    /// The address and name of the module. `None` for scripts.
+    pub module_id: Option<String>,
+    /// The hash of the interface of the module, as generated by the interface generator of the
+    /// compiler. `None` for scripts.
+    pub interface_digest: Option<String>,
+    /// The modules used by the unit
+    pub dependencies: BTreeSet<String>,
+    /// The friends declared by the module
+    pub friends: BTreeSet<String>,
+}
+
+/// The artifacts of a previous build of some packages, which can be reused by the next build of
+/// these packages.
+pub(crate) struct PreviousBuild {
+    graph: ModuleGraph,
+    units: BTreeMap<UnitKey, CompiledUnitWithSource>,
+    /// The compiled packages of the previous build
+    pub(crate) packages: BTreeMap<PackageName, (OnDiskCompiledPackage, CompiledPackage)>,
+}
+
+/// Why a source file is compiled
+#[derive(Debug, Clone)]
+pub(crate) enum RebuildReason {
+    NoPreviousBuild,
+    NotIncremental,
+    ForcedRecompilation,
+    BuildFlagsChanged,
+    NotPreviouslyCompiled,
+    SourceChanged,
+    NamedAddressesChanged,
+    MissingArtifacts,
+    InterfaceChanged(String),
+}
+
+/// The result of compiling the sources of a build incrementally
+pub(crate) struct IncrementalCompilation {
+    /// All units of the build, in dependency order, with the packages declaring them
+    pub(crate) units: Vec<(PackageName, CompiledUnitWithSource)>,
+    pub(crate) graph: ModuleGraph,
+    /// The packages for which at least one unit was recompiled or removed
+    pub(crate) recompiled_packages: BTreeSet<PackageName>,
+}
+
+impl ModuleGraph {
+    pub fn from_path(package_path: &Path) -> Result<Self> {
+        let buf = std::fs::read(package_path.join(CompiledPackageLayout::ModuleGraph.path()))?;
+        Ok(serde_yaml::from_slice(&buf)?)
+    }
+
+    /// Returns the part of the graph made of the sources and units of `package_names`
+    pub fn for_packages(&self, package_names: &BTreeSet<PackageName>) -> Self {
+        Self {
+            test_mode: self.test_mode,
+            packages: self
+                .packages
+                .iter()
+                .filter(|(name, _)| package_names.contains(name))
+                .map(|(name, sources)| (*name, sources.clone()))
+                .collect(),
+            units: self
+                .units
+                .iter()
+                .filter(|unit| package_names.contains(&unit.package_name))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn source_file_units<'a>(
+        &'a self,
+        source_path: &'a FileName,
+    ) -> impl Iterator<Item = &'a CompiledUnitInfo> {
+        self.units
+            .iter()
+            .filter(move |unit| &unit.source_path == source_path)
+    }
+}
+
+impl CompiledUnitInfo {
+    fn new(package_name: PackageName, source_path: FileName, unit: &CompiledUnit) -> Result<Self> {
+        let module_ids = |ids: Vec<ModuleId>| {
+            ids.iter()
+                .map(ModuleId::short_str_lossless)
+                .collect::<BTreeSet<_>>()
+        };
+        Ok(match unit {
+            CompiledUnit::Module(NamedCompiledModule { name, module, .. }) => {
+                let (id, interface) =
+                    write_module_to_string(&BTreeMap::<ModuleId, String>::new(), module)?;
+                Self {
+                    package_name,
+                    name: *name,
+                    source_path,
+                    module_id: Some(id.short_str_lossless()),
+                    interface_digest: Some(format!("{:X}", Sha256::digest(interface.as_bytes()))),
+                    dependencies: module_ids(module.immediate_dependencies()),
+                    friends: module_ids(module.immediate_friends()),
+                }
+            }
+            CompiledUnit::Script(NamedCompiledScript { name, script, .. }) => Self {
+                package_name,
+                name: *name,
+                source_path,
+                module_id: None,
+                interface_digest: None,
+                dependencies: module_ids(script.immediate_dependencies()),
+                friends: BTreeSet::new(),
+            },
+        })
+    }
+
+    fn key(&self) -> UnitKey {
+        (self.package_name, self.name, self.module_id.is_some())
+    }
+}
+
+impl PreviousBuild {
+    /// Loads the previous build of the packages `package_names` under `build_root`. Packages
+    /// which were not built before, or whose artifacts cannot be read, are skipped. Returns `None`
+    /// if none of the packages can be loaded.
+    pub(crate) fn load(build_root: &Path, package_names: &BTreeSet<PackageName>) -> Option<Self> {
+        let mut loaded: Option<Self> = None;
+        for package_name in package_names {
+            let (graph, on_disk_package, compiled_package) =
+                match Self::load_package(&build_root.join(package_name.as_str())) {
+                    Ok(package) => package,
+                    Err(_) => continue,
+                };
+            let previous = loaded.get_or_insert_with(|| Self {
+                graph: ModuleGraph {
+                    test_mode: graph.test_mode,
+                    ..ModuleGraph::default()
+                },
+                units: BTreeMap::new(),
+                packages: BTreeMap::new(),
+            });
+            // Packages built with different flags can't be reused together
+            if graph.test_mode != previous.graph.test_mode {
+                continue;
+            }
+            previous.merge(graph, &compiled_package);
+            previous
+                .packages
+                .insert(*package_name, (on_disk_package, compiled_package));
+        }
+        loaded
+    }
+
+    fn load_package(
+        package_path: &Path,
+    ) -> Result<(ModuleGraph, OnDiskCompiledPackage, CompiledPackage)> {
+        let graph = ModuleGraph::from_path(package_path)?;
+        let on_disk_package = OnDiskCompiledPackage::from_path(package_path)?;
+        let compiled_package = on_disk_package.into_compiled_package()?;
+        Ok((graph, on_disk_package, compiled_package))
+    }
+
+    fn merge(&mut self, graph: ModuleGraph, compiled_package: &CompiledPackage) {
+        for (package_name, sources) in graph.packages {
+            match self.graph.packages.get(&package_name) {
+                // Conflicting records of the sources of a package make it look changed, so that
+                // it is recompiled
+                Some(previous_sources) if previous_sources != &sources => {
+                    self.graph.packages.insert(
+                        package_name,
+                        CompiledSources {
+                            named_addresses: BTreeMap::new(),
+                            source_digests: BTreeMap::new(),
+                        },
+                    );
+                }
+                Some(_) => (),
+                None => {
+                    self.graph.packages.insert(package_name, sources);
+                }
+            }
+        }
+        let root_package = compiled_package.compiled_package_info.package_name;
+        let compiled_units = compiled_package
+            .root_compiled_units
+            .iter()
+            .map(|unit| (root_package, unit))
+            .chain(
+                compiled_package
+                    .deps_compiled_units
+                    .iter()
+                    .map(|(package_name, unit)| (*package_name, unit)),
+            );
+        for (package_name, unit) in compiled_units {
+            let key = (
+                package_name,
+                unit.unit.name(),
+                matches!(unit.unit, CompiledUnit::Module(_)),
+            );
+            self.units.entry(key).or_insert_with(|| unit.clone());
+        }
+        let known_units = self
+            .graph
+            .units
+            .iter()
+            .map(CompiledUnitInfo::key)
+            .collect::<BTreeSet<_>>();
+        self.graph.units.extend(
+            graph
+                .units
+                .into_iter()
+                .filter(|unit| !known_units.contains(&unit.key())),
+        );
+    }
+
+    /// Returns whether all units compiled from `source_path` were loaded
+    fn has_units_of(&self, source_path: &FileName) -> bool {
+        self.graph
+            .source_file_units(source_path)
+            .all(|unit| self.units.contains_key(&unit.key()))
+    }
+}
+
+impl fmt::Display for RebuildReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoPreviousBuild => write!(f, "no readable previous build was found"),
+            Self::NotIncremental => {
+                write!(f, "incremental compilation is not used by this command")
+            }
+            Self::ForcedRecompilation => write!(f, "recompilation was forced"),
+            Self::BuildFlagsChanged => {
+                write!(f, "the previous build was not compiled with the same flags")
+            }
+            Self::NotPreviouslyCompiled => write!(f, "it was not compiled by the previous build"),
+            Self::SourceChanged => write!(f, "the source file changed"),
+            Self::NamedAddressesChanged => write!(f, "the named addresses of its package changed"),
+            Self::MissingArtifacts => write!(f, "its compiled artifacts are missing"),
+            Self::InterfaceChanged(module_id) => {
+                write!(f, "the interface of its dependency {} changed", module_id)
+            }
+        }
+    }
+}
+
+/// Compiles the sources of `packages`, reusing the units of the `previous` build which are not
+/// affected by the changes made to the sources since.
+///
+/// The compiler compiles whole source files, which are recompiled if they changed or if a unit
+/// they declare depends on a module whose interface differs from the one it was compiled against.
+/// The interfaces of the recompiled modules are compared to their previous interfaces, and source
+/// files depending on modules with new interfaces are compiled next, until no interface changes.
+/// All other sources are given to the compiler as dependencies, which are checked but not compiled.
+pub(crate) fn compile_incrementally<W: Write>(
+    w: &mut W,
+    resolution_graph: &ResolvedGraph,
+    packages: Vec<PackagePaths>,
+    previous: Result<&PreviousBuild, RebuildReason>,
+    mut compiler_driver: impl FnMut(Compiler) -> Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
+) -> Result<IncrementalCompilation> {
+    let build_options = &resolution_graph.build_options;
+    let explain = build_options.explain_rebuild;
+    let flags = if build_options.test_mode {
+        Flags::testing()
+    } else {
+        Flags::empty()
+    };
+    let previous = match previous {
+        Ok(_) if build_options.force_recompilation => Err(RebuildReason::ForcedRecompilation),
+        Ok(previous) if previous.graph.test_mode != build_options.test_mode => {
+            Err(RebuildReason::BuildFlagsChanged)
+        }
+        previous => previous,
+    };
+    let no_graph = ModuleGraph::default();
+    let previous_graph = match previous {
+        Ok(previous) => &previous.graph,
+        Err(_) => &no_graph,
+    };
+
+    let mut sources = BTreeMap::new();
+    for package in &packages {
+        let package_name = package.name.unwrap();
+        let mut source_digests = BTreeMap::new();
+        for path in &package.paths {
+            let contents = std::fs::read(path.as_str())?;
+            source_digests.insert(*path, format!("{:X}", Sha256::digest(&contents)));
+        }
+        let named_addresses = package
+            .named_address_map
+            .iter()
+            .map(|(name, addr)| (*name, addr.into_inner()))
+            .collect();
+        sources.insert(
+            package_name,
+            CompiledSources {
+                named_addresses,
+                source_digests,
+            },
+        );
+    }
+    let source_package = sources
+        .iter()
+        .flat_map(|(package_name, package_sources)| {
+            package_sources
+                .source_digests
+                .keys()
+                .map(move |path| (*path, *package_name))
+        })
+        .collect::<BTreeMap<_, _>>();
+    // Whether the source file of a unit of the previous build is still part of its package
+    let is_current =
+        |unit: &CompiledUnitInfo| source_package.get(&unit.source_path) == Some(&unit.package_name);
+
+    // Find the source files which changed since the previous build
+    let mut to_compile = BTreeMap::new();
+    match &previous {
+        Err(reason) => {
+            if explain {
+                writeln!(
+                    w,
+                    "{} all source files: {}",
+                    "REBUILDING".bold().green(),
+                    reason
+                )?;
+            }
+            to_compile.extend(source_package.keys().map(|path| (*path, reason.clone())));
+        }
+        Ok(previous) => {
+            for (package_name, package_sources) in &sources {
+                let previous_sources = previous_graph.packages.get(package_name);
+                for (path, digest) in &package_sources.source_digests {
+                    let reason = match previous_sources {
+                        None => RebuildReason::NotPreviouslyCompiled,
+                        Some(previous_sources)
+                            if previous_sources.named_addresses
+                                != package_sources.named_addresses =>
+                        {
+                            RebuildReason::NamedAddressesChanged
+                        }
+                        Some(previous_sources) => match previous_sources.source_digests.get(path) {
+                            None => RebuildReason::NotPreviouslyCompiled,
+                            Some(previous_digest) if previous_digest != digest => {
+                                RebuildReason::SourceChanged
+                            }
+                            Some(_) if !previous.has_units_of(path) => {
+                                RebuildReason::MissingArtifacts
+                            }
+                            Some(_) => continue,
+                        },
+                    };
+                    to_compile.insert(*path, reason);
+                }
+            }
+        }
+    }
+
+    // Modules which are no longer declared by the sources of the build
+    let mut changed_interfaces = previous_graph
+        .units
+        .iter()
+        .filter(|unit| !is_current(unit))
+        .filter_map(|unit| unit.module_id.clone())
+        .collect::<BTreeSet<_>>();
+    let mut recompiled_packages = previous_graph
+        .units
+        .iter()
+        .filter(|unit| !is_current(unit))
+        .map(|unit| unit.package_name)
+        .collect::<BTreeSet<_>>();
+    let previous_interfaces = previous_graph
+        .units
+        .iter()
+        .filter_map(|unit| Some((unit.module_id.clone()?, unit.interface_digest.clone()?)))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut compiled_files = BTreeSet::new();
+    let mut units = vec![];
+    while !to_compile.is_empty() {
+        if explain && previous.is_ok() {
+            for (path, reason) in &to_compile {
+                let package_name = source_package[path];
+                let package_path = &resolution_graph.package_table[&package_name].package_path;
+                let path = Path::new(path.as_str());
+                writeln!(
+                    w,
+                    "{} {} ({}): {}",
+                    "REBUILDING".bold().green(),
+                    path.strip_prefix(package_path).unwrap_or(path).display(),
+                    package_name,
+                    reason,
+                )?;
+            }
+        }
+        let mut targets = vec![];
+        let mut deps = vec![];
+        for package in &packages {
+            let (target_paths, dep_paths): (Vec<Symbol>, Vec<Symbol>) = package
+                .paths
+                .iter()
+                .partition(|path| to_compile.contains_key(path));
+            for (paths, package_paths) in [(target_paths, &mut targets), (dep_paths, &mut deps)] {
+                if !paths.is_empty() {
+                    package_paths.push(PackagePaths {
+                        name: package.name,
+                        paths,
+                        named_address_map: package.named_address_map.clone(),
+                    });
+                }
+            }
+        }
+        let compiler = Compiler::from_package_paths(targets, deps).set_flags(flags.clone());
+        let (file_map, compiled_units) = compiler_driver(compiler)?;
+        let compiling = std::mem::take(&mut to_compile);
+        recompiled_packages.extend(compiling.keys().map(|path| source_package[path]));
+
+        let mut compiled_module_ids = BTreeSet::new();
+        for annot_unit in compiled_units {
+            let source_path = file_map[&annot_unit.loc().file_hash()].0;
+            let package_name = match &annot_unit {
+                CompiledUnitEnum::Module(m) => m.named_module.package_name.unwrap(),
+                CompiledUnitEnum::Script(s) => s.named_script.package_name.unwrap(),
+            };
+            let unit = annot_unit.into_compiled_unit();
+            let info = CompiledUnitInfo::new(package_name, source_path, &unit)?;
+            if let Some(module_id) = &info.module_id {
+                if previous_interfaces.get(module_id) != info.interface_digest.as_ref() {
+                    changed_interfaces.insert(module_id.clone());
+                }
+                compiled_module_ids.insert(module_id.clone());
+            }
+            let unit = CompiledUnitWithSource {
+                unit,
+                source_path: PathBuf::from(source_path.as_str()),
+            };
+            units.push((package_name, unit, info));
+        }
+        // Modules which are no longer declared by the recompiled sources
+        for path in compiling.keys() {
+            changed_interfaces.extend(
+                previous_graph
+                    .source_file_units(path)
+                    .filter_map(|unit| unit.module_id.clone())
+                    .filter(|module_id| !compiled_module_ids.contains(module_id)),
+            );
+        }
+        compiled_files.extend(compiling.into_keys());
+
+        // Sources which depend on modules whose interface changed must be recompiled
+        for unit in &previous_graph.units {
+            if compiled_files.contains(&unit.source_path) || !is_current(unit) {
+                continue;
+            }
+            if let Some(module_id) = unit
+                .dependencies
+                .iter()
+                .chain(unit.friends.iter())
+                .find(|module_id| changed_interfaces.contains(*module_id))
+            {
+                to_compile
+                    .entry(unit.source_path)
+                    .or_insert_with(|| RebuildReason::InterfaceChanged(module_id.clone()));
+            }
+        }
+    }
+
+    // Reuse the units of the sources which were not recompiled
+    let recompiled_units = units.len();
+    if let Ok(previous) = previous {
+        for info in &previous_graph.units {
+            if compiled_files.contains(&info.source_path) || !is_current(info) {
+                continue;
+            }
+            let unit = CompiledUnitWithSource {
+                unit: previous.units[&info.key()].unit.clone(),
+                source_path: PathBuf::from(info.source_path.as_str()),
+            };
+            units.push((info.package_name, unit, info.clone()));
+        }
+    }
+    if explain {
+        writeln!(
+            w,
+            "{} {} of {} modules and scripts",
+            "REUSED".bold().green(),
+            units.len() - recompiled_units,
+            units.len()
+        )?;
+    }
+
+    let units = dependency_order(units);
+    let graph = ModuleGraph {
+        test_mode: build_options.test_mode,
+        packages: sources,
+        units: units.iter().map(|(_, _, info)| info.clone()).collect(),
+    };
+    Ok(IncrementalCompilation {
+        units: units
+            .into_iter()
+            .map(|(package_name, unit, _)| (package_name, unit))
+            .collect(),
+        graph,
+        recompiled_packages,
+    })
+}
+
+/// Orders `units` so that modules come after the modules they depend on, followed by the scripts.
+/// Otherwise, the order of the units is kept.
+fn dependency_order(
+    units: Vec<(PackageName, CompiledUnitWithSource, CompiledUnitInfo)>,
+) -> Vec<(PackageName, CompiledUnitWithSource, CompiledUnitInfo)> {
+    fn visit(
+        index: usize,
+        units: &[(PackageName, CompiledUnitWithSource, CompiledUnitInfo)],
+        modules: &BTreeMap<&String, usize>,
+        visited: &mut BTreeSet<usize>,
+        order: &mut Vec<usize>,
+    ) {
+        if !visited.insert(index) {
+            return;
+        }
+        for dependency in &units[index].2.dependencies {
+            if let Some(dependency_index) = modules.get(dependency) {
+                visit(*dependency_index, units, modules, visited, order)
+            }
+        }
+        order.push(index)
+    }
+
+    let modules = units
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, _, info))| Some((info.module_id.as_ref()?, index)))
+        .collect::<BTreeMap<_, _>>();
+    let mut visited = BTreeSet::new();
+    let mut order = vec![];
+    for index in modules.values().copied().collect::<BTreeSet<_>>() {
+        visit(index, &units, &modules, &mut visited, &mut order);
+    }
+    order.extend((0..units.len()).filter(|index| !visited.contains(index)));
+
+    let mut units = units.into_iter().map(Some).collect::<Vec<_>>();
+    order
+        .into_iter()
+        .map(|index| units[index].take().unwrap())
+        .collect()
+}
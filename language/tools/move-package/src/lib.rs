@@ -4,6 +4,7 @@
 mod package_lock;
 
 pub mod compilation;
+pub mod registry;
 pub mod resolution;
 pub mod source_package;
 
@@ -25,8 +26,11 @@ use crate::{
         build_plan::BuildPlan, compiled_package::CompiledPackage, model_builder::ModelBuilder,
     },
     package_lock::PackageLock,
-    resolution::resolution_graph::{ResolutionGraph, ResolvedGraph},
-    source_package::{layout, manifest_parser},
+    resolution::{
+        resolution_graph::{ResolutionGraph, ResolvedGraph, ResolvingNamedAddress},
+        vendor::{vendor_dependencies, VendorInfo},
+    },
+    source_package::{layout, manifest_parser, parsed_manifest::SourceManifest},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -118,12 +122,27 @@ pub struct BuildConfig {
     #[clap(name = "force-recompilation", long = "force", global = true)]
     pub force_recompilation: bool,
 
+    /// Explain which source files are recompiled, and why. Other sources are not recompiled,
+    /// their compiled modules and scripts are reused from the previous build.
+    #[clap(name = "explain-rebuild", long = "explain-rebuild", global = true)]
+    pub explain_rebuild: bool,
+
     /// Additional named address mapping. Useful for tools in rust
     #[clap(skip)]
     pub additional_named_addresses: BTreeMap<String, AccountAddress>,
 
     #[clap(long = "arch", global = true, parse(try_from_str = Architecture::try_parse_from_str))]
     pub architecture: Option<Architecture>,
+
+    /// The package registry from which versioned dependencies are resolved, and to which
+    /// packages are published. Must be the path to a filesystem registry.
+    #[clap(long = "registry", parse(from_os_str), global = true)]
+    pub registry: Option<PathBuf>,
+
+    /// Fail instead of downloading dependencies which have not been downloaded yet. Packages
+    /// with vendored dependencies can always be built offline.
+    #[clap(long = "offline", global = true)]
+    pub offline: bool,
 }
 
 impl Default for BuildConfig {
@@ -135,8 +154,11 @@ impl Default for BuildConfig {
             generate_abis: false,
             install_dir: None,
             force_recompilation: false,
+            explain_rebuild: false,
             additional_named_addresses: BTreeMap::new(),
             architecture: None,
+            registry: None,
+            offline: false,
         }
     }
 }
@@ -160,6 +182,21 @@ impl BuildConfig {
         ret
     }
 
+    /// Compile all members of the workspace at `path` or the containing Move package, or only the
+    /// package itself if it is not a workspace. The members are compiled together, so that
+    /// dependencies which are shared between them are only compiled once.
+    pub fn compile_workspace<W: Write>(
+        self,
+        path: &Path,
+        writer: &mut W,
+    ) -> Result<Vec<CompiledPackage>> {
+        let resolved_graph = self.resolution_graph_for_package(path)?;
+        let mutx = PackageLock::lock();
+        let ret = BuildPlan::create(resolved_graph)?.compile_workspace(writer);
+        mutx.unlock();
+        ret
+    }
+
     #[cfg(feature = "evm-backend")]
     pub fn compile_package_evm<W: Write>(self, path: &Path, writer: &mut W) -> Result<()> {
         let resolved_graph = self.resolution_graph_for_package(path)?;
@@ -186,7 +223,31 @@ impl BuildConfig {
         ret
     }
 
-    pub fn resolution_graph_for_package(mut self, path: &Path) -> Result<ResolvedGraph> {
+    pub fn resolution_graph_for_package(self, path: &Path) -> Result<ResolvedGraph> {
+        self.resolve_package(path, ResolutionGraph::new)
+    }
+
+    /// Copy all dependencies of the package at `path` or the containing Move package, including
+    /// its dev dependencies, into its `vendor` directory. Later builds of the package resolve its
+    /// dependencies from there.
+    pub fn vendor_package(mut self, path: &Path) -> Result<VendorInfo> {
+        self.dev_mode = true;
+        let resolved_graph = self.resolve_package(path, ResolutionGraph::new_ignoring_vendor)?;
+        let mutx = PackageLock::lock();
+        let ret = vendor_dependencies(&resolved_graph);
+        mutx.unlock();
+        ret
+    }
+
+    fn resolve_package(
+        mut self,
+        path: &Path,
+        new_graph: impl FnOnce(
+            SourceManifest,
+            PathBuf,
+            BuildConfig,
+        ) -> Result<ResolutionGraph<ResolvingNamedAddress>>,
+    ) -> Result<ResolvedGraph> {
         if self.test_mode {
             self.dev_mode = true;
         }
@@ -197,8 +258,8 @@ impl BuildConfig {
         let mutx = PackageLock::lock();
         // This should be locked as it inspects the environment for `MOVE_HOME` which could
         // possibly be set by a different process in parallel.
-        let manifest = manifest_parser::parse_source_manifest(toml_manifest)?;
-        let resolution_graph = ResolutionGraph::new(manifest, path, self)?;
+        let manifest = manifest_parser::parse_root_manifest(toml_manifest, &path)?;
+        let resolution_graph = new_graph(manifest, path, self)?;
         let ret = resolution_graph.resolve();
         mutx.unlock();
         ret
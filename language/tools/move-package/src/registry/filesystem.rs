@@ -0,0 +1,97 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    registry::{IndexEntry, PackageIndex, Registry},
+    source_package::parsed_manifest::PackageName,
+};
+use anyhow::{bail, Context, Result};
+use semver::Version;
+use std::{fs, path::PathBuf};
+
+/// A registry which is stored in a directory, which can be used offline and in tests. It is laid
+/// out on-disk as
+/// a_registry
+/// ├── index
+/// │   └── <name>.yaml                  (the `PackageIndex` of package <name>)
+/// └── packages
+///     └── <name>
+///         └── <name>-<version>.tar.gz  (the archive of version <version> of package <name>)
+#[derive(Debug, Clone)]
+pub struct FileSystemRegistry {
+    root: PathBuf,
+}
+
+impl FileSystemRegistry {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn index_path(&self, package: PackageName) -> PathBuf {
+        self.root.join("index").join(format!("{}.yaml", package))
+    }
+
+    fn archive_path(&self, package: PackageName, version: &Version) -> PathBuf {
+        self.root
+            .join("packages")
+            .join(package.as_str())
+            .join(format!("{}-{}.tar.gz", package, version))
+    }
+}
+
+impl Registry for FileSystemRegistry {
+    fn index(&self, package: PackageName) -> Result<Option<PackageIndex>> {
+        if !self.root.is_dir() {
+            bail!(
+                "Unable to find package registry at '{}'",
+                self.root.to_string_lossy()
+            )
+        }
+        let index_path = self.index_path(package);
+        if !index_path.is_file() {
+            return Ok(None);
+        }
+        let index = serde_yaml::from_str(&fs::read_to_string(&index_path)?)
+            .with_context(|| format!("Malformed registry index of package '{}'", package))?;
+        Ok(Some(index))
+    }
+
+    fn fetch(&self, package: PackageName, version: &Version) -> Result<Vec<u8>> {
+        fs::read(self.archive_path(package, version)).with_context(|| {
+            format!(
+                "Unable to fetch archive of version {} of package '{}'",
+                version, package
+            )
+        })
+    }
+
+    fn publish(&self, package: PackageName, entry: IndexEntry, archive: Vec<u8>) -> Result<()> {
+        let mut index = if self.index_path(package).is_file() {
+            self.index(package)?.unwrap()
+        } else {
+            PackageIndex {
+                name: package,
+                versions: vec![],
+            }
+        };
+        if index.versions.iter().any(|e| e.version == entry.version) {
+            bail!(
+                "Version {} of package '{}' has already been published",
+                entry.version,
+                package
+            )
+        }
+
+        let archive_path = self.archive_path(package, &entry.version);
+        fs::create_dir_all(archive_path.parent().unwrap())?;
+        fs::write(&archive_path, archive)?;
+
+        // The archive is written first, so that every version in the index can be fetched
+        index.versions.push(entry);
+        index.versions.sort_by(|e1, e2| e1.version.cmp(&e2.version));
+        let index_path = self.index_path(package);
+        fs::create_dir_all(index_path.parent().unwrap())?;
+        fs::write(&index_path, serde_yaml::to_string(&index)?)?;
+        Ok(())
+    }
+}
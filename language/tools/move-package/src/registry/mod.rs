@@ -0,0 +1,185 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Package registries, from which versioned dependencies such as `MoveStdlib = "1.2"` are
+//! resolved.
+//!
+//! A registry provides two things for every package published to it:
+//! 1. An index, which lists all published versions of the package. Each entry of the index
+//!    records the source digest and the archive checksum of the version, as well as the version
+//!    requirements of its dependencies. Versions are selected based on the index alone, see
+//!    `resolver`.
+//! 2. An archive for each version, which is a gzipped tarball of the package sources. Archives
+//!    are only fetched once a version has been selected, and are unpacked under
+//!    `$MOVE_HOME/registry`.
+//!
+//! Only packages whose dependencies are all registry dependencies can be published.
+
+pub mod filesystem;
+pub mod resolver;
+
+use crate::{
+    resolution::resolution_graph::ResolvingPackage,
+    source_package::{
+        layout::package_files,
+        manifest_parser::{move_home, parse_move_manifest_from_file},
+        parsed_manifest::{Dependencies, PackageDigest, PackageName},
+    },
+    BuildConfig,
+};
+use anyhow::{bail, Context, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The index of a package in a registry.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PackageIndex {
+    pub name: PackageName,
+    /// The published versions of the package, in ascending order.
+    pub versions: Vec<IndexEntry>,
+}
+
+/// A published version of a package.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub version: Version,
+    /// The digest of the package sources, as computed for a build which is not in dev mode.
+    pub digest: PackageDigest,
+    /// The SHA-256 checksum of the package archive.
+    pub checksum: String,
+    /// The version requirements of the dependencies of this version.
+    pub dependencies: BTreeMap<PackageName, VersionReq>,
+    /// The version requirements of the dev dependencies of this version.
+    pub dev_dependencies: BTreeMap<PackageName, VersionReq>,
+}
+
+/// The protocol to access a package registry.
+pub trait Registry {
+    /// Returns the index of `package`, or `None` if no version of it has been published.
+    fn index(&self, package: PackageName) -> Result<Option<PackageIndex>>;
+
+    /// Returns the archive of version `version` of `package`.
+    fn fetch(&self, package: PackageName, version: &Version) -> Result<Vec<u8>>;
+
+    /// Publishes a new version of `package`, described by `entry`, together with its archive.
+    fn publish(&self, package: PackageName, entry: IndexEntry, archive: Vec<u8>) -> Result<()>;
+}
+
+/// Publishes the package at `package_path` to `registry`, and returns its name and index entry.
+pub fn publish_package(
+    package_path: &Path,
+    registry: &dyn Registry,
+) -> Result<(PackageName, IndexEntry)> {
+    let manifest = parse_move_manifest_from_file(package_path)?;
+    let package_name = manifest.package.name;
+    let (major, minor, patch) = manifest.package.version;
+    let archive = package_archive(package_path)?;
+    let entry = IndexEntry {
+        version: Version::new(major, minor, patch),
+        digest: source_digest(package_path)?,
+        checksum: checksum(&archive),
+        dependencies: registry_requirements(package_name, &manifest.dependencies)?,
+        dev_dependencies: registry_requirements(package_name, &manifest.dev_dependencies)?,
+    };
+    registry.publish(package_name, entry.clone(), archive)?;
+    Ok((package_name, entry))
+}
+
+/// Fetches version `entry` of `package` from `registry` unless it has been fetched before, and
+/// returns the directory it has been unpacked to. Both the checksum of the archive and the
+/// digest of the unpacked sources are checked against the index entry.
+pub fn fetch_package(
+    registry: &dyn Registry,
+    package: PackageName,
+    entry: &IndexEntry,
+) -> Result<PathBuf> {
+    let registry_cache = move_home().join("registry");
+    // Unpacked packages are of the form <name>-<version>_<digest>
+    let package_dir =
+        registry_cache.join(format!("{}-{}_{}", package, entry.version, entry.digest));
+    if package_dir.is_dir() && source_digest(&package_dir)? == entry.digest {
+        return Ok(package_dir);
+    }
+
+    let archive = registry.fetch(package, &entry.version)?;
+    let archive_checksum = checksum(&archive);
+    if archive_checksum != entry.checksum {
+        bail!(
+            "Checksum mismatch in archive of version {} of package '{}'. Expected '{}' but got '{}'.",
+            entry.version,
+            package,
+            entry.checksum,
+            archive_checksum
+        )
+    }
+    fs::create_dir_all(&registry_cache)?;
+    let unpack_dir = tempfile::tempdir_in(&registry_cache)?;
+    tar::Archive::new(GzDecoder::new(archive.as_slice()))
+        .unpack(unpack_dir.path())
+        .with_context(|| {
+            format!(
+                "Unable to unpack version {} of package '{}'",
+                entry.version, package
+            )
+        })?;
+    let unpacked_digest = source_digest(unpack_dir.path())?;
+    if unpacked_digest != entry.digest {
+        bail!(
+            "Source digest mismatch in version {} of package '{}'. Expected '{}' but got '{}'.",
+            entry.version,
+            package,
+            entry.digest,
+            unpacked_digest
+        )
+    }
+    if package_dir.exists() {
+        fs::remove_dir_all(&package_dir)?;
+    }
+    // The temporary directory is gone after the rename, so dropping it has no effect
+    fs::rename(unpack_dir.path(), &package_dir)?;
+    Ok(package_dir)
+}
+
+/// Returns the archive of the package at `package_path`, which contains the manifest and all
+/// source directories of the package. The archive only depends on the paths and contents of
+/// these files.
+pub fn package_archive(package_path: &Path) -> Result<Vec<u8>> {
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    builder.mode(tar::HeaderMode::Deterministic);
+    for file in package_files(package_path)? {
+        builder.append_path_with_name(package_path.join(&file), file)?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+fn registry_requirements(
+    package: PackageName,
+    deps: &Dependencies,
+) -> Result<BTreeMap<PackageName, VersionReq>> {
+    deps.iter()
+        .map(|(dep_name, dep)| match &dep.registry_info {
+            Some(info) => Ok((*dep_name, info.version_req.clone())),
+            None => bail!(
+                "Dependency '{}' of package '{}' is not a registry dependency. Only packages \
+                 whose dependencies are all registry dependencies can be published",
+                dep_name,
+                package
+            ),
+        })
+        .collect()
+}
+
+fn source_digest(package_path: &Path) -> Result<PackageDigest> {
+    ResolvingPackage::get_package_digest_for_config(package_path, &BuildConfig::default())
+}
+
+fn checksum(archive: &[u8]) -> String {
+    format!("{:X}", Sha256::digest(archive))
+}
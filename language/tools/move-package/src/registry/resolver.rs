@@ -0,0 +1,149 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Selection of the versions of registry packages. A package graph can contain only one version of
+//! each package, so a version is selected for each registry package such that the requirements of
+//! all packages depending on it are satisfied. Newer versions are preferred, and the selection
+//! backtracks to older versions if the requirements introduced by a newer version conflict with
+//! other requirements.
+
+use crate::{
+    registry::{IndexEntry, PackageIndex, Registry},
+    source_package::parsed_manifest::PackageName,
+};
+use anyhow::{bail, Result};
+use semver::VersionReq;
+use std::collections::{BTreeMap, VecDeque};
+
+/// A requirement on the version of a registry package.
+#[derive(Debug, Clone)]
+pub struct Requirement {
+    pub package: PackageName,
+    pub version_req: VersionReq,
+    /// A description of the package which states the requirement, used in error messages.
+    pub required_by: String,
+}
+
+/// Selects a version for every registry package which is required by `requirements`, either
+/// directly or through the dependencies of other selected versions. Dev dependencies are taken
+/// into account in `dev_mode`.
+pub fn select_versions(
+    registry: &dyn Registry,
+    requirements: Vec<Requirement>,
+    dev_mode: bool,
+) -> Result<BTreeMap<PackageName, IndexEntry>> {
+    let mut resolver = VersionResolver {
+        registry,
+        dev_mode,
+        indices: BTreeMap::new(),
+    };
+    resolver.select(requirements.into(), BTreeMap::new())
+}
+
+struct VersionResolver<'a> {
+    registry: &'a dyn Registry,
+    dev_mode: bool,
+    /// The indices which have been retrieved from the registry so far.
+    indices: BTreeMap<PackageName, PackageIndex>,
+}
+
+impl<'a> VersionResolver<'a> {
+    fn select(
+        &mut self,
+        mut pending: VecDeque<Requirement>,
+        selected: BTreeMap<PackageName, IndexEntry>,
+    ) -> Result<BTreeMap<PackageName, IndexEntry>> {
+        let requirement = match pending.pop_front() {
+            None => return Ok(selected),
+            Some(requirement) => requirement,
+        };
+        if let Some(entry) = selected.get(&requirement.package) {
+            if !requirement.version_req.matches(&entry.version) {
+                bail!(
+                    "Conflicting requirements for package '{}': version {} was selected, \
+                     which does not match requirement '{}' of {}",
+                    requirement.package,
+                    entry.version,
+                    requirement.version_req,
+                    requirement.required_by
+                )
+            }
+            return self.select(pending, selected);
+        }
+
+        // Try the matching versions from newest to oldest, and report the failure of the newest
+        // one if none of them works out.
+        let mut first_error = None;
+        for entry in self.candidates(&requirement)? {
+            let mut pending = pending.clone();
+            pending.extend(self.requirements_of(requirement.package, &entry));
+            let mut selected = selected.clone();
+            selected.insert(requirement.package, entry);
+            match self.select(pending, selected) {
+                Ok(selected) => return Ok(selected),
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        Err(first_error.unwrap())
+    }
+
+    /// Returns the versions which match `requirement`, from newest to oldest. Fails if there are
+    /// none.
+    fn candidates(&mut self, requirement: &Requirement) -> Result<Vec<IndexEntry>> {
+        let package = requirement.package;
+        if !self.indices.contains_key(&package) {
+            match self.registry.index(package)? {
+                Some(index) => self.indices.insert(package, index),
+                None => bail!(
+                    "Unable to find package '{}' required by {} in the registry",
+                    package,
+                    requirement.required_by
+                ),
+            };
+        }
+        let index = &self.indices[&package];
+        let mut candidates = index
+            .versions
+            .iter()
+            .filter(|entry| requirement.version_req.matches(&entry.version))
+            .cloned()
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            bail!(
+                "No version of package '{}' matches requirement '{}' of {}. Published versions: [{}]",
+                package,
+                requirement.version_req,
+                requirement.required_by,
+                index
+                    .versions
+                    .iter()
+                    .map(|entry| entry.version.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+        candidates.sort_by(|e1, e2| e2.version.cmp(&e1.version));
+        Ok(candidates)
+    }
+
+    fn requirements_of(&self, package: PackageName, entry: &IndexEntry) -> Vec<Requirement> {
+        let dev_dependencies = if self.dev_mode {
+            entry.dev_dependencies.clone()
+        } else {
+            BTreeMap::new()
+        };
+        entry
+            .dependencies
+            .clone()
+            .into_iter()
+            .chain(dev_dependencies.into_iter())
+            .map(|(dep_name, version_req)| Requirement {
+                package: dep_name,
+                version_req,
+                required_by: format!("version {} of package '{}'", entry.version, package),
+            })
+            .collect()
+    }
+}
@@ -3,3 +3,4 @@
 
 mod digest;
 pub mod resolution_graph;
+pub mod vendor;
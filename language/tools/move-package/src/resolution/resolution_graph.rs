@@ -2,13 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    resolution::digest::compute_digest,
+    registry::{
+        fetch_package,
+        filesystem::FileSystemRegistry,
+        resolver::{select_versions, Requirement},
+        IndexEntry,
+    },
+    resolution::{digest::compute_digest, vendor::VendorInfo},
     source_package::{
         layout::SourcePackageLayout,
         manifest_parser::{parse_move_manifest_string, parse_source_manifest},
         parsed_manifest::{
             Dependency, FileName, NamedAddress, PackageDigest, PackageName, SourceManifest,
-            SubstOrRename,
+            SubstOrRename, WORKSPACE_PACKAGE_NAME,
         },
     },
     BuildConfig,
@@ -38,7 +44,7 @@ pub type GraphIndex = PackageName;
 type ResolutionTable<T> = BTreeMap<NamedAddress, T>;
 type ResolvingTable = ResolutionTable<ResolvingNamedAddress>;
 type ResolvingGraph = ResolutionGraph<ResolvingNamedAddress>;
-type ResolvingPackage = ResolutionPackage<ResolvingNamedAddress>;
+pub(crate) type ResolvingPackage = ResolutionPackage<ResolvingNamedAddress>;
 
 #[derive(Debug, Clone)]
 pub struct ResolvingNamedAddress {
@@ -57,6 +63,10 @@ pub struct ResolvingNamedAddress {
 ///    through unification across the package graph.
 ///
 /// Named addresses can also be renamed in a package and will be re-exported under thes new names in this case.
+///
+/// The root of the graph can also be the root package of a workspace, which depends on all members
+/// of the workspace. Addresses are then unified across all members, and each member's
+/// dev-addresses are used in dev mode as if it was the root package.
 #[derive(Debug, Clone)]
 pub struct ResolutionGraph<T> {
     pub root_package_path: PathBuf,
@@ -68,6 +78,10 @@ pub struct ResolutionGraph<T> {
     pub graph: DiGraphMap<PackageName, ()>,
     /// A mapping of package name to its resolution
     pub package_table: BTreeMap<PackageName, ResolutionPackage<T>>,
+    /// The versions selected for the registry packages in the graph
+    pub registry_packages: BTreeMap<PackageName, IndexEntry>,
+    /// The dependencies vendored by the root package, which are used instead of the declared ones
+    pub vendor_info: Option<VendorInfo>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -86,11 +100,48 @@ pub struct ResolutionPackage<T> {
     pub source_digest: PackageDigest,
 }
 
+impl<T> ResolutionGraph<T> {
+    /// Returns whether the root package is the root package of a workspace.
+    pub fn is_workspace(&self) -> bool {
+        self.root_package.package.name.as_str() == WORKSPACE_PACKAGE_NAME
+    }
+
+    /// Returns the members of the workspace, or only the root package if it is not the root of a
+    /// workspace. These are the packages which are built.
+    pub fn workspace_members(&self) -> BTreeSet<PackageName> {
+        if self.is_workspace() {
+            self.root_package.dependencies.keys().copied().collect()
+        } else {
+            BTreeSet::from([self.root_package.package.name])
+        }
+    }
+}
+
 impl ResolvingGraph {
     pub fn new(
+        root_package: SourceManifest,
+        root_package_path: PathBuf,
+        build_options: BuildConfig,
+    ) -> Result<ResolvingGraph> {
+        let vendor_info = VendorInfo::load(&root_package_path)?;
+        Self::create(root_package, root_package_path, build_options, vendor_info)
+    }
+
+    /// Creates the resolution graph of the root package from the dependencies as declared, even
+    /// if the root package has vendored its dependencies.
+    pub fn new_ignoring_vendor(
+        root_package: SourceManifest,
+        root_package_path: PathBuf,
+        build_options: BuildConfig,
+    ) -> Result<ResolvingGraph> {
+        Self::create(root_package, root_package_path, build_options, None)
+    }
+
+    fn create(
         root_package: SourceManifest,
         root_package_path: PathBuf,
         mut build_options: BuildConfig,
+        vendor_info: Option<VendorInfo>,
     ) -> Result<ResolvingGraph> {
         if build_options.architecture.is_none() {
             if let Some(info) = &root_package.build {
@@ -103,8 +154,18 @@ impl ResolvingGraph {
             root_package: root_package.clone(),
             graph: DiGraphMap::new(),
             package_table: BTreeMap::new(),
+            registry_packages: BTreeMap::new(),
+            vendor_info,
         };
 
+        resolution_graph
+            .select_registry_versions(&root_package, &root_package_path)
+            .with_context(|| {
+                format!(
+                    "Unable to select versions of registry packages for package '{}'",
+                    root_package.package.name
+                )
+            })?;
         resolution_graph
             .build_resolution_graph(root_package.clone(), root_package_path, true)
             .with_context(|| {
@@ -123,6 +184,8 @@ impl ResolvingGraph {
             root_package,
             graph,
             package_table,
+            registry_packages,
+            vendor_info,
         } = self;
 
         let mut unresolved_addresses = Vec::new();
@@ -179,6 +242,8 @@ impl ResolvingGraph {
             root_package,
             graph,
             package_table: resolved_package_table,
+            registry_packages,
+            vendor_info,
         })
     }
 
@@ -217,19 +282,7 @@ impl ResolvingGraph {
             })
             .collect();
 
-        // include dev dependencies if in dev mode
-        let additional_deps = if self.build_options.dev_mode {
-            package.dev_dependencies.clone()
-        } else {
-            BTreeMap::new()
-        };
-
-        for (dep_name, dep) in package
-            .dependencies
-            .clone()
-            .into_iter()
-            .chain(additional_deps.into_iter())
-        {
+        for (dep_name, dep) in self.package_dependencies(&package) {
             let dep_node_id = self.get_or_add_node(dep_name).with_context(|| {
                 format!(
                     "Cycle between packages {} and {} found",
@@ -269,6 +322,8 @@ impl ResolvingGraph {
             })?;
         }
 
+        // Workspace members are resolved like root packages
+        let is_root_package = is_root_package || self.workspace_members().contains(&package_name);
         self.unify_addresses_in_package(&package, &mut resolution_table, is_root_package)?;
 
         let source_digest =
@@ -287,6 +342,112 @@ impl ResolvingGraph {
         Ok(())
     }
 
+    // Selects the versions of all registry packages in the graph. The requirements on registry
+    // packages are collected from all packages which are not registry packages, and the
+    // requirements of registry packages are then taken from the registry index.
+    fn select_registry_versions(
+        &mut self,
+        root_package: &SourceManifest,
+        root_package_path: &Path,
+    ) -> Result<()> {
+        let mut requirements = Vec::new();
+        self.collect_registry_requirements(
+            root_package,
+            root_package_path.to_path_buf(),
+            &mut requirements,
+            &mut BTreeSet::new(),
+        );
+        if !requirements.is_empty() {
+            self.registry_packages =
+                select_versions(&self.registry()?, requirements, self.build_options.dev_mode)?;
+        }
+        Ok(())
+    }
+
+    fn collect_registry_requirements(
+        &self,
+        package: &SourceManifest,
+        package_path: PathBuf,
+        requirements: &mut Vec<Requirement>,
+        visited: &mut BTreeSet<PackageName>,
+    ) {
+        if !visited.insert(package.package.name) {
+            return;
+        }
+        for (dep_name, dep) in self.package_dependencies(package) {
+            // Errors are reported when the dependency is resolved
+            let dep = match self.vendored_dependency(dep_name, dep) {
+                Ok(dep) => dep,
+                Err(_) => continue,
+            };
+            match dep.registry_info {
+                Some(info) => requirements.push(Requirement {
+                    package: dep_name,
+                    version_req: info.version_req,
+                    required_by: format!("package '{}'", package.package.name),
+                }),
+                None => {
+                    if self.download_and_update_if_repo(dep_name, &dep).is_err() {
+                        continue;
+                    }
+                    if let Ok((dep_package, dep_package_dir)) =
+                        Self::parse_package_manifest(&dep, &dep_name, package_path.clone())
+                    {
+                        self.collect_registry_requirements(
+                            &dep_package,
+                            dep_package_dir,
+                            requirements,
+                            visited,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    // Returns the dependencies of `package`, including its dev dependencies in dev mode
+    fn package_dependencies(&self, package: &SourceManifest) -> Vec<(PackageName, Dependency)> {
+        let additional_deps = if self.build_options.dev_mode {
+            package.dev_dependencies.clone()
+        } else {
+            BTreeMap::new()
+        };
+        package
+            .dependencies
+            .clone()
+            .into_iter()
+            .chain(additional_deps.into_iter())
+            .collect()
+    }
+
+    // Replaces `dep` by the vendored package of the same name, if there is one
+    fn vendored_dependency(&self, dep_name: PackageName, dep: Dependency) -> Result<Dependency> {
+        let vendored_path = match &self.vendor_info {
+            Some(vendor_info) => vendor_info.vendored_package(&self.root_package_path, dep_name)?,
+            None => None,
+        };
+        Ok(match vendored_path {
+            Some(vendored_path) => Dependency {
+                // Dependencies are located relative to the depending package
+                local: vendored_path.canonicalize()?,
+                git_info: None,
+                registry_info: None,
+                ..dep
+            },
+            None => dep,
+        })
+    }
+
+    fn registry(&self) -> Result<FileSystemRegistry> {
+        match &self.build_options.registry {
+            Some(registry_path) => Ok(FileSystemRegistry::new(registry_path.clone())),
+            None => bail!(
+                "Found dependencies on registry packages, but no package registry was given. \
+                 Use `--registry` to specify one"
+            ),
+        }
+    }
+
     fn unify_addresses_in_package(
         &mut self,
         package: &SourceManifest,
@@ -386,7 +547,22 @@ impl ResolvingGraph {
         dep: Dependency,
         root_path: PathBuf,
     ) -> Result<(Renaming, ResolvingTable)> {
-        Self::download_and_update_if_repo(dep_name_in_pkg, &dep)?;
+        let dep = self.vendored_dependency(dep_name_in_pkg, dep)?;
+        // Registry packages are located once they have been fetched
+        let dep = match dep.registry_info {
+            Some(_) => {
+                let entry = self
+                    .registry_packages
+                    .get(&dep_name_in_pkg)
+                    .context("Unable to find selected version of registry package")?;
+                Dependency {
+                    local: fetch_package(&self.registry()?, dep_name_in_pkg, entry)?,
+                    ..dep
+                }
+            }
+            None => dep,
+        };
+        self.download_and_update_if_repo(dep_name_in_pkg, &dep)?;
         let (dep_package, dep_package_dir) =
             Self::parse_package_manifest(&dep, &dep_name_in_pkg, root_path)
                 .with_context(|| format!("While processing dependency '{}'", dep_name_in_pkg))?;
@@ -509,9 +685,18 @@ impl ResolvingGraph {
         }
     }
 
-    fn download_and_update_if_repo(dep_name: PackageName, dep: &Dependency) -> Result<()> {
+    fn download_and_update_if_repo(&self, dep_name: PackageName, dep: &Dependency) -> Result<()> {
         if let Some(git_info) = &dep.git_info {
             if !git_info.download_to.exists() {
+                if self.build_options.offline {
+                    bail!(
+                        "Package '{}' has not been downloaded from '{}', and downloads are \
+                         disabled in offline mode. Vendor the dependencies of the package with \
+                         `move package vendor` to build it offline",
+                        dep_name,
+                        git_info.git_url
+                    )
+                }
                 Command::new("git")
                     .args([
                         "clone",
@@ -627,7 +812,7 @@ impl ResolvingPackage {
         Ok(places_to_look)
     }
 
-    fn get_package_digest_for_config(
+    pub(crate) fn get_package_digest_for_config(
         package_path: &Path,
         config: &BuildConfig,
     ) -> Result<PackageDigest> {
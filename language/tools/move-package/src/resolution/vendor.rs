@@ -0,0 +1,131 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Vendored dependencies. Vendoring copies all dependencies of a package into its `vendor`
+//! directory, after which they are resolved from there instead of from their original location.
+//! This allows to build packages with git or registry dependencies without network access.
+//!
+//! The `vendor` directory is laid out as
+//! vendor
+//! ├── VendorInfo.yaml  (the digests of the vendored packages)
+//! └── <name>           (a copy of the package <name>)
+//!
+//! Vendored packages are identified by name: every dependency with the name of a vendored package
+//! resolves to it, wherever it is declared in the package graph. Vendoring has to be repeated
+//! whenever the dependencies of the package change.
+
+use crate::{
+    resolution::resolution_graph::{ResolvedGraph, ResolvingPackage},
+    source_package::{
+        layout::{package_files, SourcePackageLayout},
+        parsed_manifest::{PackageDigest, PackageName},
+    },
+    BuildConfig,
+};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const VENDOR_INFO_FILENAME: &str = "VendorInfo.yaml";
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VendorInfo {
+    /// The digests of the vendored packages, including the sources of all modes.
+    pub packages: BTreeMap<PackageName, PackageDigest>,
+}
+
+impl VendorInfo {
+    /// Returns the vendor information of the package at `package_path`, if it has vendored its
+    /// dependencies.
+    pub fn load(package_path: &Path) -> Result<Option<Self>> {
+        let info_path = package_path
+            .join(SourcePackageLayout::Vendor.path())
+            .join(VENDOR_INFO_FILENAME);
+        if !info_path.is_file() {
+            return Ok(None);
+        }
+        let info = serde_yaml::from_str(&fs::read_to_string(&info_path)?)
+            .with_context(|| format!("Malformed vendor information at {:?}", info_path))?;
+        Ok(Some(info))
+    }
+
+    /// Returns the location of the vendored package `package` of the package at `package_path`,
+    /// after checking that its sources have not changed since it was vendored. Returns `None` if
+    /// `package` is not vendored.
+    pub fn vendored_package(
+        &self,
+        package_path: &Path,
+        package: PackageName,
+    ) -> Result<Option<PathBuf>> {
+        let expected_digest = match self.packages.get(&package) {
+            None => return Ok(None),
+            Some(digest) => *digest,
+        };
+        let vendored_path = package_path
+            .join(SourcePackageLayout::Vendor.path())
+            .join(package.as_str());
+        let digest = vendored_digest(&vendored_path)?;
+        if digest != expected_digest {
+            bail!(
+                "Source digest mismatch in vendored dependency '{}'. Expected '{}' but got '{}'. \
+                 Vendored dependencies must not be modified",
+                package,
+                expected_digest,
+                digest
+            )
+        }
+        Ok(Some(vendored_path))
+    }
+}
+
+/// Copies all packages of `resolved_graph` besides the root package and the members of a workspace
+/// into the `vendor` directory of the root package, replacing any previously vendored packages.
+pub fn vendor_dependencies(resolved_graph: &ResolvedGraph) -> Result<VendorInfo> {
+    let root_path = &resolved_graph.root_package_path;
+    let root_name = resolved_graph.root_package.package.name;
+    let members = resolved_graph.workspace_members();
+
+    // Copy everything into a temporary directory first, so that a failure leaves previously
+    // vendored packages intact
+    let staging_dir = tempfile::tempdir_in(root_path)?;
+    let mut packages = BTreeMap::new();
+    for (name, package) in &resolved_graph.package_table {
+        if *name == root_name || members.contains(name) {
+            continue;
+        }
+        let vendored_path = staging_dir.path().join(name.as_str());
+        for file in package_files(&package.package_path)? {
+            let dest = vendored_path.join(&file);
+            fs::create_dir_all(dest.parent().unwrap())?;
+            fs::copy(package.package_path.join(&file), &dest)?;
+        }
+        packages.insert(*name, vendored_digest(&vendored_path)?);
+    }
+    let vendor_info = VendorInfo { packages };
+    fs::write(
+        staging_dir.path().join(VENDOR_INFO_FILENAME),
+        serde_yaml::to_string(&vendor_info)?,
+    )?;
+
+    let vendor_dir = root_path.join(SourcePackageLayout::Vendor.path());
+    if vendor_dir.exists() {
+        fs::remove_dir_all(&vendor_dir)?;
+    }
+    // The temporary directory is gone after the rename, so dropping it has no effect
+    fs::rename(staging_dir.path(), &vendor_dir)?;
+    Ok(vendor_info)
+}
+
+fn vendored_digest(package_path: &Path) -> Result<PackageDigest> {
+    ResolvingPackage::get_package_digest_for_config(
+        package_path,
+        &BuildConfig {
+            dev_mode: true,
+            ..Default::default()
+        },
+    )
+}
@@ -17,6 +17,7 @@ pub enum SourcePackageLayout {
     Examples,
     Manifest,
     DocTemplates,
+    Vendor,
 }
 
 impl SourcePackageLayout {
@@ -28,7 +29,8 @@ impl SourcePackageLayout {
     /// ├── scripts        (optional)
     /// ├── specifications (optional)
     /// ├── doc_templates      (optional)
-    /// └── tests          (optional, test mode)
+    /// ├── tests          (optional, test mode)
+    /// └── vendor         (optional, vendored dependencies)
     pub fn path(&self) -> &Path {
         Path::new(self.location_str())
     }
@@ -57,6 +59,7 @@ impl SourcePackageLayout {
             Self::Examples => "examples",
             Self::Specifications => "specifications",
             Self::DocTemplates => "doc_templates",
+            Self::Vendor => "vendor",
         }
     }
 
@@ -67,7 +70,43 @@ impl SourcePackageLayout {
             | Self::Scripts
             | Self::Examples
             | Self::Specifications
-            | Self::DocTemplates => true,
+            | Self::DocTemplates
+            | Self::Vendor => true,
         }
     }
 }
+
+/// Returns the paths of the manifest and of all files in the source directories of the package at
+/// `package_path`, relative to `package_path` and in a stable order. These are the files which
+/// make up the package when it is published or vendored.
+pub fn package_files(package_path: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for layout in [
+        SourcePackageLayout::Manifest,
+        SourcePackageLayout::Sources,
+        SourcePackageLayout::Scripts,
+        SourcePackageLayout::Examples,
+        SourcePackageLayout::Tests,
+        SourcePackageLayout::Specifications,
+        SourcePackageLayout::DocTemplates,
+    ] {
+        let path = package_path.join(layout.path());
+        if !path.exists() {
+            if layout.is_optional() {
+                continue;
+            }
+            bail!(
+                "Unable to find '{}' in package at '{}'",
+                layout.location_str(),
+                package_path.to_string_lossy()
+            )
+        }
+        for entry in walkdir::WalkDir::new(&path).sort_by_file_name() {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                files.push(entry.path().strip_prefix(package_path)?.to_path_buf());
+            }
+        }
+    }
+    Ok(files)
+}
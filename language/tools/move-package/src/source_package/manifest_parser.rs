@@ -5,6 +5,7 @@ use crate::{source_package::parsed_manifest as PM, Architecture};
 use anyhow::{bail, format_err, Context, Result};
 use move_core_types::account_address::{AccountAddress, AccountAddressParseError};
 use move_symbol_pool::symbol::Symbol;
+use semver::VersionReq;
 use std::{
     collections::{BTreeMap, BTreeSet},
     path::{Path, PathBuf},
@@ -21,6 +22,7 @@ const ADDRESSES_NAME: &str = "addresses";
 const DEV_ADDRESSES_NAME: &str = "dev-addresses";
 const DEPENDENCY_NAME: &str = "dependencies";
 const DEV_DEPENDENCY_NAME: &str = "dev-dependencies";
+const WORKSPACE_NAME: &str = "workspace";
 
 const KNOWN_NAMES: &[&str] = &[
     PACKAGE_NAME,
@@ -33,22 +35,54 @@ const KNOWN_NAMES: &[&str] = &[
 
 const REQUIRED_FIELDS: &[&str] = &[PACKAGE_NAME];
 
+const WORKSPACE_KNOWN_NAMES: &[&str] = &[WORKSPACE_NAME, ADDRESSES_NAME, DEV_ADDRESSES_NAME];
+
 pub fn parse_move_manifest_from_file(path: &Path) -> Result<PM::SourceManifest> {
+    parse_source_manifest(read_move_manifest(path)?)
+}
+
+/// Returns whether the manifest at `path`, or of the package at `path`, is a workspace manifest.
+pub fn is_workspace(path: &Path) -> Result<bool> {
+    Ok(is_workspace_manifest(&read_move_manifest(path)?))
+}
+
+fn read_move_manifest(path: &Path) -> Result<TV> {
     let file_contents = if path.is_file() {
         std::fs::read_to_string(path)?
     } else {
         std::fs::read_to_string(path.join(SourcePackageLayout::Manifest.path()))?
     };
-    parse_source_manifest(parse_move_manifest_string(file_contents)?)
+    parse_move_manifest_string(file_contents)
 }
 
 pub fn parse_move_manifest_string(manifest_string: String) -> Result<TV> {
     toml::from_str::<TV>(&manifest_string).context("Unable to parse Move package manifest")
 }
 
+/// Parses the manifest of the package at `root_path` which is being built. This is either a
+/// package manifest, or a workspace manifest which is turned into the manifest of the workspace
+/// root package.
+pub fn parse_root_manifest(tval: TV, root_path: &Path) -> Result<PM::SourceManifest> {
+    if is_workspace_manifest(&tval) {
+        workspace_root_manifest(parse_workspace_manifest(tval)?, root_path)
+    } else {
+        parse_source_manifest(tval)
+    }
+}
+
+pub fn is_workspace_manifest(tval: &TV) -> bool {
+    matches!(tval, TV::Table(table) if table.contains_key(WORKSPACE_NAME))
+}
+
 pub fn parse_source_manifest(tval: TV) -> Result<PM::SourceManifest> {
     match tval {
         TV::Table(mut table) => {
+            if table.contains_key(WORKSPACE_NAME) {
+                bail!(
+                    "Error parsing package manifest: found a workspace manifest. Workspaces \
+                     cannot be used as packages"
+                )
+            }
             check_for_required_field_names(&table, REQUIRED_FIELDS)
                 .context("Error parsing package manifest")?;
             warn_if_unknown_field_names(&table, KNOWN_NAMES);
@@ -104,6 +138,131 @@ pub fn parse_source_manifest(tval: TV) -> Result<PM::SourceManifest> {
     }
 }
 
+pub fn parse_workspace_manifest(tval: TV) -> Result<PM::WorkspaceManifest> {
+    match tval {
+        TV::Table(mut table) => {
+            if table.contains_key(PACKAGE_NAME) {
+                bail!(
+                    "Error parsing workspace manifest: a manifest cannot declare both a package \
+                     and a workspace"
+                )
+            }
+            check_for_required_field_names(&table, &[WORKSPACE_NAME])
+                .context("Error parsing workspace manifest")?;
+            warn_if_unknown_field_names(&table, WORKSPACE_KNOWN_NAMES);
+            let addresses = table
+                .remove(ADDRESSES_NAME)
+                .map(parse_addresses)
+                .transpose()
+                .context("Error parsing '[addresses]' section of manifest")?;
+            let dev_address_assignments = table
+                .remove(DEV_ADDRESSES_NAME)
+                .map(parse_dev_addresses)
+                .transpose()
+                .context("Error parsing '[dev-addresses]' section of manifest")?;
+            let members = table
+                .remove(WORKSPACE_NAME)
+                .map(parse_workspace_members)
+                .transpose()
+                .context("Error parsing '[workspace]' section of manifest")?
+                .unwrap();
+            Ok(PM::WorkspaceManifest {
+                members,
+                addresses,
+                dev_address_assignments,
+            })
+        }
+        x => {
+            bail!(
+                "Malformed workspace manifest {}. Expected a table at top level, but encountered a {}",
+                x,
+                x.type_str()
+            )
+        }
+    }
+}
+
+fn parse_workspace_members(tval: TV) -> Result<Vec<PathBuf>> {
+    match tval {
+        TV::Table(mut table) => {
+            check_for_required_field_names(&table, &["members"])?;
+            warn_if_unknown_field_names(&table, &["members"]);
+            let members = table
+                .remove("members")
+                .unwrap()
+                .as_array()
+                .ok_or_else(|| format_err!("Invalid members list"))?
+                .iter()
+                .map(|tval| {
+                    tval.as_str().map(PathBuf::from).ok_or_else(|| {
+                        format_err!(
+                            "Invalid member '{}' of type {} found. Expected a path string.",
+                            tval.to_string(),
+                            tval.type_str()
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if members.is_empty() {
+                bail!("A workspace must have at least one member")
+            }
+            Ok(members)
+        }
+        x => bail!(
+            "Malformed section in manifest {}. Expected a table, but encountered a {}",
+            x,
+            x.type_str()
+        ),
+    }
+}
+
+/// Returns the manifest of the root package of the workspace at `workspace_path`. The root
+/// package depends on all members of the workspace, so that the members are resolved in a single
+/// package graph, and it carries the address assignments of the workspace.
+pub fn workspace_root_manifest(
+    workspace: PM::WorkspaceManifest,
+    workspace_path: &Path,
+) -> Result<PM::SourceManifest> {
+    let mut dependencies = BTreeMap::new();
+    for member_path in workspace.members {
+        let member = parse_move_manifest_from_file(&workspace_path.join(&member_path))
+            .with_context(|| {
+                format!(
+                    "Unable to parse the manifest of workspace member '{}'",
+                    member_path.to_string_lossy()
+                )
+            })?;
+        let member_name = member.package.name;
+        let dep = PM::Dependency {
+            local: member_path,
+            subst: None,
+            version: None,
+            digest: None,
+            git_info: None,
+            registry_info: None,
+        };
+        if dependencies.insert(member_name, dep).is_some() {
+            bail!(
+                "Found multiple workspace members with package name '{}'",
+                member_name
+            )
+        }
+    }
+    Ok(PM::SourceManifest {
+        package: PM::PackageInfo {
+            name: PM::PackageName::from(PM::WORKSPACE_PACKAGE_NAME),
+            version: (0, 0, 0),
+            authors: vec![],
+            license: None,
+        },
+        addresses: workspace.addresses,
+        dev_address_assignments: workspace.dev_address_assignments,
+        build: None,
+        dependencies,
+        dev_dependencies: BTreeMap::new(),
+    })
+}
+
 pub fn parse_package_info(tval: TV) -> Result<PM::PackageInfo> {
     match tval {
         TV::Table(mut table) => {
@@ -291,6 +450,15 @@ fn parse_address_literal(address_str: &str) -> Result<AccountAddress, AccountAdd
 
 fn parse_dependency(tval: TV) -> Result<PM::Dependency> {
     match tval {
+        // A plain version requirement, e.g. `MoveStdlib = "1.2"`, is a registry dependency
+        TV::String(version_req) => Ok(PM::Dependency {
+            local: PathBuf::new(),
+            subst: None,
+            version: None,
+            digest: None,
+            git_info: None,
+            registry_info: Some(parse_registry_info(&version_req)?),
+        }),
         TV::Table(mut table) => {
             warn_if_unknown_field_names(
                 &table,
@@ -308,7 +476,7 @@ fn parse_dependency(tval: TV) -> Result<PM::Dependency> {
                 .remove("addr_subst")
                 .map(parse_substitution)
                 .transpose()?;
-            let version = table.remove("version").map(parse_version).transpose()?;
+            let version = table.remove("version");
             let digest = table.remove("digest").map(parse_digest).transpose()?;
             let mut git_info = None;
             match (table.remove("local"), table.remove("git")) {
@@ -319,20 +487,14 @@ fn parse_dependency(tval: TV) -> Result<PM::Dependency> {
                     let local_path = PathBuf::from(local_str);
                     Ok(PM::Dependency {
                         subst,
-                        version,
+                        version: version.map(parse_version).transpose()?,
                         digest,
                         local: local_path,
                         git_info,
+                        registry_info: None,
                     })
                 }
                 (None, Some(git)) => {
-                    // Look to see if a MOVE_HOME has been set. Otherwise default to $HOME
-                    let move_home = std::env::var("MOVE_HOME").unwrap_or_else(|_| {
-                        format!(
-                            "{}/.move",
-                            std::env::var("HOME").expect("env var 'HOME' must be set")
-                        )
-                    });
                     let rev_name = match table.remove("rev") {
                         None => bail!("Git revision not supplied for dependency"),
                         Some(r) => Symbol::from(
@@ -341,7 +503,7 @@ fn parse_dependency(tval: TV) -> Result<PM::Dependency> {
                         ),
                     };
                     // Downloaded packages are of the form <sanitized_git_url>_<rev_name>
-                    let local_path = PathBuf::from(move_home).join(format!(
+                    let local_path = move_home().join(format!(
                         "{}_{}",
                         regex::Regex::new(r"/|:|\.|@").unwrap().replace_all(
                             git.as_str()
@@ -369,24 +531,61 @@ fn parse_dependency(tval: TV) -> Result<PM::Dependency> {
 
                     Ok(PM::Dependency {
                         subst,
-                        version,
+                        version: version.map(parse_version).transpose()?,
                         digest,
                         local: local_path.join(subdir),
                         git_info,
+                        registry_info: None,
                     })
                 }
                 (Some(_), Some(_)) => {
                     bail!("both 'local' and 'git' paths specified for dependency.")
                 }
-                (None, None) => {
-                    bail!("both 'local' and 'git' paths not specified for dependency.")
-                }
+                // Without a path, a dependency with a version requirement is fetched from the
+                // registry
+                (None, None) => match version {
+                    Some(version_req) => Ok(PM::Dependency {
+                        local: PathBuf::new(),
+                        subst,
+                        version: None,
+                        digest,
+                        git_info,
+                        registry_info: Some(parse_registry_info(
+                            version_req
+                                .as_str()
+                                .ok_or_else(|| format_err!("Version requirement not a string"))?,
+                        )?),
+                    }),
+                    None => bail!("both 'local' and 'git' paths not specified for dependency."),
+                },
             }
         }
         x => bail!("Malformed dependency {}", x),
     }
 }
 
+fn parse_registry_info(version_req: &str) -> Result<PM::RegistryInfo> {
+    Ok(PM::RegistryInfo {
+        version_req: VersionReq::parse(version_req).with_context(|| {
+            format!(
+                "Version requirement of registry dependency is malformed: '{}'",
+                version_req
+            )
+        })?,
+    })
+}
+
+/// Returns the directory where downloaded packages are stored. This is `MOVE_HOME` if it has
+/// been set, and `$HOME/.move` otherwise.
+pub fn move_home() -> PathBuf {
+    PathBuf::from(std::env::var("MOVE_HOME").unwrap_or_else(|_| {
+        format!(
+            "{}/.move",
+            std::env::var("HOME").expect("env var 'HOME' must be set")
+        )
+    }))
+}
+
 fn parse_substitution(tval: TV) -> Result<PM::Substitution> {
     match tval {
         TV::Table(table) => {
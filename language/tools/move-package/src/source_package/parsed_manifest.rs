@@ -4,6 +4,7 @@
 use crate::Architecture;
 use move_core_types::account_address::AccountAddress;
 use move_symbol_pool::symbol::Symbol;
+use semver::VersionReq;
 use std::{collections::BTreeMap, path::PathBuf};
 
 pub type NamedAddress = Symbol;
@@ -17,6 +18,10 @@ pub type Version = (u64, u64, u64);
 pub type Dependencies = BTreeMap<PackageName, Dependency>;
 pub type Substitution = BTreeMap<NamedAddress, SubstOrRename>;
 
+/// The name of the package at the root of a workspace. The workspace root package has no sources
+/// of its own, and depends on all members of the workspace.
+pub const WORKSPACE_PACKAGE_NAME: &str = "<workspace>";
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SourceManifest {
     pub package: PackageInfo,
@@ -27,6 +32,15 @@ pub struct SourceManifest {
     pub dev_dependencies: Dependencies,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WorkspaceManifest {
+    /// The paths of the member packages, relative to the workspace root
+    pub members: Vec<PathBuf>,
+    /// Address assignments which are shared by all members of the workspace
+    pub addresses: Option<AddressDeclarations>,
+    pub dev_address_assignments: Option<DevAddressDeclarations>,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct PackageInfo {
     pub name: PackageName,
@@ -42,6 +56,7 @@ pub struct Dependency {
     pub version: Option<Version>,
     pub digest: Option<PackageDigest>,
     pub git_info: Option<GitInfo>,
+    pub registry_info: Option<RegistryInfo>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -57,6 +72,13 @@ pub struct GitInfo {
     pub download_to: PathBuf,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RegistryInfo {
+    /// The versions of the package which satisfy this dependency, e.g. `^1.2`. The `local` path
+    /// of a registry dependency is only known once a version has been selected during resolution.
+    pub version_req: VersionReq,
+}
+
 #[derive(Default, Debug, Clone, Eq, PartialEq)]
 pub struct BuildInfo {
     pub language_version: Option<Version>,
@@ -4,26 +4,38 @@
 use move_command_line_common::testing::{format_diff, read_env_update_baseline, EXP_EXT};
 use move_package::{
     compilation::{build_plan::BuildPlan, model_builder::ModelBuilder},
+    registry::{filesystem::FileSystemRegistry, publish_package},
     resolution::resolution_graph as RG,
     source_package::{manifest_parser as MP, parsed_manifest::PackageDigest},
     BuildConfig, ModelConfig,
 };
+use once_cell::sync::Lazy;
 use std::{
     ffi::OsStr,
     fs,
     path::{Component, Path, PathBuf},
 };
-use tempfile::tempdir;
+use tempfile::{tempdir, TempDir};
 
 const COMPILE_EXT: &str = "compile";
 const MODEL_EXT: &str = "model";
+const REGISTRY_DIR: &str = "deps_only/registry";
+
+// Registry packages are unpacked under `$MOVE_HOME/registry`, so point `MOVE_HOME` at a
+// temporary directory that is shared by all tests
+static MOVE_HOME: Lazy<TempDir> = Lazy::new(|| {
+    let move_home = tempdir().unwrap();
+    std::env::set_var("MOVE_HOME", move_home.path());
+    move_home
+});
 
 pub fn run_test(path: &Path) -> datatest_stable::Result<()> {
+    Lazy::force(&MOVE_HOME);
     let update_baseline = read_env_update_baseline();
-    if path
-        .components()
-        .any(|component| component == Component::Normal(OsStr::new("deps_only")))
-    {
+    if path.components().any(|component| {
+        component == Component::Normal(OsStr::new("deps_only"))
+            || component == Component::Normal(OsStr::new("vendor"))
+    }) {
         return Ok(());
     }
     let exp_path = path.with_extension(EXP_EXT);
@@ -32,9 +44,26 @@ pub fn run_test(path: &Path) -> datatest_stable::Result<()> {
 
     let exp_exists = exp_path.is_file();
 
+    // Packages under `deps_only/registry` are published to a fresh registry for the test
+    let registry_dir = tempdir()?;
+    let registry_packages = path.parent().unwrap().join(REGISTRY_DIR);
+    let registry = if registry_packages.is_dir() {
+        let registry = FileSystemRegistry::new(registry_dir.path().to_path_buf());
+        let mut package_paths = fs::read_dir(&registry_packages)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        package_paths.sort();
+        for package_path in package_paths {
+            publish_package(&package_path, &registry)?;
+        }
+        Some(registry_dir.path().to_path_buf())
+    } else {
+        None
+    };
+
     let contents = fs::read_to_string(path)?;
     let output = match MP::parse_move_manifest_string(contents)
-        .and_then(MP::parse_source_manifest)
+        .and_then(|toml_manifest| MP::parse_root_manifest(toml_manifest, path.parent().unwrap()))
         .and_then(|parsed_manifest| {
             RG::ResolutionGraph::new(
                 parsed_manifest,
@@ -46,6 +75,8 @@ pub fn run_test(path: &Path) -> datatest_stable::Result<()> {
                     generate_abis: false,
                     install_dir: Some(tempdir().unwrap().path().to_path_buf()),
                     force_recompilation: false,
+                    registry,
+                    offline: true,
                     ..Default::default()
                 },
             )
@@ -60,15 +91,22 @@ pub fn run_test(path: &Path) -> datatest_stable::Result<()> {
                 .into())
             }
             (true, _) => match BuildPlan::create(resolved_package)
-                .and_then(|bp| bp.compile(&mut Vec::new()))
+                .and_then(|bp| bp.compile_workspace(&mut Vec::new()))
             {
-                Ok(mut pkg) => {
-                    pkg.compiled_package_info.source_digest =
-                        Some(PackageDigest::from("ELIDED_FOR_TEST"));
-                    pkg.compiled_package_info.build_flags.install_dir =
-                        Some(PathBuf::from("ELIDED_FOR_TEST"));
-                    format!("{:#?}\n", pkg.compiled_package_info)
-                }
+                Ok(pkgs) => pkgs
+                    .into_iter()
+                    .map(|mut pkg| {
+                        pkg.compiled_package_info.source_digest =
+                            Some(PackageDigest::from("ELIDED_FOR_TEST"));
+                        pkg.compiled_package_info.build_flags.install_dir =
+                            Some(PathBuf::from("ELIDED_FOR_TEST"));
+                        if pkg.compiled_package_info.build_flags.registry.is_some() {
+                            pkg.compiled_package_info.build_flags.registry =
+                                Some(PathBuf::from("ELIDED_FOR_TEST"));
+                        }
+                        format!("{:#?}\n", pkg.compiled_package_info)
+                    })
+                    .collect(),
                 Err(error) => format!("{:#}\n", error),
             },
             (_, true) => match ModelBuilder::create(
@@ -88,7 +126,15 @@ pub fn run_test(path: &Path) -> datatest_stable::Result<()> {
                     package.package_path = PathBuf::from("ELIDED_FOR_TEST");
                     package.source_digest = PackageDigest::from("ELIDED_FOR_TEST");
                 }
+                for (_, entry) in resolved_package.registry_packages.iter_mut() {
+                    entry.digest = PackageDigest::from("ELIDED_FOR_TEST");
+                    entry.checksum = "ELIDED_FOR_TEST".to_string();
+                }
                 resolved_package.build_options.install_dir = Some(PathBuf::from("ELIDED_FOR_TEST"));
+                if resolved_package.build_options.registry.is_some() {
+                    resolved_package.build_options.registry =
+                        Some(PathBuf::from("ELIDED_FOR_TEST"));
+                }
                 format!("{:#?}\n", resolved_package)
             }
         },
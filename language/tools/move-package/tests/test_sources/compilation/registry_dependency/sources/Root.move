@@ -0,0 +1,6 @@
+module A::Root {
+    use L::Lib;
+    public fun baz() {
+        Lib::bar()
+    }
+}
@@ -0,0 +1,8 @@
+module A::M {
+    use B::M;
+    use Shared::S;
+    public fun baz() {
+        M::bar();
+        S::foo()
+    }
+}
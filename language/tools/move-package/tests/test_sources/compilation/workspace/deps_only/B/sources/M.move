@@ -0,0 +1,6 @@
+module B::M {
+    use Shared::S;
+    public fun bar() {
+        S::foo()
+    }
+}
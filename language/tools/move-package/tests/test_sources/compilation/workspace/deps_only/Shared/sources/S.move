@@ -0,0 +1,3 @@
+module Shared::S {
+    public fun foo() { }
+}
@@ -0,0 +1,5 @@
+module A::M {
+    public fun baz() {
+        0x3::M::bar()
+    }
+}
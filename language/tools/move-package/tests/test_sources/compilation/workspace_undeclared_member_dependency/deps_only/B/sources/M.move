@@ -0,0 +1,3 @@
+module B::M {
+    public fun bar() { }
+}
@@ -0,0 +1,3 @@
+module B::Base {
+    public fun foo() { }
+}
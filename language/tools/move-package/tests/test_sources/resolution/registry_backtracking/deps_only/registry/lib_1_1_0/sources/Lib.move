@@ -0,0 +1,6 @@
+module L::Lib {
+    use B::Base;
+    public fun bar() {
+        Base::foo()
+    }
+}
@@ -0,0 +1,3 @@
+module L::Lib {
+    public fun bar() { }
+}
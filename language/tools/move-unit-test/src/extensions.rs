@@ -38,7 +38,7 @@ pub(crate) fn print_change_sets<W: Write>(_w: &mut W, mut extensions: NativeCont
 
 #[cfg(feature = "table-extension")]
 fn create_table_extension(extensions: &mut NativeContextExtensions) {
-    extensions.add(NativeTableContext::new(0, &*DUMMY_RESOLVER));
+    extensions.add_with_savepoints(NativeTableContext::new(0, &*DUMMY_RESOLVER));
 }
 
 #[cfg(feature = "table-extension")]
@@ -61,8 +61,16 @@ fn print_table_extension<W: Write>(w: &mut W, extensions: &mut NativeContextExte
             )
             .unwrap();
         }
+        if !cs.dropped_tables.is_empty() {
+            writeln!(
+                w,
+                "dropped tables {}",
+                cs.dropped_tables.iter().map(|h| h.to_string()).join(", ")
+            )
+            .unwrap();
+        }
         for (h, c) in cs.changes {
-            writeln!(w, "for {}", h).unwrap();
+            writeln!(w, "for {} ({:+} bytes)", h, c.bytes_delta).unwrap();
             for (k, v) in c.entries {
                 writeln!(w, "  {:X?} := {:X?}", k, v).unwrap();
             }